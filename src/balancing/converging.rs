@@ -0,0 +1,259 @@
+use crate::{configs, defaults, helpers::err, network::Graph};
+use log::info;
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+/// Convergence-metrics of a balancer-iteration, which are written into a summary-file.
+#[derive(Clone, Debug)]
+pub struct IterationStats {
+    pub iteration: usize,
+    /// `||w - w_prev|| / ||w_prev||` (euclidean) of the workloads `w` of this and the previous iteration
+    pub workload_change: Option<f64>,
+    /// Share of all routes using the edge of maximum workload
+    pub max_utilization: f64,
+    /// Gini-coefficient of the workloads, which is 0 if all edges have the same workload.
+    pub gini: f64,
+    pub avg_num_of_found_paths: f64,
+}
+
+impl IterationStats {
+    /// The workloads have to be sorted via `sort_by_node_ids(...)`, because edge-indices change between iterations.
+    pub fn compute(
+        iteration: usize,
+        workloads: &[usize],
+        prev_workloads: Option<&[usize]>,
+        num_of_routes: usize,
+        avg_num_of_found_paths: f64,
+    ) -> IterationStats {
+        let workload_change = prev_workloads.and_then(|prev_workloads| {
+            let prev_norm = norm(prev_workloads.iter().map(|&w| w as f64));
+            if prev_norm > 0.0 && prev_workloads.len() == workloads.len() {
+                let diff_norm = norm(
+                    workloads
+                        .iter()
+                        .zip(prev_workloads)
+                        .map(|(&w, &prev_w)| w as f64 - prev_w as f64),
+                );
+                Some(diff_norm / prev_norm)
+            } else {
+                None
+            }
+        });
+
+        let max_utilization = if num_of_routes > 0 {
+            workloads.iter().cloned().max().unwrap_or(0) as f64 / num_of_routes as f64
+        } else {
+            0.0
+        };
+
+        IterationStats {
+            iteration,
+            workload_change,
+            max_utilization,
+            gini: gini(workloads),
+            avg_num_of_found_paths,
+        }
+    }
+}
+
+/// Returns the workloads of all non-shortcuts, sorted by their nodes' ids.
+///
+/// Parallel edges keep their order, which is the same in every iteration.
+pub fn sort_by_node_ids(mut edges: Vec<(i64, i64, usize)>) -> Vec<usize> {
+    edges.sort_by_key(|&(src_id, dst_id, _workload)| (src_id, dst_id));
+    edges
+        .into_iter()
+        .map(|(_src_id, _dst_id, workload)| workload)
+        .collect()
+}
+
+/// Returns the given workloads (per edge-idx) of all non-shortcuts together with their nodes' ids.
+pub fn with_node_ids(graph: &Graph, abs_workloads: &[usize]) -> Vec<(i64, i64, usize)> {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    fwd_edges
+        .iter()
+        .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+        .map(|edge_idx| {
+            (
+                nodes.id(bwd_edges.dst_idx(edge_idx)),
+                nodes.id(fwd_edges.dst_idx(edge_idx)),
+                abs_workloads[*edge_idx],
+            )
+        })
+        .collect()
+}
+
+/// Returns true, if one of the stopping-criteria is fulfilled after the last iteration of the given history.
+pub fn is_stopping(history: &[IterationStats], stopping: &configs::balancing::Stopping) -> bool {
+    let last_stats = match history.last() {
+        Some(last_stats) => last_stats,
+        None => return false,
+    };
+
+    if let (Some(tolerance), Some(workload_change)) =
+        (stopping.tolerance, last_stats.workload_change)
+    {
+        if workload_change <= tolerance {
+            info!(
+                "Workloads have changed by {} <= tolerance {} in iteration {}.",
+                workload_change, tolerance, last_stats.iteration
+            );
+            return true;
+        }
+    }
+
+    if let Some(plateau) = &stopping.plateau {
+        if history.len() > plateau.num_iter {
+            let (older, recent) = history.split_at(history.len() - plateau.num_iter);
+            let best_gini = |stats: &[IterationStats]| {
+                stats
+                    .iter()
+                    .map(|stats| stats.gini)
+                    .fold(f64::INFINITY, f64::min)
+            };
+            let improvement = best_gini(older) - best_gini(recent);
+            if improvement <= plateau.min_improvement {
+                info!(
+                    "Gini-coefficient has improved by {} <= {} in the last {} iterations.",
+                    improvement, plateau.min_improvement, plateau.num_iter
+                );
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+fn summary_file(results_dir: &Path) -> PathBuf {
+    results_dir.join(defaults::balancing::files::CONVERGENCE)
+}
+
+const HEADER: &str = "iteration workload-change max-utilization gini avg-num-of-found-paths";
+
+/// Appends the stats to the summary-file in the results-dir, which is created if necessary.
+pub fn append(stats: &IterationStats, results_dir: &Path) -> err::Feedback {
+    let path = summary_file(results_dir);
+    let is_new = !path.exists();
+    let mut file = match OpenOptions::new().append(true).create(true).open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+        }
+    };
+
+    if is_new {
+        writeln!(file, "{}", HEADER)?;
+    }
+    write_stats(&mut file, stats)
+}
+
+/// Reads the stats of all iterations up to the given one from the summary-file in the results-dir.
+///
+/// Stats of later (interrupted) iterations are removed from the summary-file.
+pub fn read_and_truncate(last_iter: usize, results_dir: &Path) -> err::Result<Vec<IterationStats>> {
+    let path = summary_file(results_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = match OpenOptions::new().read(true).open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+        }
+    };
+    let mut history = Vec::new();
+    for (line_idx, line) in BufReader::new(file).lines().enumerate().skip(1) {
+        let line = line?;
+        let stats = parse_line(&line).ok_or_else(|| {
+            err::Msg::from(format!(
+                "Line {} of {} is invalid: '{}'",
+                line_idx + 1,
+                path.display(),
+                line
+            ))
+        })?;
+        if stats.iteration <= last_iter {
+            history.push(stats);
+        }
+    }
+
+    rewrite(&path, &history)?;
+    Ok(history)
+}
+
+fn parse_line(line: &str) -> Option<IterationStats> {
+    let mut params = line.split_whitespace();
+    let iteration = params.next()?.parse().ok()?;
+    let workload_change = match params.next()? {
+        defaults::writing::IGNORE_STR => None,
+        param => Some(param.parse().ok()?),
+    };
+    Some(IterationStats {
+        iteration,
+        workload_change,
+        max_utilization: params.next()?.parse().ok()?,
+        gini: params.next()?.parse().ok()?,
+        avg_num_of_found_paths: params.next()?.parse().ok()?,
+    })
+}
+
+fn rewrite(path: &Path, history: &[IterationStats]) -> err::Feedback {
+    fs::remove_file(path)?;
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+        }
+    };
+    writeln!(file, "{}", HEADER)?;
+    for stats in history {
+        write_stats(&mut file, stats)?;
+    }
+    Ok(())
+}
+
+fn write_stats<W: Write>(writer: &mut W, stats: &IterationStats) -> err::Feedback {
+    writeln!(
+        writer,
+        "{} {} {} {} {}",
+        stats.iteration,
+        stats
+            .workload_change
+            .map_or(String::from(defaults::writing::IGNORE_STR), |change| {
+                change.to_string()
+            }),
+        stats.max_utilization,
+        stats.gini,
+        stats.avg_num_of_found_paths
+    )?;
+    Ok(())
+}
+
+fn norm<I: Iterator<Item = f64>>(values: I) -> f64 {
+    values.map(|value| value * value).sum::<f64>().sqrt()
+}
+
+/// `sum_i (2i - n - 1) * w_i / (n * sum_i w_i)` with ascending workloads `w_i` and `i` starting at 1
+pub fn gini(workloads: &[usize]) -> f64 {
+    let sum: f64 = workloads.iter().map(|&w| w as f64).sum();
+    if workloads.is_empty() || sum <= 0.0 {
+        return 0.0;
+    }
+
+    let mut sorted_workloads = workloads.to_vec();
+    sorted_workloads.sort_unstable();
+    let n = sorted_workloads.len() as f64;
+    sorted_workloads
+        .iter()
+        .enumerate()
+        .map(|(i, &w)| (2.0 * (i + 1) as f64 - n - 1.0) * w as f64)
+        .sum::<f64>()
+        / (n * sum)
+}
@@ -0,0 +1,4 @@
+/// Convergence-metrics and stopping-criteria of the balancer's iterations
+pub mod converging;
+/// Working off route-pairs with several threads, independent of the number of threads
+pub mod multithreading;
@@ -1,23 +1,23 @@
-use log::{debug, info, trace, warn};
-use osmgraphing::{
+use crate::{
     configs::{
         self,
         routing::{ExploratorAlgo, RoutingAlgo},
     },
     defaults,
     helpers::err,
-    network::{Graph, RoutePair},
+    io::evaluating_balance::TimeSliceWorkloads,
+    network::{CountedRoutePair, Graph, RoutePair},
     routing::{
+        choosing,
         dijkstra::{self, Dijkstra},
         explorating::ConvexHullExplorator,
         paths::Path,
     },
 };
+use kissunits::time::Seconds;
+use log::{debug, info, trace, warn};
 use progressing::{mapping::Bar as MappingBar, Baring};
-use rand::{
-    distributions::{Distribution, Uniform},
-    Rng, SeedableRng,
-};
+use rand::{Rng, SeedableRng};
 use std::{
     ops::Deref,
     sync::{mpsc, Arc},
@@ -34,27 +34,48 @@ pub struct Master {
     last_worker_idx: Option<WorkerIdx>,
 }
 
+/// The results of working off all route-pairs.
+pub struct WorkedOff {
+    /// Workloads per edge-idx
+    pub abs_workloads: Vec<usize>,
+    /// Only given, if a time-slice is given.
+    pub time_slice_workloads: Option<TimeSliceWorkloads>,
+    /// The chosen paths with their routes' departures, only given if collected.
+    pub chosen_paths: Option<Vec<(Path, Option<Seconds>)>>,
+    /// Average number of found paths per route-pair
+    pub avg_num_of_found_paths: f64,
+}
+
 impl Master {
+    /// Workloads per time-slice are counted on the fly,
+    /// so the chosen paths are only kept in memory, if they are collected.
     pub fn work_off(
         &mut self,
-        mut route_pairs: Vec<(RoutePair<i64>, usize)>,
+        mut route_pairs: Vec<CountedRoutePair>,
         arc_ch_graph: &Arc<Graph>,
         rng: &mut rand_pcg::Lcg64Xsh32,
         is_collecting_paths: bool,
-    ) -> err::Result<(Vec<usize>, Option<Vec<Path>>)> {
+        time_slice: Option<Seconds>,
+    ) -> err::Result<WorkedOff> {
         info!("Using {} threads working off", self.num_threads());
 
         route_pairs.reverse();
         // not routes, because progress can be shown without it (though it is less accurate)
         let num_of_route_pairs = route_pairs.len();
+        // Every route-pair's rng is derived from this seed and the route-pair's index,
+        // so results don't depend on the number of threads or on which worker works off which route-pairs.
+        let seed: u64 = rng.gen();
+        let mut next_route_pair_idx = 0;
 
         let mut abs_workloads: Vec<usize> = vec![0; arc_ch_graph.fwd_edges().count()];
-        let mut chosen_paths = if is_collecting_paths {
-            // num_of_route_pairs is not accurate, but lower bound
-            Some(Vec::with_capacity(num_of_route_pairs))
+        // chunks of chosen paths, which are sorted by their first route-pair's index in the end
+        let mut chosen_path_chunks = if is_collecting_paths {
+            Some(Vec::new())
         } else {
             None
         };
+        let mut time_slice_workloads =
+            time_slice.map(|time_slice| TimeSliceWorkloads::new(time_slice, arc_ch_graph));
         let mut avg_num_of_found_paths = 0.0;
         let mut var_num_of_found_paths = 0.0;
 
@@ -65,19 +86,22 @@ impl Master {
             if let Ok(outcome) = self.recv() {
                 // update counts from outcome
 
-                for path in outcome
+                let chosen_paths: Vec<(Path, Option<Seconds>)> = outcome
                     .chosen_paths
                     .into_iter()
-                    .map(|path| path.flatten(&arc_ch_graph))
-                {
-                    for &edge_idx in &path {
+                    .map(|(path, departure)| (path.flatten(&arc_ch_graph), departure))
+                    .collect();
+                for (path, departure) in &chosen_paths {
+                    for &edge_idx in path {
                         abs_workloads[*edge_idx] += 1;
                     }
-
-                    if let Some(chosen_paths) = chosen_paths.as_mut() {
-                        chosen_paths.push(path);
+                    if let Some(time_slice_workloads) = time_slice_workloads.as_mut() {
+                        time_slice_workloads.add(path, *departure);
                     }
                 }
+                if let Some(chosen_path_chunks) = chosen_path_chunks.as_mut() {
+                    chosen_path_chunks.push((outcome.first_route_pair_idx, chosen_paths));
+                }
                 // num_of_routes is ignored here
                 progress_bar.add(outcome.num_of_route_pairs);
 
@@ -143,8 +167,10 @@ impl Master {
                         .collect();
                     self.send(Work {
                         route_pairs: chunk,
-                        seed: rng.gen(),
+                        first_route_pair_idx: next_route_pair_idx,
+                        seed,
                     })?;
+                    next_route_pair_idx += chunk_len;
                 } else {
                     self.drop_and_join_worker()?;
                 }
@@ -164,7 +190,17 @@ impl Master {
             " path(s) per exploration were found.",
         );
 
-        Ok((abs_workloads, chosen_paths))
+        let chosen_paths = chosen_path_chunks.map(|mut chunks| {
+            chunks.sort_by_key(|(first_route_pair_idx, _paths)| *first_route_pair_idx);
+            chunks.into_iter().flat_map(|(_idx, paths)| paths).collect()
+        });
+
+        Ok(WorkedOff {
+            abs_workloads,
+            time_slice_workloads,
+            chosen_paths,
+            avg_num_of_found_paths,
+        })
     }
 
     fn work_size(&self) -> usize {
@@ -198,6 +234,7 @@ impl Master {
         count: usize,
         arc_graph: &Arc<Graph>,
         arc_routing_cfg: &Arc<configs::routing::Config>,
+        route_choice: &configs::balancing::RouteChoice,
     ) -> err::Result<Master> {
         info!("Using routing-algo: {:?}", arc_routing_cfg.routing_algo);
         let mut worker_sockets = Vec::with_capacity(count);
@@ -213,6 +250,7 @@ impl Master {
                 idx,
                 arc_graph: Arc::clone(arc_graph),
                 arc_routing_cfg: Arc::clone(arc_routing_cfg),
+                route_choice: route_choice.clone(),
                 work_rx,
                 outcome_tx: outcome_txs
                     .pop()
@@ -350,14 +388,20 @@ impl WorkerSocket {
     }
 }
 
+/// The route-pairs of a work are consecutive, starting with the route-pair of the given index.
 pub struct Work {
-    pub route_pairs: Vec<(RoutePair<i64>, usize)>,
+    pub route_pairs: Vec<CountedRoutePair>,
+    pub first_route_pair_idx: usize,
+    /// Together with a route-pair's index, this seed determines the route-pair's rng.
     pub seed: u64,
 }
 
 /// Chosen paths are not necessarily the same as found paths (e.g. when using explorator), for which reason the `num_of_found_paths` is provided separatedly.
 pub struct Outcome {
-    pub chosen_paths: Vec<Path>,
+    /// Index of the work's first route-pair
+    pub first_route_pair_idx: usize,
+    /// Every chosen path refers to a route, departing at the given time.
+    pub chosen_paths: Vec<(Path, Option<Seconds>)>,
     pub num_of_found_paths: Vec<usize>,
     pub num_of_route_pairs: usize,
 }
@@ -366,6 +410,7 @@ struct WorkerContext {
     idx: WorkerIdx,
     arc_graph: Arc<Graph>,
     arc_routing_cfg: Arc<configs::routing::Config>,
+    route_choice: configs::balancing::RouteChoice,
     work_rx: mpsc::Receiver<Work>,
     outcome_tx: mpsc::Sender<(WorkerIdx, Outcome)>,
 }
@@ -377,6 +422,7 @@ struct Worker {
     idx: WorkerIdx,
     arc_graph: Arc<Graph>,
     arc_routing_cfg: Arc<configs::routing::Config>,
+    route_choice: configs::balancing::RouteChoice,
     work_rx: mpsc::Receiver<Work>,
     outcome_tx: mpsc::Sender<(WorkerIdx, Outcome)>,
 }
@@ -389,6 +435,7 @@ impl Worker {
             idx: context.idx,
             arc_graph: context.arc_graph,
             arc_routing_cfg: context.arc_routing_cfg,
+            route_choice: context.route_choice,
             work_rx: context.work_rx,
             outcome_tx: context.outcome_tx,
         }
@@ -401,6 +448,7 @@ impl Worker {
             .send((
                 self.idx,
                 Outcome {
+                    first_route_pair_idx: 0,
                     chosen_paths: Vec::new(),
                     num_of_found_paths: Vec::new(),
                     num_of_route_pairs: 0,
@@ -421,11 +469,9 @@ impl Worker {
 
                 // do work
                 let outcome = match self.arc_routing_cfg.routing_algo {
-                    super::RoutingAlgo::Dijkstra => self.work_off_with_dijkstra(work),
-                    super::RoutingAlgo::CHDijkstra => self.work_off_with_dijkstra(work),
-                    super::RoutingAlgo::Explorator { algo } => {
-                        self.work_off_with_explorator(work, algo)
-                    }
+                    RoutingAlgo::Dijkstra => self.work_off_with_dijkstra(work),
+                    RoutingAlgo::CHDijkstra => self.work_off_with_dijkstra(work),
+                    RoutingAlgo::Explorator { algo } => self.work_off_with_explorator(work, algo),
                 };
 
                 // return outcome
@@ -444,8 +490,9 @@ impl Worker {
         let mut num_of_found_paths = Vec::new();
         let num_of_route_pairs = work.route_pairs.len();
 
-        for (route_pair, route_count) in work.route_pairs {
+        for (i, (route_pair, route_count, departure)) in work.route_pairs.into_iter().enumerate() {
             let RoutePair { src, dst } = route_pair.into_node(&self.arc_graph);
+            let mut rng = route_pair_rng(work.seed, work.first_route_pair_idx + i);
 
             // find explorated routes
 
@@ -464,10 +511,10 @@ impl Worker {
             if let Some(best_path) = best_path {
                 num_of_found_paths.push(1);
 
-                for _ in 0..(route_count - 1) {
-                    chosen_paths.push(best_path.clone());
+                for _ in 0..route_count {
+                    let departure = departure.map(|departure| departure.sample(&mut rng));
+                    chosen_paths.push((best_path.clone(), departure));
                 }
-                chosen_paths.push(best_path);
             } else {
                 warn!("Didn't find any path when executing Dijkstra.")
             }
@@ -477,6 +524,7 @@ impl Worker {
         num_of_found_paths.shrink_to_fit();
 
         Outcome {
+            first_route_pair_idx: work.first_route_pair_idx,
             chosen_paths,
             num_of_found_paths,
             num_of_route_pairs,
@@ -487,13 +535,13 @@ impl Worker {
         let mut chosen_paths = Vec::new();
         let mut num_of_found_paths = Vec::new();
         let num_of_route_pairs = work.route_pairs.len();
-        let mut rng = rand_pcg::Pcg32::seed_from_u64(work.seed);
 
         let mut routing_cfg = self.arc_routing_cfg.as_ref().clone();
         routing_cfg.routing_algo = RoutingAlgo::from(explorator_algo);
 
-        for (route_pair, route_count) in work.route_pairs {
+        for (i, (route_pair, route_count, departure)) in work.route_pairs.into_iter().enumerate() {
             let RoutePair { src, dst } = route_pair.into_node(&self.arc_graph);
+            let mut rng = route_pair_rng(work.seed, work.first_route_pair_idx + i);
 
             // find explorated routes
 
@@ -514,11 +562,27 @@ impl Worker {
             // -> or shortcuts will lead to wrong best-paths, because counts won't be cumulated.
 
             if found_paths.len() > 0 {
-                let die = Uniform::from(0..found_paths.len());
-                for _ in 0..route_count {
-                    let chosen_path = found_paths[die.sample(&mut rng)].clone();
-                    trace!("    {}", chosen_path);
-                    chosen_paths.push(chosen_path);
+                // route-choice needs the flattened paths' edges
+                let found_paths: Vec<Path> = found_paths
+                    .into_iter()
+                    .map(|path| path.flatten(&self.arc_graph))
+                    .collect();
+                let route_counts = choosing::choose(
+                    choosing::Query {
+                        paths: &found_paths,
+                        graph: &self.arc_graph,
+                        alphas: &routing_cfg.alphas,
+                    },
+                    &self.route_choice,
+                    route_count,
+                    &mut rng,
+                );
+                for (path, route_count) in found_paths.iter().zip(route_counts) {
+                    for _ in 0..route_count {
+                        trace!("    {}", path);
+                        let departure = departure.map(|departure| departure.sample(&mut rng));
+                        chosen_paths.push((path.clone(), departure));
+                    }
                 }
             } else {
                 warn!("Didn't find any path when explorating.")
@@ -529,9 +593,27 @@ impl Worker {
         num_of_found_paths.shrink_to_fit();
 
         Outcome {
+            first_route_pair_idx: work.first_route_pair_idx,
             chosen_paths,
             num_of_found_paths,
             num_of_route_pairs,
         }
     }
 }
+
+/// Every route-pair gets its own rng, which only depends on the seed and the route-pair's index.
+///
+/// The rng's seed is the route-pair's output of a SplitMix64-generator (seeded with the given seed),
+/// so neighbouring route-pairs get uncorrelated rngs.
+fn route_pair_rng(seed: u64, route_pair_idx: usize) -> rand_pcg::Pcg32 {
+    const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    let mut z = seed.wrapping_add(
+        (route_pair_idx as u64)
+            .wrapping_add(1)
+            .wrapping_mul(GOLDEN_GAMMA),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    rand_pcg::Pcg32::seed_from_u64(z ^ (z >> 31))
+}
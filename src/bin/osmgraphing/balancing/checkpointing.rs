@@ -0,0 +1,224 @@
+use log::info;
+use osmgraphing::{balancing::converging, configs, defaults, helpers::err, network::Graph};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// State of the balancer after a completed iteration, which is needed to continue the balancing.
+///
+/// The graph's new metric and the workloads are stored separately (in a csv-file), since they are updated every iteration.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Checkpoint {
+    /// The last completed iteration
+    pub iteration: usize,
+    pub rng: rand_pcg::Pcg32,
+}
+
+fn checkpoint_dir(iter_dir: &Path) -> PathBuf {
+    iter_dir.join(defaults::balancing::checkpoint::DIR)
+}
+
+/// Writes the checkpoint and the new metric and workloads of all non-shortcuts into the given iteration-directory.
+///
+/// Shortcuts are not stored, because they are recomputed in every iteration.
+/// The state-file is written last, so a checkpoint is complete if and only if its state-file exists.
+pub fn write(
+    checkpoint: &Checkpoint,
+    graph: &Graph,
+    abs_workloads: &[usize],
+    balancing_cfg: &configs::balancing::Config,
+    iter_dir: &Path,
+) -> err::Feedback {
+    let checkpoint_dir = checkpoint_dir(iter_dir);
+    fs::create_dir_all(&checkpoint_dir)?;
+
+    // write new metric and workloads
+
+    let metric_idx = graph
+        .cfg()
+        .edges
+        .metrics
+        .try_idx_of(&balancing_cfg.optimization.metric_id)?;
+    let mut writer = create(&checkpoint_dir.join(defaults::balancing::checkpoint::files::EDGES))?;
+    // edges are identified by their nodes, because edge-ids are optional
+    writeln!(
+        writer,
+        "src-id dst-id {} abs-workload",
+        balancing_cfg.optimization.metric_id
+    )?;
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let bwd_edges = graph.bwd_edges();
+    let metrics = graph.metrics();
+    for edge_idx in fwd_edges
+        .iter()
+        .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+    {
+        writeln!(
+            writer,
+            "{} {} {} {}",
+            nodes.id(bwd_edges.dst_idx(edge_idx)),
+            nodes.id(fwd_edges.dst_idx(edge_idx)),
+            metrics.get_value(edge_idx, metric_idx),
+            abs_workloads[*edge_idx]
+        )?;
+    }
+    writer.flush()?;
+
+    // write state
+
+    let writer = create(&checkpoint_dir.join(defaults::balancing::checkpoint::files::STATE))?;
+    if let Err(e) = serde_yaml::to_writer(writer, checkpoint) {
+        return Err(format!(
+            "Couldn't write checkpoint of iteration {} due to error: {}",
+            checkpoint.iteration, e
+        )
+        .into());
+    }
+
+    info!(
+        "Written checkpoint of iteration {} into {}",
+        checkpoint.iteration,
+        checkpoint_dir.display()
+    );
+
+    Ok(())
+}
+
+/// Returns the checkpoint of the given iteration-directory, or `None` if it is incomplete.
+pub fn try_read(iter_dir: &Path) -> err::Result<Option<Checkpoint>> {
+    let path = checkpoint_dir(iter_dir).join(defaults::balancing::checkpoint::files::STATE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file = open(&path)?;
+    match serde_yaml::from_reader(file) {
+        Ok(checkpoint) => Ok(Some(checkpoint)),
+        Err(e) => Err(format!("Serde couldn't read {} due to error: {}", path.display(), e).into()),
+    }
+}
+
+/// Sets the new metric of all non-shortcuts to the values stored in the iteration-directory,
+/// and returns the stored workloads sorted via `converging::sort_by_node_ids(...)`.
+///
+/// Edges are matched by their nodes, so the graph's non-shortcuts have to be the ones of the checkpoint.
+/// Parallel edges are matched in the order of their edge-indices.
+pub fn restore_new_metric(
+    graph: &mut Graph,
+    balancing_cfg: &configs::balancing::Config,
+    iter_dir: &Path,
+) -> err::Result<Vec<usize>> {
+    let path = checkpoint_dir(iter_dir).join(defaults::balancing::checkpoint::files::EDGES);
+    let mut lines = BufReader::new(open(&path)?).lines();
+
+    // check header-line
+
+    let metric_id = &balancing_cfg.optimization.metric_id;
+    let header = lines
+        .next()
+        .ok_or_else(|| err::Msg::from(format!("The file {} is empty.", path.display())))??;
+    if header.split_whitespace().nth(2) != Some(metric_id.as_ref()) {
+        return Err(format!(
+            "The file {} should contain the metric {}, but its header is '{}'.",
+            path.display(),
+            metric_id,
+            header
+        )
+        .into());
+    }
+
+    // collect stored values per node-pair
+
+    let mut stored_values: HashMap<(i64, i64), VecDeque<f64>> = HashMap::new();
+    let mut workloads = Vec::new();
+    for (line_idx, line) in lines.enumerate() {
+        let line = line?;
+        let line_err = || {
+            err::Msg::from(format!(
+                "Line {} of {} is invalid: '{}'",
+                line_idx + 2,
+                path.display(),
+                line
+            ))
+        };
+        let mut params = line.split_whitespace();
+        let mut next_param = || params.next().ok_or_else(line_err);
+        let src_id: i64 = next_param()?.parse().map_err(|_| line_err())?;
+        let dst_id: i64 = next_param()?.parse().map_err(|_| line_err())?;
+        let value: f64 = next_param()?.parse().map_err(|_| line_err())?;
+        let workload: usize = next_param()?.parse().map_err(|_| line_err())?;
+        workloads.push((src_id, dst_id, workload));
+        stored_values
+            .entry((src_id, dst_id))
+            .or_insert_with(VecDeque::new)
+            .push_back(value);
+    }
+
+    // update graph
+
+    let metric_idx = graph.cfg().edges.metrics.try_idx_of(metric_id)?;
+    let edges: Vec<_> = {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        fwd_edges
+            .iter()
+            .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+            .map(|edge_idx| {
+                (
+                    edge_idx,
+                    nodes.id(bwd_edges.dst_idx(edge_idx)),
+                    nodes.id(fwd_edges.dst_idx(edge_idx)),
+                )
+            })
+            .collect()
+    };
+    let mut metrics = graph.metrics_mut();
+    for (edge_idx, src_id, dst_id) in edges {
+        let value = stored_values
+            .get_mut(&(src_id, dst_id))
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| {
+                err::Msg::from(format!(
+                    "The checkpoint {} doesn't contain the edge (id:{}->id:{}).",
+                    path.display(),
+                    src_id,
+                    dst_id
+                ))
+            })?;
+        metrics.set_value(edge_idx, metric_idx, value);
+    }
+    if let Some(((src_id, dst_id), _values)) = stored_values
+        .into_iter()
+        .find(|(_node_ids, values)| !values.is_empty())
+    {
+        return Err(format!(
+            "The checkpoint {} contains the edge (id:{}->id:{}), which is not in the graph.",
+            path.display(),
+            src_id,
+            dst_id
+        )
+        .into());
+    }
+
+    Ok(converging::sort_by_node_ids(workloads))
+}
+
+fn create(path: &Path) -> err::Result<BufWriter<fs::File>> {
+    match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => Ok(BufWriter::new(file)),
+        Err(e) => Err(format!("Couldn't open {} due to error: {}", path.display(), e).into()),
+    }
+}
+
+fn open(path: &Path) -> err::Result<fs::File> {
+    match OpenOptions::new().read(true).open(path) {
+        Ok(file) => Ok(file),
+        Err(e) => Err(format!("Couldn't open {} due to error: {}", path.display(), e).into()),
+    }
+}
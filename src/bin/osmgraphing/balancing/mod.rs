@@ -1,14 +1,19 @@
 use log::{debug, info};
 use osmgraphing::{
-    configs::{self, routing::RoutingAlgo},
+    balancing::{converging, multithreading},
+    configs,
     helpers::err,
     io,
     network::Graph,
 };
 use rand::SeedableRng;
-use std::{path::Path, sync::Arc, time::Instant};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
-pub mod multithreading;
+mod checkpointing;
 
 pub fn run(args: CmdlineArgs) -> err::Feedback {
     // check writing-cfg
@@ -18,8 +23,6 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
     info!("EXECUTE balancer");
     info!("Using balancer-seed={}", balancing_cfg.seed);
 
-    let mut rng = rand_pcg::Pcg32::seed_from_u64(balancing_cfg.seed);
-
     // prepare simulation
     // e.g. creating the results-folder and converting the graph into the right format
 
@@ -27,40 +30,105 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
     // check routing-cfg
     let _ = configs::routing::Config::try_from_yaml(&args.cfg, custom_graph.cfg())?;
 
-    // start balancing
-
-    simulation_pipeline::prepare_results(&args.cfg, &mut balancing_cfg)?;
+    // start balancing, or continue after the last completed iteration
+
+    // the last iteration is moved forward, if a stopping-criterion is fulfilled
+    let mut last_iter = balancing_cfg.num_iter - 1;
+    let checkpoint = if let Some(results_dir) = &args.resume_dir {
+        balancing_cfg.results_dir = results_dir.clone();
+        let checkpoint = simulation_pipeline::read_in_last_checkpoint(&balancing_cfg)?;
+        let history =
+            converging::read_and_truncate(checkpoint.iteration, &balancing_cfg.results_dir)?;
+        // replay the stopping-criteria of the completed iterations
+        for (i, stats) in history.iter().enumerate() {
+            if stats.iteration + 1 < last_iter
+                && converging::is_stopping(&history[..=i], &balancing_cfg.stopping)
+            {
+                last_iter = stats.iteration + 1;
+            }
+        }
+        simulation_pipeline::prepare_resuming(
+            &args.cfg,
+            checkpoint.iteration,
+            last_iter,
+            &balancing_cfg,
+        )?;
+        Some((checkpoint, history))
+    } else {
+        simulation_pipeline::prepare_results(&args.cfg, &mut balancing_cfg)?;
+        None
+    };
 
     let mut graph = custom_graph;
-    for iter in 0..balancing_cfg.num_iter {
+    // metric-independent preprocessing is done only once
+    let cch = match balancing_cfg.contraction {
+        configs::balancing::Contraction::MultiChConstructor => None,
+        configs::balancing::Contraction::Customizable => {
+            Some(simulation_pipeline::preprocess_ch_graph(&graph))
+        }
+    };
+    let (first_iter, mut rng, mut history, mut prev_workloads) =
+        if let Some((checkpoint, history)) = checkpoint {
+            if cch.is_none() {
+                // the graph has to be the ch-graph of the last completed iteration
+                graph =
+                    simulation_pipeline::read_in_ch_graph(&balancing_cfg, checkpoint.iteration)?;
+            }
+            let prev_workloads = simulation_pipeline::restore_new_metric(
+                &mut graph,
+                &balancing_cfg,
+                checkpoint.iteration,
+            )?;
+            (
+                checkpoint.iteration + 1,
+                checkpoint.rng,
+                history,
+                Some(prev_workloads),
+            )
+        } else {
+            (
+                0,
+                rand_pcg::Pcg32::seed_from_u64(balancing_cfg.seed),
+                Vec::new(),
+                None,
+            )
+        };
+    let mut iter = first_iter;
+    while iter <= last_iter {
         // Iterate +1 to get analysis of new graph as well.
         // -> store graph before creating a new one
 
-        if iter == balancing_cfg.num_iter - 1 {
+        if iter == last_iter {
             // store balanced graph
 
-            let mut writing_cfg =
-                configs::writing::network::graph::Config::try_from_yaml(&args.cfg)?;
-            writing_cfg.map_file =
-                balancing_cfg
-                    .results_dir
-                    .join(writing_cfg.map_file.file_name().ok_or(err::Msg::from(
-                        "The provided route-pairs-file in the (routing-)config is not a file.",
-                    ))?);
+            let writing_cfg =
+                simulation_pipeline::balanced_graph_writing_cfg(&args.cfg, &balancing_cfg)?;
             write_graph(&graph, &writing_cfg)?;
         }
 
         // simulate and create new balanced graph
 
         simulation_pipeline::prepare_iteration(iter, &balancing_cfg)?;
-        simulation_pipeline::write_multi_ch_graph(&balancing_cfg, graph, iter)?;
-        simulation_pipeline::construct_ch_graph(&balancing_cfg, iter)?;
-        let ch_graph = simulation_pipeline::read_in_ch_graph(&balancing_cfg, iter)?;
-        let routing_cfg =
-            simulation_pipeline::read_in_routing_cfg(&balancing_cfg, iter, &args.cfg, &ch_graph)?;
+        let (ch_graph, routing_cfg) = if let Some(cch) = &cch {
+            let routing_cfg =
+                simulation_pipeline::read_in_routing_cfg(&balancing_cfg, iter, &args.cfg, &graph)?;
+            simulation_pipeline::customize_ch_graph(&mut graph, cch, &routing_cfg)?;
+            (graph, routing_cfg)
+        } else {
+            simulation_pipeline::write_multi_ch_graph(&balancing_cfg, graph, iter)?;
+            simulation_pipeline::construct_ch_graph(&balancing_cfg, iter)?;
+            let ch_graph = simulation_pipeline::read_in_ch_graph(&balancing_cfg, iter)?;
+            let routing_cfg = simulation_pipeline::read_in_routing_cfg(
+                &balancing_cfg,
+                iter,
+                &args.cfg,
+                &ch_graph,
+            )?;
+            (ch_graph, routing_cfg)
+        };
 
         let mut arc_ch_graph = Arc::new(ch_graph);
-        simulation_pipeline::balance(
+        let (abs_workloads, num_of_routes, avg_num_of_found_paths) = simulation_pipeline::balance(
             iter,
             &balancing_cfg,
             &mut arc_ch_graph,
@@ -69,6 +137,30 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
         )?;
         graph = Arc::try_unwrap(arc_ch_graph)
             .map_err(|_e| "The ch-graph should be owned by only one Arc.")?;
+
+        // check convergence
+        // The summary is written before the checkpoint, so it contains every completed iteration.
+
+        let workloads =
+            converging::sort_by_node_ids(converging::with_node_ids(&graph, &abs_workloads));
+        let stats = converging::IterationStats::compute(
+            iter,
+            &workloads,
+            prev_workloads.as_deref(),
+            num_of_routes,
+            avg_num_of_found_paths,
+        );
+        info!("{:?}", stats);
+        converging::append(&stats, &balancing_cfg.results_dir)?;
+        history.push(stats);
+        simulation_pipeline::write_checkpoint(iter, &rng, &graph, &abs_workloads, &balancing_cfg)?;
+
+        if iter + 1 < last_iter && converging::is_stopping(&history, &balancing_cfg.stopping) {
+            info!("Stop balancing after analysing the balanced graph in the next iteration.");
+            last_iter = iter + 1;
+        }
+        prev_workloads = Some(workloads);
+        iter += 1;
     }
 
     info!(
@@ -80,10 +172,15 @@ pub fn run(args: CmdlineArgs) -> err::Feedback {
 }
 
 mod simulation_pipeline {
-    use super::multithreading;
+    use super::{checkpointing, multithreading};
     use chrono;
-    use log::info;
-    use osmgraphing::{configs, defaults, helpers::err, io, multi_ch_constructor, network::Graph};
+    use log::{info, warn};
+    use osmgraphing::{
+        configs, defaults,
+        helpers::err,
+        io, multi_ch_constructor,
+        network::{CustomizableCh, Graph},
+    };
     use std::{
         fs,
         path::{Path, PathBuf},
@@ -126,6 +223,128 @@ mod simulation_pipeline {
         Ok(())
     }
 
+    /// The balanced graph is written into the results-dir.
+    pub fn balanced_graph_writing_cfg(
+        raw_cfg: &str,
+        balancing_cfg: &configs::balancing::Config,
+    ) -> err::Result<configs::writing::network::graph::Config> {
+        let mut writing_cfg = configs::writing::network::graph::Config::try_from_yaml(raw_cfg)?;
+        writing_cfg.map_file =
+            balancing_cfg
+                .results_dir
+                .join(writing_cfg.map_file.file_name().ok_or(err::Msg::from(
+                    "The provided route-pairs-file in the (routing-)config is not a file.",
+                ))?);
+        Ok(writing_cfg)
+    }
+
+    /// Returns the checkpoint of the last completed iteration in the results-dir.
+    pub fn read_in_last_checkpoint(
+        balancing_cfg: &configs::balancing::Config,
+    ) -> err::Result<checkpointing::Checkpoint> {
+        if !balancing_cfg.results_dir.is_dir() {
+            return Err(format!(
+                "The results-dir {} to resume doesn't exist.",
+                balancing_cfg.results_dir.display()
+            )
+            .into());
+        }
+
+        let mut last_checkpoint = None;
+        for iter in 0..balancing_cfg.num_iter {
+            match checkpointing::try_read(&iter_dir(iter, balancing_cfg))? {
+                Some(checkpoint) => last_checkpoint = Some(checkpoint),
+                None => break,
+            }
+        }
+
+        let checkpoint = last_checkpoint.ok_or_else(|| {
+            err::Msg::from(format!(
+                "The results-dir {} doesn't contain any completed iteration. \
+                 Please start a new balancing instead.",
+                balancing_cfg.results_dir.display()
+            ))
+        })?;
+        info!(
+            "Resuming balancing in {} after completed iteration {}",
+            balancing_cfg.results_dir.display(),
+            checkpoint.iteration
+        );
+        Ok(checkpoint)
+    }
+
+    /// Removes the results of the interrupted iteration, which are created again.
+    ///
+    /// The balancing is finished, if the last completed iteration is the last iteration,
+    /// which is moved forward by the stopping-criteria.
+    /// Then, nothing is removed and an error is returned, since there is nothing to resume.
+    pub fn prepare_resuming(
+        raw_cfg: &str,
+        completed_iter: usize,
+        last_iter: usize,
+        balancing_cfg: &configs::balancing::Config,
+    ) -> err::Feedback {
+        if completed_iter >= last_iter {
+            return Err(format!(
+                "The balancing in {} has already completed its last iteration {}, \
+                 so there is nothing to resume.",
+                balancing_cfg.results_dir.display(),
+                last_iter
+            )
+            .into());
+        }
+
+        for iter in (completed_iter + 1)..balancing_cfg.num_iter {
+            let iter_dir = iter_dir(iter, balancing_cfg);
+            if iter_dir.exists() {
+                warn!(
+                    "Removing {} of the interrupted iteration.",
+                    iter_dir.display()
+                );
+                fs::remove_dir_all(&iter_dir)?;
+            }
+        }
+
+        // the balanced graph is written in the last iteration, which hasn't completed
+        let map_file = balanced_graph_writing_cfg(raw_cfg, balancing_cfg)?.map_file;
+        if map_file.exists() {
+            warn!(
+                "Removing {} of the interrupted iteration.",
+                map_file.display()
+            );
+            fs::remove_file(&map_file)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_checkpoint(
+        iter: usize,
+        rng: &rand_pcg::Pcg32,
+        graph: &Graph,
+        abs_workloads: &[usize],
+        balancing_cfg: &configs::balancing::Config,
+    ) -> err::Feedback {
+        checkpointing::write(
+            &checkpointing::Checkpoint {
+                iteration: iter,
+                rng: rng.clone(),
+            },
+            graph,
+            abs_workloads,
+            balancing_cfg,
+            &iter_dir(iter, balancing_cfg),
+        )
+    }
+
+    pub fn restore_new_metric(
+        graph: &mut Graph,
+        balancing_cfg: &configs::balancing::Config,
+        last_iter: usize,
+    ) -> err::Result<Vec<usize>> {
+        checkpointing::restore_new_metric(graph, balancing_cfg, &iter_dir(last_iter, balancing_cfg))
+    }
+
     pub fn prepare_iteration(
         iter: usize,
         balancing_cfg: &configs::balancing::Config,
@@ -198,6 +417,35 @@ mod simulation_pipeline {
         multi_ch_constructor::construct_ch_graph(&mchc_cfg)
     }
 
+    pub fn preprocess_ch_graph(graph: &Graph) -> CustomizableCh {
+        let now = Instant::now();
+        let cch = CustomizableCh::preprocess(graph);
+        info!(
+            "FINISHED Preprocessed ch-graph in {} seconds ({} µs).",
+            now.elapsed().as_secs(),
+            now.elapsed().as_micros(),
+        );
+        info!("");
+        cch
+    }
+
+    /// Recomputes the shortcuts in memory, instead of writing, contracting and parsing the graph.
+    pub fn customize_ch_graph(
+        graph: &mut Graph,
+        cch: &CustomizableCh,
+        routing_cfg: &configs::routing::Config,
+    ) -> err::Feedback {
+        let now = Instant::now();
+        graph.customize(cch, &routing_cfg.alphas)?;
+        info!(
+            "FINISHED Customized ch-graph in {} seconds ({} µs).",
+            now.elapsed().as_secs(),
+            now.elapsed().as_micros(),
+        );
+        info!("");
+        Ok(())
+    }
+
     pub fn read_in_ch_graph(
         balancing_cfg: &configs::balancing::Config,
         iter: usize,
@@ -223,8 +471,10 @@ mod simulation_pipeline {
                 configs::parsing::generating::edges::Category::Merge {
                     from,
                     is_file_with_header: _,
-                    edge_id: _,
+                    key: _,
+                    policies: _,
                     edges_info: _,
+                    csv: _,
                 } => *from = iter_dir.join(&from),
                 configs::parsing::generating::edges::Category::Meta { info: _, id: _ }
                 | configs::parsing::generating::edges::Category::Custom {
@@ -239,6 +489,10 @@ mod simulation_pipeline {
                     result: _,
                     a: _,
                     b: _,
+                }
+                | configs::parsing::generating::edges::Category::Formula {
+                    result: _,
+                    expression: _,
                 } => {
                     // no file to update
                 }
@@ -298,13 +552,14 @@ mod simulation_pipeline {
         Ok(routing_cfg)
     }
 
+    /// Returns the workloads (per edge-idx), the number of routes and the average number of found paths per route-pair.
     pub fn balance(
         iter: usize,
         balancing_cfg: &configs::balancing::Config,
         arc_ch_graph: &mut Arc<Graph>,
         arc_routing_cfg: &Arc<configs::routing::Config>,
         rng: &mut rand_pcg::Lcg64Xsh32,
-    ) -> err::Feedback {
+    ) -> err::Result<(Vec<usize>, usize, f64)> {
         info!(
             "Balance via explorating several routes for metrics {:?}x{:?}",
             arc_ch_graph.cfg().edges.metrics.units,
@@ -312,18 +567,34 @@ mod simulation_pipeline {
         );
 
         // reverse this vector to make splice efficient
-        let route_pairs = io::routing::Parser::parse(&arc_routing_cfg)?;
+        let route_pairs: Vec<_> =
+            io::routing::Parser::parse_entries(&arc_routing_cfg, &arc_ch_graph)?
+                .into_iter()
+                .map(|entry| (entry.route_pair, entry.count, entry.departure))
+                .collect();
+        let num_of_routes = route_pairs
+            .iter()
+            .map(|(_route_pair, route_count, _departure)| route_count)
+            .sum();
 
         let mut master = multithreading::Master::spawn_some(
             balancing_cfg.num_threads,
             &arc_ch_graph,
             &arc_routing_cfg,
+            &balancing_cfg.route_choice,
         )?;
-        let (abs_workloads, chosen_paths) = master.work_off(
+        let multithreading::WorkedOff {
+            abs_workloads,
+            time_slice_workloads,
+            chosen_paths,
+            avg_num_of_found_paths,
+        } = master.work_off(
             route_pairs,
             &arc_ch_graph,
             rng,
-            balancing_cfg.monitoring.is_writing_for_smarts,
+            balancing_cfg.monitoring.is_writing_for_smarts
+                || balancing_cfg.monitoring.is_writing_for_sumo,
+            balancing_cfg.monitoring.time_slice,
         )?;
 
         // update graph with new values
@@ -352,14 +623,36 @@ mod simulation_pipeline {
             num_threads: balancing_cfg.num_threads,
         };
         io::evaluating_balance::Writer::write(&abs_workloads, &arc_ch_graph, &writing_cfg)?;
-        // write SMARTS-paths
+        io::evaluating_balance::Writer::write_geojson(&abs_workloads, &arc_ch_graph, &writing_cfg)?;
+        if let Some(time_slice_workloads) = time_slice_workloads {
+            io::evaluating_balance::Writer::write_time_slices(
+                &time_slice_workloads,
+                &arc_ch_graph,
+                &writing_cfg,
+            )?;
+        }
         if let Some(chosen_paths) = chosen_paths {
-            let tmp_cfg = configs::writing::smarts::Config {
-                file: writing_cfg
-                    .results_dir
-                    .join(defaults::smarts::XML_FILE_NAME),
-            };
-            io::smarts::Writer::write(&chosen_paths, &arc_ch_graph, &tmp_cfg)?;
+            // write SMARTS-paths
+            if writing_cfg.monitoring.is_writing_for_smarts {
+                let tmp_cfg = configs::writing::smarts::Config {
+                    file: writing_cfg
+                        .results_dir
+                        .join(defaults::smarts::XML_FILE_NAME),
+                    vehicles: writing_cfg.monitoring.smarts.clone(),
+                    seed: writing_cfg.seed,
+                };
+                io::smarts::Writer::write(&chosen_paths, &arc_ch_graph, &tmp_cfg)?;
+            }
+            // write SUMO-routes
+            if writing_cfg.monitoring.is_writing_for_sumo {
+                io::sumo::Writer::write_routes(
+                    &chosen_paths,
+                    &arc_ch_graph,
+                    &writing_cfg
+                        .results_dir
+                        .join(defaults::sumo::ROUTES_FILE_NAME),
+                )?;
+            }
         }
 
         info!(
@@ -369,7 +662,7 @@ mod simulation_pipeline {
         );
         info!("");
 
-        Ok(())
+        Ok((abs_workloads, num_of_routes, avg_num_of_found_paths))
     }
 }
 
@@ -492,4 +785,6 @@ fn write_edges(
 pub struct CmdlineArgs {
     pub max_log_level: String,
     pub cfg: String,
+    /// Results-dir of an interrupted balancing, which is continued after its last completed iteration
+    pub resume_dir: Option<PathBuf>,
 }
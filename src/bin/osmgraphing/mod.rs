@@ -1,16 +1,21 @@
 use log::{debug, error, info, warn};
 #[cfg(feature = "gpl")]
 mod balancing;
+#[cfg(feature = "gpl")]
+use osmgraphing::{
+    balancing::multithreading, defaults, routing::explorating::ConvexHullExplorator,
+};
 use osmgraphing::{
     configs::{self, routing::RoutingAlgo},
     helpers::{err, init_logging},
     io,
     network::{Graph, RoutePair},
-    routing::dijkstra::{self, Dijkstra},
+    routing::{
+        self,
+        dijkstra::{self, Dijkstra},
+    },
 };
 #[cfg(feature = "gpl")]
-use osmgraphing::{defaults, routing::explorating::ConvexHullExplorator};
-#[cfg(feature = "gpl")]
 use rand::SeedableRng;
 use std::{convert::TryFrom, path::PathBuf, time::Instant};
 #[cfg(feature = "gpl")]
@@ -62,7 +67,7 @@ fn run(args: CmdlineArgs) -> err::Feedback {
 
     // parse graph
 
-    let graph = {
+    let mut graph = {
         // get config by provided user-input
 
         let parsing_cfg = {
@@ -136,12 +141,75 @@ fn run(args: CmdlineArgs) -> err::Feedback {
         debug!("");
     }
 
+    // writing graph as geojson
+
+    if args.is_writing_geojson {
+        // get config by provided user-input
+
+        let writing_cfg = configs::writing::geojson::Config::try_from_yaml(&args.cfg)?;
+
+        // check if new file does already exist
+
+        if writing_cfg.file.exists() {
+            return Err(err::Msg::from(format!(
+                "New file {} does already exist. Please remove it.",
+                writing_cfg.file.display()
+            )));
+        }
+
+        // writing to file
+        io::geojson::Writer::write_graph(&graph, &writing_cfg)?;
+    }
+
+    // writing graph as SUMO-network
+
+    if args.is_writing_sumo {
+        // get config by provided user-input
+
+        let writing_cfg = configs::writing::sumo::Config::try_from_yaml(&args.cfg)?;
+
+        // check if new files do already exist
+
+        for file in &[&writing_cfg.nodes_file, &writing_cfg.edges_file] {
+            if file.exists() {
+                return Err(err::Msg::from(format!(
+                    "New file {} does already exist. Please remove it.",
+                    file.display()
+                )));
+            }
+        }
+
+        // writing to files
+        io::sumo::Writer::write_network(&graph, &writing_cfg)?;
+    }
+
+    // writing graph as DIMACS-files
+
+    if args.is_writing_dimacs {
+        // get config by provided user-input
+
+        let writing_cfg = configs::writing::dimacs::Config::try_from_yaml(&args.cfg)?;
+
+        // check if new files do already exist
+
+        for file in &[&writing_cfg.graph_file, &writing_cfg.coordinates_file] {
+            if file.exists() {
+                return Err(err::Msg::from(format!(
+                    "New file {} does already exist. Please remove it.",
+                    file.display()
+                )));
+            }
+        }
+
+        // writing to files
+        io::dimacs::Writer::write(&graph, &writing_cfg)?;
+    }
+
     // writing routes to file
 
     if args.is_writing_route_pairs {
         // get config by provided user-input
 
-        let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
         let writing_cfg = configs::writing::routing::Config::try_from_yaml(&args.cfg)?;
 
         // check if new file does already exist
@@ -157,7 +225,7 @@ fn run(args: CmdlineArgs) -> err::Feedback {
 
         // measure writing-time
         let now = Instant::now();
-        io::routing::Writer::write(&graph, &routing_cfg, &writing_cfg)?;
+        io::routing::Writer::write(&graph, &writing_cfg)?;
         debug!(
             "Finished writing in {} seconds ({} µs).",
             now.elapsed().as_secs(),
@@ -166,10 +234,18 @@ fn run(args: CmdlineArgs) -> err::Feedback {
         debug!("");
     }
 
+    // assigning traffic
+
+    if args.is_assigning {
+        do_assigning(&args, &mut graph)?;
+    }
+
     // routing-example
 
     if args.is_routing || args.is_evaluating_balance {
-        if !args.is_evaluating_balance {
+        if args.is_writing_queries {
+            do_batch_routing(&args, &graph)?;
+        } else if !args.is_evaluating_balance {
             do_simply_routing(&args, &graph)?;
         } else {
             #[cfg(feature = "gpl")]
@@ -182,22 +258,126 @@ fn run(args: CmdlineArgs) -> err::Feedback {
         balancing::run(balancing::CmdlineArgs {
             max_log_level: args.max_log_level.clone(),
             cfg: args.cfg.clone(),
+            resume_dir: args.resume_dir.clone(),
         })?;
     }
 
     Ok(())
 }
 
+fn do_assigning(args: &CmdlineArgs, graph: &mut Graph) -> err::Feedback {
+    // get config by provided user-input
+    let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
+    let assigning_cfg = configs::assigning::Config::try_from_yaml(&args.cfg)?;
+
+    // check if files exist
+    io::assigning::Writer::check(&assigning_cfg)?;
+
+    info!("EXECUTE Assign traffic via {:?}", assigning_cfg.method);
+
+    // get routing-pairs
+    let route_pairs: Vec<_> = io::routing::Parser::parse_entries(&routing_cfg, graph)?
+        .into_iter()
+        .map(|entry| (entry.route_pair.into_idx(&graph), entry.count))
+        .collect();
+
+    // measure assigning-time
+    let now = Instant::now();
+    let assignment = routing::assigning::assign(graph, &route_pairs, &assigning_cfg)?;
+    info!(
+        "Finished assigning in {} seconds ({} µs).",
+        now.elapsed().as_secs(),
+        now.elapsed().as_micros(),
+    );
+
+    io::assigning::Writer::write(&assignment, &graph, &assigning_cfg)
+}
+
+/// Routes all route-pairs in parallel and writes one result-row per route-pair.
+fn do_batch_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
+    // get config by provided user-input
+    let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
+    let queries_cfg = configs::writing::queries::Config::try_from_yaml(&args.cfg)?;
+    let paths_cfg = if args.is_writing_paths {
+        Some(configs::writing::paths::Config::try_from_yaml(&args.cfg)?)
+    } else {
+        None
+    };
+
+    // check if new files do already exist
+
+    for file in Some(&queries_cfg.file)
+        .into_iter()
+        .chain(paths_cfg.as_ref().map(|paths_cfg| &paths_cfg.file))
+    {
+        if file.exists() {
+            return Err(err::Msg::from(format!(
+                "New file {} does already exist. Please remove it.",
+                file.display()
+            )));
+        }
+    }
+
+    // route
+
+    let thread_pool = match queries_cfg.num_threads {
+        Some(num_threads) => Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .map_err(|e| format!("Couldn't build thread-pool due to error: {}", e))?,
+        ),
+        None => None,
+    };
+    let route_entries = io::routing::Parser::parse_entries(&routing_cfg, graph)?;
+    let now = Instant::now();
+    let results = routing::batch::route(route_entries, graph, &routing_cfg, thread_pool.as_ref())?;
+    info!(
+        "Ran {} queries in {} ms",
+        results.len(),
+        now.elapsed().as_micros() as f64 / 1_000.0,
+    );
+
+    // write
+
+    io::queries::Writer::write(&results, graph, &queries_cfg)?;
+    if let Some(paths_cfg) = paths_cfg {
+        let paths: Vec<_> = results
+            .into_iter()
+            .filter_map(|result| result.path)
+            .collect();
+        io::paths::Writer::write(&paths, graph, &paths_cfg)?;
+    }
+
+    Ok(())
+}
+
 fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
     // get config by provided user-input
     let routing_cfg = configs::routing::Config::try_from_yaml(&args.cfg, graph.cfg())?;
     info!("EXECUTE Do routing with alphas: {:?}", routing_cfg.alphas);
 
+    // computed paths are only kept, if they are written
+
+    let paths_cfg = if args.is_writing_paths {
+        let paths_cfg = configs::writing::paths::Config::try_from_yaml(&args.cfg)?;
+        if paths_cfg.file.exists() {
+            return Err(err::Msg::from(format!(
+                "New paths-file {} does already exist. Please remove it.",
+                paths_cfg.file.display()
+            )));
+        }
+        Some(paths_cfg)
+    } else {
+        None
+    };
+    let mut computed_paths = Vec::new();
+
     // get routing-pairs
-    let routing_pairs = io::routing::Parser::parse(&routing_cfg)?;
-    let iter_route_pairs = routing_pairs
+    let route_entries = io::routing::Parser::parse_entries(&routing_cfg, graph)?;
+    let iter_route_pairs = route_entries
         .iter()
-        .map(|(route_pair, route_count)| (route_pair.into_node(&graph), *route_count));
+        .map(|entry| (entry.route_pair.into_node(&graph), entry.count));
 
     match routing_cfg.routing_algo {
         RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => {
@@ -226,6 +406,9 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                         src,
                         dst
                     );
+                    if paths_cfg.is_some() {
+                        computed_paths.push(best_path);
+                    }
                 } else {
                     warn!("No path from ({}) to ({}).", src, dst);
                 }
@@ -259,7 +442,10 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
                 if !found_paths.is_empty() {
                     if !found_paths.is_empty() {
                         info!("Found {} path(s):", found_paths.len());
-                        found_paths.iter().for_each(|path| info!("  {}", path))
+                        found_paths.iter().for_each(|path| info!("  {}", path));
+                        if paths_cfg.is_some() {
+                            computed_paths.extend(found_paths);
+                        }
                     } else {
                         info!("No path found from ({}) to ({}).", src, dst);
                     }
@@ -270,6 +456,10 @@ fn do_simply_routing(args: &CmdlineArgs, graph: &Graph) -> err::Feedback {
         }
     }
 
+    if let Some(paths_cfg) = paths_cfg {
+        io::paths::Writer::write(&computed_paths, &graph, &paths_cfg)?;
+    }
+
     Ok(())
 }
 
@@ -287,35 +477,72 @@ fn do_evaluating_routing(args: &CmdlineArgs, arc_graph: &Arc<Graph>) -> err::Fee
     info!("EXECUTE Do routing with alphas: {:?}", routing_cfg.alphas);
 
     // get routing-pairs
-    let route_pairs = io::routing::Parser::parse(&routing_cfg)?;
+    let route_pairs: Vec<_> = io::routing::Parser::parse_entries(&routing_cfg, arc_graph)?
+        .into_iter()
+        .map(|entry| (entry.route_pair, entry.count, entry.departure))
+        .collect();
 
     // work-off multithreaded
 
     let arc_routing_cfg = Arc::new(routing_cfg);
-    let mut master = balancing::multithreading::Master::spawn_some(
+    let mut master = multithreading::Master::spawn_some(
         evaluating_balance_cfg.num_threads,
         &arc_graph,
         &arc_routing_cfg,
+        &defaults::balancing::ROUTE_CHOICE,
     )?;
-    let (abs_workloads, chosen_paths) = master.work_off(
+    let multithreading::WorkedOff {
+        abs_workloads,
+        time_slice_workloads,
+        chosen_paths,
+        avg_num_of_found_paths: _,
+    } = master.work_off(
         route_pairs,
         &arc_graph,
         &mut rng,
-        evaluating_balance_cfg.monitoring.is_writing_for_smarts,
+        evaluating_balance_cfg.monitoring.is_writing_for_smarts
+            || evaluating_balance_cfg.monitoring.is_writing_for_sumo,
+        evaluating_balance_cfg.monitoring.time_slice,
     )?;
 
     // write results from (optional) evaluation
 
     fs::create_dir_all(&evaluating_balance_cfg.results_dir)?;
     io::evaluating_balance::Writer::write(&abs_workloads, &arc_graph, &evaluating_balance_cfg)?;
-    // write SMARTS-paths
+    io::evaluating_balance::Writer::write_geojson(
+        &abs_workloads,
+        &arc_graph,
+        &evaluating_balance_cfg,
+    )?;
+    if let Some(time_slice_workloads) = time_slice_workloads {
+        io::evaluating_balance::Writer::write_time_slices(
+            &time_slice_workloads,
+            &arc_graph,
+            &evaluating_balance_cfg,
+        )?;
+    }
     if let Some(chosen_paths) = chosen_paths {
-        let tmp_cfg = configs::writing::smarts::Config {
-            file: evaluating_balance_cfg
-                .results_dir
-                .join(defaults::smarts::XML_FILE_NAME),
-        };
-        io::smarts::Writer::write(&chosen_paths, &arc_graph, &tmp_cfg)?;
+        // write SMARTS-paths
+        if evaluating_balance_cfg.monitoring.is_writing_for_smarts {
+            let tmp_cfg = configs::writing::smarts::Config {
+                file: evaluating_balance_cfg
+                    .results_dir
+                    .join(defaults::smarts::XML_FILE_NAME),
+                vehicles: evaluating_balance_cfg.monitoring.smarts.clone(),
+                seed: evaluating_balance_cfg.seed,
+            };
+            io::smarts::Writer::write(&chosen_paths, &arc_graph, &tmp_cfg)?;
+        }
+        // write SUMO-routes
+        if evaluating_balance_cfg.monitoring.is_writing_for_sumo {
+            io::sumo::Writer::write_routes(
+                &chosen_paths,
+                &arc_graph,
+                &evaluating_balance_cfg
+                    .results_dir
+                    .join(defaults::sumo::ROUTES_FILE_NAME),
+            )?;
+        }
     }
 
     Ok(())
@@ -391,6 +618,42 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
         args.arg(arg_is_writing_edges)
     };
 
+    let args = {
+        let arg_is_writing_geojson = clap::Arg::with_name(constants::ids::IS_WRITING_GEOJSON)
+            .long("writing_geojson")
+            .help(
+                "The generated graph will be exported as GeoJSON \
+               as described in the provided config.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::CFG);
+        args.arg(arg_is_writing_geojson)
+    };
+
+    let args = {
+        let arg_is_writing_sumo = clap::Arg::with_name(constants::ids::IS_WRITING_SUMO)
+            .long("writing_sumo")
+            .help(
+                "The generated graph will be exported as SUMO-network (plain-xml) \
+               as described in the provided config.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::CFG);
+        args.arg(arg_is_writing_sumo)
+    };
+
+    let args = {
+        let arg_is_writing_dimacs = clap::Arg::with_name(constants::ids::IS_WRITING_DIMACS)
+            .long("writing_dimacs")
+            .help(
+                "The generated graph will be exported as DIMACS-files (*.gr and *.co) \
+               as described in the provided config.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::CFG);
+        args.arg(arg_is_writing_dimacs)
+    };
+
     let args = {
         let arg_is_writing_route_pairs =
             clap::Arg::with_name(constants::ids::IS_WRITING_ROUTE_PAIRS)
@@ -414,6 +677,32 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
         args.arg(arg_is_routing)
     };
 
+    let args = {
+        let arg_is_writing_queries = clap::Arg::with_name(constants::ids::IS_WRITING_QUERIES)
+            .long("writing_queries")
+            .help(
+                "Instead of logging, all route-pairs are routed in parallel \
+               and their results are exported as described in the provided config.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::IS_ROUTING)
+            .conflicts_with(constants::ids::IS_EVALUATING_BALANCE);
+        args.arg(arg_is_writing_queries)
+    };
+
+    let args = {
+        let arg_is_writing_paths = clap::Arg::with_name(constants::ids::IS_WRITING_PATHS)
+            .long("writing_paths")
+            .help(
+                "The computed paths (or explorated alternatives) will be exported \
+               as GPX or KML as described in the provided config.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::IS_ROUTING)
+            .conflicts_with(constants::ids::IS_EVALUATING_BALANCE);
+        args.arg(arg_is_writing_paths)
+    };
+
     let args = {
         let arg_is_balancing = clap::Arg::with_name(constants::ids::IS_BALANCING)
             .long("balancing")
@@ -439,6 +728,21 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
         args.arg(arg_is_balancing)
     };
 
+    let args = {
+        let arg_resume_dir = clap::Arg::with_name(constants::ids::RESUME_DIR)
+            .long("resume")
+            .value_name("RESULTS_DIR")
+            .help(
+                "Continues an interrupted balancing after its last completed iteration, \
+                which is stored in the provided results-dir (e.g. <RESULTS_DIR/DATE>). \
+                Please provide the same config as for the interrupted balancing.",
+            )
+            .takes_value(true)
+            .hidden(!cfg!(feature = "gpl"))
+            .requires(constants::ids::IS_BALANCING);
+        args.arg(arg_resume_dir)
+    };
+
     let args = {
         let arg_is_evaluating_balance = clap::Arg::with_name(constants::ids::IS_EVALUATING_BALANCE)
             .long("evaluating_balance")
@@ -456,6 +760,27 @@ fn parse_cmdline<'a>() -> err::Result<CmdlineArgs> {
         args.arg(arg_is_evaluating_balance)
     };
 
+    let args = {
+        let arg_is_assigning = clap::Arg::with_name(constants::ids::IS_ASSIGNING)
+            .long("assigning")
+            .help(
+                "Assigns the route-pairs of the routing-config to the graph, until the \
+                user-equilibrium is reached (static traffic-assignment). Travel-times follow \
+                BPR-functions with capacities derived from lane-counts and speed-limits.\n\
+                \n\
+                Hence a correct config-file contains following:\n\
+                - A parsing-config reading the graph without shortcuts.\n\
+                - A routing-config providing the route-pairs.\n\
+                - An assigning-config defining the settings for the assignment.\n\
+                \n\
+                Flows and travel-times per edge and the relative gap per iteration are written \
+                into the results-dir.",
+            )
+            .takes_value(false)
+            .requires(constants::ids::CFG);
+        args.arg(arg_is_assigning)
+    };
+
     CmdlineArgs::try_from(args.get_matches())
 }
 
@@ -465,11 +790,18 @@ mod constants {
         pub const CFG: &str = "cfg";
         pub const IS_WRITING_GRAPH: &str = "is_writing_graph";
         pub const IS_WRITING_EDGES: &str = "is_writing_edges";
+        pub const IS_WRITING_GEOJSON: &str = "is_writing_geojson";
+        pub const IS_WRITING_SUMO: &str = "is_writing_sumo";
+        pub const IS_WRITING_DIMACS: &str = "is_writing_dimacs";
         pub const IS_WRITING_ROUTE_PAIRS: &str = "is_writing_route_pairs";
         pub const IS_ROUTING: &str = "is_routing";
+        pub const IS_WRITING_PATHS: &str = "is_writing_paths";
+        pub const IS_WRITING_QUERIES: &str = "is_writing_queries";
         pub const IS_EXPLORATING: &str = "is_explorating";
         pub const IS_BALANCING: &str = "is_balancing";
+        pub const RESUME_DIR: &str = "resume_dir";
         pub const IS_EVALUATING_BALANCE: &str = "is_evaluating_balance";
+        pub const IS_ASSIGNING: &str = "is_assigning";
     }
 }
 
@@ -478,11 +810,19 @@ struct CmdlineArgs {
     cfg: String,
     is_writing_graph: bool,
     is_writing_edges: bool,
+    is_writing_geojson: bool,
+    is_writing_sumo: bool,
+    is_writing_dimacs: bool,
     is_writing_route_pairs: bool,
     is_routing: bool,
+    is_writing_paths: bool,
+    is_writing_queries: bool,
     #[cfg(feature = "gpl")]
     is_balancing: bool,
+    #[cfg(feature = "gpl")]
+    resume_dir: Option<PathBuf>,
     is_evaluating_balance: bool,
+    is_assigning: bool,
 }
 
 impl<'a> TryFrom<clap::ArgMatches<'a>> for CmdlineArgs {
@@ -497,13 +837,22 @@ impl<'a> TryFrom<clap::ArgMatches<'a>> for CmdlineArgs {
             .expect(&format!("cmdline-arg: {}", constants::ids::CFG));
         let is_writing_graph = matches.is_present(constants::ids::IS_WRITING_GRAPH);
         let is_writing_edges = matches.is_present(constants::ids::IS_WRITING_EDGES);
+        let is_writing_geojson = matches.is_present(constants::ids::IS_WRITING_GEOJSON);
+        let is_writing_sumo = matches.is_present(constants::ids::IS_WRITING_SUMO);
+        let is_writing_dimacs = matches.is_present(constants::ids::IS_WRITING_DIMACS);
         let is_writing_route_pairs = matches.is_present(constants::ids::IS_WRITING_ROUTE_PAIRS);
         let is_routing = matches.is_present(constants::ids::IS_ROUTING);
+        let is_writing_paths = matches.is_present(constants::ids::IS_WRITING_PATHS);
+        let is_writing_queries = matches.is_present(constants::ids::IS_WRITING_QUERIES);
         let is_explorating = matches.is_present(constants::ids::IS_EXPLORATING);
         let is_balancing = matches.is_present(constants::ids::IS_BALANCING);
+        let resume_dir = matches
+            .value_of(constants::ids::RESUME_DIR)
+            .map(PathBuf::from);
         let is_evaluating_balance = matches.is_present(constants::ids::IS_EVALUATING_BALANCE);
+        let is_assigning = matches.is_present(constants::ids::IS_ASSIGNING);
 
-        if is_explorating || is_balancing || is_evaluating_balance {
+        if is_explorating || is_balancing || resume_dir.is_some() || is_evaluating_balance {
             check_for_activated_feature()?;
         }
 
@@ -512,11 +861,19 @@ impl<'a> TryFrom<clap::ArgMatches<'a>> for CmdlineArgs {
             cfg: String::from(cfg),
             is_writing_graph,
             is_writing_edges,
+            is_writing_geojson,
+            is_writing_sumo,
+            is_writing_dimacs,
             is_writing_route_pairs,
             is_routing,
+            is_writing_paths,
+            is_writing_queries,
             #[cfg(feature = "gpl")]
             is_balancing,
+            #[cfg(feature = "gpl")]
+            resume_dir,
             is_evaluating_balance,
+            is_assigning,
         })
     }
 }
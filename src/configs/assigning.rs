@@ -0,0 +1,206 @@
+use crate::{configs::SimpleId, defaults, helpers::err, io::SupportingFileExts};
+use serde::Deserialize;
+use std::{
+    convert::TryFrom,
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Static traffic-assignment, computing the user-equilibrium of the route-pairs given in the routing-config.
+///
+/// Travel-times of edges follow the BPR-function `t = t_0 * (1 + alpha * (flow / capacity)^beta)`, where the capacity is derived from the lane-count and the speed-limit.
+/// Route-counts are interpreted as vehicles per hour.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub results_dir: PathBuf,
+    pub method: Method,
+    pub max_iter: usize,
+    pub relative_gap: f64,
+    pub bpr: Bpr,
+    pub metrics: Metrics,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl FromStr for Config {
+    type Err = err::Msg;
+
+    fn from_str(yaml_str: &str) -> err::Result<Config> {
+        Config::try_from_str(yaml_str)
+    }
+}
+
+impl Config {
+    pub fn try_from_str(yaml_str: &str) -> err::Result<Config> {
+        let proto_cfg: ProtoConfig = {
+            match serde_yaml::from_str(yaml_str) {
+                Ok(proto_cfg) => proto_cfg,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Serde couldn't parse yaml-str due to error: {}",
+                        e
+                    )))
+                }
+            }
+        };
+        Ok(Config::from(proto_cfg))
+    }
+
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: ProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        Ok(Config::from(proto_cfg))
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            results_dir: proto_cfg.results_dir,
+            method: proto_cfg.method.unwrap_or(defaults::assigning::METHOD),
+            max_iter: proto_cfg.max_iter.unwrap_or(defaults::assigning::MAX_ITER),
+            relative_gap: proto_cfg
+                .relative_gap
+                .unwrap_or(defaults::assigning::RELATIVE_GAP),
+            bpr: proto_cfg.bpr.unwrap_or(Bpr {
+                alpha: defaults::assigning::bpr::ALPHA,
+                beta: defaults::assigning::bpr::BETA,
+            }),
+            metrics: proto_cfg.metrics,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum Method {
+    /// The step-size of every iteration is found by a line-search minimizing the Beckmann-objective.
+    #[serde(rename = "frank-wolfe")]
+    FrankWolfe,
+    /// Method of successive averages, where the `k`-th step-size is `1 / (k + 1)`, starting with `1 / 2`.
+    #[serde(rename = "msa")]
+    Msa,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Bpr {
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+/// Ids of the graph's metrics, which are needed for the assignment.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Metrics {
+    /// The travel-time without any traffic, which is replaced by the travel-time under traffic while assigning.
+    #[serde(rename = "free-flow-time")]
+    pub free_flow_time: SimpleId,
+    #[serde(rename = "lane-count")]
+    pub lane_count: SimpleId,
+    #[serde(rename = "speed")]
+    pub kmph: SimpleId,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "RawConfig")]
+pub struct ProtoConfig {
+    pub results_dir: PathBuf,
+    pub method: Option<Method>,
+    pub max_iter: Option<usize>,
+    pub relative_gap: Option<f64>,
+    pub bpr: Option<Bpr>,
+    pub metrics: Metrics,
+}
+
+impl TryFrom<RawConfig> for ProtoConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: RawConfig) -> err::Result<ProtoConfig> {
+        let raw_cfg = raw_cfg.assigning;
+
+        if let Some(relative_gap) = raw_cfg.relative_gap {
+            if relative_gap.is_nan() || relative_gap < 0.0 {
+                return Err(format!(
+                    "The relative-gap should be non-negative, but is {}.",
+                    relative_gap
+                )
+                .into());
+            }
+        }
+        if let Some(bpr) = &raw_cfg.bpr {
+            if !bpr.alpha.is_finite() || bpr.alpha < 0.0 || !bpr.beta.is_finite() || bpr.beta < 0.0
+            {
+                return Err(format!(
+                    "The bpr-parameters should be non-negative, but are {:?}.",
+                    bpr
+                )
+                .into());
+            }
+        }
+
+        Ok(ProtoConfig {
+            results_dir: raw_cfg.results_dir,
+            method: raw_cfg.method,
+            max_iter: raw_cfg.max_iter,
+            relative_gap: raw_cfg.relative_gap,
+            bpr: raw_cfg.bpr,
+            metrics: raw_cfg.metrics,
+        })
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub assigning: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    #[serde(rename = "results-dir")]
+    pub results_dir: PathBuf,
+    pub method: Option<Method>,
+    #[serde(rename = "number_of_iterations")]
+    pub max_iter: Option<usize>,
+    #[serde(rename = "relative-gap")]
+    pub relative_gap: Option<f64>,
+    pub bpr: Option<Bpr>,
+    pub metrics: Metrics,
+}
@@ -5,8 +5,10 @@ use crate::{
     io::SupportingFileExts,
     multi_ch_constructor,
 };
+use kissunits::time::Seconds;
 use serde::Deserialize;
 use std::{
+    convert::TryFrom,
     fs::OpenOptions,
     path::{Path, PathBuf},
 };
@@ -14,11 +16,15 @@ use std::{
 #[derive(Clone, Debug)]
 pub struct Config {
     pub results_dir: PathBuf,
+    pub contraction: Contraction,
     pub multi_ch_constructor: multi_ch_constructor::Config,
     pub iter_0_cfg: PathBuf,
     pub iter_i_cfg: PathBuf,
     pub optimization: Optimization,
+    pub route_choice: RouteChoice,
+    /// Maximum number of iterations, including the last one for analysing the balanced graph
     pub num_iter: usize,
+    pub stopping: Stopping,
     pub monitoring: MonitoringConfig,
     pub num_threads: usize,
     pub seed: u64,
@@ -99,11 +105,22 @@ impl From<ProtoConfig> for Config {
             results_dir: proto_cfg.results_dir,
             iter_0_cfg: proto_cfg.iter_0_cfg,
             iter_i_cfg: proto_cfg.iter_i_cfg,
+            contraction: proto_cfg
+                .contraction
+                .unwrap_or(defaults::balancing::CONTRACTION),
             multi_ch_constructor: proto_cfg.multi_ch_constructor,
             // +1 because analysing last graph needs one iteration as well
             num_iter: proto_cfg.num_metric_updates + 1,
+            stopping: proto_cfg.stopping.map(Stopping::from).unwrap_or(Stopping {
+                tolerance: None,
+                plateau: None,
+            }),
             monitoring: MonitoringConfig::from(proto_cfg.monitoring),
             optimization: Optimization::from(proto_cfg.optimization),
+            route_choice: proto_cfg
+                .route_choice
+                .map(RouteChoice::from)
+                .unwrap_or(defaults::balancing::ROUTE_CHOICE),
             num_threads: proto_cfg
                 .num_threads
                 .unwrap_or(defaults::balancing::NUM_THREADS),
@@ -115,6 +132,17 @@ impl From<ProtoConfig> for Config {
     }
 }
 
+/// Defines how the graph is contracted in every iteration.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum Contraction {
+    /// The graph is written, contracted by the external multi-ch-constructor and parsed again.
+    #[serde(rename = "multi-ch-constructor")]
+    MultiChConstructor,
+    /// The graph is preprocessed once and its shortcuts are customized in memory after every metric-update.
+    #[serde(rename = "customizable")]
+    Customizable,
+}
+
 #[derive(Clone, Debug)]
 pub struct Optimization {
     pub metric_id: SimpleId,
@@ -147,10 +175,77 @@ impl From<ProtoOptimizationMethod> for OptimizationMethod {
     }
 }
 
+/// Defines how the routes of a route-pair are distributed over the paths found by the explorator.
+///
+/// Logit-models weight the paths by their costs (wrt the routing-alphas) relative to the cheapest path's costs.
+/// Hence a path with `scale` percent more costs than the cheapest path is chosen `e` times less likely (ignoring path-sizes).
+#[derive(Clone, Debug, PartialEq)]
+pub enum RouteChoice {
+    /// Every found path is chosen with the same probability.
+    Uniform,
+    /// The probability of a path is proportional to `exp(-scale * relative_costs)`.
+    MultinomialLogit { scale: f64 },
+    /// Like the multinomial-logit, but paths are weighted with their path-size as well,
+    /// reducing the probabilities of overlapping paths.
+    PathSizeLogit { scale: f64 },
+    /// The routes are distributed deterministically according to the multinomial-logit,
+    /// where remaining routes are given to the cheapest paths first.
+    /// Hence a single route always takes the cheapest path.
+    CheapestFirst { scale: f64 },
+}
+
+impl From<ProtoRouteChoice> for RouteChoice {
+    fn from(proto_route_choice: ProtoRouteChoice) -> RouteChoice {
+        match proto_route_choice {
+            ProtoRouteChoice::Uniform => RouteChoice::Uniform,
+            ProtoRouteChoice::MultinomialLogit { scale } => RouteChoice::MultinomialLogit { scale },
+            ProtoRouteChoice::PathSizeLogit { scale } => RouteChoice::PathSizeLogit { scale },
+            ProtoRouteChoice::CheapestFirst { scale } => RouteChoice::CheapestFirst { scale },
+        }
+    }
+}
+
+/// Criteria for stopping the balancing before the maximum number of metric-updates is reached.
+///
+/// If one criterion is fulfilled after an iteration, the next iteration is the last one, analysing the balanced graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stopping {
+    /// Stops, when the workloads' relative change to the previous iteration is at most this tolerance.
+    pub tolerance: Option<f64>,
+    pub plateau: Option<Plateau>,
+}
+
+impl From<ProtoStopping> for Stopping {
+    fn from(proto_stopping: ProtoStopping) -> Stopping {
+        Stopping {
+            tolerance: proto_stopping.tolerance,
+            plateau: proto_stopping.plateau.map(|plateau| Plateau {
+                num_iter: plateau.num_iter,
+                min_improvement: plateau.min_improvement,
+            }),
+        }
+    }
+}
+
+/// Stops, when the workloads' Gini-coefficient of the last `num_iter` iterations hasn't improved by more than `min_improvement` compared to the iterations before.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plateau {
+    pub num_iter: usize,
+    pub min_improvement: f64,
+}
+
 #[derive(Clone, Debug)]
 pub struct MonitoringConfig {
     pub edges_info: configs::writing::network::edges::Config,
     pub is_writing_for_smarts: bool,
+    /// Vehicle-attributes of the exported SMARTS-vehicles
+    pub smarts: configs::writing::smarts::Vehicles,
+    pub is_writing_for_sumo: bool,
+    /// If true, the absolute workloads are additionally written as styled GeoJSON.
+    pub is_writing_geojson: bool,
+    /// If given, workloads are additionally written per time-slice of this duration,
+    /// where routes count for the time-slice of their departure.
+    pub time_slice: Option<Seconds>,
 }
 
 impl From<ProtoMonitoringConfig> for MonitoringConfig {
@@ -160,6 +255,14 @@ impl From<ProtoMonitoringConfig> for MonitoringConfig {
             is_writing_for_smarts: proto_cfg
                 .is_writing_for_smarts
                 .unwrap_or(defaults::smarts::IS_WRITING),
+            smarts: proto_cfg.smarts.unwrap_or_default(),
+            is_writing_for_sumo: proto_cfg
+                .is_writing_for_sumo
+                .unwrap_or(defaults::sumo::IS_WRITING),
+            is_writing_geojson: proto_cfg
+                .is_writing_geojson
+                .unwrap_or(defaults::balancing::stats::IS_WRITING_GEOJSON),
+            time_slice: proto_cfg.time_slice,
         }
     }
 }
@@ -172,32 +275,85 @@ pub struct ProtoConfig {
     pub results_dir: PathBuf,
     pub iter_0_cfg: PathBuf,
     pub iter_i_cfg: PathBuf,
+    pub contraction: Option<Contraction>,
     pub multi_ch_constructor: multi_ch_constructor::Config,
     pub num_metric_updates: usize,
+    pub stopping: Option<ProtoStopping>,
     pub monitoring: ProtoMonitoringConfig,
     pub optimization: ProtoOptimization,
+    pub route_choice: Option<ProtoRouteChoice>,
     pub num_threads: Option<usize>,
     pub min_new_metric: Option<f64>,
     pub is_err_when_metric_is_zero: Option<bool>,
 }
 
-impl From<RawConfig> for ProtoConfig {
-    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+impl TryFrom<RawConfig> for ProtoConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: RawConfig) -> err::Result<ProtoConfig> {
         let raw_cfg = raw_cfg.balancing;
 
-        ProtoConfig {
+        Ok(ProtoConfig {
             seed: raw_cfg.seed,
             results_dir: raw_cfg.results_dir,
+            contraction: raw_cfg.contraction,
             multi_ch_constructor: raw_cfg.multi_ch_constructor,
             num_metric_updates: raw_cfg.number_of_metric_updates,
+            stopping: match raw_cfg.stopping {
+                Some(raw_stopping) => Some(ProtoStopping::try_from(raw_stopping)?),
+                None => None,
+            },
             iter_0_cfg: raw_cfg.iter_0_cfg,
             iter_i_cfg: raw_cfg.iter_i_cfg,
-            monitoring: ProtoMonitoringConfig::from(raw_cfg.monitoring),
+            monitoring: ProtoMonitoringConfig::try_from(raw_cfg.monitoring)?,
             optimization: ProtoOptimization::from(raw_cfg.optimization),
+            route_choice: match raw_cfg.route_choice {
+                Some(raw_route_choice) => Some(ProtoRouteChoice::try_from(raw_route_choice)?),
+                None => None,
+            },
             num_threads: raw_cfg.num_threads,
             min_new_metric: raw_cfg.min_new_metric,
             is_err_when_metric_is_zero: raw_cfg.is_err_when_metric_is_zero,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoStopping {
+    tolerance: Option<f64>,
+    plateau: Option<RawPlateau>,
+}
+
+impl TryFrom<RawStopping> for ProtoStopping {
+    type Error = err::Msg;
+
+    fn try_from(raw_stopping: RawStopping) -> err::Result<ProtoStopping> {
+        if let Some(tolerance) = raw_stopping.tolerance {
+            if !tolerance.is_finite() || tolerance < 0.0 {
+                return Err(format!(
+                    "The stopping-tolerance should be non-negative, but is {}.",
+                    tolerance
+                )
+                .into());
+            }
         }
+        if let Some(plateau) = &raw_stopping.plateau {
+            if plateau.num_iter == 0 {
+                return Err("The plateau should span at least one iteration.".into());
+            }
+            if !plateau.min_improvement.is_finite() || plateau.min_improvement < 0.0 {
+                return Err(format!(
+                    "The plateau's min-improvement should be non-negative, but is {}.",
+                    plateau.min_improvement
+                )
+                .into());
+            }
+        }
+
+        Ok(ProtoStopping {
+            tolerance: raw_stopping.tolerance,
+            plateau: raw_stopping.plateau,
+        })
     }
 }
 
@@ -205,14 +361,39 @@ impl From<RawConfig> for ProtoConfig {
 pub struct ProtoMonitoringConfig {
     pub edges_info: configs::writing::network::edges::Config,
     pub is_writing_for_smarts: Option<bool>,
+    pub smarts: Option<configs::writing::smarts::Vehicles>,
+    pub is_writing_for_sumo: Option<bool>,
+    pub is_writing_geojson: Option<bool>,
+    pub time_slice: Option<Seconds>,
 }
 
-impl From<RawMonitoringConfig> for ProtoMonitoringConfig {
-    fn from(raw_cfg: RawMonitoringConfig) -> ProtoMonitoringConfig {
-        ProtoMonitoringConfig {
+impl TryFrom<RawMonitoringConfig> for ProtoMonitoringConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: RawMonitoringConfig) -> err::Result<ProtoMonitoringConfig> {
+        if let Some(time_slice) = raw_cfg.time_slice {
+            if !time_slice.is_finite() || time_slice <= 0.0 {
+                return Err(format!(
+                    "The time-slice should be positive, but is {} seconds.",
+                    time_slice
+                )
+                .into());
+            }
+        }
+
+        Ok(ProtoMonitoringConfig {
             edges_info: configs::writing::network::edges::Config::from(raw_cfg.edges_info),
             is_writing_for_smarts: raw_cfg.is_writing_for_smarts,
-        }
+            smarts: match raw_cfg.smarts {
+                Some(raw_vehicles) => {
+                    Some(configs::writing::smarts::Vehicles::try_from(raw_vehicles)?)
+                }
+                None => None,
+            },
+            is_writing_for_sumo: raw_cfg.is_writing_for_sumo,
+            is_writing_geojson: raw_cfg.is_writing_geojson,
+            time_slice: raw_cfg.time_slice.map(Seconds),
+        })
     }
 }
 
@@ -248,6 +429,44 @@ impl From<RawOptimizationMethod> for ProtoOptimizationMethod {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum ProtoRouteChoice {
+    Uniform,
+    MultinomialLogit { scale: f64 },
+    PathSizeLogit { scale: f64 },
+    CheapestFirst { scale: f64 },
+}
+
+impl TryFrom<RawRouteChoice> for ProtoRouteChoice {
+    type Error = err::Msg;
+
+    fn try_from(raw_route_choice: RawRouteChoice) -> err::Result<ProtoRouteChoice> {
+        match raw_route_choice {
+            RawRouteChoice::MultinomialLogit { scale }
+            | RawRouteChoice::PathSizeLogit { scale }
+            | RawRouteChoice::CheapestFirst { scale } => {
+                if !scale.is_finite() || scale < 0.0 {
+                    return Err(format!(
+                        "The scale of the route-choice should be non-negative, but is {}.",
+                        scale
+                    )
+                    .into());
+                }
+            }
+            RawRouteChoice::Uniform => (),
+        }
+
+        Ok(match raw_route_choice {
+            RawRouteChoice::Uniform => ProtoRouteChoice::Uniform,
+            RawRouteChoice::MultinomialLogit { scale } => {
+                ProtoRouteChoice::MultinomialLogit { scale }
+            }
+            RawRouteChoice::PathSizeLogit { scale } => ProtoRouteChoice::PathSizeLogit { scale },
+            RawRouteChoice::CheapestFirst { scale } => ProtoRouteChoice::CheapestFirst { scale },
+        })
+    }
+}
+
 /// Don't deny unknown fields to allow multiple configs in one yaml-file.
 #[derive(Debug, Deserialize)]
 pub struct RawConfig {
@@ -264,13 +483,17 @@ pub struct RawContent {
     pub iter_0_cfg: PathBuf,
     #[serde(rename = "iter-i-cfg")]
     pub iter_i_cfg: PathBuf,
+    pub contraction: Option<Contraction>,
     #[serde(flatten)]
     pub multi_ch_constructor: multi_ch_constructor::Config,
     #[serde(rename = "number_of_metric-updates")]
     pub number_of_metric_updates: usize,
+    pub stopping: Option<RawStopping>,
     pub monitoring: RawMonitoringConfig,
     #[serde(rename = "optimizing_with")]
     pub optimization: RawOptimization,
+    #[serde(rename = "route-choice")]
+    pub route_choice: Option<RawRouteChoice>,
     #[serde(rename = "number_of_threads")]
     pub num_threads: Option<usize>,
     pub min_new_metric: Option<f64>,
@@ -278,6 +501,22 @@ pub struct RawContent {
     pub is_err_when_metric_is_zero: Option<bool>,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawStopping {
+    tolerance: Option<f64>,
+    plateau: Option<RawPlateau>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawPlateau {
+    #[serde(rename = "number_of_iterations")]
+    num_iter: usize,
+    #[serde(rename = "min-improvement")]
+    min_improvement: f64,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RawMonitoringConfig {
@@ -285,6 +524,15 @@ pub struct RawMonitoringConfig {
     edges_info: configs::writing::network::edges::ProtoConfig,
     #[serde(rename = "export_vehicles_for_SMARTS")]
     is_writing_for_smarts: Option<bool>,
+    #[serde(rename = "SMARTS")]
+    smarts: Option<configs::writing::smarts::RawVehicles>,
+    #[serde(rename = "export_vehicles_for_SUMO")]
+    is_writing_for_sumo: Option<bool>,
+    #[serde(rename = "geojson")]
+    is_writing_geojson: Option<bool>,
+    /// in seconds
+    #[serde(rename = "time-slice")]
+    time_slice: Option<f64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -308,3 +556,16 @@ pub enum RawOptimizationMethod {
     // some kind of correction-function:
     // interpolating linear between point-pairs given in a file?
 }
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawRouteChoice {
+    #[serde(rename = "uniform")]
+    Uniform,
+    #[serde(rename = "multinomial-logit")]
+    MultinomialLogit { scale: f64 },
+    #[serde(rename = "path-size-logit")]
+    PathSizeLogit { scale: f64 },
+    #[serde(rename = "cheapest-first")]
+    CheapestFirst { scale: f64 },
+}
@@ -0,0 +1,61 @@
+use crate::defaults;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+/// Format of files with edge-data according to [RFC 4180](https://tools.ietf.org/html/rfc4180).
+///
+/// If no csv-config is given, columns are separated by whitespace,
+/// and lines starting with `#` are skipped (like before).
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "RawConfig")]
+pub struct Config {
+    pub delimiter: char,
+    /// Fields containing the delimiter, the quote or line-breaks are quoted,
+    /// where quotes inside fields are doubled.
+    pub quote: char,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            delimiter: defaults::writing::csv::DELIMITER,
+            quote: defaults::writing::csv::QUOTE,
+        }
+    }
+}
+
+impl TryFrom<RawConfig> for Config {
+    type Error = String;
+
+    fn try_from(raw_cfg: RawConfig) -> Result<Config, String> {
+        let cfg = Config {
+            delimiter: raw_cfg
+                .delimiter
+                .unwrap_or(defaults::writing::csv::DELIMITER),
+            quote: raw_cfg.quote.unwrap_or(defaults::writing::csv::QUOTE),
+        };
+
+        if cfg.delimiter == cfg.quote {
+            return Err(format!(
+                "The csv-delimiter and the csv-quote should differ, but are both '{}'.",
+                cfg.delimiter
+            ));
+        }
+        for c in &[cfg.delimiter, cfg.quote] {
+            if *c == '\n' || *c == '\r' {
+                return Err(String::from(
+                    "The csv-delimiter and the csv-quote should not be line-breaks.",
+                ));
+            }
+        }
+
+        Ok(cfg)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    pub delimiter: Option<char>,
+    pub quote: Option<char>,
+}
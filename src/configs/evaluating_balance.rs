@@ -1,6 +1,7 @@
 use crate::{configs, defaults, helpers::err, io::SupportingFileExts};
 use serde::Deserialize;
 use std::{
+    convert::TryFrom,
     fs::OpenOptions,
     path::{Path, PathBuf},
 };
@@ -107,16 +108,18 @@ pub struct ProtoConfig {
     pub num_threads: Option<usize>,
 }
 
-impl From<RawConfig> for ProtoConfig {
-    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+impl TryFrom<RawConfig> for ProtoConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: RawConfig) -> err::Result<ProtoConfig> {
         let raw_cfg = raw_cfg.evaluating_balance;
 
-        ProtoConfig {
+        Ok(ProtoConfig {
             seed: raw_cfg.seed,
             results_dir: raw_cfg.results_dir,
-            monitoring: super::balancing::ProtoMonitoringConfig::from(raw_cfg.monitoring),
+            monitoring: super::balancing::ProtoMonitoringConfig::try_from(raw_cfg.monitoring)?,
             num_threads: raw_cfg.num_threads,
-        }
+        })
     }
 }
 
@@ -1,8 +1,10 @@
 use serde::Deserialize;
 use std::{fmt, fmt::Display};
 
+pub mod assigning;
 #[cfg(feature = "gpl")]
 pub mod balancing;
+pub mod csv;
 #[cfg(feature = "gpl")]
 pub mod evaluating_balance;
 pub mod parsing;
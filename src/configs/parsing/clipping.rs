@@ -0,0 +1,152 @@
+use crate::{defaults, helpers::err};
+use kissunits::geo::Coordinate;
+use serde::Deserialize;
+use std::{convert::TryFrom, path::PathBuf};
+
+/// Restricts the parsed graph to an area, e.g. to carve a city out of a bigger extract.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub area: Area,
+    pub boundary: Boundary,
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let area = Area::from(proto_cfg.area);
+
+        if let Area::BoundingBox { min, max } = &area {
+            if min.lat > max.lat || min.lon > max.lon {
+                return Err(format!(
+                    "The clipping-bbox has min ({}) greater than max ({}).",
+                    min, max
+                )
+                .into());
+            }
+        }
+
+        Ok(Config {
+            area,
+            boundary: proto_cfg
+                .boundary
+                .unwrap_or(defaults::parsing::clipping::BOUNDARY),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Area {
+    BoundingBox {
+        min: Coordinate,
+        max: Coordinate,
+    },
+    /// Polygon-file in the format of Osmosis (`*.poly`)
+    Polygon {
+        file: PathBuf,
+    },
+}
+
+impl From<ProtoArea> for Area {
+    fn from(proto_area: ProtoArea) -> Area {
+        match proto_area {
+            ProtoArea::BoundingBox {
+                min_lat,
+                min_lon,
+                max_lat,
+                max_lon,
+            } => Area::BoundingBox {
+                min: Coordinate {
+                    lat: min_lat,
+                    lon: min_lon,
+                },
+                max: Coordinate {
+                    lat: max_lat,
+                    lon: max_lon,
+                },
+            },
+            ProtoArea::Polygon { file } => Area::Polygon { file },
+        }
+    }
+}
+
+/// Defines what happens with edges crossing the area's boundary.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum Boundary {
+    /// Edges are only kept, if both of their nodes are inside the area.
+    Cut,
+    /// Edges are kept, if at least one of their nodes is inside the area.
+    Keep,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub area: ProtoArea,
+    pub boundary: Option<Boundary>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            area: ProtoArea::from(raw_cfg.area),
+            boundary: raw_cfg.boundary,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ProtoArea {
+    BoundingBox {
+        min_lat: f64,
+        min_lon: f64,
+        max_lat: f64,
+        max_lon: f64,
+    },
+    Polygon {
+        file: PathBuf,
+    },
+}
+
+impl From<RawArea> for ProtoArea {
+    fn from(raw_area: RawArea) -> ProtoArea {
+        match raw_area {
+            RawArea::BoundingBox {
+                min_lat,
+                min_lon,
+                max_lat,
+                max_lon,
+            } => ProtoArea::BoundingBox {
+                min_lat,
+                min_lon,
+                max_lat,
+                max_lon,
+            },
+            RawArea::Polygon(file) => ProtoArea::Polygon { file },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    pub area: RawArea,
+    pub boundary: Option<Boundary>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawArea {
+    #[serde(rename = "bbox")]
+    BoundingBox {
+        #[serde(rename = "min-lat")]
+        min_lat: f64,
+        #[serde(rename = "min-lon")]
+        min_lon: f64,
+        #[serde(rename = "max-lat")]
+        max_lat: f64,
+        #[serde(rename = "max-lon")]
+        max_lon: f64,
+    },
+    #[serde(rename = "poly")]
+    Polygon(PathBuf),
+}
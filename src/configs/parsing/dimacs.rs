@@ -0,0 +1,43 @@
+use crate::defaults;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Files of the [9th DIMACS-challenge](http://www.diag.uniroma1.it/challenge9/format.shtml),
+/// where the map-file (`*.gr`) contains the arcs and this coordinates-file (`*.co`) contains the nodes' coordinates.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub coordinates_file: PathBuf,
+}
+
+impl Config {
+    /// If no coordinates-file is given, the map-file's path with extension `co` is used.
+    pub fn from(proto_cfg: Option<ProtoConfig>, map_file: &Path) -> Config {
+        Config {
+            coordinates_file: proto_cfg
+                .and_then(|proto_cfg| proto_cfg.coordinates_file)
+                .unwrap_or_else(|| {
+                    map_file.with_extension(defaults::parsing::dimacs::COORDINATES_EXT)
+                }),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub coordinates_file: Option<PathBuf>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            coordinates_file: raw_cfg.coordinates_file,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    #[serde(rename = "coordinates-file")]
+    pub coordinates_file: Option<PathBuf>,
+}
@@ -1,14 +1,6 @@
-use crate::{
-    configs::{parsing::generating::edges::metrics as gen, SimpleId},
-    defaults::capacity::DimVec,
-    helpers::err,
-    network::MetricIdx,
-};
-use kissunits::{
-    distance::{Kilometers, Meters},
-    time::{Hours, Minutes, Seconds},
-};
+use crate::{configs::SimpleId, defaults::capacity::DimVec, helpers::err, network::MetricIdx};
 use serde::Deserialize;
+use std::{convert::TryFrom, fmt, fmt::Display};
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -48,119 +40,201 @@ impl Config {
     }
 }
 
+/// Units of edge-metrics, which are parsed, displayed and converted via the table `UNITS`.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
 pub enum UnitInfo {
     Meters,
     Kilometers,
+    Miles,
     Seconds,
     Minutes,
     Hours,
     KilometersPerHour,
+    MilesPerHour,
+    MetersPerSecond,
+    PercentGradient,
     LaneCount,
+    KilowattHours,
+    GramsCO2,
     F64,
 }
 
-impl From<ProtoUnitInfo> for UnitInfo {
-    fn from(proto_unit: ProtoUnitInfo) -> UnitInfo {
-        match proto_unit {
-            ProtoUnitInfo::Meters => UnitInfo::Meters,
-            ProtoUnitInfo::Kilometers => UnitInfo::Kilometers,
-            ProtoUnitInfo::Seconds => UnitInfo::Seconds,
-            ProtoUnitInfo::Minutes => UnitInfo::Minutes,
-            ProtoUnitInfo::Hours => UnitInfo::Hours,
-            ProtoUnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
-            ProtoUnitInfo::LaneCount => UnitInfo::LaneCount,
-            ProtoUnitInfo::F64 => UnitInfo::F64,
-        }
+/// Every unit with its name in configs, its dimension and its factor to the base-units
+/// (meters, seconds and kilograms).
+///
+/// Parsing, displaying, converting and calculating are derived from this table,
+/// so adding a unit only needs a new variant and a new line here.
+const UNITS: [(UnitInfo, &str, Dimension, f64); 14] = [
+    (UnitInfo::Meters, "Meters", Dimension::LENGTH, 1.0),
+    (
+        UnitInfo::Kilometers,
+        "Kilometers",
+        Dimension::LENGTH,
+        1_000.0,
+    ),
+    (UnitInfo::Miles, "Miles", Dimension::LENGTH, 1_609.344),
+    (UnitInfo::Seconds, "Seconds", Dimension::TIME, 1.0),
+    (UnitInfo::Minutes, "Minutes", Dimension::TIME, 60.0),
+    (UnitInfo::Hours, "Hours", Dimension::TIME, 3_600.0),
+    (
+        UnitInfo::KilometersPerHour,
+        "KilometersPerHour",
+        Dimension::SPEED,
+        1.0 / 3.6,
+    ),
+    (
+        UnitInfo::MilesPerHour,
+        "MilesPerHour",
+        Dimension::SPEED,
+        0.447_04,
+    ),
+    (
+        UnitInfo::MetersPerSecond,
+        "MetersPerSecond",
+        Dimension::SPEED,
+        1.0,
+    ),
+    // rise per run
+    (
+        UnitInfo::PercentGradient,
+        "PercentGradient",
+        Dimension::NONE,
+        0.01,
+    ),
+    (UnitInfo::LaneCount, "LaneCount", Dimension::NONE, 1.0),
+    (
+        UnitInfo::KilowattHours,
+        "KilowattHours",
+        Dimension::ENERGY,
+        3_600_000.0,
+    ),
+    // mass of emitted CO2
+    (UnitInfo::GramsCO2, "GramsCO2", Dimension::MASS, 0.001),
+    (UnitInfo::F64, "F64", Dimension::NONE, 1.0),
+];
+
+impl TryFrom<String> for UnitInfo {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<UnitInfo, String> {
+        UnitInfo::from_name(&name).ok_or_else(|| {
+            format!(
+                "Unit {} is unknown, expected one of [{}].",
+                name,
+                UNITS
+                    .iter()
+                    .map(|(_unit, name, _dim, _factor)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+    }
+}
+
+impl Display for UnitInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.row().1)
+    }
+}
+
+/// Physical dimension as exponents of length (meters), time (seconds) and mass (kilograms),
+/// e.g. speed is `length^1 time^-1`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Dimension {
+    pub length: i32,
+    pub time: i32,
+    pub mass: i32,
+}
+
+impl Dimension {
+    pub const NONE: Dimension = Dimension::new(0, 0, 0);
+    pub const LENGTH: Dimension = Dimension::new(1, 0, 0);
+    pub const TIME: Dimension = Dimension::new(0, 1, 0);
+    pub const SPEED: Dimension = Dimension::new(1, -1, 0);
+    pub const MASS: Dimension = Dimension::new(0, 0, 1);
+    /// kg m^2 / s^2
+    pub const ENERGY: Dimension = Dimension::new(2, -2, 1);
+
+    pub const fn new(length: i32, time: i32, mass: i32) -> Dimension {
+        Dimension { length, time, mass }
+    }
+
+    pub fn mul(&self, other: &Dimension) -> Dimension {
+        Dimension::new(
+            self.length + other.length,
+            self.time + other.time,
+            self.mass + other.mass,
+        )
+    }
+
+    pub fn div(&self, other: &Dimension) -> Dimension {
+        Dimension::new(
+            self.length - other.length,
+            self.time - other.time,
+            self.mass - other.mass,
+        )
     }
 }
 
-impl From<gen::UnitInfo> for UnitInfo {
-    fn from(gen_unit: gen::UnitInfo) -> UnitInfo {
-        match gen_unit {
-            gen::UnitInfo::Meters => UnitInfo::Meters,
-            gen::UnitInfo::Kilometers => UnitInfo::Kilometers,
-            gen::UnitInfo::Seconds => UnitInfo::Seconds,
-            gen::UnitInfo::Minutes => UnitInfo::Minutes,
-            gen::UnitInfo::Hours => UnitInfo::Hours,
-            gen::UnitInfo::KilometersPerHour => UnitInfo::KilometersPerHour,
-            gen::UnitInfo::LaneCount => UnitInfo::LaneCount,
-            gen::UnitInfo::F64 => UnitInfo::F64,
+impl Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if *self == Dimension::NONE {
+            write!(f, "no dimension")
+        } else {
+            write!(
+                f,
+                "length^{} time^{} mass^{}",
+                self.length, self.time, self.mass
+            )
         }
     }
 }
 
 impl UnitInfo {
+    fn row(&self) -> &'static (UnitInfo, &'static str, Dimension, f64) {
+        UNITS
+            .iter()
+            .find(|(unit, _name, _dim, _factor)| unit == self)
+            .expect("Every unit should be in the table of units.")
+    }
+
+    /// Returns the unit's dimension and its factor to the base-units (meters, seconds and kilograms).
+    pub fn base(&self) -> (Dimension, f64) {
+        let (_unit, _name, dim, factor) = self.row();
+        (*dim, *factor)
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.base().0
+    }
+
+    /// Returns the factor to the base-units (meters, seconds and kilograms).
+    pub fn factor(&self) -> f64 {
+        self.base().1
+    }
+
+    /// Returns the unit of the given name, as used in configs.
+    pub fn from_name(name: &str) -> Option<UnitInfo> {
+        UNITS
+            .iter()
+            .find(|(_unit, unit_name, _dim, _factor)| *unit_name == name)
+            .map(|(unit, _name, _dim, _factor)| *unit)
+    }
+
+    /// Units of the same dimension are converted via their factors.
+    /// Every unit can be converted from and to `F64`, keeping the raw value.
+    /// Different dimensionless units (e.g. `LaneCount` and `PercentGradient`) measure different things,
+    /// so they can't be converted into each other.
     pub fn try_convert(&self, to: &UnitInfo, raw_value: f64) -> err::Result<f64> {
-        let new_raw_value = match self {
-            UnitInfo::Meters => match to {
-                UnitInfo::Meters | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Kilometers => Some(*Kilometers::from(Meters(raw_value))),
-                UnitInfo::Seconds
-                | UnitInfo::Minutes
-                | UnitInfo::Hours
-                | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
-            },
-            UnitInfo::Kilometers => match to {
-                UnitInfo::Meters => Some(*Meters::from(Kilometers(raw_value))),
-                UnitInfo::Kilometers | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Seconds
-                | UnitInfo::Minutes
-                | UnitInfo::Hours
-                | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
-            },
-            UnitInfo::Seconds => match to {
-                UnitInfo::Seconds | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Minutes => Some(*Minutes::from(Seconds(raw_value))),
-                UnitInfo::Hours => Some(*Hours::from(Seconds(raw_value))),
-                UnitInfo::Meters
-                | UnitInfo::Kilometers
-                | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
-            },
-            UnitInfo::Minutes => match to {
-                UnitInfo::Minutes | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Seconds => Some(*Seconds::from(Minutes(raw_value))),
-                UnitInfo::Hours => Some(*Hours::from(Minutes(raw_value))),
-                UnitInfo::Meters
-                | UnitInfo::Kilometers
-                | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
-            },
-            UnitInfo::Hours => match to {
-                UnitInfo::Hours | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Seconds => Some(*Seconds::from(Hours(raw_value))),
-                UnitInfo::Minutes => Some(*Minutes::from(Hours(raw_value))),
-                UnitInfo::Meters
-                | UnitInfo::Kilometers
-                | UnitInfo::KilometersPerHour
-                | UnitInfo::LaneCount => None,
-            },
-            UnitInfo::KilometersPerHour => match to {
-                UnitInfo::KilometersPerHour | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Meters
-                | UnitInfo::Kilometers
-                | UnitInfo::Seconds
-                | UnitInfo::Minutes
-                | UnitInfo::Hours
-                | UnitInfo::LaneCount => None,
-            },
-            UnitInfo::LaneCount => match to {
-                UnitInfo::LaneCount | UnitInfo::F64 => Some(raw_value),
-                UnitInfo::Meters
-                | UnitInfo::Kilometers
-                | UnitInfo::Seconds
-                | UnitInfo::Minutes
-                | UnitInfo::Hours
-                | UnitInfo::KilometersPerHour => None,
-            },
-            UnitInfo::F64 => Some(raw_value),
-        };
+        if self == to || self == &UnitInfo::F64 || to == &UnitInfo::F64 {
+            return Ok(raw_value);
+        }
 
-        if let Some(new_raw_value) = new_raw_value {
-            Ok(new_raw_value)
+        let (dim, factor) = self.base();
+        let (to_dim, to_factor) = to.base();
+        if dim == to_dim && dim != Dimension::NONE {
+            Ok(raw_value * factor / to_factor)
         } else {
             Err(format!("Unit {:?} can't be converted to {:?}.", self, to).into())
         }
@@ -172,43 +246,38 @@ impl UnitInfo {
             Err(msg) => panic!("{}", msg),
         }
     }
-}
 
-#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
-pub enum ProtoUnitInfo {
-    Meters,
-    Kilometers,
-    Seconds,
-    Minutes,
-    Hours,
-    KilometersPerHour,
-    LaneCount,
-    F64,
-}
+    /// Calculates this unit by multiplying or dividing a by b, depending on their dimensions,
+    /// e.g. `Minutes` are `Kilometers / KilometersPerHour`.
+    pub fn try_calc(
+        &self,
+        unit_a: &UnitInfo,
+        raw_a: f64,
+        unit_b: &UnitInfo,
+        raw_b: f64,
+    ) -> err::Result<f64> {
+        let (dim, factor) = self.base();
+        let (dim_a, factor_a) = unit_a.base();
+        let (dim_b, factor_b) = unit_b.base();
+        let (a, b) = (raw_a * factor_a, raw_b * factor_b);
+
+        // Dimensionless b would be ambiguous.
+        let is_product = dim_a.mul(&dim_b) == dim;
+        let is_quotient = dim_a.div(&dim_b) == dim;
+        let raw_result = match (is_product, is_quotient) {
+            (true, false) => Some(a * b),
+            (false, true) => Some(a / b),
+            (true, true) | (false, false) => None,
+        };
 
-impl From<RawUnitInfo> for ProtoUnitInfo {
-    fn from(raw_info: RawUnitInfo) -> ProtoUnitInfo {
-        match raw_info {
-            RawUnitInfo::Meters => ProtoUnitInfo::Meters,
-            RawUnitInfo::Kilometers => ProtoUnitInfo::Kilometers,
-            RawUnitInfo::Seconds => ProtoUnitInfo::Seconds,
-            RawUnitInfo::Minutes => ProtoUnitInfo::Minutes,
-            RawUnitInfo::Hours => ProtoUnitInfo::Hours,
-            RawUnitInfo::KilometersPerHour => ProtoUnitInfo::KilometersPerHour,
-            RawUnitInfo::LaneCount => ProtoUnitInfo::LaneCount,
-            RawUnitInfo::F64 => ProtoUnitInfo::F64,
+        if let (Some(raw_result), false) = (raw_result, self == &UnitInfo::F64) {
+            Ok(raw_result / factor)
+        } else {
+            Err(format!(
+                "{:?} can't be calculated by {:?} and {:?}.",
+                self, unit_a, unit_b
+            )
+            .into())
         }
     }
 }
-
-#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq)]
-pub enum RawUnitInfo {
-    Meters,
-    Kilometers,
-    Seconds,
-    Minutes,
-    Hours,
-    KilometersPerHour,
-    LaneCount,
-    F64,
-}
@@ -68,7 +68,7 @@ impl TryFrom<ProtoConfig> for Config {
                 // for better access-performance through metric-indices
                 ProtoCategory::Metric { unit, id } => {
                     categories.push(category.clone().into());
-                    metric_units.push(unit.clone().into());
+                    metric_units.push(*unit);
                     metric_ids.push(id.clone());
                 }
                 ProtoCategory::Meta { info: _, id: _ } | ProtoCategory::Ignored => {
@@ -126,10 +126,7 @@ impl From<ProtoCategory> for Category {
                 info: MetaInfo::from(info),
                 id,
             },
-            ProtoCategory::Metric { unit, id } => Category::Metric {
-                unit: metrics::UnitInfo::from(unit),
-                id,
-            },
+            ProtoCategory::Metric { unit, id } => Category::Metric { unit, id },
             ProtoCategory::Ignored => Category::Ignored,
         }
     }
@@ -202,7 +199,7 @@ pub enum ProtoCategory {
         id: SimpleId,
     },
     Metric {
-        unit: metrics::ProtoUnitInfo,
+        unit: metrics::UnitInfo,
         id: SimpleId,
     },
     Ignored,
@@ -215,10 +212,7 @@ impl From<RawCategory> for ProtoCategory {
                 info: ProtoMetaInfo::from(info),
                 id,
             },
-            RawCategory::Metric { unit, id } => ProtoCategory::Metric {
-                unit: metrics::ProtoUnitInfo::from(unit),
-                id,
-            },
+            RawCategory::Metric { unit, id } => ProtoCategory::Metric { unit, id },
             RawCategory::Ignored => ProtoCategory::Ignored,
         }
     }
@@ -261,7 +255,7 @@ pub enum RawCategory {
         id: SimpleId,
     },
     Metric {
-        unit: metrics::RawUnitInfo,
+        unit: metrics::UnitInfo,
         id: SimpleId,
     },
     Ignored,
@@ -0,0 +1,703 @@
+use crate::{
+    approximating::Approx,
+    configs::{
+        parsing::edges::metrics::{Dimension, UnitInfo},
+        SimpleId,
+    },
+    defaults::capacity::DimVec,
+    helpers::err,
+    network::MetricIdx,
+};
+use std::{fmt, fmt::Display, iter::Peekable, str::CharIndices, str::FromStr};
+
+/// Arithmetic expression over metric-ids and constants,
+/// e.g. `duration * (1 + 0.15 * max(0, 3 - lanecount))`.
+///
+/// Supported are `+`, `-`, `*`, `/`, comparisons (`<`, `<=`, `>`, `>=`, `==`, `!=`),
+/// logical operators (`&&`, `||`, `!`) and the functions `min(...)`, `max(...)`, `abs(x)`
+/// and `if(condition, then, else)`.
+/// Metric-ids are given as they are, or in braces (e.g. `{free-flow-time}`),
+/// if they contain other characters than letters, digits and `_`.
+///
+/// Units are checked, meaning only values of the same dimension can be added or compared,
+/// and the dimension of the result has to match the result's unit.
+/// Constants don't have a dimension, unless a unit is given in brackets, e.g. `100[KilometersPerHour]`.
+/// Metrics are evaluated in base-units (meters, seconds and kilograms),
+/// so e.g. `kilometers / kmph` can be stored in minutes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression<M = SimpleId> {
+    Constant(f64),
+    Quantity(f64, UnitInfo),
+    Metric(M),
+    Neg(Box<Expression<M>>),
+    Not(Box<Expression<M>>),
+    Binary {
+        op: BinaryOp,
+        a: Box<Expression<M>>,
+        b: Box<Expression<M>>,
+    },
+    Min(Vec<Expression<M>>),
+    Max(Vec<Expression<M>>),
+    Abs(Box<Expression<M>>),
+    If {
+        condition: Box<Expression<M>>,
+        then: Box<Expression<M>>,
+        otherwise: Box<Expression<M>>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl<M: Display> Display for Expression<M> {
+    /// Writes the expression fully parenthesized, so it can be parsed again.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_args = |f: &mut fmt::Formatter, name: &str, args: &[&Expression<M>]| {
+            write!(f, "{}(", name)?;
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", arg)?;
+            }
+            write!(f, ")")
+        };
+
+        match self {
+            Expression::Constant(value) => write!(f, "{}", value),
+            Expression::Quantity(value, unit) => write!(f, "{}[{}]", value, unit),
+            Expression::Metric(id) => write!(f, "{{{}}}", id),
+            Expression::Neg(a) => write!(f, "-({})", a),
+            Expression::Not(a) => write!(f, "!({})", a),
+            Expression::Abs(a) => write_args(f, "abs", &[a]),
+            Expression::Binary { op, a, b } => write!(f, "({} {} {})", a, op, b),
+            Expression::Min(args) => write_args(f, "min", &args.iter().collect::<Vec<_>>()),
+            Expression::Max(args) => write_args(f, "max", &args.iter().collect::<Vec<_>>()),
+            Expression::If {
+                condition,
+                then,
+                otherwise,
+            } => write_args(f, "if", &[condition, then, otherwise]),
+        }
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// Type of a (sub-)expression
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Type {
+    Number(Dimension),
+    Bool,
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Number(dim) => write!(f, "a number ({})", dim),
+            Type::Bool => write!(f, "a condition"),
+        }
+    }
+}
+
+impl Expression {
+    /// Returns all metric-ids of the expression.
+    pub fn ids(&self) -> Vec<&SimpleId> {
+        let mut ids = Vec::new();
+        self.visit_ids(&mut |id| {
+            if !ids.contains(&id) {
+                ids.push(id)
+            }
+        });
+        ids
+    }
+
+    fn visit_ids<'a>(&'a self, visit: &mut dyn FnMut(&'a SimpleId)) {
+        match self {
+            Expression::Constant(_) | Expression::Quantity(_, _) => (),
+            Expression::Metric(id) => visit(id),
+            Expression::Neg(a) | Expression::Not(a) | Expression::Abs(a) => a.visit_ids(visit),
+            Expression::Binary { op: _, a, b } => {
+                a.visit_ids(visit);
+                b.visit_ids(visit);
+            }
+            Expression::Min(args) | Expression::Max(args) => {
+                args.iter().for_each(|arg| arg.visit_ids(visit))
+            }
+            Expression::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                condition.visit_ids(visit);
+                then.visit_ids(visit);
+                otherwise.visit_ids(visit);
+            }
+        }
+    }
+
+    /// Checks the units and replaces the metric-ids by their indices and factors,
+    /// so the result can be evaluated per edge.
+    pub fn try_bind(
+        &self,
+        metrics: &crate::configs::parsing::edges::metrics::Config,
+        result_unit: &UnitInfo,
+    ) -> err::Result<BoundExpression> {
+        let expression = self.try_bind_ids(metrics)?;
+
+        let (result_dim, result_factor) = result_unit.base();
+        match expression.try_type(metrics)? {
+            Type::Number(dim) if dim == result_dim => Ok(BoundExpression {
+                expression,
+                result_factor,
+            }),
+            actual => Err(format!(
+                "The formula results in {}, but should result in {:?} ({}).",
+                actual, result_unit, result_dim
+            )
+            .into()),
+        }
+    }
+
+    fn try_bind_ids(
+        &self,
+        metrics: &crate::configs::parsing::edges::metrics::Config,
+    ) -> err::Result<Expression<MetricIdx>> {
+        let bind = |a: &Expression| a.try_bind_ids(metrics).map(Box::new);
+        let bind_all = |args: &Vec<Expression>| {
+            args.iter()
+                .map(|arg| arg.try_bind_ids(metrics))
+                .collect::<err::Result<Vec<_>>>()
+        };
+        Ok(match self {
+            Expression::Constant(value) => Expression::Constant(*value),
+            Expression::Quantity(value, unit) => Expression::Quantity(*value, *unit),
+            Expression::Metric(id) => Expression::Metric(metrics.try_idx_of(id)?),
+            Expression::Neg(a) => Expression::Neg(bind(a)?),
+            Expression::Not(a) => Expression::Not(bind(a)?),
+            Expression::Abs(a) => Expression::Abs(bind(a)?),
+            Expression::Binary { op, a, b } => Expression::Binary {
+                op: *op,
+                a: bind(a)?,
+                b: bind(b)?,
+            },
+            Expression::Min(args) => Expression::Min(bind_all(args)?),
+            Expression::Max(args) => Expression::Max(bind_all(args)?),
+            Expression::If {
+                condition,
+                then,
+                otherwise,
+            } => Expression::If {
+                condition: bind(condition)?,
+                then: bind(then)?,
+                otherwise: bind(otherwise)?,
+            },
+        })
+    }
+}
+
+impl Expression<MetricIdx> {
+    fn try_type(
+        &self,
+        metrics: &crate::configs::parsing::edges::metrics::Config,
+    ) -> err::Result<Type> {
+        let expect = |actual: Type, expected: Type, what: &str| {
+            if actual == expected {
+                Ok(actual)
+            } else {
+                Err(err::Msg::from(format!(
+                    "The formula expects {} as {}, but got {}.",
+                    expected, what, actual
+                )))
+            }
+        };
+        let expect_number = |actual: Type, what: &str| match actual {
+            Type::Number(dim) => Ok(dim),
+            Type::Bool => Err(err::Msg::from(format!(
+                "The formula expects a number as {}, but got a condition.",
+                what
+            ))),
+        };
+
+        Ok(match self {
+            Expression::Constant(_) => Type::Number(Dimension::NONE),
+            Expression::Quantity(_, unit) => Type::Number(unit.dimension()),
+            Expression::Metric(metric_idx) => Type::Number(metrics.units[**metric_idx].dimension()),
+            Expression::Neg(a) | Expression::Abs(a) => {
+                Type::Number(expect_number(a.try_type(metrics)?, "operand")?)
+            }
+            Expression::Not(a) => expect(a.try_type(metrics)?, Type::Bool, "operand of '!'")?,
+            Expression::Binary { op, a, b } => {
+                let (a, b) = (a.try_type(metrics)?, b.try_type(metrics)?);
+                match op {
+                    BinaryOp::Mul | BinaryOp::Div => {
+                        let a = expect_number(a, "operand of '*' or '/'")?;
+                        let b = expect_number(b, "operand of '*' or '/'")?;
+                        Type::Number(if *op == BinaryOp::Mul {
+                            a.mul(&b)
+                        } else {
+                            a.div(&b)
+                        })
+                    }
+                    BinaryOp::Add | BinaryOp::Sub => {
+                        expect_number(a, "operand of '+' or '-'")?;
+                        expect(b, a, "second operand of '+' or '-'")?
+                    }
+                    BinaryOp::Lt
+                    | BinaryOp::Le
+                    | BinaryOp::Gt
+                    | BinaryOp::Ge
+                    | BinaryOp::Eq
+                    | BinaryOp::Ne => {
+                        expect_number(a, "operand of a comparison")?;
+                        expect(b, a, "second operand of a comparison")?;
+                        Type::Bool
+                    }
+                    BinaryOp::And | BinaryOp::Or => {
+                        expect(a, Type::Bool, "operand of '&&' or '||'")?;
+                        expect(b, Type::Bool, "operand of '&&' or '||'")?
+                    }
+                }
+            }
+            Expression::Min(args) | Expression::Max(args) => {
+                let mut types = args.iter().map(|arg| arg.try_type(metrics));
+                let first = match types.next() {
+                    Some(first) => first?,
+                    None => return Err("The functions min and max need arguments.".into()),
+                };
+                expect_number(first, "argument of min or max")?;
+                for other in types {
+                    expect(other?, first, "argument of min or max")?;
+                }
+                first
+            }
+            Expression::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                expect(condition.try_type(metrics)?, Type::Bool, "condition of if")?;
+                let then = then.try_type(metrics)?;
+                expect_number(then, "result of if")?;
+                expect(otherwise.try_type(metrics)?, then, "else-result of if")?
+            }
+        })
+    }
+
+    /// Conditions are evaluated to `1.0` (true) or `0.0` (false).
+    fn eval(&self, values: &DimVec<f64>, factors: &[f64]) -> f64 {
+        let is_true = |value: f64| value != 0.0;
+        let from_bool = |value: bool| if value { 1.0 } else { 0.0 };
+
+        match self {
+            Expression::Constant(value) => *value,
+            Expression::Quantity(value, unit) => value * unit.factor(),
+            Expression::Metric(metric_idx) => values[**metric_idx] * factors[**metric_idx],
+            Expression::Neg(a) => -a.eval(values, factors),
+            Expression::Not(a) => from_bool(!is_true(a.eval(values, factors))),
+            Expression::Abs(a) => a.eval(values, factors).abs(),
+            Expression::Binary { op, a, b } => {
+                let a = a.eval(values, factors);
+                // no short-circuit necessary, since there are no side-effects
+                let b = b.eval(values, factors);
+                match op {
+                    BinaryOp::Add => a + b,
+                    BinaryOp::Sub => a - b,
+                    BinaryOp::Mul => a * b,
+                    BinaryOp::Div => a / b,
+                    BinaryOp::Lt => from_bool(a < b && Approx(a) != Approx(b)),
+                    BinaryOp::Le => from_bool(a < b || Approx(a) == Approx(b)),
+                    BinaryOp::Gt => from_bool(a > b && Approx(a) != Approx(b)),
+                    BinaryOp::Ge => from_bool(a > b || Approx(a) == Approx(b)),
+                    BinaryOp::Eq => from_bool(Approx(a) == Approx(b)),
+                    BinaryOp::Ne => from_bool(Approx(a) != Approx(b)),
+                    BinaryOp::And => from_bool(is_true(a) && is_true(b)),
+                    BinaryOp::Or => from_bool(is_true(a) || is_true(b)),
+                }
+            }
+            Expression::Min(args) => args
+                .iter()
+                .map(|arg| arg.eval(values, factors))
+                .fold(f64::INFINITY, f64::min),
+            Expression::Max(args) => args
+                .iter()
+                .map(|arg| arg.eval(values, factors))
+                .fold(f64::NEG_INFINITY, f64::max),
+            Expression::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if is_true(condition.eval(values, factors)) {
+                    then.eval(values, factors)
+                } else {
+                    otherwise.eval(values, factors)
+                }
+            }
+        }
+    }
+}
+
+/// An expression, whose units have been checked against the graph's metrics.
+#[derive(Clone, Debug)]
+pub struct BoundExpression {
+    expression: Expression<MetricIdx>,
+    result_factor: f64,
+}
+
+impl BoundExpression {
+    /// Evaluates the expression for an edge's metrics,
+    /// where `factors` are the metrics' factors to the base-units (see `UnitInfo::base()`).
+    pub fn eval(&self, values: &DimVec<f64>, factors: &[f64]) -> f64 {
+        self.expression.eval(values, factors) / self.result_factor
+    }
+}
+
+impl FromStr for Expression {
+    type Err = err::Msg;
+
+    fn from_str(formula: &str) -> err::Result<Expression> {
+        let mut parser = Parser {
+            formula,
+            tokens: Tokens {
+                formula,
+                chars: formula.char_indices().peekable(),
+            }
+            .collect::<err::Result<Vec<_>>>()?
+            .into_iter()
+            .peekable(),
+        };
+        let expression = parser.or()?;
+        match parser.tokens.next() {
+            None => Ok(expression),
+            Some((pos, token)) => Err(parser.error(pos, &format!("unexpected {:?}", token))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Id(String),
+    Unit(String),
+    Op(&'static str),
+}
+
+/// Splits the formula into tokens with their positions.
+struct Tokens<'a> {
+    formula: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = err::Result<(usize, Token)>;
+
+    fn next(&mut self) -> Option<err::Result<(usize, Token)>> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let (pos, c) = self.chars.next()?;
+
+        let formula = self.formula;
+        let error = |msg: &str| {
+            Some(Err(format!(
+                "Formula '{}' is invalid at position {}: {}",
+                formula, pos, msg
+            )
+            .into()))
+        };
+
+        // numbers, e.g. 0.15 or 1e-3
+
+        if c.is_ascii_digit() || c == '.' {
+            let mut end = pos + c.len_utf8();
+            let mut prev = c;
+            while let Some((i, c)) = self.chars.next_if(|&(_, c)| {
+                c.is_ascii_digit()
+                    || c == '.'
+                    || c == 'e'
+                    || c == 'E'
+                    || ((c == '+' || c == '-') && (prev == 'e' || prev == 'E'))
+            }) {
+                end = i + c.len_utf8();
+                prev = c;
+            }
+            return match self.formula[pos..end].parse::<f64>() {
+                Ok(value) => Some(Ok((pos, Token::Number(value)))),
+                Err(_) => error(&format!("'{}' is no number", &self.formula[pos..end])),
+            };
+        }
+
+        // ids
+
+        if c.is_alphabetic() || c == '_' {
+            let mut id = String::from(c);
+            while let Some((_, c)) = self
+                .chars
+                .next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+            {
+                id.push(c);
+            }
+            return Some(Ok((pos, Token::Id(id))));
+        }
+        if c == '{' || c == '[' {
+            let (close, token): (char, fn(String) -> Token) = if c == '{' {
+                ('}', Token::Id)
+            } else {
+                (']', Token::Unit)
+            };
+            let mut name = String::new();
+            loop {
+                match self.chars.next() {
+                    Some((_, c)) if c == close => return Some(Ok((pos, token(name)))),
+                    Some((_, c)) => name.push(c),
+                    None => return error(&format!("'{}' is not closed", c)),
+                }
+            }
+        }
+
+        // operators
+
+        let op = match (c, self.chars.peek().map(|(_, c)| *c)) {
+            ('<', Some('=')) => "<=",
+            ('>', Some('=')) => ">=",
+            ('=', Some('=')) => "==",
+            ('!', Some('=')) => "!=",
+            ('&', Some('&')) => "&&",
+            ('|', Some('|')) => "||",
+            ('+', _) => "+",
+            ('-', _) => "-",
+            ('*', _) => "*",
+            ('/', _) => "/",
+            ('(', _) => "(",
+            (')', _) => ")",
+            (',', _) => ",",
+            ('<', _) => "<",
+            ('>', _) => ">",
+            ('!', _) => "!",
+            _ => return error(&format!("unexpected '{}'", c)),
+        };
+        if op.len() == 2 {
+            self.chars.next();
+        }
+        Some(Ok((pos, Token::Op(op))))
+    }
+}
+
+/// Recursive-descent-parser, where every method parses one level of precedence.
+struct Parser<'a> {
+    formula: &'a str,
+    tokens: Peekable<std::vec::IntoIter<(usize, Token)>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, pos: usize, msg: &str) -> err::Msg {
+        format!(
+            "Formula '{}' is invalid at position {}: {}",
+            self.formula, pos, msg
+        )
+        .into()
+    }
+
+    /// Returns the operator, if it's next and one of the given ones.
+    fn next_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.tokens.peek() {
+            Some((_, Token::Op(op))) if ops.contains(op) => {
+                let op = *op;
+                self.tokens.next();
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect_op(&mut self, op: &'static str) -> err::Feedback {
+        match self.tokens.next() {
+            Some((_, Token::Op(next))) if next == op => Ok(()),
+            Some((pos, token)) => {
+                Err(self.error(pos, &format!("expected '{}', but got {:?}", op, token)))
+            }
+            None => Err(self.error(self.formula.len(), &format!("expected '{}'", op))),
+        }
+    }
+
+    fn binary(op: BinaryOp, a: Expression, b: Expression) -> Expression {
+        Expression::Binary {
+            op,
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    fn or(&mut self) -> err::Result<Expression> {
+        let mut a = self.and()?;
+        while self.next_op(&["||"]).is_some() {
+            a = Parser::binary(BinaryOp::Or, a, self.and()?);
+        }
+        Ok(a)
+    }
+
+    fn and(&mut self) -> err::Result<Expression> {
+        let mut a = self.comparison()?;
+        while self.next_op(&["&&"]).is_some() {
+            a = Parser::binary(BinaryOp::And, a, self.comparison()?);
+        }
+        Ok(a)
+    }
+
+    fn comparison(&mut self) -> err::Result<Expression> {
+        let a = self.sum()?;
+        let op = match self.next_op(&["<", "<=", ">", ">=", "==", "!="]) {
+            Some("<") => BinaryOp::Lt,
+            Some("<=") => BinaryOp::Le,
+            Some(">") => BinaryOp::Gt,
+            Some(">=") => BinaryOp::Ge,
+            Some("==") => BinaryOp::Eq,
+            Some(_) => BinaryOp::Ne,
+            None => return Ok(a),
+        };
+        Ok(Parser::binary(op, a, self.sum()?))
+    }
+
+    fn sum(&mut self) -> err::Result<Expression> {
+        let mut a = self.product()?;
+        while let Some(op) = self.next_op(&["+", "-"]) {
+            let op = if op == "+" {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            a = Parser::binary(op, a, self.product()?);
+        }
+        Ok(a)
+    }
+
+    fn product(&mut self) -> err::Result<Expression> {
+        let mut a = self.unary()?;
+        while let Some(op) = self.next_op(&["*", "/"]) {
+            let op = if op == "*" {
+                BinaryOp::Mul
+            } else {
+                BinaryOp::Div
+            };
+            a = Parser::binary(op, a, self.unary()?);
+        }
+        Ok(a)
+    }
+
+    fn unary(&mut self) -> err::Result<Expression> {
+        match self.next_op(&["-", "!"]) {
+            Some("-") => Ok(Expression::Neg(Box::new(self.unary()?))),
+            Some(_) => Ok(Expression::Not(Box::new(self.unary()?))),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> err::Result<Expression> {
+        let (pos, token) = match self.tokens.next() {
+            Some(next) => next,
+            None => return Err(self.error(self.formula.len(), "unexpected end")),
+        };
+
+        match token {
+            Token::Number(value) => {
+                let unit = match self.tokens.peek() {
+                    Some((unit_pos, Token::Unit(name))) => Some((*unit_pos, name.clone())),
+                    _ => None,
+                };
+                match unit {
+                    Some((unit_pos, name)) => {
+                        self.tokens.next();
+                        match UnitInfo::from_name(&name) {
+                            Some(unit) => Ok(Expression::Quantity(value, unit)),
+                            None => Err(self.error(unit_pos, &format!("unknown unit {}", name))),
+                        }
+                    }
+                    None => Ok(Expression::Constant(value)),
+                }
+            }
+            Token::Op("(") => {
+                let expression = self.or()?;
+                self.expect_op(")")?;
+                Ok(expression)
+            }
+            Token::Id(id) => {
+                if self.next_op(&["("]).is_none() {
+                    return Ok(Expression::Metric(SimpleId(id)));
+                }
+
+                // function-call
+
+                let mut args = vec![self.or()?];
+                while self.next_op(&[","]).is_some() {
+                    args.push(self.or()?);
+                }
+                self.expect_op(")")?;
+
+                let expect_args = |count: usize| {
+                    if args.len() == count {
+                        Ok(())
+                    } else {
+                        Err(self.error(
+                            pos,
+                            &format!(
+                                "{} expects {} argument(s), but got {}",
+                                id,
+                                count,
+                                args.len()
+                            ),
+                        ))
+                    }
+                };
+                match id.as_str() {
+                    "min" => Ok(Expression::Min(args)),
+                    "max" => Ok(Expression::Max(args)),
+                    "abs" => {
+                        expect_args(1)?;
+                        Ok(Expression::Abs(Box::new(args.remove(0))))
+                    }
+                    "if" => {
+                        expect_args(3)?;
+                        let mut args = args.into_iter().map(Box::new);
+                        Ok(Expression::If {
+                            condition: args.next().expect("Checked above."),
+                            then: args.next().expect("Checked above."),
+                            otherwise: args.next().expect("Checked above."),
+                        })
+                    }
+                    _ => Err(self.error(pos, &format!("unknown function {}", id))),
+                }
+            }
+            token => Err(self.error(pos, &format!("unexpected {:?}", token))),
+        }
+    }
+}
@@ -1,5 +1,7 @@
-use crate::configs::SimpleId;
+use crate::{configs::SimpleId, defaults, helpers::err};
+use kissunits::distance::Kilometers;
 use serde::Deserialize;
+use std::convert::TryFrom;
 
 #[derive(Clone, Debug, Deserialize)]
 pub enum Category {
@@ -16,6 +18,142 @@ impl From<ProtoCategory> for Category {
     }
 }
 
+/// Joins the rows of the edges-info with the graph's edges.
+///
+/// All ids refer to columns of the edges-info.
+/// Since edges may be matched by multiple rows and vice versa,
+/// a row's values are set for all of its matched edges.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Key {
+    /// The graph's edge-ids
+    EdgeId(SimpleId),
+    /// All (non-shortcut) edges from src-id to dst-id
+    NodeIds { src_id: SimpleId, dst_id: SimpleId },
+    /// The edges of the osm-way, which are nearest to the coordinate (both directions).
+    /// Only graphs parsed from pbf-files know their ways.
+    Way {
+        way_id: SimpleId,
+        lat: SimpleId,
+        lon: SimpleId,
+    },
+    /// The (non-shortcut) edges nearest to the coordinate (both directions),
+    /// if they are within the optional maximum distance.
+    Nearest {
+        lat: SimpleId,
+        lon: SimpleId,
+        max_distance: Option<Kilometers>,
+    },
+}
+
+impl Key {
+    /// Returns the ids of the columns, which are needed for joining.
+    pub fn ids(&self) -> Vec<&SimpleId> {
+        match self {
+            Key::EdgeId(edge_id) => vec![edge_id],
+            Key::NodeIds { src_id, dst_id } => vec![src_id, dst_id],
+            Key::Way { way_id, lat, lon } => vec![way_id, lat, lon],
+            Key::Nearest {
+                lat,
+                lon,
+                max_distance: _,
+            } => vec![lat, lon],
+        }
+    }
+}
+
+impl From<ProtoKey> for Key {
+    fn from(proto_key: ProtoKey) -> Key {
+        match proto_key {
+            ProtoKey::EdgeId(edge_id) => Key::EdgeId(edge_id),
+            ProtoKey::NodeIds { src_id, dst_id } => Key::NodeIds { src_id, dst_id },
+            ProtoKey::Way { way_id, lat, lon } => Key::Way { way_id, lat, lon },
+            ProtoKey::Nearest {
+                lat,
+                lon,
+                max_distance,
+            } => Key::Nearest {
+                lat,
+                lon,
+                max_distance: max_distance.map(Kilometers),
+            },
+        }
+    }
+}
+
+/// Behavior for rows without matched edges, for edges without matched rows,
+/// and for edges matched by multiple rows.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Policy {
+    Error,
+    /// Logs a warning with the number of occurrences.
+    Warn,
+    Ignore,
+    /// Sets the merged metrics of the affected edges to this value.
+    Default(f64),
+}
+
+impl From<ProtoPolicy> for Policy {
+    fn from(proto_policy: ProtoPolicy) -> Policy {
+        match proto_policy {
+            ProtoPolicy::Error => Policy::Error,
+            ProtoPolicy::Warn => Policy::Warn,
+            ProtoPolicy::Ignore => Policy::Ignore,
+            ProtoPolicy::Default(value) => Policy::Default(value),
+        }
+    }
+}
+
+/// Policies of merging, where defaults behave like merging by edge-ids always did.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Policies {
+    /// Rows can't be set to a default, since they have no edges.
+    pub unmatched_rows: Policy,
+    /// Only non-shortcut edges are considered.
+    pub unmatched_edges: Policy,
+    /// If not set to a default, the last row wins.
+    pub duplicates: Policy,
+}
+
+impl TryFrom<ProtoPolicies> for Policies {
+    type Error = err::Msg;
+
+    fn try_from(proto_policies: ProtoPolicies) -> err::Result<Policies> {
+        let policies = Policies {
+            unmatched_rows: proto_policies
+                .unmatched_rows
+                .map(Policy::from)
+                .unwrap_or(defaults::parsing::merging::UNMATCHED_ROWS),
+            unmatched_edges: proto_policies
+                .unmatched_edges
+                .map(Policy::from)
+                .unwrap_or(defaults::parsing::merging::UNMATCHED_EDGES),
+            duplicates: proto_policies
+                .duplicates
+                .map(Policy::from)
+                .unwrap_or(defaults::parsing::merging::DUPLICATES),
+        };
+
+        if let Policy::Default(_) = policies.unmatched_rows {
+            return Err(err::Msg::from(
+                "Unmatched rows of the edges-info can't be set to a default value, since they have no edges.",
+            ));
+        }
+        for policy in &[policies.unmatched_edges, policies.duplicates] {
+            if let Policy::Default(value) = policy {
+                if !value.is_finite() {
+                    return Err(format!(
+                        "The default value {} of merging should be finite.",
+                        value
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(policies)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub enum ProtoCategory {
     Id(SimpleId),
@@ -31,9 +169,115 @@ impl From<RawCategory> for ProtoCategory {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum ProtoKey {
+    EdgeId(SimpleId),
+    NodeIds {
+        src_id: SimpleId,
+        dst_id: SimpleId,
+    },
+    Way {
+        way_id: SimpleId,
+        lat: SimpleId,
+        lon: SimpleId,
+    },
+    Nearest {
+        lat: SimpleId,
+        lon: SimpleId,
+        max_distance: Option<f64>,
+    },
+}
+
+impl From<RawKey> for ProtoKey {
+    fn from(raw_key: RawKey) -> ProtoKey {
+        match raw_key {
+            RawKey::EdgeId(edge_id) => ProtoKey::EdgeId(edge_id),
+            RawKey::NodeIds { src_id, dst_id } => ProtoKey::NodeIds { src_id, dst_id },
+            RawKey::Way { way_id, lat, lon } => ProtoKey::Way { way_id, lat, lon },
+            RawKey::Nearest {
+                lat,
+                lon,
+                max_distance,
+            } => ProtoKey::Nearest {
+                lat,
+                lon,
+                max_distance,
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ProtoPolicy {
+    Error,
+    Warn,
+    Ignore,
+    Default(f64),
+}
+
+impl From<RawPolicy> for ProtoPolicy {
+    fn from(raw_policy: RawPolicy) -> ProtoPolicy {
+        match raw_policy {
+            RawPolicy::Error => ProtoPolicy::Error,
+            RawPolicy::Warn => ProtoPolicy::Warn,
+            RawPolicy::Ignore => ProtoPolicy::Ignore,
+            RawPolicy::Default(value) => ProtoPolicy::Default(value),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ProtoPolicies {
+    pub unmatched_rows: Option<ProtoPolicy>,
+    pub unmatched_edges: Option<ProtoPolicy>,
+    pub duplicates: Option<ProtoPolicy>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RawCategory {
     Id(SimpleId),
     Ignored,
 }
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawKey {
+    #[serde(rename = "edge-id")]
+    EdgeId(SimpleId),
+    #[serde(rename = "node-ids")]
+    NodeIds {
+        #[serde(rename = "src-id")]
+        src_id: SimpleId,
+        #[serde(rename = "dst-id")]
+        dst_id: SimpleId,
+    },
+    #[serde(rename = "way")]
+    Way {
+        #[serde(rename = "way-id")]
+        way_id: SimpleId,
+        #[serde(rename = "latitude")]
+        lat: SimpleId,
+        #[serde(rename = "longitude")]
+        lon: SimpleId,
+    },
+    #[serde(rename = "nearest")]
+    Nearest {
+        #[serde(rename = "latitude")]
+        lat: SimpleId,
+        #[serde(rename = "longitude")]
+        lon: SimpleId,
+        /// in kilometers
+        #[serde(rename = "max-distance")]
+        max_distance: Option<f64>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "lowercase")]
+pub enum RawPolicy {
+    Error,
+    Warn,
+    Ignore,
+    Default(f64),
+}
@@ -1,15 +1,20 @@
-use crate::{configs::SimpleId, defaults};
+use crate::{
+    configs::{csv, SimpleId},
+    defaults,
+    helpers::err,
+};
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::{convert::TryFrom, path::PathBuf};
+pub mod formula;
 pub mod merge;
 pub mod metrics;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct Config {
     pub categories: Vec<Category>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug)]
 pub enum Category {
     Meta {
         info: MetaInfo,
@@ -42,31 +47,35 @@ pub enum Category {
         a: metrics::Category,
         b: metrics::Category,
     },
+    // out-of-place
+    Formula {
+        result: metrics::Category,
+        expression: formula::Expression,
+    },
     // in-place
+    /// If the file is csv with a header-line, the edges-info are looked up by their column-names.
+    /// Otherwise, the edges-info are given by their column-positions.
     Merge {
         from: PathBuf,
         is_file_with_header: bool,
-        edge_id: SimpleId,
+        key: merge::Key,
+        policies: merge::Policies,
         edges_info: Vec<merge::Category>,
+        csv: Option<csv::Config>,
     },
 }
 
-impl From<ProtoCategory> for Category {
-    fn from(proto_category: ProtoCategory) -> Category {
-        match proto_category {
+impl TryFrom<ProtoCategory> for Category {
+    type Error = err::Msg;
+
+    fn try_from(proto_category: ProtoCategory) -> err::Result<Category> {
+        Ok(match proto_category {
             ProtoCategory::Meta { info, id } => Category::Meta {
                 info: info.into(),
                 id,
             },
-            ProtoCategory::Custom { unit, id, default } => Category::Custom {
-                unit: unit.into(),
-                id,
-                default,
-            },
-            ProtoCategory::Haversine { unit, id } => Category::Haversine {
-                unit: unit.into(),
-                id,
-            },
+            ProtoCategory::Custom { unit, id, default } => Category::Custom { unit, id, default },
+            ProtoCategory::Haversine { unit, id } => Category::Haversine { unit, id },
             ProtoCategory::Copy { from, to } => Category::Copy {
                 from: from.into(),
                 to: to.into(),
@@ -80,19 +89,74 @@ impl From<ProtoCategory> for Category {
                 a: a.into(),
                 b: b.into(),
             },
+            ProtoCategory::Formula { result, expression } => Category::Formula {
+                result: result.into(),
+                expression: expression.parse()?,
+            },
             ProtoCategory::Merge {
                 from,
                 is_file_with_header,
                 edge_id,
+                join,
+                policies,
                 edges_info,
-            } => Category::Merge {
-                from,
-                is_file_with_header: is_file_with_header
-                    .unwrap_or(defaults::writing::IS_WRITING_WITH_HEADER),
-                edge_id,
-                edges_info: edges_info.into_iter().map(merge::Category::from).collect(),
-            },
-        }
+                csv,
+            } => {
+                // edge-id is the short form of joining by edge-ids
+                let key = match (edge_id, join) {
+                    (Some(edge_id), None) => merge::Key::EdgeId(edge_id),
+                    (None, Some(join)) => merge::Key::from(join),
+                    _ => {
+                        return Err(format!(
+                            "Merging {} needs either an edge-id or a join, but not both.",
+                            from.display()
+                        )
+                        .into())
+                    }
+                };
+                if let merge::Key::Nearest {
+                    lat: _,
+                    lon: _,
+                    max_distance: Some(max_distance),
+                } = key
+                {
+                    if max_distance.is_nan() || *max_distance < 0.0 {
+                        return Err(format!(
+                            "The max-distance {} of merging {} should be non-negative.",
+                            max_distance,
+                            from.display()
+                        )
+                        .into());
+                    }
+                }
+
+                let edges_info: Vec<_> =
+                    edges_info.into_iter().map(merge::Category::from).collect();
+                for id in key.ids() {
+                    if !edges_info.iter().any(|category| match category {
+                        merge::Category::Id(info_id) => info_id == id,
+                        merge::Category::Ignored => false,
+                    }) {
+                        return Err(format!(
+                            "The column {} for joining should be part of the edges-info of merging {}.",
+                            id,
+                            from.display()
+                        )
+                        .into());
+                    }
+                }
+
+                Category::Merge {
+                    from,
+                    is_file_with_header: is_file_with_header
+                        .unwrap_or(defaults::writing::IS_WRITING_WITH_HEADER),
+                    key,
+                    policies: merge::Policies::try_from(policies)?,
+                    edges_info,
+                    csv,
+                }
+            }
+        })
     }
 }
 
@@ -135,20 +199,19 @@ impl From<RawConfig> for ProtoConfig {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Clone, Debug)]
 pub enum ProtoCategory {
     Meta {
         info: ProtoMetaInfo,
         id: SimpleId,
     },
     Custom {
-        unit: metrics::ProtoUnitInfo,
+        unit: metrics::UnitInfo,
         id: SimpleId,
         default: f64,
     },
     Haversine {
-        unit: metrics::ProtoUnitInfo,
+        unit: metrics::UnitInfo,
         id: SimpleId,
     },
     Copy {
@@ -164,11 +227,18 @@ pub enum ProtoCategory {
         a: metrics::ProtoCategory,
         b: metrics::ProtoCategory,
     },
+    Formula {
+        result: metrics::ProtoCategory,
+        expression: String,
+    },
     Merge {
         from: PathBuf,
         is_file_with_header: Option<bool>,
-        edge_id: SimpleId,
+        edge_id: Option<SimpleId>,
+        join: Option<merge::ProtoKey>,
+        policies: merge::ProtoPolicies,
         edges_info: Vec<merge::ProtoCategory>,
+        csv: Option<csv::Config>,
     },
 }
 
@@ -180,14 +250,11 @@ impl From<RawCategory> for ProtoCategory {
                 id,
             },
             RawCategory::Custom { unit, id, default } => ProtoCategory::Custom {
-                unit: unit.unwrap_or(metrics::RawUnitInfo::F64).into(),
+                unit: unit.unwrap_or(metrics::UnitInfo::F64),
                 id,
                 default: default.unwrap_or_default(),
             },
-            RawCategory::Haversine { unit, id } => ProtoCategory::Haversine {
-                unit: metrics::ProtoUnitInfo::from(unit),
-                id,
-            },
+            RawCategory::Haversine { unit, id } => ProtoCategory::Haversine { unit, id },
             RawCategory::Copy { from, to } => ProtoCategory::Copy {
                 from: metrics::ProtoCategory::from(from),
                 to: metrics::ProtoCategory::from(to),
@@ -201,19 +268,35 @@ impl From<RawCategory> for ProtoCategory {
                 a: metrics::ProtoCategory::from(a),
                 b: metrics::ProtoCategory::from(b),
             },
+            RawCategory::Formula { result, expression } => ProtoCategory::Formula {
+                result: metrics::ProtoCategory::from(result),
+                expression,
+            },
             RawCategory::Merge {
                 from,
                 is_file_with_header,
                 edge_id,
+                join,
+                unmatched_rows,
+                unmatched_edges,
+                duplicates,
                 edges_info,
+                csv,
             } => ProtoCategory::Merge {
                 from,
                 is_file_with_header,
                 edge_id,
+                join: join.map(merge::ProtoKey::from),
+                policies: merge::ProtoPolicies {
+                    unmatched_rows: unmatched_rows.map(merge::ProtoPolicy::from),
+                    unmatched_edges: unmatched_edges.map(merge::ProtoPolicy::from),
+                    duplicates: duplicates.map(merge::ProtoPolicy::from),
+                },
                 edges_info: edges_info
                     .into_iter()
                     .map(merge::ProtoCategory::from)
                     .collect(),
+                csv,
             },
         }
     }
@@ -258,12 +341,12 @@ pub enum RawCategory {
         id: SimpleId,
     },
     Custom {
-        unit: Option<metrics::RawUnitInfo>,
+        unit: Option<metrics::UnitInfo>,
         id: SimpleId,
         default: Option<f64>,
     },
     Haversine {
-        unit: metrics::RawUnitInfo,
+        unit: metrics::UnitInfo,
         id: SimpleId,
     },
     Copy {
@@ -279,14 +362,26 @@ pub enum RawCategory {
         a: metrics::RawCategory,
         b: metrics::RawCategory,
     },
+    /// e.g. `duration * (1 + 0.15 * max(0, 3 - lanecount))`
+    Formula {
+        result: metrics::RawCategory,
+        expression: String,
+    },
     Merge {
         from: PathBuf,
         #[serde(rename = "with_header-line")]
         is_file_with_header: Option<bool>,
         #[serde(rename = "edge-id")]
-        edge_id: SimpleId,
+        edge_id: Option<SimpleId>,
+        join: Option<merge::RawKey>,
+        #[serde(rename = "unmatched-rows")]
+        unmatched_rows: Option<merge::RawPolicy>,
+        #[serde(rename = "unmatched-edges")]
+        unmatched_edges: Option<merge::RawPolicy>,
+        duplicates: Option<merge::RawPolicy>,
         #[serde(rename = "edges-info")]
         edges_info: Vec<merge::RawCategory>,
+        csv: Option<csv::Config>,
     },
 }
 
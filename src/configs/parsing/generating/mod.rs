@@ -1,4 +1,6 @@
+use crate::helpers::err;
 use serde::Deserialize;
+use std::convert::TryFrom;
 pub mod edges;
 pub mod nodes;
 
@@ -8,9 +10,11 @@ pub struct Config {
     pub edges: edges::Config,
 }
 
-impl From<ProtoConfig> for Config {
-    fn from(proto_cfg: ProtoConfig) -> Config {
-        Config {
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        Ok(Config {
             nodes: nodes::Config {
                 categories: proto_cfg
                     .nodes
@@ -24,10 +28,10 @@ impl From<ProtoConfig> for Config {
                     .edges
                     .0
                     .into_iter()
-                    .map(|proto_category| proto_category.into())
-                    .collect(),
+                    .map(edges::Category::try_from)
+                    .collect::<err::Result<_>>()?,
             },
-        }
+        })
     }
 }
 
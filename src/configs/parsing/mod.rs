@@ -1,6 +1,7 @@
 use crate::{
+    defaults,
     helpers::err,
-    io::{network::graph::Parser, SupportingFileExts},
+    io::{self, network::graph::Parser, SupportingFileExts},
 };
 use serde::Deserialize;
 use std::{
@@ -9,9 +10,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
+pub mod clipping;
+pub mod dimacs;
 pub mod edges;
 pub mod generating;
 pub mod nodes;
+pub mod storage;
+pub mod streaming;
 pub mod vehicles;
 
 /// # Set config-values with yaml-file (TODO update this text)
@@ -29,6 +34,11 @@ pub mod vehicles;
 #[serde(try_from = "ProtoConfig")]
 pub struct Config {
     pub map_file: PathBuf,
+    pub clipping: Option<clipping::Config>,
+    /// Only given, if the map-file is a DIMACS-graph (`*.gr`).
+    pub dimacs: Option<dimacs::Config>,
+    pub streaming: Option<streaming::Config>,
+    pub storage: storage::Config,
     pub vehicles: vehicles::Config,
     pub nodes: nodes::Config,
     pub edges: edges::Config,
@@ -87,15 +97,45 @@ impl TryFrom<ProtoConfig> for Config {
     type Error = err::Msg;
 
     fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let is_dimacs = match io::ext_from(&proto_cfg.map_file) {
+            Ok(ext) => ext.to_ascii_lowercase() == defaults::parsing::dimacs::GRAPH_EXT,
+            Err(_) => false,
+        };
+        let dimacs = if is_dimacs {
+            Some(dimacs::Config::from(proto_cfg.dimacs, &proto_cfg.map_file))
+        } else if proto_cfg.dimacs.is_some() {
+            return Err(format!(
+                "The dimacs-config is only supported for DIMACS-graphs (*.{}), but the map-file is {}.",
+                defaults::parsing::dimacs::GRAPH_EXT,
+                proto_cfg.map_file.display()
+            )
+            .into());
+        } else {
+            None
+        };
+
         Ok(Config {
             map_file: proto_cfg.map_file,
+            dimacs,
+            clipping: match proto_cfg.clipping {
+                Some(proto_clipping) => Some(clipping::Config::try_from(proto_clipping)?),
+                None => None,
+            },
+            streaming: proto_cfg.streaming.map(streaming::Config::from),
+            storage: match proto_cfg.storage {
+                Some(proto_storage) => storage::Config::try_from(proto_storage)?,
+                None => storage::Config::default(),
+            },
             vehicles: match proto_cfg.vehicles {
                 Some(proto_vehicles) => vehicles::Config::from(proto_vehicles),
                 None => vehicles::Config::default(),
             },
             nodes: nodes::Config::from(proto_cfg.nodes),
             edges: edges::Config::try_from(proto_cfg.edges)?,
-            generating: proto_cfg.generating.map(generating::Config::from),
+            generating: match proto_cfg.generating {
+                Some(proto_generating) => Some(generating::Config::try_from(proto_generating)?),
+                None => None,
+            },
         })
     }
 }
@@ -104,6 +144,10 @@ impl TryFrom<ProtoConfig> for Config {
 #[serde(try_from = "RawConfig")]
 pub struct ProtoConfig {
     pub map_file: PathBuf,
+    pub clipping: Option<clipping::ProtoConfig>,
+    pub dimacs: Option<dimacs::ProtoConfig>,
+    pub streaming: Option<streaming::ProtoConfig>,
+    pub storage: Option<storage::ProtoConfig>,
     pub vehicles: Option<vehicles::ProtoConfig>,
     pub nodes: nodes::ProtoConfig,
     pub edges: edges::ProtoConfig,
@@ -116,6 +160,10 @@ impl From<RawConfig> for ProtoConfig {
 
         ProtoConfig {
             map_file: raw_cfg.map_file,
+            clipping: raw_cfg.clipping.map(clipping::ProtoConfig::from),
+            dimacs: raw_cfg.dimacs.map(dimacs::ProtoConfig::from),
+            streaming: raw_cfg.streaming.map(streaming::ProtoConfig::from),
+            storage: raw_cfg.storage.map(storage::ProtoConfig::from),
             vehicles: raw_cfg.vehicles.map(vehicles::ProtoConfig::from),
             nodes: nodes::ProtoConfig::from(raw_cfg.nodes),
             edges: edges::ProtoConfig::from(raw_cfg.edges),
@@ -135,6 +183,10 @@ pub struct RawConfig {
 pub struct RawContent {
     #[serde(rename = "map-file")]
     pub map_file: PathBuf,
+    pub clipping: Option<clipping::RawConfig>,
+    pub dimacs: Option<dimacs::RawConfig>,
+    pub streaming: Option<streaming::RawConfig>,
+    pub storage: Option<storage::RawConfig>,
     pub vehicles: Option<vehicles::RawConfig>,
     pub nodes: nodes::RawConfig,
     pub edges: edges::RawConfig,
@@ -0,0 +1,85 @@
+use crate::{defaults, helpers::err};
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+/// Defines how the graph is stored in memory.
+///
+/// The compact layout reduces memory-usage and cache-misses (e.g. when routing on big graphs), but restricts the graph to less than `2^32` nodes and edges.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub layout: Layout,
+    pub metric_precision: Precision,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            layout: defaults::parsing::storage::LAYOUT,
+            metric_precision: defaults::parsing::storage::METRIC_PRECISION,
+        }
+    }
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let layout = proto_cfg
+            .layout
+            .unwrap_or(defaults::parsing::storage::LAYOUT);
+        let metric_precision = proto_cfg
+            .metric_precision
+            .unwrap_or(defaults::parsing::storage::METRIC_PRECISION);
+
+        if layout == Layout::Wide && metric_precision != Precision::F64 {
+            return Err(format!(
+                "The metric-precision {:?} is only supported with the layout {:?}.",
+                metric_precision,
+                Layout::Compact
+            )
+            .into());
+        }
+
+        Ok(Config {
+            layout,
+            metric_precision,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum Layout {
+    /// Indices are stored as `usize` and every edge has its own vector of metrics.
+    Wide,
+    /// Indices are stored as `u32`, metrics are stored as flat struct-of-arrays and edge-ids are marked by a bitset.
+    Compact,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum Precision {
+    F64,
+    F32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub layout: Option<Layout>,
+    pub metric_precision: Option<Precision>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            layout: raw_cfg.layout,
+            metric_precision: raw_cfg.metric_precision,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    pub layout: Option<Layout>,
+    #[serde(rename = "metric-precision")]
+    pub metric_precision: Option<Precision>,
+}
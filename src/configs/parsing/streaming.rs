@@ -0,0 +1,46 @@
+use crate::defaults;
+use serde::Deserialize;
+use std::{env, path::PathBuf};
+
+/// Bounds the memory-usage of parsing big maps (e.g. countries) by writing sorted runs of proto-edges into temporary files.
+/// These runs are merged when finalizing the graph.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub tmp_dir: PathBuf,
+    pub max_edges_per_run: usize,
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            tmp_dir: proto_cfg.tmp_dir.unwrap_or_else(env::temp_dir),
+            max_edges_per_run: proto_cfg
+                .max_edges_per_run
+                .unwrap_or(defaults::parsing::streaming::MAX_EDGES_PER_RUN),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub tmp_dir: Option<PathBuf>,
+    pub max_edges_per_run: Option<usize>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        ProtoConfig {
+            tmp_dir: raw_cfg.tmp_dir,
+            max_edges_per_run: raw_cfg.max_edges_per_run,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawConfig {
+    #[serde(rename = "tmp-dir")]
+    pub tmp_dir: Option<PathBuf>,
+    #[serde(rename = "max-edges-per-run")]
+    pub max_edges_per_run: Option<usize>,
+}
@@ -0,0 +1,166 @@
+use crate::{
+    configs::SimpleId,
+    defaults,
+    helpers::err,
+    io::{dimacs::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Exports the graph in the format of the [9th DIMACS-challenge](http://www.diag.uniroma1.it/challenge9/format.shtml).
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// `*.gr`
+    pub graph_file: PathBuf,
+    /// `*.co`
+    pub coordinates_file: PathBuf,
+    /// The arcs' weights are this metric's values, rounded to integers.
+    pub metric_id: SimpleId,
+    /// The metric's values are multiplied by this scale before rounding,
+    /// e.g. `1000` writes kilometers as meters.
+    pub scale: u64,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            graph_file: proto_cfg.graph_file,
+            coordinates_file: proto_cfg.coordinates_file,
+            metric_id: proto_cfg.metric_id,
+            scale: proto_cfg.scale,
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: WrappedProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let cfg = Config::from(proto_cfg.writing);
+
+        if cfg.scale == 0 {
+            return Err("The scale of the DIMACS-weights should be positive.".into());
+        }
+
+        for (file, ext) in &[
+            (&cfg.graph_file, defaults::parsing::dimacs::GRAPH_EXT),
+            (
+                &cfg.coordinates_file,
+                defaults::parsing::dimacs::COORDINATES_EXT,
+            ),
+        ] {
+            match Writer::find_supported_ext(file) {
+                Ok(supported_ext) if supported_ext == *ext => (),
+                Ok(_) => {
+                    return Err(err::Msg::from(format!(
+                        "Wrong writer-dimacs-file: {} should have extension {}.",
+                        file.display(),
+                        ext
+                    )))
+                }
+                Err(msg) => {
+                    return Err(err::Msg::from(format!("Wrong writer-dimacs-file: {}", msg)))
+                }
+            }
+        }
+        Ok(cfg)
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub graph_file: PathBuf,
+    pub coordinates_file: PathBuf,
+    pub metric_id: SimpleId,
+    pub scale: u64,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.dimacs;
+
+        ProtoConfig {
+            graph_file: raw_cfg.graph_file,
+            coordinates_file: raw_cfg.coordinates_file,
+            metric_id: raw_cfg.metric_id,
+            scale: raw_cfg.scale.unwrap_or(defaults::writing::dimacs::SCALE),
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub dimacs: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    #[serde(rename = "graph-file")]
+    pub graph_file: PathBuf,
+    #[serde(rename = "coordinates-file")]
+    pub coordinates_file: PathBuf,
+    #[serde(rename = "metric-id")]
+    pub metric_id: SimpleId,
+    pub scale: Option<u64>,
+}
@@ -0,0 +1,166 @@
+use crate::{
+    configs::{parsing::clipping, SimpleId},
+    defaults,
+    helpers::err,
+    io::{geojson::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    convert::TryFrom,
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Exports a graph (or a subgraph) as GeoJSON, where every edge is a `LineString` with the selected metrics as properties.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub file: PathBuf,
+    pub is_writing_shortcuts: bool,
+    pub is_denormalizing: bool,
+    pub metric_ids: Vec<SimpleId>,
+    /// If given, only edges with both nodes inside the area are written.
+    pub area: Option<clipping::Area>,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        let area = proto_cfg.area.map(clipping::Area::from);
+        if let Some(clipping::Area::BoundingBox { min, max }) = &area {
+            if min.lat > max.lat || min.lon > max.lon {
+                return Err(format!(
+                    "The geojson-bbox has min ({}) greater than max ({}).",
+                    min, max
+                )
+                .into());
+            }
+        }
+
+        Ok(Config {
+            file: proto_cfg.file,
+            is_writing_shortcuts: proto_cfg
+                .is_writing_shortcuts
+                .unwrap_or(defaults::parsing::IS_USING_SHORTCUTS),
+            is_denormalizing: proto_cfg
+                .is_denormalizing
+                .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+            metric_ids: proto_cfg.metric_ids,
+            area,
+        })
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: WrappedProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let cfg = Config::try_from(proto_cfg.writing)?;
+
+        match Writer::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!(
+                "Wrong writer-geojson-file: {}",
+                msg
+            ))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub is_writing_shortcuts: Option<bool>,
+    pub is_denormalizing: Option<bool>,
+    pub metric_ids: Vec<SimpleId>,
+    pub area: Option<clipping::ProtoArea>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.geojson;
+
+        ProtoConfig {
+            file: raw_cfg.file,
+            is_writing_shortcuts: raw_cfg.is_writing_shortcuts,
+            is_denormalizing: raw_cfg.is_denormalizing,
+            metric_ids: raw_cfg.metric_ids.unwrap_or_default(),
+            area: raw_cfg.area.map(clipping::ProtoArea::from),
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub geojson: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "with_shortcuts")]
+    pub is_writing_shortcuts: Option<bool>,
+    #[serde(rename = "will_denormalize_metrics_by_mean")]
+    pub is_denormalizing: Option<bool>,
+    #[serde(rename = "metrics")]
+    pub metric_ids: Option<Vec<SimpleId>>,
+    pub area: Option<clipping::RawArea>,
+}
@@ -1,3 +1,8 @@
+pub mod dimacs;
+pub mod geojson;
 pub mod network;
+pub mod paths;
+pub mod queries;
 pub mod routing;
 pub mod smarts;
+pub mod sumo;
@@ -1,5 +1,5 @@
 use crate::{
-    configs::{writing::network::graph, SimpleId},
+    configs::{csv, writing::network::graph, SimpleId},
     defaults,
     helpers::err,
     io::{network::edges::Writer, SupportingFileExts},
@@ -18,6 +18,8 @@ pub struct Config {
     pub is_writing_header: bool,
     pub is_denormalizing: bool,
     pub ids: Vec<Option<SimpleId>>,
+    /// If `None`, columns are separated by whitespace.
+    pub csv: Option<csv::Config>,
 }
 
 impl SupportingFileExts for Config {
@@ -44,6 +46,7 @@ impl From<ProtoConfig> for Config {
                 .is_denormalizing
                 .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
             ids: proto_cfg.ids,
+            csv: proto_cfg.csv,
         }
     }
 }
@@ -57,6 +60,7 @@ impl From<graph::Config> for Config {
             is_writing_header: false,
             is_denormalizing: graph_cfg.edges.is_denormalizing,
             ids: graph_cfg.edges.ids,
+            csv: None,
         }
     }
 }
@@ -124,6 +128,7 @@ pub struct ProtoConfig {
     pub is_writing_shortcuts: Option<bool>,
     pub is_denormalizing: Option<bool>,
     pub ids: Vec<Option<SimpleId>>,
+    pub csv: Option<csv::Config>,
 }
 
 impl From<RawConfig> for ProtoConfig {
@@ -142,6 +147,7 @@ impl From<RawConfig> for ProtoConfig {
                     RawCategory::Ignored => None,
                 })
                 .collect(),
+            csv: raw_cfg.csv,
         }
     }
 }
@@ -169,6 +175,7 @@ pub struct RawContent {
     #[serde(rename = "will_denormalize_metrics_by_mean")]
     pub is_denormalizing: Option<bool>,
     pub ids: Vec<RawCategory>,
+    pub csv: Option<csv::Config>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -84,6 +84,7 @@ impl From<WrappedProtoConfig> for Config {
                     .is_denormalizing
                     .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
                 ids: proto_cfg.edges.ids,
+                csv: None,
             },
         }
     }
@@ -110,6 +111,7 @@ impl From<WrappedRawConfig> for WrappedProtoConfig {
                     is_writing_shortcuts: raw_cfg.edges.is_writing_shortcuts,
                     is_denormalizing: raw_cfg.edges.is_denormalizing,
                     ids: raw_cfg.edges.ids,
+                    csv: None,
                 },
             }),
         }
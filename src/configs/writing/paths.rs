@@ -0,0 +1,128 @@
+use crate::{
+    defaults,
+    helpers::err,
+    io::{paths::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Exports computed paths as GPX-tracks or KML-placemarks, depending on the file-extension.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub file: PathBuf,
+    pub is_denormalizing: bool,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            file: proto_cfg.file,
+            is_denormalizing: proto_cfg
+                .is_denormalizing
+                .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: WrappedProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let cfg = Config::from(proto_cfg.writing);
+
+        match Writer::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!("Wrong writer-paths-file: {}", msg))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub is_denormalizing: Option<bool>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.paths;
+
+        ProtoConfig {
+            file: raw_cfg.file,
+            is_denormalizing: raw_cfg.is_denormalizing,
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub paths: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "will_denormalize_metrics_by_mean")]
+    pub is_denormalizing: Option<bool>,
+}
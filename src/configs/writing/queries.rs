@@ -0,0 +1,162 @@
+use crate::{
+    defaults,
+    helpers::err,
+    io::{queries::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    convert::TryFrom,
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Exports the results of batch-routing (one row per route-pair) as csv or JSON Lines, depending on the file-extension.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub file: PathBuf,
+    pub is_denormalizing: bool,
+    /// If given, every found path is written as sequence of these ids.
+    pub sequence: Option<Sequence>,
+    /// If not given, rayon's default is used.
+    pub num_threads: Option<usize>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub enum Sequence {
+    #[serde(rename = "edge-ids")]
+    EdgeIds,
+    #[serde(rename = "node-ids")]
+    NodeIds,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl TryFrom<ProtoConfig> for Config {
+    type Error = err::Msg;
+
+    fn try_from(proto_cfg: ProtoConfig) -> err::Result<Config> {
+        if proto_cfg.num_threads == Some(0) {
+            return Err(err::Msg::from(
+                "The number of threads for batch-routing should be positive.",
+            ));
+        }
+
+        Ok(Config {
+            file: proto_cfg.file,
+            is_denormalizing: proto_cfg
+                .is_denormalizing
+                .unwrap_or(defaults::writing::WILL_DENORMALIZE_METRICS_BY_MEAN),
+            sequence: proto_cfg.sequence,
+            num_threads: proto_cfg.num_threads,
+        })
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: WrappedProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let cfg = Config::try_from(proto_cfg.writing)?;
+
+        match Writer::find_supported_ext(&cfg.file) {
+            Ok(_) => Ok(cfg),
+            Err(msg) => Err(err::Msg::from(format!(
+                "Wrong writer-queries-file: {}",
+                msg
+            ))),
+        }
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub file: PathBuf,
+    pub is_denormalizing: Option<bool>,
+    pub sequence: Option<Sequence>,
+    pub num_threads: Option<usize>,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.queries;
+
+        ProtoConfig {
+            file: raw_cfg.file,
+            is_denormalizing: raw_cfg.is_denormalizing,
+            sequence: raw_cfg.sequence,
+            num_threads: raw_cfg.num_threads,
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub queries: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    pub file: PathBuf,
+    #[serde(rename = "will_denormalize_metrics_by_mean")]
+    pub is_denormalizing: Option<bool>,
+    pub sequence: Option<Sequence>,
+    #[serde(rename = "number_of_threads")]
+    pub num_threads: Option<usize>,
+}
@@ -3,8 +3,10 @@ use crate::{
     helpers::err,
     io::{routing::Writer, SupportingFileExts},
 };
+use kissunits::distance::Kilometers;
 use serde::Deserialize;
 use std::{
+    convert::TryFrom,
     fs::OpenOptions,
     path::{Path, PathBuf},
 };
@@ -75,7 +77,27 @@ impl Config {
 
 #[derive(Debug)]
 pub enum Category {
-    RandomOrAll { seed: u64, max_count: usize },
+    RandomOrAll {
+        seed: u64,
+        max_count: usize,
+    },
+    /// Generates `max_count` routes, where a route from node `i` to node `j` is chosen with probability proportional to
+    /// `w_i * w_j * exp(-d_ij / distance_scale)` with node-weights `w` and the beeline-distance `d`.
+    /// Without distance-scale, the distance is ignored.
+    Gravity {
+        seed: u64,
+        max_count: usize,
+        weights: NodeWeights,
+        distance_scale: Option<Kilometers>,
+    },
+    /// Generates `max_count` routes, whose beeline-distances follow the given distribution.
+    /// Nodes are chosen proportional to the (optional) node-weights.
+    DistanceStratified {
+        seed: u64,
+        max_count: usize,
+        weights: Option<NodeWeights>,
+        bins: Vec<DistanceBin>,
+    },
 }
 
 impl From<ProtoCategory> for Category {
@@ -84,41 +106,175 @@ impl From<ProtoCategory> for Category {
             ProtoCategory::RandomOrAll { seed, max_count } => {
                 Category::RandomOrAll { seed, max_count }
             }
+            ProtoCategory::Gravity {
+                seed,
+                max_count,
+                weights,
+                distance_scale,
+            } => Category::Gravity {
+                seed,
+                max_count,
+                weights,
+                distance_scale,
+            },
+            ProtoCategory::DistanceStratified {
+                seed,
+                max_count,
+                weights,
+                bins,
+            } => Category::DistanceStratified {
+                seed,
+                max_count,
+                weights,
+                bins,
+            },
         }
     }
 }
 
+/// Weighted points, which are added to their nearest nodes.
+/// Points farther away from every node are ignored.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub enum NodeWeights {
+    /// Every functional line consists of `lat lon weight`.
+    #[serde(rename = "csv")]
+    Csv(PathBuf),
+    /// ESRI-ASCII-grid (`*.asc`) in WGS84, where every cell's center gets the cell's value.
+    #[serde(rename = "raster")]
+    Raster(PathBuf),
+    /// Points of interest (nodes) and landuses or buildings (ways) of an `osm.pbf`-file.
+    #[serde(rename = "osm")]
+    Osm(PathBuf),
+}
+
+/// The share of routes with a beeline-distance below `max_distance` (and above the previous bin's `max_distance`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceBin {
+    pub max_distance: Kilometers,
+    pub share: f64,
+}
+
 #[derive(Debug, Deserialize)]
-#[serde(from = "WrappedRawConfig", deny_unknown_fields)]
+#[serde(try_from = "WrappedRawConfig", deny_unknown_fields)]
 pub struct WrappedProtoConfig {
     pub file: PathBuf,
     #[serde(flatten)]
     pub category: ProtoCategory,
 }
 
-impl From<WrappedRawConfig> for WrappedProtoConfig {
-    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+impl TryFrom<WrappedRawConfig> for WrappedProtoConfig {
+    type Error = err::Msg;
+
+    fn try_from(raw_cfg: WrappedRawConfig) -> err::Result<WrappedProtoConfig> {
         let raw_cfg = raw_cfg.writing.route_pairs;
 
-        WrappedProtoConfig {
+        Ok(WrappedProtoConfig {
             file: raw_cfg.file,
-            category: ProtoCategory::from(raw_cfg.category),
-        }
+            category: ProtoCategory::try_from(raw_cfg.category)?,
+        })
     }
 }
 
 #[derive(Debug)]
 pub enum ProtoCategory {
-    RandomOrAll { seed: u64, max_count: usize },
+    RandomOrAll {
+        seed: u64,
+        max_count: usize,
+    },
+    Gravity {
+        seed: u64,
+        max_count: usize,
+        weights: NodeWeights,
+        distance_scale: Option<Kilometers>,
+    },
+    DistanceStratified {
+        seed: u64,
+        max_count: usize,
+        weights: Option<NodeWeights>,
+        bins: Vec<DistanceBin>,
+    },
 }
 
-impl From<RawCategory> for ProtoCategory {
-    fn from(raw_category: RawCategory) -> ProtoCategory {
+impl TryFrom<RawCategory> for ProtoCategory {
+    type Error = err::Msg;
+
+    fn try_from(raw_category: RawCategory) -> err::Result<ProtoCategory> {
         match raw_category {
-            RawCategory::RandomOrAll { seed, max_count } => ProtoCategory::RandomOrAll {
+            RawCategory::RandomOrAll { seed, max_count } => Ok(ProtoCategory::RandomOrAll {
                 seed: seed.unwrap_or(defaults::SEED),
                 max_count,
-            },
+            }),
+            RawCategory::Gravity {
+                seed,
+                max_count,
+                weights,
+                distance_scale,
+            } => {
+                if let Some(distance_scale) = distance_scale {
+                    if !distance_scale.is_finite() || distance_scale <= 0.0 {
+                        return Err(format!(
+                            "The distance-scale should be positive, but is {} km.",
+                            distance_scale
+                        )
+                        .into());
+                    }
+                }
+
+                Ok(ProtoCategory::Gravity {
+                    seed: seed.unwrap_or(defaults::SEED),
+                    max_count,
+                    weights,
+                    distance_scale: distance_scale.map(Kilometers),
+                })
+            }
+            RawCategory::DistanceStratified {
+                seed,
+                max_count,
+                weights,
+                bins,
+            } => {
+                if bins.is_empty() {
+                    return Err("The distance-bins shouldn't be empty.".into());
+                }
+                let mut prev_max_distance = 0.0;
+                for bin in &bins {
+                    if bin.max_distance.is_nan() || bin.max_distance <= prev_max_distance {
+                        return Err(format!(
+                            "The distance-bins' max-km should be positive and ascending, \
+                             but {} follows {}.",
+                            bin.max_distance, prev_max_distance
+                        )
+                        .into());
+                    }
+                    if !bin.share.is_finite() || bin.share < 0.0 {
+                        return Err(format!(
+                            "The distance-bins' shares should be non-negative, but one is {}.",
+                            bin.share
+                        )
+                        .into());
+                    }
+                    prev_max_distance = bin.max_distance;
+                }
+                // shares are normalized
+                let sum: f64 = bins.iter().map(|bin| bin.share).sum();
+                if sum <= 0.0 {
+                    return Err("The distance-bins' shares shouldn't sum up to 0.".into());
+                }
+
+                Ok(ProtoCategory::DistanceStratified {
+                    seed: seed.unwrap_or(defaults::SEED),
+                    max_count,
+                    weights,
+                    bins: bins
+                        .into_iter()
+                        .map(|bin| DistanceBin {
+                            max_distance: Kilometers(bin.max_distance),
+                            share: bin.share / sum,
+                        })
+                        .collect(),
+                })
+            }
         }
     }
 }
@@ -147,4 +303,28 @@ pub struct RawContent {
 pub enum RawCategory {
     #[serde(rename = "random_or_all")]
     RandomOrAll { seed: Option<u64>, max_count: usize },
+    #[serde(rename = "gravity")]
+    Gravity {
+        seed: Option<u64>,
+        max_count: usize,
+        weights: NodeWeights,
+        /// in kilometers
+        #[serde(rename = "distance-scale")]
+        distance_scale: Option<f64>,
+    },
+    #[serde(rename = "distance_stratified")]
+    DistanceStratified {
+        seed: Option<u64>,
+        max_count: usize,
+        weights: Option<NodeWeights>,
+        bins: Vec<RawDistanceBin>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawDistanceBin {
+    #[serde(rename = "max-km")]
+    pub max_distance: f64,
+    pub share: f64,
 }
@@ -1,6 +1,167 @@
-use std::path::PathBuf;
+use crate::{defaults, helpers::err};
+use kissunits::time::Seconds;
+use serde::Deserialize;
+use std::{convert::TryFrom, path::PathBuf};
 
 #[derive(Debug)]
 pub struct Config {
     pub file: PathBuf,
+    pub vehicles: Vehicles,
+    /// Seeds the drawing of vehicle-types, driver-profiles and departures.
+    pub seed: u64,
+}
+
+/// Attributes of SMARTS-vehicles, which are drawn per vehicle.
+///
+/// Shares are relative, hence they don't have to sum up to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vehicles {
+    pub types: Vec<Share>,
+    pub driver_profiles: Vec<Share>,
+    /// Used for vehicles without departure.
+    /// If not given, these vehicles start at the default start-time.
+    pub departures: Option<Departures>,
+}
+
+impl Default for Vehicles {
+    fn default() -> Vehicles {
+        Vehicles {
+            types: vec![Share {
+                id: String::from(defaults::smarts::route_file_format::VEHICLE_TYPE),
+                share: 1.0,
+            }],
+            driver_profiles: vec![Share {
+                id: String::from(defaults::smarts::route_file_format::DRIVER_PROFILE),
+                share: 1.0,
+            }],
+            departures: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Share {
+    pub id: String,
+    pub share: f64,
+}
+
+/// Distributions of departures (in seconds)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Departures {
+    Uniform {
+        from: Seconds,
+        to: Seconds,
+    },
+    /// Negative departures are resampled, so the normal distribution is truncated at 0.
+    /// The mean is non-negative.
+    Normal {
+        mean: Seconds,
+        std_dev: Seconds,
+    },
+    /// Every record of the csv-file ([RFC 4180](https://tools.ietf.org/html/rfc4180)) is a bin
+    /// with the columns `from`, `to` and `weight` (in seconds), named in the header-line,
+    /// where departures are uniformly distributed within a bin.
+    Empirical {
+        file: PathBuf,
+    },
+}
+
+impl TryFrom<RawVehicles> for Vehicles {
+    type Error = err::Msg;
+
+    fn try_from(raw_vehicles: RawVehicles) -> err::Result<Vehicles> {
+        let defaults = Vehicles::default();
+        let types = raw_vehicles.types.unwrap_or(defaults.types);
+        check_shares(&types, "vehicle-types")?;
+        let driver_profiles = raw_vehicles
+            .driver_profiles
+            .unwrap_or(defaults.driver_profiles);
+        check_shares(&driver_profiles, "driver-profiles")?;
+
+        let departures = match raw_vehicles.departures {
+            Some(RawDepartures::Uniform { from, to }) => {
+                if !from.is_finite() || !to.is_finite() || from < 0.0 || to < from {
+                    return Err(format!(
+                        "The uniform departures should satisfy 0 <= from <= to, but are from {} to {}.",
+                        from, to
+                    )
+                    .into());
+                }
+                Some(Departures::Uniform {
+                    from: Seconds(from),
+                    to: Seconds(to),
+                })
+            }
+            Some(RawDepartures::Normal { mean, std_dev }) => {
+                if !mean.is_finite() || !std_dev.is_finite() || mean < 0.0 || std_dev < 0.0 {
+                    return Err(format!(
+                        "The normal departures should have a finite, non-negative mean and a non-negative std-dev, but have mean {} and std-dev {}.",
+                        mean, std_dev
+                    )
+                    .into());
+                }
+                Some(Departures::Normal {
+                    mean: Seconds(mean),
+                    std_dev: Seconds(std_dev),
+                })
+            }
+            Some(RawDepartures::Empirical { file }) => Some(Departures::Empirical { file }),
+            None => None,
+        };
+
+        Ok(Vehicles {
+            types,
+            driver_profiles,
+            departures,
+        })
+    }
+}
+
+fn check_shares(shares: &[Share], name: &str) -> err::Feedback {
+    if shares.is_empty() {
+        return Err(format!("The SMARTS-{} should not be empty.", name).into());
+    }
+    for share in shares {
+        if !share.share.is_finite() || share.share < 0.0 {
+            return Err(format!(
+                "The share of SMARTS-{} {} should be non-negative, but is {}.",
+                name, share.id, share.share
+            )
+            .into());
+        }
+    }
+    if shares.iter().map(|share| share.share).sum::<f64>() <= 0.0 {
+        return Err(format!(
+            "The shares of SMARTS-{} should sum up to a positive value.",
+            name
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawVehicles {
+    #[serde(rename = "vehicle-types")]
+    types: Option<Vec<Share>>,
+    #[serde(rename = "driver-profiles")]
+    driver_profiles: Option<Vec<Share>>,
+    departures: Option<RawDepartures>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub enum RawDepartures {
+    #[serde(rename = "uniform")]
+    Uniform { from: f64, to: f64 },
+    #[serde(rename = "normal")]
+    Normal {
+        mean: f64,
+        #[serde(rename = "std-dev")]
+        std_dev: f64,
+    },
+    #[serde(rename = "empirical")]
+    Empirical { file: PathBuf },
 }
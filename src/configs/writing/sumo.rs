@@ -0,0 +1,150 @@
+use crate::{
+    configs::SimpleId,
+    helpers::err,
+    io::{sumo::Writer, SupportingFileExts},
+};
+use serde::Deserialize;
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+/// Exports the graph as plain-xml-network for SUMO, which can be built via `netconvert`.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// `*.nod.xml`
+    pub nodes_file: PathBuf,
+    /// `*.edg.xml`
+    pub edges_file: PathBuf,
+    pub metrics: Metrics,
+}
+
+/// Ids of the graph's metrics, which are needed for SUMO's edges.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Metrics {
+    /// Given in meters or kilometers
+    pub distance: SimpleId,
+    /// Given in kilometers per hour
+    #[serde(rename = "speed")]
+    pub kmph: SimpleId,
+    /// If not given, every edge has one lane.
+    #[serde(rename = "lane-count")]
+    pub lane_count: Option<SimpleId>,
+}
+
+impl SupportingFileExts for Config {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["yaml"]
+    }
+}
+
+impl From<ProtoConfig> for Config {
+    fn from(proto_cfg: ProtoConfig) -> Config {
+        Config {
+            nodes_file: proto_cfg.nodes_file,
+            edges_file: proto_cfg.edges_file,
+            metrics: proto_cfg.metrics,
+        }
+    }
+}
+
+impl Config {
+    pub fn try_from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Config> {
+        let path = path.as_ref();
+        let file = {
+            Config::find_supported_ext(path)?;
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => file,
+                Err(e) => {
+                    return Err(err::Msg::from(format!(
+                        "Couldn't open {} due to error: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        };
+
+        let proto_cfg: WrappedProtoConfig = match serde_yaml::from_reader(file) {
+            Ok(proto_cfg) => proto_cfg,
+            Err(e) => {
+                return Err(err::Msg::from(format!(
+                    "Serde couldn't read {} due to error: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let cfg = Config::from(proto_cfg.writing);
+
+        for file in &[&cfg.nodes_file, &cfg.edges_file] {
+            if let Err(msg) = Writer::find_supported_ext(file) {
+                return Err(err::Msg::from(format!("Wrong writer-sumo-file: {}", msg)));
+            }
+        }
+        Ok(cfg)
+    }
+
+    pub fn from_yaml<P: AsRef<Path> + ?Sized>(path: &P) -> Config {
+        match Config::try_from_yaml(path) {
+            Ok(cfg) => cfg,
+            Err(msg) => panic!("{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "WrappedRawConfig")]
+pub struct WrappedProtoConfig {
+    pub writing: ProtoConfig,
+}
+
+impl From<WrappedRawConfig> for WrappedProtoConfig {
+    fn from(raw_cfg: WrappedRawConfig) -> WrappedProtoConfig {
+        WrappedProtoConfig {
+            writing: ProtoConfig::from(raw_cfg.writing),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProtoConfig {
+    pub nodes_file: PathBuf,
+    pub edges_file: PathBuf,
+    pub metrics: Metrics,
+}
+
+impl From<RawConfig> for ProtoConfig {
+    fn from(raw_cfg: RawConfig) -> ProtoConfig {
+        let raw_cfg = raw_cfg.sumo;
+
+        ProtoConfig {
+            nodes_file: raw_cfg.nodes_file,
+            edges_file: raw_cfg.edges_file,
+            metrics: raw_cfg.metrics,
+        }
+    }
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct WrappedRawConfig {
+    pub writing: RawConfig,
+}
+
+/// Don't deny unknown fields to allow multiple configs in one yaml-file.
+#[derive(Debug, Deserialize)]
+pub struct RawConfig {
+    pub sumo: RawContent,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawContent {
+    #[serde(rename = "nodes-file")]
+    pub nodes_file: PathBuf,
+    #[serde(rename = "edges-file")]
+    pub edges_file: PathBuf,
+    pub metrics: Metrics,
+}
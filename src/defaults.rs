@@ -42,8 +42,49 @@ pub mod parsing {
 
     pub const WILL_NORMALIZE_METRICS_BY_MEAN: bool = false;
 
+    /// Bits per needed node in the bitset, which rejects unneeded nodes while parsing nodes.
+    /// A false positive costs a binary-search, and happens for at most `1 / BITS_PER_NEEDED_NODE` of the unneeded nodes.
+    pub const BITS_PER_NEEDED_NODE: usize = 8;
+
     // vehicles
 
+    pub mod clipping {
+        use crate::configs::parsing::clipping::Boundary;
+
+        pub const BOUNDARY: Boundary = Boundary::Cut;
+    }
+
+    /// [9th DIMACS-challenge](http://www.diag.uniroma1.it/challenge9/format.shtml)
+    pub mod dimacs {
+        pub const GRAPH_EXT: &str = "gr";
+        pub const COORDINATES_EXT: &str = "co";
+        /// Coordinates are given in millionths of degrees.
+        pub const COORDINATE_FACTOR: f64 = 1_000_000.0;
+    }
+
+    /// Policies of the generating-category `merge`, which behave like merging by edge-ids always did.
+    pub mod merging {
+        use crate::configs::parsing::generating::edges::merge::Policy;
+
+        pub const UNMATCHED_ROWS: Policy = Policy::Error;
+        pub const UNMATCHED_EDGES: Policy = Policy::Ignore;
+        pub const DUPLICATES: Policy = Policy::Ignore;
+    }
+
+    pub mod storage {
+        use crate::configs::parsing::storage::{Layout, Precision};
+
+        pub const LAYOUT: Layout = Layout::Wide;
+        pub const METRIC_PRECISION: Precision = Precision::F64;
+    }
+
+    pub mod streaming {
+        // about 200 MB per run, depending on the graph's dimension
+        pub const MAX_EDGES_PER_RUN: usize = 2_000_000;
+        // node-ids are deduplicated when their count has doubled, but not below this value
+        pub const MIN_NODE_IDS_FOR_DEDUP: usize = 1_000_000;
+    }
+
     pub mod vehicles {
         use crate::network::vehicles::Category as VehicleCategory;
 
@@ -58,6 +99,80 @@ pub mod writing {
 
     pub const IS_WRITING_WITH_HEADER: bool = true;
     pub const WILL_DENORMALIZE_METRICS_BY_MEAN: bool = false;
+
+    /// [RFC 4180](https://tools.ietf.org/html/rfc4180)
+    pub mod csv {
+        pub const DELIMITER: char = ',';
+        pub const QUOTE: char = '"';
+    }
+
+    pub mod dimacs {
+        /// Metrics are written as they are, before rounding to integers.
+        pub const SCALE: u64 = 1;
+    }
+
+    pub mod json {
+        /// Non-finite numbers aren't valid json.
+        pub const NON_FINITE: &str = "null";
+    }
+
+    pub mod geojson {
+        /// Colors of the workload-bins (from low to high), where the number of colors defines the number of bins.
+        pub const WORKLOAD_COLORS: &[&str] =
+            &["#ffffb2", "#fecc5c", "#fd8d3c", "#f03b20", "#bd0026"];
+    }
+
+    /// Formats of GPX- and KML-files
+    pub mod paths {
+        pub const CREATOR: &str = env!("CARGO_PKG_NAME");
+        pub const GPX_NAMESPACE: &str = "http://www.topografix.com/GPX/1/1";
+        pub const KML_NAMESPACE: &str = "http://www.opengis.net/kml/2.2";
+        /// GPX-extensions need their own namespace.
+        pub const EXTENSIONS_PREFIX: &str = "osmgraphing";
+        pub const EXTENSIONS_NAMESPACE: &str = env!("CARGO_PKG_REPOSITORY");
+    }
+
+    /// Results of batch-routing
+    pub mod queries {
+        pub mod csv_names {
+            pub const SRC_ID: &str = "src-id";
+            pub const DST_ID: &str = "dst-id";
+            pub const FOUND: &str = "found";
+            pub const HOP_DISTANCE: &str = "hop-distance";
+            pub const QUERY_MS: &str = "query-ms";
+            /// Only in JSON Lines, where costs are grouped by metric-id
+            pub const COSTS: &str = "costs";
+            /// Only in JSON Lines, where metadata is grouped by column-name
+            pub const METADATA: &str = "metadata";
+            pub const EDGE_IDS: &str = "edge-ids";
+            pub const NODE_IDS: &str = "node-ids";
+        }
+    }
+
+    pub mod route_pairs {
+        /// Sampling stops after this number of attempts per requested route.
+        pub const MAX_ATTEMPTS_PER_ROUTE: usize = 1_000;
+        /// Sampled route-pairs are checked in parallel batches of this size.
+        pub const CANDIDATES_PER_BATCH: usize = 10_000;
+
+        /// Weights of `osm`-objects, when deriving node-weights from `osm.pbf`-files.
+        pub mod osm {
+            /// Nodes with one of these keys are points of interest.
+            pub const POI_KEYS: &[&str] = &["amenity", "shop", "office", "tourism", "leisure"];
+            pub const POI_WEIGHT: f64 = 1.0;
+            /// Weight per square-kilometre of a building (closed way), spread over its nodes.
+            /// Hence, a building of `100 m²` weighs as much as a point of interest.
+            pub const BUILDING_WEIGHT: f64 = 10_000.0;
+            /// Weights per square-kilometre of landuses (closed ways), spread over their nodes.
+            /// Open ways are weighted per kilometre instead.
+            pub const LANDUSE_WEIGHTS: &[(&str, f64)] = &[
+                ("residential", 1_000.0),
+                ("commercial", 500.0),
+                ("retail", 500.0),
+                ("industrial", 200.0),
+            ];
+        }
+    }
 }
 
 pub mod smarts {
@@ -73,6 +188,23 @@ pub mod smarts {
     }
 }
 
+pub mod sumo {
+    pub const IS_WRITING: bool = false;
+    pub const ROUTES_FILE_NAME: &str = "sumo.rou.xml";
+
+    pub mod network {
+        /// Used, if no lane-count is given.
+        pub const LANE_COUNT: usize = 1;
+    }
+
+    pub mod route_file_format {
+        pub const VEHICLE_TYPE: &str = "car";
+        pub const VEHICLE_CLASS: &str = "passenger";
+        /// in seconds
+        pub const DEPART: f64 = 0.0;
+    }
+}
+
 pub mod routing {
     pub const ALPHA: f64 = 1.0;
     pub const TOLERATED_SCALE_INF: f64 = std::f64::INFINITY;
@@ -81,6 +213,53 @@ pub mod routing {
     /// This sort isn't stable in combination with a ch-construction and varying metrics, because a ch-constructor sets the ch-levels dependent on the metrics.
     /// In result, edges can't be identified in balancer.
     pub const IS_USING_CH_LEVEL_SPEEDUP: bool = true;
+
+    pub mod route_pairs {
+        /// Column-names of route-pairs-files in csv-format
+        pub mod csv_names {
+            pub const SRC_ID: &str = "src-id";
+            pub const SRC_LAT: &str = "src-lat";
+            pub const SRC_LON: &str = "src-lon";
+            pub const DST_ID: &str = "dst-id";
+            pub const DST_LAT: &str = "dst-lat";
+            pub const DST_LON: &str = "dst-lon";
+            pub const COUNT: &str = "count";
+            pub const DEPARTURE: &str = "departure";
+            pub const LABEL: &str = "label";
+        }
+    }
+}
+
+pub mod assigning {
+    use crate::configs::assigning::Method;
+    use kissunits::{distance::Kilometers, speed::KilometersPerHour, time::Hours};
+
+    pub const METHOD: Method = Method::FrankWolfe;
+    pub const MAX_ITER: usize = 50;
+    pub const RELATIVE_GAP: f64 = 0.000_1;
+    /// Bisection-steps of Frank-Wolfe's line-search
+    pub const LINE_SEARCH_ITER: usize = 30;
+
+    pub mod bpr {
+        /// Values of the Bureau of Public Roads
+        pub const ALPHA: f64 = 0.15;
+        pub const BETA: f64 = 4.0;
+    }
+
+    pub mod files {
+        pub const EDGES: &str = "edges.csv";
+        pub const STATS: &str = "stats.csv";
+    }
+
+    /// Like the Nagel-Schreckenberg-Model, every vehicle needs `7.5 m` space.
+    pub const VEHICLE_LENGTH: Kilometers = Kilometers(0.0075);
+    /// Time-gap between vehicles, known as two-second-rule
+    pub const TIME_GAP: Hours = Hours(2.0 / 3600.0);
+
+    /// Vehicles per hour passing a lane, if every vehicle drives with the given speed keeping the time-gap.
+    pub fn capacity_per_lane(kmph: KilometersPerHour) -> f64 {
+        kmph.0 / (VEHICLE_LENGTH.0 + kmph.0 * TIME_GAP.0)
+    }
 }
 
 #[cfg(feature = "gpl")]
@@ -104,12 +283,20 @@ pub mod balancing {
     pub const WORK_SIZE_MINUS: usize = 10;
     pub const NUM_THREADS: usize = 4;
     pub const IS_ERR_WHEN_METRIC_IS_ZERO: bool = true;
+    pub const CONTRACTION: configs::balancing::Contraction =
+        configs::balancing::Contraction::MultiChConstructor;
+    pub const ROUTE_CHOICE: configs::balancing::RouteChoice =
+        configs::balancing::RouteChoice::Uniform;
 
     pub mod stats {
         pub const DIR: &str = "stats";
+        /// Contains the absolute workloads per time-slice
+        pub const TIME_SLICES_DIR: &str = "time_slices";
+        pub const IS_WRITING_GEOJSON: bool = false;
 
         pub mod files {
             pub const ABS_WORKLOADS: &str = "abs_workloads.csv";
+            pub const WORKLOADS_GEOJSON: &str = "workloads.geojson";
         }
 
         pub mod csv_names {
@@ -119,6 +306,16 @@ pub mod balancing {
 
     pub mod files {
         pub const ITERATION_CFG: &str = "iteration.yaml";
+        pub const CONVERGENCE: &str = "convergence.csv";
+    }
+
+    pub mod checkpoint {
+        pub const DIR: &str = "checkpoint";
+
+        pub mod files {
+            pub const STATE: &str = "state.yaml";
+            pub const EDGES: &str = "edges.csv";
+        }
     }
 
     /// Nagel-Schreckenberg-Model -> `7.5 m` space for every vehicle
@@ -173,7 +370,7 @@ pub mod balancing {
 
         for (edge_idx, new_metric) in new_metrics.iter_mut().enumerate() {
             *new_metric = {
-                let old_metric = metrics[EdgeIdx(edge_idx)][*old_metric_idx];
+                let old_metric = metrics.get_value(EdgeIdx(edge_idx), old_metric_idx);
 
                 match balancing_cfg.optimization.method {
                     configs::balancing::OptimizationMethod::ExplicitEuler { correction } => {
@@ -252,7 +449,7 @@ pub mod balancing {
         // update graph's metric
 
         for (edge_idx, new_metric) in new_metrics.into_iter().enumerate() {
-            metrics[EdgeIdx(edge_idx)][*old_metric_idx] = new_metric;
+            metrics.set_value(EdgeIdx(edge_idx), old_metric_idx, new_metric);
         }
 
         Ok(())
@@ -271,7 +468,11 @@ pub mod explorating {
 
 pub mod network {
     pub mod nodes {
+        use kissunits::distance::Kilometers;
+
         pub const LEVEL: usize = 0;
+        /// Coordinates (e.g. weighted points or locations of route-pairs) farther away from every node aren't snapped to a node.
+        pub const MAX_SNAPPING_DISTANCE: Kilometers = Kilometers(2.0);
     }
 
     use crate::{
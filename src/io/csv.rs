@@ -0,0 +1,200 @@
+use crate::{
+    configs::csv,
+    helpers::{self, err},
+};
+use std::{
+    io::{BufRead, Lines, Write},
+    path::{Path, PathBuf},
+};
+
+/// Writes the fields as one line, either as [RFC 4180](https://tools.ietf.org/html/rfc4180)-record
+/// or separated by spaces, if no csv-config is given.
+pub(super) fn write_record<W: Write, S: AsRef<str>>(
+    writer: &mut W,
+    fields: &[S],
+    csv_cfg: Option<&csv::Config>,
+) -> err::Feedback {
+    for (i, field) in fields.iter().enumerate() {
+        let field = field.as_ref();
+
+        match csv_cfg {
+            Some(csv_cfg) => {
+                if i > 0 {
+                    write!(writer, "{}", csv_cfg.delimiter)?;
+                }
+                write!(writer, "{}", escape(field, csv_cfg))?;
+            }
+            None => {
+                if i > 0 {
+                    write!(writer, " ")?;
+                }
+                write!(writer, "{}", field)?;
+            }
+        }
+    }
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Quotes the field, if it contains the delimiter, the quote or line-breaks.
+fn escape(field: &str, csv_cfg: &csv::Config) -> String {
+    if !field
+        .chars()
+        .any(|c| c == csv_cfg.delimiter || c == csv_cfg.quote || c == '\n' || c == '\r')
+    {
+        return String::from(field);
+    }
+
+    let mut escaped = String::with_capacity(field.len() + 2);
+    escaped.push(csv_cfg.quote);
+    for c in field.chars() {
+        if c == csv_cfg.quote {
+            escaped.push(c);
+        }
+        escaped.push(c);
+    }
+    escaped.push(csv_cfg.quote);
+    escaped
+}
+
+/// Iterates over the records of a file, either as [RFC 4180](https://tools.ietf.org/html/rfc4180)-records
+/// or as whitespace-separated lines, if no csv-config is given.
+///
+/// Empty lines are skipped, and without csv-config, lines starting with `#` are skipped as well.
+/// Quoted fields may contain line-breaks, hence a record may span multiple lines.
+pub(super) struct Records<R: BufRead> {
+    lines: Lines<R>,
+    line_idx: usize,
+    path: PathBuf,
+    csv_cfg: Option<csv::Config>,
+}
+
+impl<R: BufRead> Records<R> {
+    pub(super) fn new(reader: R, path: &Path, csv_cfg: Option<csv::Config>) -> Records<R> {
+        Records {
+            lines: reader.lines(),
+            line_idx: 0,
+            path: PathBuf::from(path),
+            csv_cfg,
+        }
+    }
+
+    fn next_line(&mut self) -> Option<err::Result<String>> {
+        let line = self.lines.next()?;
+        self.line_idx += 1;
+        Some(
+            line.map(|line| line.trim_end_matches('\r').to_owned())
+                .map_err(err::Msg::from),
+        )
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = err::Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<err::Result<Vec<String>>> {
+        let mut line = loop {
+            let line = match self.next_line()? {
+                Ok(line) => line,
+                Err(msg) => return Some(Err(msg)),
+            };
+            let is_functional = match self.csv_cfg {
+                Some(_) => !line.is_empty(),
+                None => helpers::is_line_functional(&line),
+            };
+            if is_functional {
+                break line;
+            }
+        };
+
+        let csv_cfg = match &self.csv_cfg {
+            Some(csv_cfg) => *csv_cfg,
+            None => {
+                return Some(Ok(line.split_whitespace().map(String::from).collect()));
+            }
+        };
+
+        let first_line_idx = self.line_idx;
+        loop {
+            match split(&line, &csv_cfg) {
+                Ok(Some(fields)) => return Some(Ok(fields)),
+                // quoted field continues in the next line
+                Ok(None) => match self.next_line() {
+                    Some(Ok(next_line)) => {
+                        line.push('\n');
+                        line.push_str(&next_line);
+                    }
+                    Some(Err(msg)) => return Some(Err(msg)),
+                    None => {
+                        return Some(Err(format!(
+                            "The quoted field starting in line {} of {} is not closed.",
+                            first_line_idx,
+                            self.path.display()
+                        )
+                        .into()))
+                    }
+                },
+                Err(msg) => {
+                    return Some(Err(format!(
+                        "Line {} of {} is no valid csv-record: {}",
+                        first_line_idx,
+                        self.path.display(),
+                        msg
+                    )
+                    .into()))
+                }
+            }
+        }
+    }
+}
+
+/// Returns `None`, if a quoted field isn't closed in this line.
+fn split(line: &str, csv_cfg: &csv::Config) -> Result<Option<Vec<String>>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut is_quoted_field = false;
+    let mut is_in_quotes = false;
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_in_quotes {
+            if c == csv_cfg.quote {
+                // doubled quotes are escaped quotes
+                if chars.peek() == Some(&csv_cfg.quote) {
+                    chars.next();
+                    field.push(c);
+                } else {
+                    is_in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == csv_cfg.delimiter {
+            fields.push(field);
+            field = String::new();
+            is_quoted_field = false;
+        } else if is_quoted_field {
+            return Err(format!(
+                "Quoted field \"{}\" should be followed by a delimiter, but is followed by '{}'.",
+                field, c
+            ));
+        } else if c == csv_cfg.quote {
+            if !field.is_empty() {
+                return Err(format!(
+                    "Unquoted field \"{}\" should not contain quotes.",
+                    field
+                ));
+            }
+            is_quoted_field = true;
+            is_in_quotes = true;
+        } else {
+            field.push(c);
+        }
+    }
+
+    if is_in_quotes {
+        return Ok(None);
+    }
+    fields.push(field);
+    Ok(Some(fields))
+}
@@ -1,12 +1,31 @@
 use crate::helpers::err;
 use std::path::Path;
 
+mod csv;
 mod parsing;
 mod writing;
 
+pub mod assigning {
+    pub use crate::io::writing::assigning::Writer;
+}
+pub mod dimacs {
+    pub use crate::io::writing::dimacs::Writer;
+}
+pub mod geojson {
+    pub use crate::io::writing::geojson::Writer;
+}
+pub mod paths {
+    pub use crate::io::writing::paths::Writer;
+}
+pub mod queries {
+    pub use crate::io::writing::queries::Writer;
+}
 pub mod smarts {
     pub use crate::io::writing::smarts::Writer;
 }
+pub mod sumo {
+    pub use crate::io::writing::sumo::Writer;
+}
 pub mod network {
     pub mod graph {
         pub use crate::io::parsing::network::graph::Parser;
@@ -16,14 +35,23 @@ pub mod network {
         pub use crate::io::parsing::network::edges::Parser;
         pub use crate::io::writing::network::edges::Writer;
     }
+    pub mod osc {
+        pub use crate::io::parsing::network::osc::Parser;
+    }
+    pub mod poly {
+        pub use crate::io::parsing::network::poly::Parser;
+    }
 }
 pub mod routing {
     pub use crate::io::parsing::routing::Parser;
     pub use crate::io::writing::routing::Writer;
 }
+pub mod weights {
+    pub use crate::io::parsing::weights::Parser;
+}
 #[cfg(feature = "gpl")]
 pub mod evaluating_balance {
-    pub use crate::io::writing::evaluating_balance::Writer;
+    pub use crate::io::writing::evaluating_balance::{TimeSliceWorkloads, Writer};
 }
 
 pub fn ext_from<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<&str> {
@@ -88,12 +116,13 @@ pub trait SupportingFileExts {
 pub enum MapFileExt {
     PBF,
     FMI,
+    DIMACS,
 }
 
 impl SupportingMapFileExts for MapFileExt {}
 impl SupportingFileExts for MapFileExt {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["osm.pbf", "pbf", "fmi"]
+        &["osm.pbf", "pbf", "fmi", "gr"]
     }
 }
 
@@ -102,6 +131,7 @@ pub trait SupportingMapFileExts: SupportingFileExts {
         match Self::find_supported_ext(path)? {
             "osm.pbf" | "pbf" => Ok(MapFileExt::PBF),
             "fmi" => Ok(MapFileExt::FMI),
+            "gr" => Ok(MapFileExt::DIMACS),
             _ => Err(err::Msg::from(
                 "Should not happen, since 'find_supported_ext(...)' should cover this.",
             )),
@@ -1,2 +1,3 @@
 pub mod network;
 pub mod routing;
+pub mod weights;
@@ -1,38 +1,45 @@
 use crate::{
-    helpers::{self, err},
-    io::{self, SupportingFileExts},
-};
-use std::{
-    fs::OpenOptions,
-    io::{BufRead, BufReader},
-    path::Path,
+    configs::csv,
+    helpers::err,
+    io::{self, csv::Records, SupportingFileExts},
 };
+use std::{fs::OpenOptions, io::BufReader, path::Path};
+
+/// Column-names of the header-line
+pub type Header = Vec<String>;
 
 pub struct Parser;
 
 impl Parser {
+    /// Returns the header (if expected) and the following records,
+    /// which are csv-records (RFC 4180) or whitespace-separated lines, if no csv-config is given.
     pub fn new_reader<P: AsRef<Path>>(
         path: &P,
         is_file_with_header: bool,
-    ) -> err::Result<impl Iterator<Item = String>> {
+        csv_cfg: Option<csv::Config>,
+    ) -> err::Result<(
+        Option<Header>,
+        impl Iterator<Item = err::Result<Vec<String>>>,
+    )> {
         let path = path.as_ref();
 
-        io::network::edges::Parser::check_ext_support(&path)?;
-        let file = OpenOptions::new()
-            .read(true)
-            .open(&path)
-            .expect(&format!("Couldn't open {}", path.display()));
-        let mut reader = BufReader::new(file)
-            .lines()
-            .map(Result::unwrap)
-            .filter(helpers::is_line_functional);
+        io::network::edges::Parser::check_ext_support(path)?;
+        let file = match OpenOptions::new().read(true).open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+            }
+        };
+        let mut records = Records::new(BufReader::new(file), path, csv_cfg);
 
-        // consume header-line
-        if is_file_with_header {
-            reader.next();
-        }
+        // consume header-line (if the file isn't empty)
+        let header = if is_file_with_header {
+            records.next().transpose()?
+        } else {
+            None
+        };
 
-        Ok(reader)
+        Ok((header, records))
     }
 }
 
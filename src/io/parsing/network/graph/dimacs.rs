@@ -0,0 +1,177 @@
+use crate::{
+    configs::parsing,
+    defaults::parsing::dimacs as format,
+    helpers::err,
+    network::{EdgeBuilder, NodeBuilder, ProtoNode, ProtoShortcut},
+};
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Parses graphs of the [9th DIMACS-challenge](http://www.diag.uniroma1.it/challenge9/format.shtml).
+///
+/// The map-file (`*.gr`) contains the arcs as `a <src-id> <dst-id> <weight>`,
+/// which are parsed according to the edges' categories (without the leading `a`).
+/// Hence the weight can be mapped onto any named metric.
+///
+/// The coordinates-file (`*.co`) contains the nodes as `v <id> <x> <y>`,
+/// which are parsed according to the nodes' categories (without the leading `v`).
+/// Coordinates are given in millionths of degrees, where x is the longitude and y is the latitude.
+pub struct Parser;
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser {}
+    }
+}
+
+impl super::Parsing for Parser {
+    fn preprocess(&mut self, cfg: &parsing::Config) -> err::Feedback {
+        info!("START Start preprocessing dimacs-parser.");
+        super::check_config(cfg)?;
+
+        if cfg.clipping.is_some() {
+            return Err("Clipping is only supported for pbf-files.".into());
+        }
+        if cfg.dimacs.is_none() {
+            return Err("The DIMACS-parser needs a dimacs-config.".into());
+        }
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn parse_ways(&self, builder: &mut EdgeBuilder) -> err::Feedback {
+        info!("START Create edges from input-file.");
+        let map_file = builder.cfg().map_file.clone();
+        let mut arc_count = 0;
+        let expected_arc_count = for_each_line(&map_file, "sp", "a", |params| {
+            let proto_edge = ProtoShortcut::try_from_str(params, &builder.cfg().edges.categories)?;
+            builder.insert(proto_edge)?;
+            arc_count += 1;
+            Ok(())
+        })?;
+        if arc_count != expected_arc_count {
+            return Err(format!(
+                "The DIMACS-file {} should have {} arcs, but has {}.",
+                map_file.display(),
+                expected_arc_count,
+                arc_count
+            )
+            .into());
+        }
+        info!("FINISHED");
+
+        Ok(())
+    }
+
+    fn parse_nodes(&self, builder: &mut NodeBuilder) -> err::Feedback {
+        info!("START Create nodes from input-file.");
+        let coordinates_file = match &builder.cfg().dimacs {
+            Some(dimacs_cfg) => dimacs_cfg.coordinates_file.clone(),
+            None => return Err("The DIMACS-parser needs a dimacs-config.".into()),
+        };
+        let mut node_count = 0;
+        let expected_node_count = for_each_line(&coordinates_file, "aux", "v", |params| {
+            let mut proto_node = ProtoNode::from_str(params, &builder.cfg().nodes)?;
+            proto_node.coord.lat /= format::COORDINATE_FACTOR;
+            proto_node.coord.lon /= format::COORDINATE_FACTOR;
+            builder.insert(proto_node);
+            node_count += 1;
+            Ok(())
+        })?;
+        if node_count != expected_node_count {
+            return Err(format!(
+                "The DIMACS-file {} should have {} nodes, but has {}.",
+                coordinates_file.display(),
+                expected_node_count,
+                node_count
+            )
+            .into());
+        }
+        info!("FINISHED");
+
+        Ok(())
+    }
+}
+
+/// Calls `process` with the params (without descriptor) of every line starting with the given descriptor,
+/// and returns the count given in the problem-line (`p <problem> ... <count>`).
+///
+/// Comment-lines (`c ...`) and empty lines are skipped.
+fn for_each_line<F>(
+    file: &Path,
+    problem: &str,
+    descriptor: &str,
+    mut process: F,
+) -> err::Result<usize>
+where
+    F: FnMut(&str) -> err::Feedback,
+{
+    let input_file = match OpenOptions::new().read(true).open(file) {
+        Ok(input_file) => input_file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+        }
+    };
+
+    let mut count = None;
+    for (line_idx, line) in BufReader::new(input_file).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        let (first, params) = match line.find(char::is_whitespace) {
+            Some(idx) => (&line[..idx], &line[idx..]),
+            None => (line, ""),
+        };
+
+        match first {
+            "" | "c" => continue,
+            "p" => {
+                let params: Vec<&str> = params.split_whitespace().collect();
+                if count.is_some() || params.first() != Some(&problem) {
+                    return Err(format!(
+                        "Line {} of {} should not be the problem-line '{}'.",
+                        line_idx + 1,
+                        file.display(),
+                        line
+                    )
+                    .into());
+                }
+                count = params.last().and_then(|param| param.parse::<usize>().ok());
+                if count.is_none() {
+                    return Err(format!(
+                        "The problem-line '{}' of {} should end with a count.",
+                        line,
+                        file.display()
+                    )
+                    .into());
+                }
+            }
+            _ if first == descriptor => {
+                if count.is_none() {
+                    return Err(format!(
+                        "The problem-line of {} should precede line {}.",
+                        file.display(),
+                        line_idx + 1
+                    )
+                    .into());
+                }
+                process(params)?;
+            }
+            _ => {
+                return Err(format!(
+                    "Line {} of {} has unknown descriptor '{}'.",
+                    line_idx + 1,
+                    file.display(),
+                    first
+                )
+                .into())
+            }
+        }
+    }
+
+    count.ok_or_else(|| format!("{} should have a problem-line.", file.display()).into())
+}
@@ -35,6 +35,10 @@ impl super::Parsing for Parser {
         info!("START Start preprocessing fmi-parser.");
         super::check_config(cfg)?;
 
+        if cfg.clipping.is_some() {
+            return Err("Clipping is only supported for pbf-files.".into());
+        }
+
         // only functional-lines are counted
         let mut line_number = 0;
         let mut is_taking_counts = false;
@@ -299,6 +303,7 @@ impl ProtoShortcut {
         Ok(ProtoShortcut {
             proto_edge: ProtoEdge {
                 id: edge_id,
+                way_id: None,
                 src_id: src_id.ok_or("Proto-edge should have a src-id, but doesn't.".to_owned())?,
                 dst_id: dst_id.ok_or("Proto-edge should have a dst-id, but doesn't.".to_owned())?,
                 metrics: metric_values,
@@ -1,3 +1,4 @@
+pub mod dimacs;
 pub mod fmi;
 pub mod pbf;
 
@@ -11,7 +12,7 @@ use crate::{
 use log::{info, warn};
 use std::path::Path;
 
-/// The parser parsing `*.osm.pbf`- and `*.fmi`-files into a graphbuilder or a graph.
+/// The parser parsing `*.osm.pbf`-, `*.fmi`- and `*.gr`-files (DIMACS) into a graphbuilder or a graph.
 ///
 ///
 /// ## The filter-pipeline
@@ -49,6 +50,7 @@ impl Parser {
         match Parser::from_path(&cfg.map_file)? {
             MapFileExt::PBF => pbf::Parser::new().parse(cfg),
             MapFileExt::FMI => fmi::Parser::new().parse(cfg),
+            MapFileExt::DIMACS => dimacs::Parser::new().parse(cfg),
         }
     }
 
@@ -56,6 +58,7 @@ impl Parser {
         match Parser::from_path(&cfg.map_file)? {
             MapFileExt::PBF => pbf::Parser::new().parse_and_finalize(cfg),
             MapFileExt::FMI => fmi::Parser::new().parse_and_finalize(cfg),
+            MapFileExt::DIMACS => dimacs::Parser::new().parse_and_finalize(cfg),
         }
     }
 }
@@ -63,7 +66,7 @@ impl Parser {
 impl SupportingMapFileExts for Parser {}
 impl SupportingFileExts for Parser {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["pbf", "fmi"]
+        &["pbf", "fmi", "gr"]
     }
 }
 
@@ -157,6 +160,10 @@ fn check_config(cfg: &parsing::Config) -> err::Feedback {
                         a: _,
                         b: _,
                     } => 1,
+                    generating::edges::Category::Formula {
+                        result: _,
+                        expression: _,
+                    } => 1,
                     generating::edges::Category::Copy { from: _, to: _ } => 1,
                     generating::edges::Category::Haversine { unit: _, id: _ } => 1,
                     generating::edges::Category::Custom {
@@ -167,8 +174,10 @@ fn check_config(cfg: &parsing::Config) -> err::Feedback {
                     generating::edges::Category::Merge {
                         from: _,
                         is_file_with_header: _,
-                        edge_id: _,
+                        key: _,
+                        policies: _,
                         edges_info: _,
+                        csv: _,
                     } => 0,
                 })
                 .sum()
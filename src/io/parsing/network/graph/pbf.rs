@@ -1,67 +1,210 @@
 use crate::{
-    configs::parsing::{self, edges},
+    configs::parsing::{self, clipping, edges},
     defaults::capacity::DimVec,
     helpers::err,
-    network::{EdgeBuilder, NodeBuilder, ProtoEdge, ProtoNode, StreetCategory},
+    io,
+    network::{EdgeBuilder, NodeBuilder, Polygon, ProtoEdge, ProtoNode, StreetCategory},
 };
 use kissunits::geo::Coordinate;
 use log::info;
-use osmpbfreader::{reader::OsmPbfReader, OsmObj};
+use osmpbfreader::{reader::OsmPbfReader, OsmObj, Way};
 use smallvec::smallvec;
-use std::fs::OpenOptions;
+use std::{collections::HashSet, fs::OpenOptions};
 
-pub struct Parser;
+pub struct Parser {
+    /// If the config clips the graph, only these nodes are inside the clipping-area.
+    inner_node_ids: Option<HashSet<i64>>,
+}
 
 impl Parser {
     pub fn new() -> Parser {
-        Parser {}
+        Parser {
+            inner_node_ids: None,
+        }
+    }
+
+    /// Returns true, if the edge should be added with respect to the optional clipping-area.
+    fn is_edge_in_area(&self, src_id: i64, dst_id: i64, boundary: clipping::Boundary) -> bool {
+        let inner_node_ids = match &self.inner_node_ids {
+            Some(inner_node_ids) => inner_node_ids,
+            None => return true,
+        };
+
+        let is_src_inside = inner_node_ids.contains(&src_id);
+        let is_dst_inside = inner_node_ids.contains(&dst_id);
+        match boundary {
+            clipping::Boundary::Cut => is_src_inside && is_dst_inside,
+            clipping::Boundary::Keep => is_src_inside || is_dst_inside,
+        }
+    }
+
+    /// Collects all nodes inside the clipping-area, since ways don't know their nodes' coordinates.
+    fn collect_inner_node_ids(
+        &mut self,
+        cfg: &parsing::Config,
+        clipping_cfg: &clipping::Config,
+    ) -> err::Feedback {
+        info!("START Collect nodes inside clipping-area.");
+        let polygon = match &clipping_cfg.area {
+            clipping::Area::BoundingBox { min, max } => Polygon::from_bbox(*min, *max),
+            clipping::Area::Polygon { file } => io::network::poly::Parser::parse(file)?,
+        };
+
+        let file = OpenOptions::new()
+            .read(true)
+            .open(&cfg.map_file)
+            .expect(&format!("Couldn't open {}", cfg.map_file.display()));
+        let mut inner_node_ids = HashSet::new();
+        for node in OsmPbfReader::new(file)
+            .par_iter()
+            .filter_map(Result::ok)
+            .filter_map(|obj| match obj {
+                OsmObj::Node(node) => Some(node),
+                _ => None,
+            })
+        {
+            let coord = Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon);
+            if polygon.contains(&coord) {
+                inner_node_ids.insert(node.id.0);
+            }
+        }
+        info!(
+            "Found {} nodes inside the clipping-area.",
+            inner_node_ids.len()
+        );
+
+        self.inner_node_ids = Some(inner_node_ids);
+        info!("FINISHED");
+        Ok(())
     }
 }
 
+/// Checks, if the config can be used for osm-data, e.g. because pbf-files don't provide any distances.
+pub(in crate::io) fn check_config(cfg: &parsing::Config) -> err::Feedback {
+    super::check_config(cfg)?;
+
+    for category in cfg.edges.categories.iter() {
+        match category {
+            edges::Category::Meta { info, id: _ } => match info {
+                edges::MetaInfo::SrcId | edges::MetaInfo::DstId => {
+                    // already checked in check_config(...)
+                }
+                edges::MetaInfo::EdgeId
+                | edges::MetaInfo::SrcIdx
+                | edges::MetaInfo::SrcLat
+                | edges::MetaInfo::SrcLon
+                | edges::MetaInfo::DstIdx
+                | edges::MetaInfo::DstLat
+                | edges::MetaInfo::DstLon
+                | edges::MetaInfo::ShortcutIdx0
+                | edges::MetaInfo::ShortcutIdx1 => {
+                    return Err(format!("{:?} are not supported in pbf-files.", category).into())
+                }
+            },
+            edges::Category::Metric { unit, id: _ } => {
+                // Only speeds and lane-counts are provided by pbf-files.
+                if unit != &edges::metrics::UnitInfo::LaneCount
+                    && unit.dimension() != edges::metrics::Dimension::SPEED
+                {
+                    return Err(format!(
+                        "The {:?} of an edge in a pbf-file has to be calculated, \
+                         but is expected to be provided.",
+                        category
+                    )
+                    .into());
+                }
+            }
+            edges::Category::Ignored => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the proto-edges of an osm-way as expected by the config, ignoring the (optional) clipping-area.
+/// Ways, which are no streets for the configured vehicles, don't have any edges.
+///
+/// The config is expected to be checked already (see `check_config(...)`).
+pub(in crate::io) fn proto_edges_from(cfg: &parsing::Config, mut way: Way) -> Vec<ProtoEdge> {
+    if way.nodes.len() < 2 {
+        return Vec::new();
+    }
+
+    // collect relevant data from file, if way-type is as expected by user
+    let highway_tag = match StreetCategory::from(&way) {
+        Some(highway_tag) => highway_tag,
+        None => return Vec::new(),
+    };
+    if !highway_tag.is_for(&cfg.vehicles.category, cfg.vehicles.are_drivers_picky) {
+        return Vec::new();
+    }
+
+    // get nodes of way to create proto-edges later
+    let (is_oneway, is_reverse) = highway_tag.parse_oneway(&way);
+    if is_reverse {
+        way.nodes.reverse();
+    }
+    let iter_range = if is_oneway {
+        0..0
+    } else {
+        // if not oneway
+        // -> add node-IDs reversed to generate edges forwards and backwards
+        // -> don't use last one, to not use it twice (a->b->c->c->b->a)
+        0..(way.nodes.len() - 1)
+    };
+    let nodes: Vec<i64> = way
+        .nodes
+        .iter()
+        .chain(way.nodes[iter_range].iter().rev())
+        .map(|id| id.0)
+        .collect();
+
+    // Collect metrics as expected by user-config
+    // ATTENTION: A way contains multiple edges, thus be careful when adding new metrics.
+
+    let mut metrics: DimVec<_> = smallvec![];
+
+    for category in cfg.edges.categories.iter() {
+        match category {
+            edges::Category::Meta { info: _, id: _ } => {
+                // already checked in preprocessing
+            }
+            edges::Category::Metric { unit, id: _ } => {
+                if unit == &edges::metrics::UnitInfo::LaneCount {
+                    let lane_count = highway_tag.parse_lane_count(&way);
+                    metrics.push(lane_count as f64);
+                } else {
+                    // any speed, as checked in preprocessing
+                    let maxspeed = highway_tag.parse_maxspeed(&way);
+                    metrics
+                        .push(edges::metrics::UnitInfo::KilometersPerHour.convert(unit, *maxspeed));
+                }
+            }
+            edges::Category::Ignored => {
+                // already checked in preprocessing
+            }
+        }
+    }
+
+    // for n nodes in a way, you can create (n-1) edges
+    (0..(nodes.len() - 1))
+        .map(|node_idx| ProtoEdge {
+            id: None,
+            way_id: Some(way.id.0),
+            src_id: nodes[node_idx],
+            dst_id: nodes[node_idx + 1],
+            metrics: metrics.clone(),
+        })
+        .collect()
+}
+
 impl super::Parsing for Parser {
     fn preprocess(&mut self, cfg: &parsing::Config) -> err::Feedback {
         info!("START Start preprocessing pbf-parser.");
-        super::check_config(cfg)?;
+        check_config(cfg)?;
 
-        for category in cfg.edges.categories.iter() {
-            match category {
-                edges::Category::Meta { info, id: _ } => match info {
-                    edges::MetaInfo::SrcId | edges::MetaInfo::DstId => {
-                        // already checked in check_config(...)
-                    }
-                    edges::MetaInfo::EdgeId
-                    | edges::MetaInfo::SrcIdx
-                    | edges::MetaInfo::SrcLat
-                    | edges::MetaInfo::SrcLon
-                    | edges::MetaInfo::DstIdx
-                    | edges::MetaInfo::DstLat
-                    | edges::MetaInfo::DstLon
-                    | edges::MetaInfo::ShortcutIdx0
-                    | edges::MetaInfo::ShortcutIdx1 => {
-                        return Err(format!("{:?} are not supported in pbf-files.", category).into())
-                    }
-                },
-                edges::Category::Metric { unit, id: _ } => match unit {
-                    edges::metrics::UnitInfo::Meters
-                    | edges::metrics::UnitInfo::Kilometers
-                    | edges::metrics::UnitInfo::Seconds
-                    | edges::metrics::UnitInfo::Minutes
-                    | edges::metrics::UnitInfo::Hours
-                    | edges::metrics::UnitInfo::F64 => {
-                        return Err(format!(
-                            "The {:?} of an edge in a pbf-file has to be calculated, \
-                             but is expected to be provided.",
-                            category
-                        )
-                        .into());
-                    }
-                    edges::metrics::UnitInfo::KilometersPerHour
-                    | edges::metrics::UnitInfo::LaneCount => {
-                        // irrelevant
-                    }
-                },
-                edges::Category::Ignored => (),
-            }
+        if let Some(clipping_cfg) = &cfg.clipping {
+            self.collect_inner_node_ids(cfg, clipping_cfg)?;
         }
 
         info!("FINISHED");
@@ -78,7 +221,7 @@ impl super::Parsing for Parser {
                 builder.cfg().map_file.display()
             ));
 
-        for mut way in OsmPbfReader::new(file)
+        for way in OsmPbfReader::new(file)
             .par_iter()
             .filter_map(Result::ok)
             .filter_map(|obj| match obj {
@@ -86,85 +229,20 @@ impl super::Parsing for Parser {
                 _ => None,
             })
         {
-            if way.nodes.len() < 2 {
-                continue;
-            }
-
-            // collect relevant data from file, if way-type is as expected by user
-            let highway_tag = match StreetCategory::from(&way) {
-                Some(highway_tag) => highway_tag,
-                None => continue,
-            };
-            if !highway_tag.is_for(
-                &builder.cfg().vehicles.category,
-                builder.cfg().vehicles.are_drivers_picky,
-            ) {
-                continue;
-            }
-
-            // get nodes of way to create proto-edges later
-            let (is_oneway, is_reverse) = highway_tag.parse_oneway(&way);
-            if is_reverse {
-                way.nodes.reverse();
-            }
-            let iter_range = if is_oneway {
-                0..0
-            } else {
-                // if not oneway
-                // -> add node-IDs reversed to generate edges forwards and backwards
-                // -> don't use last one, to not use it twice (a->b->c->c->b->a)
-                0..(way.nodes.len() - 1)
-            };
-            let nodes: Vec<i64> = way
-                .nodes
-                .iter()
-                .chain(way.nodes[iter_range].iter().rev())
-                .map(|id| id.0)
-                .collect();
-
-            // Collect metrics as expected by user-config
-            // ATTENTION: A way contains multiple edges, thus be careful when adding new metrics.
-
-            let mut metrics: DimVec<_> = smallvec![];
-
-            for category in builder.cfg().edges.categories.iter() {
-                match category {
-                    edges::Category::Meta { info: _, id: _ } => {
-                        // already checked in preprocessing
-                    }
-                    edges::Category::Metric { unit, id: _ } => match unit {
-                        edges::metrics::UnitInfo::KilometersPerHour => {
-                            let maxspeed = highway_tag.parse_maxspeed(&way);
-                            metrics.push(*maxspeed);
-                        }
-                        edges::metrics::UnitInfo::LaneCount => {
-                            let lane_count = highway_tag.parse_lane_count(&way);
-                            metrics.push(lane_count as f64);
-                        }
-                        edges::metrics::UnitInfo::Meters
-                        | edges::metrics::UnitInfo::Kilometers
-                        | edges::metrics::UnitInfo::Seconds
-                        | edges::metrics::UnitInfo::Minutes
-                        | edges::metrics::UnitInfo::Hours
-                        | edges::metrics::UnitInfo::F64 => {
-                            // already checked in preprocessing
-                        }
-                    },
-                    edges::Category::Ignored => {
-                        // already checked in preprocessing
+            for proto_edge in proto_edges_from(builder.cfg(), way) {
+                // skip edges outside of the (optional) clipping-area
+                if let Some(clipping_cfg) = &builder.cfg().clipping {
+                    if !self.is_edge_in_area(
+                        proto_edge.src_id,
+                        proto_edge.dst_id,
+                        clipping_cfg.boundary,
+                    ) {
+                        continue;
                     }
                 }
-            }
 
-            // for n nodes in a way, you can create (n-1) edges
-            for node_idx in 0..(nodes.len() - 1) {
                 // add proto-edge to graph
-                builder.insert(ProtoEdge {
-                    id: None,
-                    src_id: nodes[node_idx],
-                    dst_id: nodes[node_idx + 1],
-                    metrics: metrics.clone(),
-                })?;
+                builder.insert(proto_edge)?;
             }
         }
         info!("FINISHED");
@@ -187,7 +265,10 @@ impl super::Parsing for Parser {
                 _ => None,
             })
         {
-            // add node to graph if it's part of an edge
+            // most nodes of a map don't belong to any edge
+            if !builder.is_needed(node.id.0) {
+                continue;
+            }
             builder.insert(ProtoNode {
                 id: node.id.0,
                 coord: Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon),
@@ -1,2 +1,4 @@
 pub mod edges;
 pub mod graph;
+pub mod osc;
+pub mod poly;
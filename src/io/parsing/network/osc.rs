@@ -0,0 +1,255 @@
+use crate::{
+    configs::parsing,
+    helpers::err,
+    io::{self, parsing::network::graph::pbf, SupportingFileExts},
+    network::{ProtoChanges, ProtoNode},
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use osmpbfreader::{NodeId, Tags, Way, WayId};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{collections::BTreeMap, io::BufRead, path::Path};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Action {
+    Create,
+    Modify,
+    Delete,
+}
+
+/// Parses osm-changes in the format of [OsmChange](https://wiki.openstreetmap.org/wiki/OsmChange) (`*.osc`), e.g. daily diffs of an osm-extract.
+///
+/// Nodes and ways are processed as in pbf-files, so the provided config should be the config the graph has been parsed with.
+/// Relations are ignored.
+/// If a node or way is changed multiple times, only its last change is relevant.
+pub struct Parser;
+
+impl Parser {
+    pub fn parse<P: AsRef<Path> + ?Sized>(
+        cfg: &parsing::Config,
+        path: &P,
+    ) -> err::Result<ProtoChanges> {
+        let path = path.as_ref();
+        info!("START Parse osm-changes from {}", path.display());
+
+        io::network::osc::Parser::check_ext_support(path)?;
+        pbf::check_config(cfg)?;
+        let mut reader = match Reader::from_file(path) {
+            Ok(reader) => reader,
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+            }
+        };
+        reader.trim_text(true);
+        reader.expand_empty_elements(true);
+
+        // None means deleted
+        let mut nodes: BTreeMap<i64, Option<Coordinate>> = BTreeMap::new();
+        let mut ways: BTreeMap<i64, Option<Way>> = BTreeMap::new();
+
+        let mut action = None;
+        let mut current_node: Option<(i64, Option<Coordinate>)> = None;
+        let mut current_way: Option<Way> = None;
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event(&mut buf) {
+                Ok(Event::Start(ref e)) => match e.name() {
+                    b"create" => action = Some(Action::Create),
+                    b"modify" => action = Some(Action::Modify),
+                    b"delete" => action = Some(Action::Delete),
+                    b"node" => {
+                        let id = Parser::parse_attribute(&reader, e, b"id")?;
+                        let lat = Parser::try_parse_attribute::<_, f64>(&reader, e, b"lat")?;
+                        let lon = Parser::try_parse_attribute::<_, f64>(&reader, e, b"lon")?;
+                        let coord = match (lat, lon) {
+                            // same accuracy as in pbf-files
+                            (Some(lat), Some(lon)) => Some(Coordinate::from_decimicro(
+                                (lat * 1e7).round() as i32,
+                                (lon * 1e7).round() as i32,
+                            )),
+                            _ => None,
+                        };
+                        current_node = Some((id, coord));
+                    }
+                    b"way" => {
+                        current_way = Some(Way {
+                            id: WayId(Parser::parse_attribute(&reader, e, b"id")?),
+                            tags: Tags::new(),
+                            nodes: Vec::new(),
+                        });
+                    }
+                    b"nd" => {
+                        if let Some(way) = &mut current_way {
+                            way.nodes
+                                .push(NodeId(Parser::parse_attribute(&reader, e, b"ref")?));
+                        }
+                    }
+                    b"tag" => {
+                        if let Some(way) = &mut current_way {
+                            let key = Parser::parse_attribute(&reader, e, b"k")?;
+                            let value = Parser::parse_attribute(&reader, e, b"v")?;
+                            way.tags.insert(key, value);
+                        }
+                    }
+                    _ => (),
+                },
+                Ok(Event::End(ref e)) => match e.name() {
+                    b"create" | b"modify" | b"delete" => action = None,
+                    b"node" => {
+                        if let Some((id, coord)) = current_node.take() {
+                            match action {
+                                Some(Action::Create) | Some(Action::Modify) => match coord {
+                                    Some(coord) => {
+                                        nodes.insert(id, Some(coord));
+                                    }
+                                    None => {
+                                        return Err(format!(
+                                            "The node (id: {}) in {} has no coordinates.",
+                                            id,
+                                            path.display()
+                                        )
+                                        .into())
+                                    }
+                                },
+                                Some(Action::Delete) => {
+                                    nodes.insert(id, None);
+                                }
+                                None => {
+                                    return Err(format!(
+                                        "The node (id: {}) in {} doesn't belong to any action.",
+                                        id,
+                                        path.display()
+                                    )
+                                    .into())
+                                }
+                            }
+                        }
+                    }
+                    b"way" => {
+                        if let Some(way) = current_way.take() {
+                            match action {
+                                Some(Action::Create) | Some(Action::Modify) => {
+                                    ways.insert(way.id.0, Some(way));
+                                }
+                                Some(Action::Delete) => {
+                                    ways.insert(way.id.0, None);
+                                }
+                                None => {
+                                    return Err(format!(
+                                        "The way (id: {}) in {} doesn't belong to any action.",
+                                        way.id.0,
+                                        path.display()
+                                    )
+                                    .into())
+                                }
+                            }
+                        }
+                    }
+                    _ => (),
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(format!(
+                        "Couldn't read {} at position {} due to error: {}",
+                        path.display(),
+                        reader.buffer_position(),
+                        e
+                    )
+                    .into())
+                }
+                _ => (),
+            }
+            buf.clear();
+        }
+
+        // convert into changes
+
+        let mut changes = ProtoChanges::default();
+        for (id, coord) in nodes.into_iter() {
+            match coord {
+                Some(coord) => changes.nodes.push(ProtoNode {
+                    id,
+                    coord,
+                    ch_level: None,
+                }),
+                None => changes.deleted_node_ids.push(id),
+            }
+        }
+        for (id, way) in ways.into_iter() {
+            // modified ways are removed and created again
+            changes.removed_way_ids.push(id);
+            if let Some(way) = way {
+                changes.edges.extend(pbf::proto_edges_from(cfg, way));
+            }
+        }
+        info!(
+            "Parsed {} nodes, {} deleted nodes, {} removed ways and {} new edges.",
+            changes.nodes.len(),
+            changes.deleted_node_ids.len(),
+            changes.removed_way_ids.len(),
+            changes.edges.len()
+        );
+
+        info!("FINISHED");
+        Ok(changes)
+    }
+
+    fn try_parse_attribute<B: BufRead, T: std::str::FromStr>(
+        reader: &Reader<B>,
+        e: &BytesStart,
+        key: &[u8],
+    ) -> err::Result<Option<T>> {
+        for attribute in e.attributes() {
+            let attribute = match attribute {
+                Ok(attribute) => attribute,
+                Err(e) => return Err(format!("Couldn't read attribute due to error: {}", e).into()),
+            };
+            if attribute.key != key {
+                continue;
+            }
+
+            let value = match attribute.unescape_and_decode_value(reader) {
+                Ok(value) => value,
+                Err(e) => {
+                    return Err(format!("Couldn't decode attribute due to error: {}", e).into())
+                }
+            };
+            return match value.parse::<T>() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(format!(
+                    "Parsing attribute '{}' with value '{}' didn't work.",
+                    String::from_utf8_lossy(key),
+                    value
+                )
+                .into()),
+            };
+        }
+
+        Ok(None)
+    }
+
+    fn parse_attribute<B: BufRead, T: std::str::FromStr>(
+        reader: &Reader<B>,
+        e: &BytesStart,
+        key: &[u8],
+    ) -> err::Result<T> {
+        match Parser::try_parse_attribute(reader, e, key)? {
+            Some(value) => Ok(value),
+            None => Err(format!(
+                "The element '{}' has no attribute '{}'.",
+                String::from_utf8_lossy(e.name()),
+                String::from_utf8_lossy(key)
+            )
+            .into()),
+        }
+    }
+}
+
+impl SupportingFileExts for Parser {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["osc"]
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::Polygon,
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Parses polygon-files in the format of [Osmosis](https://wiki.openstreetmap.org/wiki/Osmosis/Polygon_Filter_File_Format).
+///
+/// The first line is the polygon's name.
+/// Every following ring starts with a name (holes start with `!`), followed by lines of `lon lat`, and ends with `END`.
+/// The file itself ends with another `END`.
+pub struct Parser;
+
+impl Parser {
+    pub fn parse<P: AsRef<Path> + ?Sized>(path: &P) -> err::Result<Polygon> {
+        let path = path.as_ref();
+        info!("START Parse polygon from {}", path.display());
+
+        io::network::poly::Parser::check_ext_support(path)?;
+        let file = match OpenOptions::new().read(true).open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+            }
+        };
+
+        let mut outer_rings = Vec::new();
+        let mut inner_rings = Vec::new();
+        // (is_hole, ring)
+        let mut current_ring: Option<(bool, Vec<Coordinate>)> = None;
+        let mut has_name = false;
+        let mut has_ended = false;
+
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    return Err(
+                        format!("Couldn't read {} due to error: {}", path.display(), e).into(),
+                    )
+                }
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if has_ended {
+                return Err(format!(
+                    "The polygon-file {} has content after its last END.",
+                    path.display()
+                )
+                .into());
+            }
+
+            if !has_name {
+                has_name = true;
+                continue;
+            }
+
+            match current_ring.take() {
+                // new ring or end of file
+                None => {
+                    if line == "END" {
+                        has_ended = true;
+                    } else {
+                        current_ring = Some((line.starts_with('!'), Vec::new()));
+                    }
+                }
+                // coordinates of a ring or its end
+                Some((is_hole, mut ring)) => {
+                    if line == "END" {
+                        if is_hole {
+                            inner_rings.push(ring);
+                        } else {
+                            outer_rings.push(ring);
+                        }
+                    } else {
+                        let params: Vec<&str> = line.split_whitespace().collect();
+                        let (lon, lat) = match params.as_slice() {
+                            [lon, lat] => match (lon.parse::<f64>(), lat.parse::<f64>()) {
+                                (Ok(lon), Ok(lat)) => (lon, lat),
+                                _ => {
+                                    return Err(format!(
+                                        "Couldn't parse coordinate '{}' in {}.",
+                                        line,
+                                        path.display()
+                                    )
+                                    .into())
+                                }
+                            },
+                            _ => {
+                                return Err(format!(
+                                    "Expected 'lon lat', but got '{}' in {}.",
+                                    line,
+                                    path.display()
+                                )
+                                .into())
+                            }
+                        };
+                        ring.push(Coordinate { lat, lon });
+                        current_ring = Some((is_hole, ring));
+                    }
+                }
+            }
+        }
+
+        if !has_ended {
+            return Err(format!(
+                "The polygon-file {} is incomplete, because the last END is missing.",
+                path.display()
+            )
+            .into());
+        }
+        if outer_rings.is_empty() {
+            return Err(format!(
+                "The polygon-file {} doesn't contain any outer ring.",
+                path.display()
+            )
+            .into());
+        }
+
+        info!("FINISHED");
+        Ok(Polygon::new(outer_rings, inner_rings))
+    }
+}
+
+impl SupportingFileExts for Parser {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["poly"]
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    configs,
+    defaults::{self, routing::route_pairs::csv_names},
+    helpers::err,
+    io::csv::Records,
+    network::{Departure, RoutePair},
+};
+use kissunits::geo::Coordinate;
+use std::{fs::OpenOptions, io::BufReader, path::Path};
+
+/// A route's src or dst, given by the node's id or by a coordinate, which has to be snapped.
+pub enum Location {
+    Id(i64),
+    Coord(Coordinate),
+}
+
+pub struct Row {
+    pub route_pair: RoutePair<Location>,
+    pub count: usize,
+    pub departure: Option<Departure>,
+    pub label: Option<String>,
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Column-indices of a location, where ids are preferred over coordinates.
+enum LocationColumns {
+    Id(usize),
+    Coord { lat: usize, lon: usize },
+}
+
+impl LocationColumns {
+    fn from(header: &[&str], id: &str, lat: &str, lon: &str) -> err::Result<LocationColumns> {
+        let position = |name: &str| header.iter().position(|column| *column == name);
+
+        if let Some(idx) = position(id) {
+            return Ok(LocationColumns::Id(idx));
+        }
+        match (position(lat), position(lon)) {
+            (Some(lat), Some(lon)) => Ok(LocationColumns::Coord { lat, lon }),
+            _ => Err(format!(
+                "The route-pairs' header should contain column {} or columns {} and {}.",
+                id, lat, lon
+            )
+            .into()),
+        }
+    }
+
+    fn parse(&self, params: &[&str]) -> Option<Location> {
+        match self {
+            LocationColumns::Id(idx) => params[*idx].parse::<i64>().ok().map(Location::Id),
+            LocationColumns::Coord { lat, lon } => {
+                let lat = params[*lat].parse::<f64>().ok()?;
+                let lon = params[*lon].parse::<f64>().ok()?;
+                Some(Location::Coord(Coordinate { lat, lon }))
+            }
+        }
+    }
+}
+
+/// The file is read as [RFC 4180](https://tools.ietf.org/html/rfc4180)-csv (with the default csv-config),
+/// where the first record is the header, naming the columns.
+///
+/// A route's src and dst are given by `src-id` and `dst-id`, or by `src-lat`, `src-lon`, `dst-lat` and `dst-lon`.
+/// The columns `count` (default is 1), `departure` and `label` are optional,
+/// and all other columns are kept as metadata.
+/// Values equal to `_` are treated as missing.
+pub fn parse(path: &Path) -> err::Result<Vec<Row>> {
+    let file = match OpenOptions::new().read(true).open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", path.display(), e).into())
+        }
+    };
+    let mut records = Records::new(
+        BufReader::new(file),
+        path,
+        Some(configs::csv::Config::default()),
+    );
+
+    // header
+
+    let header_record = match records.next().transpose()? {
+        Some(header_record) => header_record,
+        None => return Err(format!("The route-pairs-file {} is empty.", path.display()).into()),
+    };
+    let header: Vec<&str> = header_record.iter().map(|column| column.trim()).collect();
+    let position = |name: &str| header.iter().position(|column| *column == name);
+
+    let src_columns = LocationColumns::from(
+        &header,
+        csv_names::SRC_ID,
+        csv_names::SRC_LAT,
+        csv_names::SRC_LON,
+    )?;
+    let dst_columns = LocationColumns::from(
+        &header,
+        csv_names::DST_ID,
+        csv_names::DST_LAT,
+        csv_names::DST_LON,
+    )?;
+    let count_column = position(csv_names::COUNT);
+    let departure_column = position(csv_names::DEPARTURE);
+    let label_column = position(csv_names::LABEL);
+    let metadata_columns: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .filter(|(_idx, name)| {
+            ![
+                csv_names::SRC_ID,
+                csv_names::SRC_LAT,
+                csv_names::SRC_LON,
+                csv_names::DST_ID,
+                csv_names::DST_LAT,
+                csv_names::DST_LON,
+                csv_names::COUNT,
+                csv_names::DEPARTURE,
+                csv_names::LABEL,
+            ]
+            .contains(name)
+        })
+        .map(|(idx, _name)| idx)
+        .collect();
+
+    // rows
+
+    let mut rows = Vec::new();
+    for record in records {
+        let record = record?;
+        let record_err = |what: &str| {
+            err::Msg::from(format!(
+                "The record {:?} of {} has an invalid {}.",
+                record,
+                path.display(),
+                what
+            ))
+        };
+
+        let params: Vec<&str> = record.iter().map(|param| param.trim()).collect();
+        if params.len() != header.len() {
+            return Err(format!(
+                "The record {:?} of {} has {} values, but the header has {} columns.",
+                record,
+                path.display(),
+                params.len(),
+                header.len()
+            )
+            .into());
+        }
+        // missing values are None
+        let value = |column: Option<usize>| {
+            column
+                .map(|idx| params[idx])
+                .filter(|param| *param != defaults::writing::IGNORE_STR)
+        };
+
+        let src = src_columns
+            .parse(&params)
+            .ok_or_else(|| record_err("src"))?;
+        let dst = dst_columns
+            .parse(&params)
+            .ok_or_else(|| record_err("dst"))?;
+        let count = match value(count_column) {
+            Some(param) => param.parse::<usize>().map_err(|_| record_err("count"))?,
+            None => 1,
+        };
+        let departure = match value(departure_column) {
+            Some(param) => {
+                Some(super::routes::parse_departure(param).ok_or_else(|| record_err("departure"))?)
+            }
+            None => None,
+        };
+        let label = value(label_column).map(String::from);
+        let metadata = metadata_columns
+            .iter()
+            .filter_map(|&idx| {
+                value(Some(idx)).map(|param| (String::from(header[idx]), String::from(param)))
+            })
+            .collect();
+
+        rows.push(Row {
+            route_pair: RoutePair { src, dst },
+            count,
+            departure,
+            label,
+            metadata,
+        });
+    }
+
+    Ok(rows)
+}
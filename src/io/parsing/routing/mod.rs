@@ -1,27 +1,127 @@
-use crate::{configs, helpers::err, io::SupportingFileExts, network::RoutePair};
+use crate::{
+    configs, defaults,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{CountedRoutePair, Graph, NodeLocator, RouteEntry, RoutePair},
+};
 use log::info;
 
+mod csv;
 mod routes;
 
 pub struct Parser;
 
 impl Parser {
+    /// Ignores the route-pairs' departures.
     pub fn parse(cfg: &configs::routing::Config) -> err::Result<Vec<(RoutePair<i64>, usize)>> {
+        Ok(Parser::parse_with_departures(cfg)?
+            .into_iter()
+            .map(|(route_pair, route_count, _departure)| (route_pair, route_count))
+            .collect())
+    }
+
+    /// A route-line may have an optional departure as 4th value,
+    /// which is either a time `t` or a period `t0-t1` (both in seconds).
+    pub fn parse_with_departures(
+        cfg: &configs::routing::Config,
+    ) -> err::Result<Vec<CountedRoutePair>> {
         let route_pairs_file = cfg
             .route_pairs_file
             .as_ref()
             .expect("No routes-file specified.");
 
         match Parser::find_supported_ext(route_pairs_file) {
+            Ok("csv") => csv::parse(route_pairs_file)?
+                .into_iter()
+                .map(|row| {
+                    let src = Parser::id_of(&row.route_pair.src)?;
+                    let dst = Parser::id_of(&row.route_pair.dst)?;
+                    Ok((RoutePair { src, dst }, row.count, row.departure))
+                })
+                .collect(),
             Ok(_) => routes::Parser::new().parse(cfg),
             Err(msg) => Err(format!("Wrong parser-routes-file: {}", msg).into()),
         }
     }
+
+    /// Parses the route-pairs with their optional departures, labels and metadata.
+    ///
+    /// Route-pairs of `csv`-files can be given by coordinates,
+    /// which are snapped to their nearest nodes of the graph.
+    pub fn parse_entries(
+        cfg: &configs::routing::Config,
+        graph: &Graph,
+    ) -> err::Result<Vec<RouteEntry>> {
+        let route_pairs_file = cfg
+            .route_pairs_file
+            .as_ref()
+            .expect("No routes-file specified.");
+
+        if Parser::find_supported_ext(route_pairs_file)? != "csv" {
+            return Ok(Parser::parse_with_departures(cfg)?
+                .into_iter()
+                .map(|(route_pair, count, departure)| RouteEntry {
+                    departure,
+                    ..RouteEntry::new(route_pair, count)
+                })
+                .collect());
+        }
+
+        info!("DO Parse route-pairs from csv");
+        let rows = csv::parse(route_pairs_file)?;
+
+        // snap coordinates to their nearest nodes
+
+        let mut locator = None;
+        let mut snap = |location: csv::Location| -> err::Result<i64> {
+            match location {
+                csv::Location::Id(id) => Ok(id),
+                csv::Location::Coord(coord) => {
+                    let locator = locator.get_or_insert_with(|| NodeLocator::new(graph));
+                    match locator
+                        .nearest_within(&coord, defaults::network::nodes::MAX_SNAPPING_DISTANCE)
+                    {
+                        Some(idx) => Ok(graph.nodes().id(idx)),
+                        None => Err(format!(
+                            "The coordinate {} is farther than {} away from every node.",
+                            coord,
+                            defaults::network::nodes::MAX_SNAPPING_DISTANCE
+                        )
+                        .into()),
+                    }
+                }
+            }
+        };
+
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let src = snap(row.route_pair.src)?;
+            let dst = snap(row.route_pair.dst)?;
+            entries.push(RouteEntry {
+                route_pair: RoutePair { src, dst },
+                count: row.count,
+                departure: row.departure,
+                label: row.label,
+                metadata: row.metadata,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn id_of(location: &csv::Location) -> err::Result<i64> {
+        match location {
+            csv::Location::Id(id) => Ok(*id),
+            csv::Location::Coord(_) => Err(
+                "Route-pairs given by coordinates need a graph for snapping (see `parse_entries`)."
+                    .into(),
+            ),
+        }
+    }
 }
 
 impl SupportingFileExts for Parser {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["route-pairs"]
+        &["route-pairs", "csv"]
     }
 }
 
@@ -43,12 +143,9 @@ trait Parsing {
     fn parse_route_pairs(
         &self,
         cfg: &configs::routing::Config,
-    ) -> Result<Vec<(RoutePair<i64>, usize)>, String>;
+    ) -> Result<Vec<CountedRoutePair>, String>;
 
-    fn parse(
-        &mut self,
-        cfg: &configs::routing::Config,
-    ) -> err::Result<Vec<(RoutePair<i64>, usize)>> {
+    fn parse(&mut self, cfg: &configs::routing::Config) -> err::Result<Vec<CountedRoutePair>> {
         info!("DO Parse route-pairs");
         self.preprocess(cfg)?;
         let routes = self.parse_route_pairs(cfg)?;
@@ -1,8 +1,9 @@
 use crate::{
     configs,
     helpers::{self, err},
-    network::RoutePair,
+    network::{CountedRoutePair, Departure, RoutePair},
 };
+use kissunits::time::Seconds;
 use std::{
     fs::OpenOptions,
     io::{BufRead, BufReader},
@@ -76,7 +77,7 @@ impl super::Parsing for Parser {
     fn parse_route_pairs(
         &self,
         cfg: &configs::routing::Config,
-    ) -> Result<Vec<(RoutePair<i64>, usize)>, String> {
+    ) -> Result<Vec<CountedRoutePair>, String> {
         let mut route_pairs = Vec::with_capacity(self.route_lines.len());
         let route_pairs_file = cfg
             .route_pairs_file
@@ -103,10 +104,10 @@ impl super::Parsing for Parser {
             // create route
 
             let params: Vec<&str> = line.split_whitespace().collect();
-            if params.len() != 3 {
+            if params.len() != 3 && params.len() != 4 {
                 return Err(format!(
-                    "A route-line is expected to consist of (src-id, dst-id, count), \
-                     but {} values are provided.",
+                    "A route-line is expected to consist of (src-id, dst-id, count) \
+                     and an optional departure, but {} values are provided.",
                     params.len()
                 ));
             }
@@ -126,6 +127,13 @@ impl super::Parsing for Parser {
                 .parse::<usize>()
                 .ok()
                 .ok_or(format!("Could not parse route's count {}", param))?;
+            let departure = match params.get(3) {
+                Some(param) => Some(
+                    parse_departure(param)
+                        .ok_or(format!("Could not parse route's departure {}", param))?,
+                ),
+                None => None,
+            };
 
             route_pairs.push((
                 RoutePair {
@@ -133,9 +141,38 @@ impl super::Parsing for Parser {
                     dst: dst_id,
                 },
                 n,
+                departure,
             ));
         }
 
         Ok(route_pairs)
     }
 }
+
+/// Parses `t` or `t0-t1` (in seconds), where `0 <= t` and `0 <= t0 < t1`.
+///
+/// Times are parsed as a whole first, so exponents like in `1e-3` are no separators,
+/// and negative times are rejected instead of being split.
+pub fn parse_departure(param: &str) -> Option<Departure> {
+    let parse_time = |param: &str| {
+        param
+            .parse::<f64>()
+            .ok()
+            .filter(|time| time.is_finite() && *time >= 0.0)
+            .map(Seconds)
+    };
+
+    if param.parse::<f64>().is_ok() {
+        return parse_time(param).map(Departure::At);
+    }
+
+    // the separator is the '-', where both sides are times
+    let (start, end) = param
+        .match_indices('-')
+        .find_map(|(idx, _)| Some((parse_time(&param[..idx])?, parse_time(&param[idx + 1..])?)))?;
+    if start < end {
+        Some(Departure::Within { start, end })
+    } else {
+        None
+    }
+}
@@ -0,0 +1,40 @@
+use crate::helpers::{self, err};
+use kissunits::geo::Coordinate;
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Every functional line consists of `lat lon weight`, optionally followed by a comment.
+pub fn parse(path: &Path) -> err::Result<Vec<(Coordinate, f64)>> {
+    let mut points = Vec::new();
+    for (line_idx, line) in BufReader::new(super::open(path)?).lines().enumerate() {
+        let line = line?;
+        if !helpers::is_line_functional(&line) {
+            continue;
+        }
+
+        let line_err = || {
+            err::Msg::from(format!(
+                "Line {} of {} should consist of (lat lon weight), but is '{}'",
+                line_idx + 1,
+                path.display(),
+                line
+            ))
+        };
+        let params: Vec<&str> = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        if params.len() != 3 {
+            return Err(line_err());
+        }
+        let lat: f64 = params[0].parse().map_err(|_| line_err())?;
+        let lon: f64 = params[1].parse().map_err(|_| line_err())?;
+        let weight = super::parse_weight(params[2]).ok_or_else(line_err)?;
+        points.push((Coordinate { lat, lon }, weight));
+    }
+    Ok(points)
+}
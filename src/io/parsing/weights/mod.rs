@@ -0,0 +1,70 @@
+use crate::{
+    configs::writing::routing::NodeWeights,
+    defaults,
+    helpers::err,
+    network::{Graph, NodeLocator},
+};
+use kissunits::geo::Coordinate;
+use log::{info, warn};
+use std::{fs::OpenOptions, path::Path};
+
+mod csv;
+mod osm;
+mod raster;
+
+/// Parses weighted points (e.g. population or points of interest), which can be added to their nearest nodes.
+pub struct Parser;
+
+impl Parser {
+    pub fn parse(weights_cfg: &NodeWeights) -> err::Result<Vec<(Coordinate, f64)>> {
+        info!("START Parse weighted points via {:?}", weights_cfg);
+        let points = match weights_cfg {
+            NodeWeights::Csv(file) => csv::parse(file)?,
+            NodeWeights::Raster(file) => raster::parse(file)?,
+            NodeWeights::Osm(file) => osm::parse(file)?,
+        };
+        info!("FINISHED Parsed {} weighted points", points.len());
+        Ok(points)
+    }
+
+    /// Returns the weight of every node (per node-idx), which is the sum of the weights of all points nearest to it.
+    pub fn parse_node_weights(graph: &Graph, weights_cfg: &NodeWeights) -> err::Result<Vec<f64>> {
+        let points = Parser::parse(weights_cfg)?;
+
+        info!("START Add weighted points to their nearest nodes");
+        let locator = NodeLocator::new(graph);
+        let mut node_weights = vec![0.0; graph.nodes().count()];
+        let mut num_of_ignored = 0;
+        for (coord, weight) in points {
+            match locator.nearest_within(&coord, defaults::network::nodes::MAX_SNAPPING_DISTANCE) {
+                Some(node_idx) => node_weights[*node_idx] += weight,
+                None => num_of_ignored += 1,
+            }
+        }
+        if num_of_ignored > 0 {
+            warn!(
+                "Ignored {} weighted points, which are farther than {} away from every node.",
+                num_of_ignored,
+                defaults::network::nodes::MAX_SNAPPING_DISTANCE
+            );
+        }
+        info!("FINISHED");
+
+        Ok(node_weights)
+    }
+}
+
+fn open(path: &Path) -> err::Result<std::fs::File> {
+    match OpenOptions::new().read(true).open(path) {
+        Ok(file) => Ok(file),
+        Err(e) => Err(format!("Couldn't open {} due to error: {}", path.display(), e).into()),
+    }
+}
+
+/// Weights have to be finite and non-negative.
+fn parse_weight(param: &str) -> Option<f64> {
+    param
+        .parse::<f64>()
+        .ok()
+        .filter(|weight| weight.is_finite() && *weight >= 0.0)
+}
@@ -0,0 +1,116 @@
+use crate::{defaults::writing::route_pairs::osm as defaults, helpers::err};
+use kissunits::geo::{self, Coordinate};
+use osmpbfreader::{reader::OsmPbfReader, OsmObj};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+/// Points of interest (nodes) are returned with their weight.
+/// The weight of landuses and buildings (ways) is a density, which is scaled by the area of closed ways (polygons) or by the length of open ways.
+/// It is spread over the ways' nodes, which are collected in a second pass, because ways don't know their nodes' coordinates.
+pub fn parse(path: &Path) -> err::Result<Vec<(Coordinate, f64)>> {
+    let mut points = Vec::new();
+    // (weight, node-ids)
+    let mut ways: Vec<(f64, Vec<i64>)> = Vec::new();
+    for obj in OsmPbfReader::new(super::open(path)?)
+        .par_iter()
+        .filter_map(Result::ok)
+    {
+        match obj {
+            OsmObj::Node(node) => {
+                if defaults::POI_KEYS
+                    .iter()
+                    .any(|key| node.tags.contains_key(*key))
+                {
+                    let coord = Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon);
+                    points.push((coord, defaults::POI_WEIGHT));
+                }
+            }
+            OsmObj::Way(way) => {
+                let landuse_weight = way.tags.get("landuse").and_then(|landuse| {
+                    defaults::LANDUSE_WEIGHTS
+                        .iter()
+                        .find(|(value, _weight)| landuse.as_str() == *value)
+                        .map(|(_value, weight)| *weight)
+                });
+                let weight = if way.tags.contains_key("building") {
+                    Some(defaults::BUILDING_WEIGHT)
+                } else {
+                    landuse_weight
+                };
+                if let Some(weight) = weight {
+                    ways.push((weight, way.nodes.iter().map(|node_id| node_id.0).collect()));
+                }
+            }
+            OsmObj::Relation(_) => (),
+        }
+    }
+
+    // collect coordinates of the ways' nodes
+
+    let node_ids: HashSet<i64> = ways
+        .iter()
+        .flat_map(|(_weight, node_ids)| node_ids.iter().cloned())
+        .collect();
+    let mut coords: HashMap<i64, Coordinate> = HashMap::with_capacity(node_ids.len());
+    if !node_ids.is_empty() {
+        for obj in OsmPbfReader::new(super::open(path)?)
+            .par_iter()
+            .filter_map(Result::ok)
+        {
+            if let OsmObj::Node(node) = obj {
+                if node_ids.contains(&node.id.0) {
+                    coords.insert(
+                        node.id.0,
+                        Coordinate::from_decimicro(node.decimicro_lat, node.decimicro_lon),
+                    );
+                }
+            }
+        }
+    }
+
+    for (weight, node_ids) in ways {
+        let mut way_coords: Vec<Coordinate> = node_ids
+            .iter()
+            .filter_map(|node_id| coords.get(node_id).cloned())
+            .collect();
+        let is_closed = way_coords.len() > 3 && way_coords.first() == way_coords.last();
+        if is_closed {
+            // the closing node would get its share twice
+            way_coords.pop();
+            let share = weight * area_km2(&way_coords) / way_coords.len() as f64;
+            points.extend(way_coords.into_iter().map(|coord| (coord, share)));
+        } else {
+            // every node gets half of its adjacent segments
+            let mut shares = vec![0.0; way_coords.len()];
+            for (i, segment) in way_coords.windows(2).enumerate() {
+                let half_length = geo::haversine_distance_km(&segment[0], &segment[1]).0 / 2.0;
+                shares[i] += weight * half_length;
+                shares[i + 1] += weight * half_length;
+            }
+            points.extend(way_coords.into_iter().zip(shares));
+        }
+    }
+
+    Ok(points)
+}
+
+/// Area of a polygon (without closing coordinate) via the shoelace-formula,
+/// projecting the coordinates equirectangularly around the polygon's mean latitude.
+fn area_km2(polygon: &[Coordinate]) -> f64 {
+    let km_per_degree = geo::haversine_distance_km(
+        &Coordinate { lat: 0.0, lon: 0.0 },
+        &Coordinate { lat: 1.0, lon: 0.0 },
+    )
+    .0;
+    let mean_lat = polygon.iter().map(|coord| coord.lat).sum::<f64>() / polygon.len() as f64;
+    let lon_scale = mean_lat.to_radians().cos();
+
+    let doubled_area: f64 = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.lon * lon_scale * b.lat - b.lon * lon_scale * a.lat)
+        .sum();
+    (doubled_area / 2.0).abs() * km_per_degree * km_per_degree
+}
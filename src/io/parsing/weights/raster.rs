@@ -0,0 +1,110 @@
+use crate::helpers::err;
+use kissunits::geo::Coordinate;
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Parses an [ESRI-ASCII-grid](https://en.wikipedia.org/wiki/Esri_grid) in WGS84 (`x` is the longitude, `y` the latitude).
+///
+/// The header consists of `ncols`, `nrows`, `xllcorner` (or `xllcenter`), `yllcorner` (or `yllcenter`), `cellsize` and an optional `nodata_value`.
+/// The values follow row by row, starting with the northernmost row.
+/// Every cell's center is returned with the cell's value, except cells without data or with value `0`.
+pub fn parse(path: &Path) -> err::Result<Vec<(Coordinate, f64)>> {
+    let mut header: HashMap<String, f64> = HashMap::new();
+    let mut values = Vec::new();
+    for line in BufReader::new(super::open(path)?).lines() {
+        let line = line?;
+        let params: Vec<&str> = line.split_whitespace().collect();
+        if params.is_empty() {
+            continue;
+        }
+
+        // header-lines start with a key
+        if values.is_empty() && params[0].starts_with(char::is_alphabetic) {
+            let value = match params.get(1).and_then(|param| param.parse().ok()) {
+                Some(value) if params.len() == 2 => value,
+                _ => {
+                    return Err(format!(
+                        "The header-line '{}' of {} is invalid.",
+                        line,
+                        path.display()
+                    )
+                    .into())
+                }
+            };
+            header.insert(params[0].to_ascii_lowercase(), value);
+            continue;
+        }
+
+        for param in params {
+            let value: f64 = param.parse().map_err(|_| {
+                err::Msg::from(format!(
+                    "The value {} of {} is invalid.",
+                    param,
+                    path.display()
+                ))
+            })?;
+            values.push(value);
+        }
+    }
+
+    let get = |key: &str| {
+        header.get(key).cloned().ok_or_else(|| {
+            err::Msg::from(format!(
+                "The header of {} should contain {}.",
+                path.display(),
+                key
+            ))
+        })
+    };
+    let num_cols = get("ncols")? as usize;
+    let num_rows = get("nrows")? as usize;
+    let cell_size = get("cellsize")?;
+    // lower-left corner
+    let (x_ll, y_ll) = match (header.get("xllcorner"), header.get("yllcorner")) {
+        (Some(x), Some(y)) => (*x, *y),
+        _ => (
+            get("xllcenter")? - cell_size / 2.0,
+            get("yllcenter")? - cell_size / 2.0,
+        ),
+    };
+    let no_data = header.get("nodata_value").cloned();
+
+    if values.len() != num_rows * num_cols {
+        return Err(format!(
+            "The raster {} should have {} values ({} rows, {} cols), but has {}.",
+            path.display(),
+            num_rows * num_cols,
+            num_rows,
+            num_cols,
+            values.len()
+        )
+        .into());
+    }
+
+    let mut points = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        if Some(value) == no_data || value == 0.0 {
+            continue;
+        }
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!(
+                "The raster {} should only contain non-negative values, but contains {}.",
+                path.display(),
+                value
+            )
+            .into());
+        }
+
+        // first row is the northernmost row
+        let (row, col) = (i / num_cols, i % num_cols);
+        let coord = Coordinate {
+            lat: y_ll + ((num_rows - 1 - row) as f64 + 0.5) * cell_size,
+            lon: x_ll + (col as f64 + 0.5) * cell_size,
+        };
+        points.push((coord, value));
+    }
+    Ok(points)
+}
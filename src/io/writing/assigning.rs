@@ -0,0 +1,109 @@
+use crate::{
+    configs::assigning::Config as AssigningConfig, defaults, helpers::err, network::Graph,
+    routing::assigning::Assignment,
+};
+use log::info;
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+pub struct Writer;
+
+impl Writer {
+    pub fn check(assigning_cfg: &AssigningConfig) -> err::Feedback {
+        for file_name in &[
+            defaults::assigning::files::EDGES,
+            defaults::assigning::files::STATS,
+        ] {
+            let path = assigning_cfg.results_dir.join(file_name);
+            if path.exists() {
+                return Err(err::Msg::from(format!(
+                    "New file {} does already exist. Please remove it.",
+                    path.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the assignment's values per edge and the stats per iteration into the results-dir.
+    pub fn write(
+        assignment: &Assignment,
+        graph: &Graph,
+        assigning_cfg: &AssigningConfig,
+    ) -> err::Feedback {
+        info!(
+            "START Write the assignment into {}",
+            assigning_cfg.results_dir.display()
+        );
+        fs::create_dir_all(&assigning_cfg.results_dir)?;
+
+        // write edges
+
+        let mut writer = Writer::create(
+            &assigning_cfg
+                .results_dir
+                .join(defaults::assigning::files::EDGES),
+        )?;
+        // edges are identified by their nodes, because edge-ids are optional
+        writeln!(
+            writer,
+            "src-id dst-id capacity flow free-flow-time travel-time volume-capacity-ratio"
+        )?;
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        for edge_idx in &fwd_edges {
+            writeln!(
+                writer,
+                "{} {} {} {} {} {} {}",
+                nodes.id(bwd_edges.dst_idx(edge_idx)),
+                nodes.id(fwd_edges.dst_idx(edge_idx)),
+                assignment.capacities[*edge_idx],
+                assignment.flows[*edge_idx],
+                assignment.free_flow_times[*edge_idx],
+                assignment.travel_times[*edge_idx],
+                assignment.flows[*edge_idx] / assignment.capacities[*edge_idx]
+            )?;
+        }
+
+        // write stats
+
+        let mut writer = Writer::create(
+            &assigning_cfg
+                .results_dir
+                .join(defaults::assigning::files::STATS),
+        )?;
+        writeln!(writer, "iteration relative-gap total-travel-time step-size")?;
+        for stats in &assignment.stats {
+            writeln!(
+                writer,
+                "{} {} {} {}",
+                stats.iteration,
+                stats.relative_gap,
+                stats.total_travel_time,
+                stats
+                    .step_size
+                    .map_or(String::from(defaults::writing::IGNORE_STR), |step_size| {
+                        step_size.to_string()
+                    })
+            )?;
+        }
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    fn create(path: &Path) -> err::Result<BufWriter<std::fs::File>> {
+        match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(file) => Ok(BufWriter::new(file)),
+            Err(e) => Err(err::Msg::from(format!(
+                "Couldn't open {} due to error: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
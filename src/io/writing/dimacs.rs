@@ -0,0 +1,140 @@
+use crate::{
+    configs::writing::dimacs::Config as WritingConfig,
+    defaults::parsing::dimacs as format,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{Graph, NodeIdx},
+};
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path,
+};
+
+/// Writes graphs in the format of the [9th DIMACS-challenge](http://www.diag.uniroma1.it/challenge9/format.shtml),
+/// e.g. for exchanging graphs with other routing-codes.
+///
+/// DIMACS expects node-ids `1..=n`.
+/// Hence the graph's node-ids are kept, if they are `1..=n` already (e.g. when the graph has been parsed from DIMACS),
+/// and replaced by their node-indices (plus 1) otherwise.
+pub struct Writer;
+
+impl Writer {
+    /// Shortcuts are not written, and normalized metrics are denormalized before scaling and rounding.
+    pub fn write(graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the graph as DIMACS-files {} and {}",
+            writing_cfg.graph_file.display(),
+            writing_cfg.coordinates_file.display()
+        );
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let metrics = graph.metrics();
+        let metric_idx = graph
+            .cfg()
+            .edges
+            .metrics
+            .try_idx_of(&writing_cfg.metric_id)?;
+        let mean = metrics.mean(metric_idx).unwrap_or(1.0);
+
+        let node_count = nodes.count();
+        let is_keeping_ids = nodes
+            .iter()
+            .all(|node_idx| 1 <= nodes.id(node_idx) && nodes.id(node_idx) <= node_count as i64);
+        let dimacs_id = |node_idx: NodeIdx| {
+            if is_keeping_ids {
+                nodes.id(node_idx)
+            } else {
+                *node_idx as i64 + 1
+            }
+        };
+
+        if writing_cfg.scale == 0 {
+            return Err("The scale of the DIMACS-weights should be positive.".into());
+        }
+
+        // DIMACS expects non-negative integer weights,
+        // which are checked before writing any file.
+        let arcs = fwd_edges
+            .iter()
+            .filter(|&edge_idx| !fwd_edges.is_shortcut(edge_idx))
+            .map(|edge_idx| {
+                let value = metrics.get_value(edge_idx, metric_idx) * mean;
+                let weight = (value * writing_cfg.scale as f64).round();
+                if !weight.is_finite() || weight < 0.0 {
+                    return Err(format!(
+                        "The metric {} should be non-negative for DIMACS, but is {}.",
+                        writing_cfg.metric_id, weight
+                    )
+                    .into());
+                }
+                let src = dimacs_id(bwd_edges.dst_idx(edge_idx));
+                let dst = dimacs_id(fwd_edges.dst_idx(edge_idx));
+                // e.g. kilometers would be rounded to 0 without scaling them to meters
+                if weight == 0.0 && value != 0.0 {
+                    return Err(format!(
+                        "The metric {} of the arc from {} to {} is {}, \
+                         which is rounded to 0 with scale {}, hence the scale should be increased.",
+                        writing_cfg.metric_id, src, dst, value, writing_cfg.scale
+                    )
+                    .into());
+                }
+                Ok((src, dst, weight as u64))
+            })
+            .collect::<err::Result<Vec<(i64, i64, u64)>>>()?;
+
+        // write arcs
+
+        let mut writer = create(&writing_cfg.graph_file)?;
+        writeln!(
+            writer,
+            "c graph with weights from metric {}",
+            writing_cfg.metric_id
+        )?;
+        writeln!(writer, "p sp {} {}", node_count, arcs.len())?;
+        for (src_id, dst_id, weight) in &arcs {
+            writeln!(writer, "a {} {} {}", src_id, dst_id, weight)?;
+        }
+        writer.flush()?;
+
+        // write coordinates
+
+        let mut writer = create(&writing_cfg.coordinates_file)?;
+        writeln!(
+            writer,
+            "c coordinates (longitude, latitude) in millionths of degrees"
+        )?;
+        writeln!(writer, "p aux sp co {}", node_count)?;
+        for node_idx in nodes.iter() {
+            let coord = nodes.coord(node_idx);
+            writeln!(
+                writer,
+                "v {} {} {}",
+                dimacs_id(node_idx),
+                (coord.lon * format::COORDINATE_FACTOR).round() as i64,
+                (coord.lat * format::COORDINATE_FACTOR).round() as i64
+            )?;
+        }
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &[format::GRAPH_EXT, format::COORDINATES_EXT]
+    }
+}
+
+fn create(file: &path::Path) -> err::Result<BufWriter<std::fs::File>> {
+    Writer::find_supported_ext(file)?;
+    match OpenOptions::new().write(true).create_new(true).open(file) {
+        Ok(output_file) => Ok(BufWriter::new(output_file)),
+        Err(e) => Err(format!("Couldn't open {} due to error: {}", file.display(), e).into()),
+    }
+}
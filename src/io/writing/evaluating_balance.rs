@@ -4,8 +4,10 @@ use crate::{
     helpers::err,
     io::{self, SupportingFileExts},
     network::Graph,
+    routing::paths::Path,
 };
-use std::fmt::Display;
+use kissunits::time::Seconds;
+use std::{collections::BTreeMap, fmt::Display, fs};
 
 pub struct Writer;
 
@@ -18,9 +20,18 @@ impl Writer {
             writing_cfg
                 .results_dir
                 .join(defaults::balancing::stats::files::ABS_WORKLOADS),
+            writing_cfg
+                .results_dir
+                .join(defaults::balancing::stats::files::WORKLOADS_GEOJSON),
             writing_cfg
                 .results_dir
                 .join(defaults::smarts::XML_FILE_NAME),
+            writing_cfg
+                .results_dir
+                .join(defaults::sumo::ROUTES_FILE_NAME),
+            writing_cfg
+                .results_dir
+                .join(defaults::balancing::stats::TIME_SLICES_DIR),
         ] {
             if path.exists() {
                 return Err(err::Msg::from(format!(
@@ -45,32 +56,6 @@ impl Writer {
 
         // write absolute workloads
 
-        // look for name of edge-id (which occurs only once)
-        let edge_id_name = {
-            let id = graph
-                .cfg()
-                .edges
-                .categories
-                .iter()
-                .find_map(|category| match category {
-                    configs::parsing::edges::Category::Meta { info, id } => {
-                        if info == &configs::parsing::edges::MetaInfo::EdgeId {
-                            Some(id)
-                        } else {
-                            None
-                        }
-                    }
-                    configs::parsing::edges::Category::Metric { unit: _, id: _ }
-                    | configs::parsing::edges::Category::Ignored => None,
-                });
-            if let Some(id) = id {
-                id.clone()
-            } else {
-                return Err(err::Msg::from(
-                    "For writing absolute workloads to csv, an edge-id should be given.",
-                ));
-            }
-        };
         let mut tmp_cfg = writing_cfg.monitoring.edges_info.clone();
         // path is relative to results-dir
         tmp_cfg.file = writing_cfg
@@ -78,7 +63,7 @@ impl Writer {
             .join(defaults::balancing::stats::files::ABS_WORKLOADS);
         // header-line
         tmp_cfg.ids = vec![
-            Some(edge_id_name),
+            Some(edge_id_name(graph)?),
             Some(SimpleId::from(
                 defaults::balancing::stats::csv_names::NUM_ROUTES,
             )),
@@ -87,6 +72,120 @@ impl Writer {
 
         Ok(())
     }
+
+    /// Writes the absolute workloads as styled GeoJSON (if configured), e.g. for viewing them in QGIS or Leaflet.
+    pub fn write_geojson(
+        abs_workloads: &[usize],
+        graph: &Graph,
+        writing_cfg: &WritingConfig,
+    ) -> err::Feedback {
+        if !writing_cfg.monitoring.is_writing_geojson {
+            return Ok(());
+        }
+
+        io::geojson::Writer::write_workloads(
+            abs_workloads,
+            graph,
+            &writing_cfg
+                .results_dir
+                .join(defaults::balancing::stats::files::WORKLOADS_GEOJSON),
+        )
+    }
+
+    /// Writes the absolute workloads per time-slice (if configured).
+    ///
+    /// Only time-slices with departing routes are written, named by their start and end (in seconds).
+    pub fn write_time_slices(
+        time_slice_workloads: &TimeSliceWorkloads,
+        graph: &Graph,
+        writing_cfg: &WritingConfig,
+    ) -> err::Feedback {
+        let time_slice = time_slice_workloads.time_slice;
+        let time_slices_dir = writing_cfg
+            .results_dir
+            .join(defaults::balancing::stats::TIME_SLICES_DIR);
+        fs::create_dir_all(&time_slices_dir)?;
+
+        let mut tmp_cfg = writing_cfg.monitoring.edges_info.clone();
+        tmp_cfg.ids = vec![
+            Some(edge_id_name(graph)?),
+            Some(SimpleId::from(
+                defaults::balancing::stats::csv_names::NUM_ROUTES,
+            )),
+        ];
+        for (slice_idx, values) in &time_slice_workloads.abs_workloads {
+            tmp_cfg.file = time_slices_dir.join(format!(
+                "{}-{}.csv",
+                *slice_idx as f64 * time_slice.0,
+                (slice_idx + 1) as f64 * time_slice.0
+            ));
+            io::network::edges::Writer::write_external_values(values, graph, &tmp_cfg)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Absolute workloads per time-slice, which are counted while working off the routes,
+/// so the paths don't have to be kept in memory.
+///
+/// Every route counts for the time-slice of its departure, where routes without departure depart at `0 s`.
+pub struct TimeSliceWorkloads {
+    time_slice: Seconds,
+    edge_count: usize,
+    /// Only time-slices with departing routes have workloads.
+    abs_workloads: BTreeMap<usize, Vec<usize>>,
+}
+
+impl TimeSliceWorkloads {
+    pub fn new(time_slice: Seconds, graph: &Graph) -> TimeSliceWorkloads {
+        TimeSliceWorkloads {
+            time_slice,
+            edge_count: graph.fwd_edges().count(),
+            abs_workloads: BTreeMap::new(),
+        }
+    }
+
+    /// The path has to be flattened (e.g. via `path.flatten(...)`).
+    pub fn add(&mut self, path: &Path, departure: Option<Seconds>) {
+        let departure = departure.unwrap_or(Seconds(0.0));
+        let slice_idx = (departure.0 / self.time_slice.0).floor() as usize;
+        let edge_count = self.edge_count;
+        let slice_workloads = self
+            .abs_workloads
+            .entry(slice_idx)
+            .or_insert_with(|| vec![0; edge_count]);
+        for &edge_idx in path {
+            slice_workloads[*edge_idx] += 1;
+        }
+    }
+}
+
+/// Returns the name of the edge-id (which occurs only once).
+fn edge_id_name(graph: &Graph) -> err::Result<SimpleId> {
+    let id = graph
+        .cfg()
+        .edges
+        .categories
+        .iter()
+        .find_map(|category| match category {
+            configs::parsing::edges::Category::Meta { info, id } => {
+                if info == &configs::parsing::edges::MetaInfo::EdgeId {
+                    Some(id)
+                } else {
+                    None
+                }
+            }
+            configs::parsing::edges::Category::Metric { unit: _, id: _ }
+            | configs::parsing::edges::Category::Ignored => None,
+        });
+    if let Some(id) = id {
+        Ok(id.clone())
+    } else {
+        Err(err::Msg::from(
+            "For writing absolute workloads to csv, an edge-id should be given.",
+        ))
+    }
 }
 
 impl SupportingFileExts for Writer {
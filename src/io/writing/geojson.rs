@@ -0,0 +1,250 @@
+use super::json;
+use crate::{
+    configs::{parsing::clipping, writing::geojson::Config as WritingConfig},
+    defaults::writing::geojson as style,
+    helpers::err,
+    io::{self, SupportingFileExts},
+    network::{EdgeIdx, Graph, MetricIdx, Polygon},
+    routing::paths::Path,
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path,
+};
+
+/// Writes GeoJSON (`FeatureCollection`), which can be opened directly in QGIS or Leaflet.
+pub struct Writer;
+
+impl Writer {
+    /// Every (selected) edge is written as `LineString` with its ids and the configured metrics as properties.
+    pub fn write_graph(graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!("START Write the graph as {}", writing_cfg.file.display());
+
+        let metric_indices = writing_cfg
+            .metric_ids
+            .iter()
+            .map(|id| graph.cfg().edges.metrics.try_idx_of(id))
+            .collect::<err::Result<Vec<MetricIdx>>>()?;
+        let polygon = match &writing_cfg.area {
+            Some(clipping::Area::BoundingBox { min, max }) => Some(Polygon::from_bbox(*min, *max)),
+            Some(clipping::Area::Polygon { file }) => Some(io::network::poly::Parser::parse(file)?),
+            None => None,
+        };
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let metrics = graph.metrics();
+
+        let mut writer = FeatureWriter::create(&writing_cfg.file)?;
+        for edge_idx in fwd_edges.iter() {
+            if !writing_cfg.is_writing_shortcuts && fwd_edges.is_shortcut(edge_idx) {
+                continue;
+            }
+            let src_idx = bwd_edges.dst_idx(edge_idx);
+            let dst_idx = fwd_edges.dst_idx(edge_idx);
+            if let Some(polygon) = &polygon {
+                if !polygon.contains(&nodes.coord(src_idx))
+                    || !polygon.contains(&nodes.coord(dst_idx))
+                {
+                    continue;
+                }
+            }
+
+            // edge-ids are optional
+            let mut properties: Vec<(String, String)> = fwd_edges
+                .try_id(edge_idx)
+                .map(|edge_id| (String::from("edge-id"), edge_id.to_string()))
+                .into_iter()
+                .collect();
+            properties.push((String::from("src-id"), nodes.id(src_idx).to_string()));
+            properties.push((String::from("dst-id"), nodes.id(dst_idx).to_string()));
+            for (metric_id, &metric_idx) in writing_cfg.metric_ids.iter().zip(&metric_indices) {
+                let mut value = metrics.get_value(edge_idx, metric_idx);
+                if writing_cfg.is_denormalizing {
+                    if let Some(mean) = metrics.mean(metric_idx) {
+                        value *= mean;
+                    }
+                }
+                properties.push((metric_id.0.clone(), json::number(value)));
+            }
+
+            writer.write_line_string(&[nodes.coord(src_idx), nodes.coord(dst_idx)], &properties)?;
+        }
+        writer.finish()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    /// Every path is flattened and written as `LineString` with its src, dst and costs (per metric-id) as properties.
+    pub fn write_paths(paths: &[Path], graph: &Graph, file: &path::Path) -> err::Feedback {
+        info!("START Write {} paths as {}", paths.len(), file.display());
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let metric_ids = &graph.cfg().edges.metrics.ids;
+
+        let mut writer = FeatureWriter::create(file)?;
+        for path in paths {
+            let path = path.clone().try_flatten(graph)?;
+
+            let mut coords = Vec::with_capacity(path.iter().len() + 1);
+            coords.push(nodes.coord(path.src_idx()));
+            coords.extend(
+                path.iter()
+                    .map(|&edge_idx| nodes.coord(fwd_edges.dst_idx(edge_idx))),
+            );
+
+            let costs: Vec<String> = metric_ids
+                .iter()
+                .zip(path.costs())
+                .map(|(metric_id, cost)| {
+                    format!("{}: {}", json::string(&metric_id.0), json::number(*cost))
+                })
+                .collect();
+            let properties = vec![
+                (String::from("src-id"), nodes.id(path.src_idx()).to_string()),
+                (String::from("dst-id"), nodes.id(path.dst_idx()).to_string()),
+                (String::from("hop-distance"), path.iter().len().to_string()),
+                (String::from("costs"), format!("{{{}}}", costs.join(", "))),
+            ];
+
+            writer.write_line_string(&coords, &properties)?;
+        }
+        writer.finish()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    /// Every edge with positive workload is written as `LineString` with its workload as property.
+    ///
+    /// Workloads are binned by quantiles, and every bin is styled by
+    /// [simplestyle](https://github.com/mapbox/simplestyle-spec) (`stroke` and `stroke-width`).
+    pub fn write_workloads(workloads: &[usize], graph: &Graph, file: &path::Path) -> err::Feedback {
+        info!("START Write workloads as {}", file.display());
+
+        let fwd_edges = graph.fwd_edges();
+
+        // bin-boundaries are the quantiles of the positive workloads
+        let mut sorted_workloads: Vec<usize> =
+            workloads.iter().copied().filter(|&w| w > 0).collect();
+        sorted_workloads.sort_unstable();
+        let num_bins = style::WORKLOAD_COLORS.len();
+        let bin_starts: Vec<usize> = (1..num_bins)
+            .filter_map(|bin| sorted_workloads.get(bin * sorted_workloads.len() / num_bins))
+            .copied()
+            .collect();
+
+        let mut writer = FeatureWriter::create(file)?;
+        for edge_idx in fwd_edges.iter() {
+            let workload = workloads[*edge_idx];
+            if workload == 0 {
+                continue;
+            }
+            let bin = bin_starts
+                .iter()
+                .filter(|&&start| workload >= start)
+                .count();
+
+            let mut properties: Vec<(String, String)> = fwd_edges
+                .try_id(edge_idx)
+                .map(|edge_id| (String::from("edge-id"), edge_id.to_string()))
+                .into_iter()
+                .collect();
+            properties.extend(vec![
+                (String::from("workload"), workload.to_string()),
+                (String::from("bin"), bin.to_string()),
+                (
+                    String::from("stroke"),
+                    json::string(style::WORKLOAD_COLORS[bin]),
+                ),
+                (String::from("stroke-width"), (bin + 1).to_string()),
+            ]);
+            writer.write_line_string(&edge_coords(graph, edge_idx), &properties)?;
+        }
+        writer.finish()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["geojson", "json"]
+    }
+}
+
+fn edge_coords(graph: &Graph, edge_idx: EdgeIdx) -> [Coordinate; 2] {
+    let nodes = graph.nodes();
+    [
+        nodes.coord(graph.bwd_edges().dst_idx(edge_idx)),
+        nodes.coord(graph.fwd_edges().dst_idx(edge_idx)),
+    ]
+}
+
+/// Writes features one after another, so big graphs don't have to be kept in memory.
+struct FeatureWriter {
+    writer: BufWriter<std::fs::File>,
+    is_first: bool,
+}
+
+impl FeatureWriter {
+    fn create(file: &path::Path) -> err::Result<FeatureWriter> {
+        Writer::find_supported_ext(file)?;
+        let output_file = match OpenOptions::new().write(true).create_new(true).open(file) {
+            Ok(output_file) => output_file,
+            Err(e) => {
+                return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+            }
+        };
+        let mut writer = BufWriter::new(output_file);
+        writeln!(writer, "{{\"type\": \"FeatureCollection\", \"features\": [")?;
+
+        Ok(FeatureWriter {
+            writer,
+            is_first: true,
+        })
+    }
+
+    /// Property-values have to be valid json already.
+    fn write_line_string(
+        &mut self,
+        coords: &[Coordinate],
+        properties: &[(String, String)],
+    ) -> err::Feedback {
+        if !self.is_first {
+            writeln!(self.writer, ",")?;
+        }
+        self.is_first = false;
+
+        // GeoJSON expects (lon, lat)
+        let coords: Vec<String> = coords
+            .iter()
+            .map(|coord| format!("[{}, {}]", json::number(coord.lon), json::number(coord.lat)))
+            .collect();
+        let properties: Vec<String> = properties
+            .iter()
+            .map(|(key, value)| format!("{}: {}", json::string(key), value))
+            .collect();
+        write!(
+            self.writer,
+            "{{\"type\": \"Feature\", \"geometry\": {{\"type\": \"LineString\", \"coordinates\": [{}]}}, \"properties\": {{{}}}}}",
+            coords.join(", "),
+            properties.join(", ")
+        )?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> err::Feedback {
+        writeln!(self.writer)?;
+        writeln!(self.writer, "]}}")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate::defaults;
+
+pub(super) const NULL: &str = "null";
+
+/// Non-finite numbers aren't valid json.
+pub(super) fn number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        String::from(defaults::writing::json::NON_FINITE)
+    }
+}
+
+pub(super) fn string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
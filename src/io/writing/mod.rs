@@ -1,5 +1,12 @@
+pub mod assigning;
+pub mod dimacs;
 #[cfg(feature = "gpl")]
 pub mod evaluating_balance;
+pub mod geojson;
+mod json;
 pub mod network;
+pub mod paths;
+pub mod queries;
 pub mod routing;
 pub mod smarts;
+pub mod sumo;
@@ -2,15 +2,11 @@ use crate::{
     configs::writing::network::edges::Config as WritingConfig,
     defaults,
     helpers::err,
-    io::{writing::network::write_edges_to_file, SupportingFileExts, SupportingMapFileExts},
+    io::{csv, writing::network::write_edges_to_file, SupportingFileExts, SupportingMapFileExts},
     network::Graph,
 };
 use log::info;
-use std::{
-    fmt::Display,
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-};
+use std::{fmt::Display, fs::OpenOptions, io::BufWriter};
 
 pub struct Writer;
 
@@ -93,21 +89,16 @@ impl Writer {
         // write header
 
         if writing_cfg.is_writing_header {
-            for (i, id) in writing_cfg.ids.iter().enumerate() {
-                write!(
-                    writer,
-                    "{}",
+            let header: Vec<&str> = writing_cfg
+                .ids
+                .iter()
+                .map(|id| {
                     id.as_ref()
                         .map(|id| id.as_ref())
                         .unwrap_or(defaults::writing::IGNORE_STR)
-                )?;
-
-                // if not last line -> enter space
-                if i < writing_cfg.ids.len() - 1 {
-                    write!(writer, " ")?;
-                }
-            }
-            writeln!(writer, "")?;
+                })
+                .collect();
+            csv::write_record(&mut writer, &header, writing_cfg.csv.as_ref())?;
         }
 
         // write values
@@ -116,7 +107,14 @@ impl Writer {
         for edge_idx in fwd_edges.iter().filter(|&edge_idx| {
             writing_cfg.is_writing_shortcuts || !fwd_edges.is_shortcut(edge_idx)
         }) {
-            writeln!(writer, "{} {}", fwd_edges.id(edge_idx), values[*edge_idx])?;
+            csv::write_record(
+                &mut writer,
+                &[
+                    format!("{}", fwd_edges.id(edge_idx)),
+                    format!("{}", values[*edge_idx]),
+                ],
+                writing_cfg.csv.as_ref(),
+            )?;
         }
 
         Ok(())
@@ -20,7 +20,13 @@ impl Writer {
         match Writer::from_path(&writing_cfg.map_file)? {
             MapFileExt::FMI => fmi::Writer::new().write(graph, writing_cfg)?,
             MapFileExt::PBF => {
-                return Err(format!("No support for writing pbf-files.").into());
+                return Err("No support for writing pbf-files.".into());
+            }
+            MapFileExt::DIMACS => {
+                return Err(
+                    "DIMACS-files are written via the dimacs-writer (see writing: { dimacs: ... })."
+                        .into(),
+                );
             }
         }
 
@@ -4,6 +4,7 @@ pub mod graph;
 use crate::{
     configs, defaults,
     helpers::err,
+    io::csv,
     network::{Graph, MetricIdx},
 };
 use log::info;
@@ -22,26 +23,15 @@ fn write_edges_to_file<W: Write>(
     // write header
 
     if writing_cfg.is_writing_header {
-        for (i, edge_info) in writing_cfg
+        let header: Vec<String> = writing_cfg
             .ids
             .iter()
             .map(|id| match id {
-                Some(id) => format!("{}", id.0),
-                None => format!("{}", defaults::writing::IGNORE_STR),
+                Some(id) => id.0.clone(),
+                None => defaults::writing::IGNORE_STR.to_string(),
             })
-            .enumerate()
-        {
-            write!(writer, "{}", edge_info)?;
-
-            // Sth has been written, so
-            // write space if needed
-            if i < writing_cfg.ids.len() - 1 {
-                write!(writer, " ")?;
-            }
-        }
-
-        // write end of line
-        writeln!(writer, "")?;
+            .collect();
+        csv::write_record(writer, &header, writing_cfg.csv.as_ref())?;
     }
 
     // write edges to file
@@ -69,7 +59,8 @@ fn write_edges_to_file<W: Write>(
         // if id fits
 
         // for every writing-cfg-item
-        for (i, next_id) in writing_cfg.ids.iter().enumerate() {
+        let mut fields = Vec::with_capacity(writing_cfg.ids.len());
+        for next_id in writing_cfg.ids.iter() {
             if let Some(next_id) = next_id {
                 let mut has_been_written = false;
 
@@ -87,64 +78,58 @@ fn write_edges_to_file<W: Write>(
                             match info {
                                 configs::parsing::edges::MetaInfo::EdgeId => {
                                     let edge_id = fwd_edges.id(edge_idx);
-                                    write!(writer, "{}", edge_id)?;
+                                    fields.push(format!("{}", edge_id));
                                 }
                                 configs::parsing::edges::MetaInfo::SrcId => {
                                     let src_idx = bwd_edges.dst_idx(edge_idx);
                                     let src_id = nodes.id(src_idx);
-                                    write!(writer, "{}", src_id)?;
+                                    fields.push(format!("{}", src_id));
                                 }
                                 configs::parsing::edges::MetaInfo::SrcIdx => {
                                     let src_idx = bwd_edges.dst_idx(edge_idx);
-                                    write!(writer, "{}", src_idx)?;
+                                    fields.push(format!("{}", src_idx));
                                 }
                                 configs::parsing::edges::MetaInfo::SrcLat => {
                                     let src_idx = bwd_edges.dst_idx(edge_idx);
                                     let src_lat = nodes.coord(src_idx).lat;
-                                    write!(writer, "{}", src_lat)?;
+                                    fields.push(format!("{}", src_lat));
                                 }
                                 configs::parsing::edges::MetaInfo::SrcLon => {
                                     let src_idx = bwd_edges.dst_idx(edge_idx);
                                     let src_lon = nodes.coord(src_idx).lon;
-                                    write!(writer, "{}", src_lon)?;
+                                    fields.push(format!("{}", src_lon));
                                 }
                                 configs::parsing::edges::MetaInfo::DstId => {
                                     let dst_idx = fwd_edges.dst_idx(edge_idx);
                                     let dst_id = nodes.id(dst_idx);
-                                    write!(writer, "{}", dst_id)?;
+                                    fields.push(format!("{}", dst_id));
                                 }
                                 configs::parsing::edges::MetaInfo::DstIdx => {
                                     let dst_idx = fwd_edges.dst_idx(edge_idx);
-                                    write!(writer, "{}", dst_idx)?;
+                                    fields.push(format!("{}", dst_idx));
                                 }
                                 configs::parsing::edges::MetaInfo::DstLat => {
                                     let dst_idx = bwd_edges.dst_idx(edge_idx);
                                     let dst_lat = nodes.coord(dst_idx).lat;
-                                    write!(writer, "{}", dst_lat)?;
+                                    fields.push(format!("{}", dst_lat));
                                 }
                                 configs::parsing::edges::MetaInfo::DstLon => {
                                     let dst_idx = bwd_edges.dst_idx(edge_idx);
                                     let dst_lon = nodes.coord(dst_idx).lon;
-                                    write!(writer, "{}", dst_lon)?;
+                                    fields.push(format!("{}", dst_lon));
                                 }
                                 configs::parsing::edges::MetaInfo::ShortcutIdx0 => {
                                     match fwd_edges.sc_edges(edge_idx) {
-                                        Some(sc_edges) => write!(writer, "{}", sc_edges[0])?,
-                                        None => write!(
-                                            writer,
-                                            "{}",
-                                            defaults::writing::NO_SHORTCUT_IDX
-                                        )?,
+                                        Some(sc_edges) => fields.push(format!("{}", sc_edges[0])),
+                                        None => fields
+                                            .push(defaults::writing::NO_SHORTCUT_IDX.to_string()),
                                     }
                                 }
                                 configs::parsing::edges::MetaInfo::ShortcutIdx1 => {
                                     match fwd_edges.sc_edges(edge_idx) {
-                                        Some(sc_edges) => write!(writer, "{}", sc_edges[1])?,
-                                        None => write!(
-                                            writer,
-                                            "{}",
-                                            defaults::writing::NO_SHORTCUT_IDX
-                                        )?,
+                                        Some(sc_edges) => fields.push(format!("{}", sc_edges[1])),
+                                        None => fields
+                                            .push(defaults::writing::NO_SHORTCUT_IDX.to_string()),
                                     }
                                 }
                             }
@@ -174,7 +159,7 @@ fn write_edges_to_file<W: Write>(
 
                             // denormalize metric if wished
 
-                            let mut metric_value = graph.metrics()[edge_idx][*metric_idx];
+                            let mut metric_value = graph.metrics().get_value(edge_idx, metric_idx);
                             if writing_cfg.is_denormalizing {
                                 // check if graph is normalized
                                 if let Some(mean) = graph.metrics().mean(metric_idx) {
@@ -182,7 +167,7 @@ fn write_edges_to_file<W: Write>(
                                 }
                             };
 
-                            write!(writer, "{}", metric_value)?;
+                            fields.push(format!("{}", metric_value));
                         }
                         configs::parsing::edges::Category::Ignored => continue, // covered in else-case
                     }
@@ -205,18 +190,12 @@ fn write_edges_to_file<W: Write>(
             } else {
                 // if id is None
                 // -> ignore column
-                write!(writer, "{}", defaults::writing::IGNORE_STR)?;
-            }
-
-            // Sth has been written, so
-            // write space if needed
-            if i < writing_cfg.ids.len() - 1 {
-                write!(writer, " ")?;
+                fields.push(defaults::writing::IGNORE_STR.to_string());
             }
         }
 
-        // write end of line
-        writeln!(writer, "")?;
+        // write line
+        csv::write_record(writer, &fields, writing_cfg.csv.as_ref())?;
 
         // print progress
         progress_bar.add(true);
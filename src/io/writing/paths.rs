@@ -0,0 +1,256 @@
+use crate::{
+    configs::writing::paths::Config as WritingConfig,
+    defaults::writing::paths as format,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{Graph, MetricIdx},
+    routing::paths::Path,
+};
+use kissunits::geo::Coordinate;
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+/// Writes paths as GPX-tracks (`*.gpx`) or KML-placemarks (`*.kml`), e.g. for handheld devices.
+///
+/// Every path carries its src-id, dst-id and costs (per metric-id) as metadata.
+/// Alternatives of the same route-pair are written as separate tracks (or placemarks).
+pub struct Writer;
+
+impl Writer {
+    pub fn write(paths: &[Path], graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write {} paths as {}",
+            paths.len(),
+            writing_cfg.file.display()
+        );
+
+        let is_gpx = match Writer::find_supported_ext(&writing_cfg.file)? {
+            "gpx" => true,
+            "kml" => false,
+            ext => return Err(format!("Unsupported extension {} for paths.", ext).into()),
+        };
+
+        let tracks = paths
+            .iter()
+            .map(|path| Track::try_from(path, graph, writing_cfg))
+            .collect::<err::Result<Vec<Track>>>()?;
+
+        let output_file = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&writing_cfg.file)
+        {
+            Ok(output_file) => output_file,
+            Err(e) => {
+                return Err(format!(
+                    "Couldn't open {} due to error: {}",
+                    writing_cfg.file.display(),
+                    e
+                )
+                .into())
+            }
+        };
+        let mut writer = BufWriter::new(output_file);
+
+        if is_gpx {
+            write_gpx(&tracks, &mut writer)?;
+        } else {
+            write_kml(&tracks, &mut writer)?;
+        }
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["gpx", "kml"]
+    }
+}
+
+/// A flattened path with its metadata.
+struct Track {
+    src_id: i64,
+    dst_id: i64,
+    coords: Vec<Coordinate>,
+    costs: Vec<(String, f64)>,
+}
+
+impl Track {
+    fn try_from(path: &Path, graph: &Graph, writing_cfg: &WritingConfig) -> err::Result<Track> {
+        let path = path.clone().try_flatten(graph)?;
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let metrics = graph.metrics();
+
+        let mut coords = Vec::with_capacity(path.iter().len() + 1);
+        coords.push(nodes.coord(path.src_idx()));
+        coords.extend(
+            path.iter()
+                .map(|&edge_idx| nodes.coord(fwd_edges.dst_idx(edge_idx))),
+        );
+
+        let costs = graph
+            .cfg()
+            .edges
+            .metrics
+            .ids
+            .iter()
+            .zip(path.costs())
+            .enumerate()
+            .map(|(metric_idx, (metric_id, &cost))| {
+                let mean = if writing_cfg.is_denormalizing {
+                    metrics.mean(MetricIdx(metric_idx)).unwrap_or(1.0)
+                } else {
+                    1.0
+                };
+                (metric_id.0.clone(), cost * mean)
+            })
+            .collect();
+
+        Ok(Track {
+            src_id: nodes.id(path.src_idx()),
+            dst_id: nodes.id(path.dst_idx()),
+            coords,
+            costs,
+        })
+    }
+
+    fn name(&self) -> String {
+        format!("{} -> {}", self.src_id, self.dst_id)
+    }
+
+    /// Human-readable costs, e.g. `kilometers: 48, hours: 0.5`
+    fn description(&self) -> String {
+        self.costs
+            .iter()
+            .map(|(metric_id, cost)| format!("{}: {}", metric_id, cost))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// [GPX 1.1](https://www.topografix.com/GPX/1/1/), where metadata is added as extensions.
+fn write_gpx<W: Write>(tracks: &[Track], writer: &mut W) -> err::Feedback {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        writer,
+        "<gpx version=\"1.1\" creator=\"{}\" xmlns=\"{}\" xmlns:{}=\"{}\">",
+        format::CREATOR,
+        format::GPX_NAMESPACE,
+        format::EXTENSIONS_PREFIX,
+        format::EXTENSIONS_NAMESPACE
+    )?;
+
+    let prefix = format::EXTENSIONS_PREFIX;
+    for track in tracks {
+        writeln!(writer, "  <trk>")?;
+        writeln!(writer, "    <name>{}</name>", xml_escaped(&track.name()))?;
+        writeln!(
+            writer,
+            "    <desc>{}</desc>",
+            xml_escaped(&track.description())
+        )?;
+        writeln!(writer, "    <extensions>")?;
+        writeln!(
+            writer,
+            "      <{0}:src-id>{1}</{0}:src-id>",
+            prefix, track.src_id
+        )?;
+        writeln!(
+            writer,
+            "      <{0}:dst-id>{1}</{0}:dst-id>",
+            prefix, track.dst_id
+        )?;
+        for (metric_id, cost) in &track.costs {
+            writeln!(
+                writer,
+                "      <{0}:cost metric=\"{1}\">{2}</{0}:cost>",
+                prefix,
+                xml_escaped(metric_id),
+                cost
+            )?;
+        }
+        writeln!(writer, "    </extensions>")?;
+        writeln!(writer, "    <trkseg>")?;
+        for coord in &track.coords {
+            writeln!(
+                writer,
+                "      <trkpt lat=\"{}\" lon=\"{}\"/>",
+                coord.lat, coord.lon
+            )?;
+        }
+        writeln!(writer, "    </trkseg>")?;
+        writeln!(writer, "  </trk>")?;
+    }
+
+    writeln!(writer, "</gpx>")?;
+    Ok(())
+}
+
+/// [KML 2.2](https://www.opengis.net/kml/2.2), where metadata is added as extended data.
+fn write_kml<W: Write>(tracks: &[Track], writer: &mut W) -> err::Feedback {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(writer, "<kml xmlns=\"{}\">", format::KML_NAMESPACE)?;
+    writeln!(writer, "<Document>")?;
+
+    for track in tracks {
+        writeln!(writer, "  <Placemark>")?;
+        writeln!(writer, "    <name>{}</name>", xml_escaped(&track.name()))?;
+        writeln!(
+            writer,
+            "    <description>{}</description>",
+            xml_escaped(&track.description())
+        )?;
+        writeln!(writer, "    <ExtendedData>")?;
+        writeln!(
+            writer,
+            "      <Data name=\"src-id\"><value>{}</value></Data>",
+            track.src_id
+        )?;
+        writeln!(
+            writer,
+            "      <Data name=\"dst-id\"><value>{}</value></Data>",
+            track.dst_id
+        )?;
+        for (metric_id, cost) in &track.costs {
+            writeln!(
+                writer,
+                "      <Data name=\"{}\"><value>{}</value></Data>",
+                xml_escaped(metric_id),
+                cost
+            )?;
+        }
+        writeln!(writer, "    </ExtendedData>")?;
+        // KML expects (lon, lat)
+        let coords: Vec<String> = track
+            .coords
+            .iter()
+            .map(|coord| format!("{},{}", coord.lon, coord.lat))
+            .collect();
+        writeln!(
+            writer,
+            "    <LineString><coordinates>{}</coordinates></LineString>",
+            coords.join(" ")
+        )?;
+        writeln!(writer, "  </Placemark>")?;
+    }
+
+    writeln!(writer, "</Document>")?;
+    writeln!(writer, "</kml>")?;
+    Ok(())
+}
+
+fn xml_escaped(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
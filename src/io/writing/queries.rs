@@ -0,0 +1,325 @@
+use super::json;
+use crate::{
+    configs::{
+        csv as csv_cfg,
+        writing::queries::{Config as WritingConfig, Sequence},
+    },
+    defaults,
+    helpers::err,
+    io::{csv, SupportingFileExts},
+    network::{Graph, MetricIdx},
+    routing::batch::QueryResult,
+};
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+/// Writes one row per query-result, either as csv (`*.csv`) or as JSON Lines (`*.jsonl`).
+///
+/// Every row contains src-id, dst-id, found-flag, hop-distance, query-time (in ms) and the costs per metric-id,
+/// followed by the route-entry's label and metadata (if given) and the path's sequence (if configured).
+pub struct Writer;
+
+impl Writer {
+    pub fn write(
+        results: &[QueryResult],
+        graph: &Graph,
+        writing_cfg: &WritingConfig,
+    ) -> err::Feedback {
+        info!(
+            "START Write {} query-results as {}",
+            results.len(),
+            writing_cfg.file.display()
+        );
+
+        let is_csv = match Writer::find_supported_ext(&writing_cfg.file)? {
+            "csv" => true,
+            "jsonl" => false,
+            ext => return Err(format!("Unsupported extension {} for queries.", ext).into()),
+        };
+
+        let rows = results
+            .iter()
+            .map(|result| Row::try_from(result, graph, writing_cfg))
+            .collect::<err::Result<Vec<Row>>>()?;
+
+        let output_file = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&writing_cfg.file)
+        {
+            Ok(output_file) => output_file,
+            Err(e) => {
+                return Err(format!(
+                    "Couldn't open {} due to error: {}",
+                    writing_cfg.file.display(),
+                    e
+                )
+                .into())
+            }
+        };
+        let mut writer = BufWriter::new(output_file);
+
+        if is_csv {
+            write_csv(&rows, graph, writing_cfg, &mut writer)?;
+        } else {
+            write_jsonl(&rows, graph, writing_cfg, &mut writer)?;
+        }
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["csv", "jsonl"]
+    }
+}
+
+/// A query-result with its values prepared for writing.
+struct Row<'a> {
+    result: &'a QueryResult,
+    /// Only given, if a path has been found.
+    hop_distance: Option<usize>,
+    costs: Option<Vec<f64>>,
+    sequence: Option<Vec<String>>,
+}
+
+impl<'a> Row<'a> {
+    fn try_from(
+        result: &'a QueryResult,
+        graph: &Graph,
+        writing_cfg: &WritingConfig,
+    ) -> err::Result<Row<'a>> {
+        let path = match &result.path {
+            Some(path) => path,
+            None => {
+                return Ok(Row {
+                    result,
+                    hop_distance: None,
+                    costs: None,
+                    sequence: None,
+                })
+            }
+        };
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let metrics = graph.metrics();
+
+        let costs = path
+            .costs()
+            .iter()
+            .enumerate()
+            .map(|(metric_idx, &cost)| {
+                if writing_cfg.is_denormalizing {
+                    cost * metrics.mean(MetricIdx(metric_idx)).unwrap_or(1.0)
+                } else {
+                    cost
+                }
+            })
+            .collect();
+
+        let sequence = match writing_cfg.sequence {
+            Some(Sequence::EdgeIds) => Some(
+                path.iter()
+                    .map(|&edge_idx| match fwd_edges.try_id(edge_idx) {
+                        Some(edge_id) => Ok(edge_id.to_string()),
+                        None => Err(err::Msg::from(
+                            "For writing edge-ids, the graph should have edge-ids.",
+                        )),
+                    })
+                    .collect::<err::Result<Vec<String>>>()?,
+            ),
+            Some(Sequence::NodeIds) => Some(
+                Some(path.src_idx())
+                    .into_iter()
+                    .chain(path.iter().map(|&edge_idx| fwd_edges.dst_idx(edge_idx)))
+                    .map(|node_idx| nodes.id(node_idx).to_string())
+                    .collect(),
+            ),
+            None => None,
+        };
+
+        Ok(Row {
+            result,
+            hop_distance: Some(path.iter().len()),
+            costs: Some(costs),
+            sequence,
+        })
+    }
+
+    fn query_ms(&self) -> f64 {
+        self.result.query_time.as_micros() as f64 / 1_000.0
+    }
+}
+
+fn sequence_name(sequence: Sequence) -> &'static str {
+    match sequence {
+        Sequence::EdgeIds => defaults::writing::queries::csv_names::EDGE_IDS,
+        Sequence::NodeIds => defaults::writing::queries::csv_names::NODE_IDS,
+    }
+}
+
+/// [RFC 4180](https://tools.ietf.org/html/rfc4180)-csv with a header-line, where missing values are written as `_`.
+fn write_csv<W: Write>(
+    rows: &[Row],
+    graph: &Graph,
+    writing_cfg: &WritingConfig,
+    writer: &mut W,
+) -> err::Feedback {
+    use defaults::writing::queries::csv_names;
+    let csv_cfg = csv_cfg::Config::default();
+
+    // labels and metadata are only written, if given
+    let is_writing_labels = rows.iter().any(|row| row.result.entry.label.is_some());
+    let mut metadata_names: Vec<&str> = Vec::new();
+    for row in rows {
+        for (name, _value) in &row.result.entry.metadata {
+            if !metadata_names.contains(&name.as_str()) {
+                metadata_names.push(name);
+            }
+        }
+    }
+
+    // header
+
+    let mut header = vec![
+        csv_names::SRC_ID,
+        csv_names::DST_ID,
+        csv_names::FOUND,
+        csv_names::HOP_DISTANCE,
+        csv_names::QUERY_MS,
+    ];
+    let metric_ids = &graph.cfg().edges.metrics.ids;
+    header.extend(metric_ids.iter().map(|metric_id| metric_id.0.as_str()));
+    if is_writing_labels {
+        header.push(defaults::routing::route_pairs::csv_names::LABEL);
+    }
+    header.extend(metadata_names.iter());
+    if let Some(sequence) = writing_cfg.sequence {
+        header.push(sequence_name(sequence));
+    }
+    csv::write_record(writer, &header, Some(&csv_cfg))?;
+
+    // rows
+
+    let ignore = defaults::writing::IGNORE_STR;
+    for row in rows {
+        let entry = &row.result.entry;
+        let mut values = vec![
+            entry.route_pair.src.to_string(),
+            entry.route_pair.dst.to_string(),
+            row.result.path.is_some().to_string(),
+            row.hop_distance
+                .map_or(String::from(ignore), |hop_distance| {
+                    hop_distance.to_string()
+                }),
+            row.query_ms().to_string(),
+        ];
+        match &row.costs {
+            Some(costs) => values.extend(costs.iter().map(|cost| cost.to_string())),
+            None => values.extend(metric_ids.iter().map(|_| String::from(ignore))),
+        }
+        if is_writing_labels {
+            values.push(entry.label.clone().unwrap_or_else(|| String::from(ignore)));
+        }
+        for name in &metadata_names {
+            values.push(
+                entry
+                    .metadata
+                    .iter()
+                    .find(|(key, _value)| key == name)
+                    .map_or(String::from(ignore), |(_key, value)| value.clone()),
+            );
+        }
+        if writing_cfg.sequence.is_some() {
+            values.push(
+                row.sequence
+                    .as_ref()
+                    .map_or(String::from(ignore), |sequence| sequence.join(",")),
+            );
+        }
+        csv::write_record(writer, &values, Some(&csv_cfg))?;
+    }
+
+    Ok(())
+}
+
+/// One json-object per line, where missing values are `null`.
+fn write_jsonl<W: Write>(
+    rows: &[Row],
+    graph: &Graph,
+    writing_cfg: &WritingConfig,
+    writer: &mut W,
+) -> err::Feedback {
+    use defaults::writing::queries::csv_names;
+
+    let metric_ids = &graph.cfg().edges.metrics.ids;
+    for row in rows {
+        let entry = &row.result.entry;
+        let mut fields = vec![
+            (csv_names::SRC_ID, entry.route_pair.src.to_string()),
+            (csv_names::DST_ID, entry.route_pair.dst.to_string()),
+            (csv_names::FOUND, row.result.path.is_some().to_string()),
+            (
+                csv_names::HOP_DISTANCE,
+                row.hop_distance
+                    .map_or(String::from(json::NULL), |hop_distance| {
+                        hop_distance.to_string()
+                    }),
+            ),
+            (csv_names::QUERY_MS, json::number(row.query_ms())),
+            (
+                csv_names::COSTS,
+                row.costs
+                    .as_ref()
+                    .map_or(String::from(json::NULL), |costs| {
+                        let costs: Vec<String> = metric_ids
+                            .iter()
+                            .zip(costs)
+                            .map(|(metric_id, &cost)| {
+                                format!("{}: {}", json::string(&metric_id.0), json::number(cost))
+                            })
+                            .collect();
+                        format!("{{{}}}", costs.join(", "))
+                    }),
+            ),
+        ];
+        if let Some(label) = &entry.label {
+            fields.push((
+                defaults::routing::route_pairs::csv_names::LABEL,
+                json::string(label),
+            ));
+        }
+        if !entry.metadata.is_empty() {
+            let metadata: Vec<String> = entry
+                .metadata
+                .iter()
+                .map(|(key, value)| format!("{}: {}", json::string(key), json::string(value)))
+                .collect();
+            fields.push((csv_names::METADATA, format!("{{{}}}", metadata.join(", "))));
+        }
+        if let Some(sequence) = writing_cfg.sequence {
+            fields.push((
+                sequence_name(sequence),
+                row.sequence
+                    .as_ref()
+                    .map_or(String::from(json::NULL), |ids| {
+                        format!("[{}]", ids.join(", "))
+                    }),
+            ));
+        }
+
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", json::string(key), value))
+            .collect();
+        writeln!(writer, "{{{}}}", fields.join(", "))?;
+    }
+
+    Ok(())
+}
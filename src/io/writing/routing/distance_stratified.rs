@@ -0,0 +1,117 @@
+use super::sampling::{self, Sampler};
+use crate::{
+    configs::{
+        self,
+        writing::routing::{DistanceBin, NodeWeights},
+    },
+    defaults,
+    helpers::err,
+    io,
+    network::Graph,
+};
+use kissunits::geo;
+use log::{info, warn};
+use progressing::{bernoulli::Bar as BernoulliBar, Baring};
+use std::collections::BTreeMap;
+
+pub struct Writer<'a> {
+    seed: u64,
+    max_count: usize,
+    weights: Option<&'a NodeWeights>,
+    bins: &'a [DistanceBin],
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(
+        seed: u64,
+        max_count: usize,
+        weights: Option<&'a NodeWeights>,
+        bins: &'a [DistanceBin],
+    ) -> Writer<'a> {
+        Writer {
+            seed,
+            max_count,
+            weights,
+            bins,
+        }
+    }
+}
+
+impl<'a> Writer<'a> {
+    pub fn write(
+        &self,
+        graph: &Graph,
+        writing_cfg: &configs::writing::routing::Config,
+    ) -> err::Feedback {
+        let node_weights = match self.weights {
+            Some(weights) => Some(io::weights::Parser::parse_node_weights(graph, weights)?),
+            None => None,
+        };
+        let mut sampler = Sampler::new(graph, self.seed, node_weights)?;
+        let nodes = graph.nodes();
+
+        // Every bin gets its share of routes,
+        // where rounding the cumulated shares keeps the sum of all bins.
+        let mut remaining_counts = Vec::with_capacity(self.bins.len());
+        let mut cum_share = 0.0;
+        let mut cum_count = 0;
+        for bin in self.bins {
+            cum_share += bin.share;
+            let next_cum_count =
+                ((cum_share * self.max_count as f64).round() as usize).min(self.max_count);
+            remaining_counts.push(next_cum_count - cum_count);
+            cum_count = next_cum_count;
+        }
+
+        // Sampled route-pairs are accepted, if their distance's bin isn't full yet.
+
+        let max_attempts = self.max_count * defaults::writing::route_pairs::MAX_ATTEMPTS_PER_ROUTE;
+        let mut route_pairs = BTreeMap::new();
+        let mut progress_bar = BernoulliBar::with_goal(self.max_count).timed();
+        info!("{}", progress_bar);
+        while progress_bar.progress().successes < self.max_count
+            && progress_bar.progress().attempts < max_attempts
+        {
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+
+            let src_idx = sampler.sample_node();
+            let dst_idx = sampler.sample_node();
+            let distance = geo::haversine_distance_km(&nodes.coord(src_idx), &nodes.coord(dst_idx));
+            let bin_idx = self.bins.iter().position(|bin| distance < bin.max_distance);
+
+            match bin_idx {
+                Some(bin_idx)
+                    if remaining_counts[bin_idx] > 0 && sampler.is_routable(src_idx, dst_idx) =>
+                {
+                    remaining_counts[bin_idx] -= 1;
+                    *route_pairs
+                        .entry((nodes.id(src_idx), nodes.id(dst_idx)))
+                        .or_insert(0) += 1;
+                    progress_bar.add(true);
+                }
+                _ => progress_bar.add(false),
+            }
+        }
+        info!("{}", progress_bar);
+
+        for (bin, remaining_count) in self.bins.iter().zip(remaining_counts) {
+            if remaining_count > 0 {
+                warn!(
+                    "{} routes below {} are missing after {} attempts.",
+                    remaining_count, bin.max_distance, max_attempts
+                );
+            }
+        }
+
+        sampling::write(
+            graph,
+            writing_cfg,
+            "distance-stratified routes",
+            self.seed,
+            &route_pairs,
+        )
+    }
+}
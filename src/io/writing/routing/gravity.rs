@@ -0,0 +1,103 @@
+use super::sampling::{self, Sampler};
+use crate::{
+    configs::{self, writing::routing::NodeWeights},
+    defaults,
+    helpers::err,
+    io,
+    network::Graph,
+};
+use kissunits::{distance::Kilometers, geo};
+use log::{info, warn};
+use progressing::{bernoulli::Bar as BernoulliBar, Baring};
+use rand::Rng;
+use std::collections::BTreeMap;
+
+pub struct Writer<'a> {
+    seed: u64,
+    max_count: usize,
+    weights: &'a NodeWeights,
+    distance_scale: Option<Kilometers>,
+}
+
+impl<'a> Writer<'a> {
+    pub fn new(
+        seed: u64,
+        max_count: usize,
+        weights: &'a NodeWeights,
+        distance_scale: Option<Kilometers>,
+    ) -> Writer<'a> {
+        Writer {
+            seed,
+            max_count,
+            weights,
+            distance_scale,
+        }
+    }
+}
+
+impl<'a> Writer<'a> {
+    pub fn write(
+        &self,
+        graph: &Graph,
+        writing_cfg: &configs::writing::routing::Config,
+    ) -> err::Feedback {
+        let node_weights = io::weights::Parser::parse_node_weights(graph, self.weights)?;
+        let mut sampler = Sampler::new(graph, self.seed, Some(node_weights))?;
+        let nodes = graph.nodes();
+
+        // Sources and destinations are sampled proportional to their weights,
+        // and accepted proportional to the deterrence of their distance.
+
+        let max_attempts = self.max_count * defaults::writing::route_pairs::MAX_ATTEMPTS_PER_ROUTE;
+        let mut route_pairs = BTreeMap::new();
+        let mut progress_bar = BernoulliBar::with_goal(self.max_count).timed();
+        info!("{}", progress_bar);
+        while progress_bar.progress().successes < self.max_count
+            && progress_bar.progress().attempts < max_attempts
+        {
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+
+            let src_idx = sampler.sample_node();
+            let dst_idx = sampler.sample_node();
+            if let Some(distance_scale) = self.distance_scale {
+                let distance =
+                    geo::haversine_distance_km(&nodes.coord(src_idx), &nodes.coord(dst_idx));
+                let deterrence = (-distance.0 / distance_scale.0).exp();
+                if sampler.rng.gen::<f64>() >= deterrence {
+                    progress_bar.add(false);
+                    continue;
+                }
+            }
+
+            if sampler.is_routable(src_idx, dst_idx) {
+                *route_pairs
+                    .entry((nodes.id(src_idx), nodes.id(dst_idx)))
+                    .or_insert(0) += 1;
+                progress_bar.add(true);
+            } else {
+                progress_bar.add(false);
+            }
+        }
+        info!("{}", progress_bar);
+
+        if progress_bar.progress().successes < self.max_count {
+            warn!(
+                "Only {} of {} routes have been found after {} attempts.",
+                progress_bar.progress().successes,
+                self.max_count,
+                max_attempts
+            );
+        }
+
+        sampling::write(
+            graph,
+            writing_cfg,
+            "gravity-model routes",
+            self.seed,
+            &route_pairs,
+        )
+    }
+}
@@ -1,16 +1,26 @@
-use crate::{configs, helpers::err, io::SupportingFileExts, network::Graph};
+use crate::{
+    configs,
+    defaults::{self, routing::route_pairs::csv_names},
+    helpers::err,
+    io::{csv, SupportingFileExts},
+    network::{Graph, RouteEntry},
+};
 use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path::Path,
+};
 
+mod distance_stratified;
+mod gravity;
 mod random_or_all;
+mod sampling;
 
 pub struct Writer;
 
 impl Writer {
-    pub fn write(
-        graph: &Graph,
-        routing_cfg: &configs::routing::Config,
-        writing_cfg: &configs::writing::routing::Config,
-    ) -> err::Feedback {
+    pub fn write(graph: &Graph, writing_cfg: &configs::writing::routing::Config) -> err::Feedback {
         info!(
             "START Write routes {} from graph with {:?}",
             writing_cfg.file.display(),
@@ -18,16 +28,196 @@ impl Writer {
         );
         let result = match writing_cfg.category {
             configs::writing::routing::Category::RandomOrAll { seed, max_count } => {
-                random_or_all::Writer::new(seed, max_count).write(graph, routing_cfg, writing_cfg)
+                random_or_all::Writer::new(seed, max_count).write(graph, writing_cfg)
             }
+            configs::writing::routing::Category::Gravity {
+                seed,
+                max_count,
+                ref weights,
+                distance_scale,
+            } => gravity::Writer::new(seed, max_count, weights, distance_scale)
+                .write(graph, writing_cfg),
+            configs::writing::routing::Category::DistanceStratified {
+                seed,
+                max_count,
+                ref weights,
+                ref bins,
+            } => distance_stratified::Writer::new(seed, max_count, weights.as_ref(), bins)
+                .write(graph, writing_cfg),
         };
         info!("FINISHED");
         result
     }
+
+    /// Writes the given route-pairs as `route-pairs`- or `csv`-file, depending on the file's extension.
+    /// Only `csv`-files contain the nodes' coordinates, and the route-pairs' labels and metadata.
+    pub fn write_entries(graph: &Graph, entries: &[RouteEntry], file: &Path) -> err::Feedback {
+        Writer::write_entries_with_description(graph, entries, file, "routes", None)
+    }
+
+    /// The description and the seed are only written into `route-pairs`-files.
+    fn write_entries_with_description(
+        graph: &Graph,
+        entries: &[RouteEntry],
+        file: &Path,
+        description: &str,
+        seed: Option<u64>,
+    ) -> err::Feedback {
+        let is_csv = Writer::find_supported_ext(file)? == "csv";
+        let output_file = OpenOptions::new().write(true).create_new(true).open(file)?;
+        let mut writer = BufWriter::new(output_file);
+
+        if is_csv {
+            write_csv(&mut writer, graph, entries)
+        } else {
+            write_route_pairs(&mut writer, graph, entries, description, seed)
+        }
+    }
 }
 
 impl SupportingFileExts for Writer {
     fn supported_exts<'a>() -> &'a [&'a str] {
-        &["route-pairs"]
+        &["route-pairs", "csv"]
+    }
+}
+
+fn write_route_pairs<W: Write>(
+    writer: &mut W,
+    graph: &Graph,
+    entries: &[RouteEntry],
+    description: &str,
+    seed: Option<u64>,
+) -> err::Feedback {
+    // write header
+
+    writeln!(writer, "# graph-file: {}", graph.cfg().map_file.display())?;
+    writeln!(writer, "# node-count: {}", graph.nodes().count())?;
+    writeln!(writer, "# edge-count: {}", graph.fwd_edges().count())?;
+    writeln!(writer)?;
+
+    // write route-count
+
+    writeln!(writer, "# route-count")?;
+    writeln!(writer, "{}", entries.len())?;
+    writeln!(writer)?;
+
+    // write routes
+
+    if entries.iter().any(|entry| entry.departure.is_some()) {
+        writeln!(
+            writer,
+            "# {}: (src-id dst-id count departure) as (i64, i64, usize, seconds)",
+            description
+        )?;
+    } else {
+        writeln!(
+            writer,
+            "# {}: (src-id dst-id count) as (i64, i64, usize)",
+            description
+        )?;
+    }
+    if let Some(seed) = seed {
+        writeln!(writer, "# seed: {}", seed)?;
+    }
+    for entry in entries {
+        write!(
+            writer,
+            "{} {} {}",
+            entry.route_pair.src, entry.route_pair.dst, entry.count
+        )?;
+        if let Some(departure) = &entry.departure {
+            write!(writer, " {}", departure)?;
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Optional columns are only written, if any route-pair has a value.
+///
+/// The file is written as [RFC 4180](https://tools.ietf.org/html/rfc4180)-csv (with the default csv-config).
+fn write_csv<W: Write>(writer: &mut W, graph: &Graph, entries: &[RouteEntry]) -> err::Feedback {
+    let csv_cfg = configs::csv::Config::default();
+    let nodes = graph.nodes();
+    let is_writing_departures = entries.iter().any(|entry| entry.departure.is_some());
+    let is_writing_labels = entries.iter().any(|entry| entry.label.is_some());
+    let mut metadata_names: Vec<&str> = Vec::new();
+    for (name, _value) in entries.iter().flat_map(|entry| &entry.metadata) {
+        if !metadata_names.contains(&name.as_str()) {
+            metadata_names.push(name);
+        }
+    }
+
+    // write header
+
+    let mut header = vec![
+        csv_names::SRC_ID,
+        csv_names::SRC_LAT,
+        csv_names::SRC_LON,
+        csv_names::DST_ID,
+        csv_names::DST_LAT,
+        csv_names::DST_LON,
+        csv_names::COUNT,
+    ];
+    if is_writing_departures {
+        header.push(csv_names::DEPARTURE);
+    }
+    if is_writing_labels {
+        header.push(csv_names::LABEL);
+    }
+    header.extend(metadata_names.iter());
+    csv::write_record(writer, &header, Some(&csv_cfg))?;
+
+    // write routes
+
+    for entry in entries {
+        let coord_of = |id: i64| match nodes.idx_from(id) {
+            Ok(idx) => Ok(nodes.coord(idx)),
+            Err(_) => Err(err::Msg::from(format!(
+                "The node-id {} isn't part of the graph.",
+                id
+            ))),
+        };
+        let (src_id, dst_id) = (entry.route_pair.src, entry.route_pair.dst);
+        let (src_coord, dst_coord) = (coord_of(src_id)?, coord_of(dst_id)?);
+        let mut fields = vec![
+            src_id.to_string(),
+            src_coord.lat.to_string(),
+            src_coord.lon.to_string(),
+            dst_id.to_string(),
+            dst_coord.lat.to_string(),
+            dst_coord.lon.to_string(),
+            entry.count.to_string(),
+        ];
+        if is_writing_departures {
+            fields.push(
+                entry
+                    .departure
+                    .as_ref()
+                    .map_or(String::from(defaults::writing::IGNORE_STR), |departure| {
+                        departure.to_string()
+                    }),
+            );
+        }
+        if is_writing_labels {
+            fields.push(String::from(
+                entry
+                    .label
+                    .as_deref()
+                    .unwrap_or(defaults::writing::IGNORE_STR),
+            ));
+        }
+        for name in &metadata_names {
+            let value = entry
+                .metadata
+                .iter()
+                .find(|(other_name, _value)| other_name == name)
+                .map_or(defaults::writing::IGNORE_STR, |(_name, value)| value);
+            fields.push(String::from(value));
+        }
+        csv::write_record(writer, &fields, Some(&csv_cfg))?;
     }
+
+    Ok(())
 }
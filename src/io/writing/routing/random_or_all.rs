@@ -1,8 +1,7 @@
 use crate::{
-    configs,
+    configs, defaults,
     helpers::err,
-    network::{Graph, NodeIdx},
-    routing::{dijkstra, dijkstra::Dijkstra},
+    network::{Graph, NodeIdx, RouteEntry, RoutePair, StronglyConnectedComponents},
 };
 use log::{info, warn};
 use progressing::{bernoulli::Bar as BernoulliBar, Baring};
@@ -10,12 +9,8 @@ use rand::{
     distributions::{Distribution, Uniform},
     SeedableRng,
 };
-use std::{
-    cmp::min,
-    collections::HashSet,
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-};
+use rayon::prelude::*;
+use std::{cmp::min, collections::HashSet};
 
 pub struct Writer {
     seed: u64,
@@ -32,19 +27,9 @@ impl Writer {
     pub fn write(
         &self,
         graph: &Graph,
-        routing_cfg: &configs::routing::Config,
         writing_cfg: &configs::writing::routing::Config,
     ) -> err::Feedback {
-        // prepare
-
-        let output_file = OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&writing_cfg.file)?;
-        let mut writer = BufWriter::new(output_file);
-
         let nodes = graph.nodes();
-        let fwd_edges = graph.fwd_edges();
 
         // create routes
 
@@ -58,7 +43,12 @@ impl Writer {
             let mut rng = rand_pcg::Pcg32::seed_from_u64(self.seed);
             let die = Uniform::from(0..nodes.count());
 
-            let mut dijkstra = Dijkstra::new();
+            // Route-pairs are accepted or rejected by their components.
+            let components = StronglyConnectedComponents::new(graph);
+            info!(
+                "The graph has {} strongly connected components.",
+                components.count()
+            );
 
             if num_possible_routes <= self.max_count {
                 warn!(
@@ -75,48 +65,64 @@ impl Writer {
 
             // Stop when enough existing routes have been found
             // or when all possible routes are processed.
-            while progress_bar.progress().successes < max_count
-                && progress_bar.progress().attempts < num_possible_routes
-            {
-                if progress_bar.has_progressed_significantly() {
-                    progress_bar.remember_significant_progress();
-                    info!("{}", progress_bar);
+            let mut num_of_attempts = 0;
+            while found_route_pairs.len() < max_count && num_of_attempts < num_possible_routes {
+                // Candidates are created sequentially, so they only depend on the seed.
+                let batch_size = min(
+                    defaults::writing::route_pairs::CANDIDATES_PER_BATCH,
+                    num_possible_routes - num_of_attempts,
+                );
+                let mut candidates = Vec::with_capacity(batch_size);
+                for _ in 0..batch_size {
+                    let (src_idx, dst_idx) = {
+                        // if all possible routes are less than the preferred route-count
+                        // -> just print all possible routes
+                        // else: print random routes
+                        if num_possible_routes <= self.max_count {
+                            let i = num_of_attempts;
+                            let src_idx = NodeIdx(i / nodes.count());
+                            let dst_idx = NodeIdx(i % nodes.count());
+                            (src_idx, dst_idx)
+                        } else {
+                            let src_idx = NodeIdx(die.sample(&mut rng));
+                            let dst_idx = NodeIdx(die.sample(&mut rng));
+                            (src_idx, dst_idx)
+                        }
+                    };
+                    num_of_attempts += 1;
+
+                    let is_already_processed = !processed_indices.insert((src_idx, dst_idx));
+                    let src_equals_dst = src_idx == dst_idx;
+                    candidates.push((src_idx, dst_idx, !src_equals_dst && !is_already_processed));
                 }
 
-                let (src_idx, dst_idx) = {
-                    // if all possible routes are less than the preferred route-count
-                    // -> just print all possible routes
-                    // else: print random routes
-                    if num_possible_routes <= self.max_count {
-                        let i = progress_bar.progress().attempts;
-                        let src_idx = NodeIdx(i / nodes.count());
-                        let dst_idx = NodeIdx(i % nodes.count());
-                        (src_idx, dst_idx)
+                // Remaining candidates are checked in parallel,
+                // and collecting keeps their order.
+                let are_routable: Vec<bool> = candidates
+                    .par_iter()
+                    .map(|&(src_idx, dst_idx, is_candidate)| {
+                        is_candidate && components.is_reachable(src_idx, dst_idx)
+                    })
+                    .collect();
+
+                for ((src_idx, dst_idx, _is_candidate), is_routable) in
+                    candidates.into_iter().zip(are_routable)
+                {
+                    if found_route_pairs.len() >= max_count {
+                        break;
+                    }
+
+                    if progress_bar.has_progressed_significantly() {
+                        progress_bar.remember_significant_progress();
+                        info!("{}", progress_bar);
+                    }
+
+                    if is_routable {
+                        found_route_pairs.push((nodes.id(src_idx), nodes.id(dst_idx)));
+                        progress_bar.add(true);
                     } else {
-                        let src_idx = NodeIdx(die.sample(&mut rng));
-                        let dst_idx = NodeIdx(die.sample(&mut rng));
-                        (src_idx, dst_idx)
+                        progress_bar.add(false);
                     }
-                };
-
-                let is_already_processed = !processed_indices.insert((src_idx, dst_idx));
-                let src_equals_dst = src_idx == dst_idx;
-                if !src_equals_dst
-                    && !is_already_processed
-                    && dijkstra
-                        .compute_best_path(dijkstra::Query {
-                            src_idx,
-                            dst_idx,
-                            graph: &graph,
-                            routing_cfg: &routing_cfg,
-                        })
-                        .is_some()
-                {
-                    found_route_pairs
-                        .push((nodes.create(src_idx).id(), nodes.create(dst_idx).id()));
-                    progress_bar.add(true);
-                } else {
-                    progress_bar.add(false);
                 }
             }
 
@@ -130,30 +136,16 @@ impl Writer {
             found_route_pairs
         };
 
-        // write header
-
-        writeln!(writer, "# graph-file: {}", graph.cfg().map_file.display())?;
-        writeln!(writer, "# node-count: {}", nodes.count(),)?;
-        writeln!(writer, "# edge-count: {}", fwd_edges.count(),)?;
-        writeln!(writer, "")?;
-
-        // write route-count
-
-        writeln!(writer, "# route-count")?;
-        writeln!(writer, "{}", found_route_pairs.len())?;
-        writeln!(writer, "")?;
-
-        // write routes
-
-        writeln!(
-            writer,
-            "# random routes: (src-id dst-id count) as (i64, i64, usize)"
-        )?;
-        writeln!(writer, "# seed: {}", self.seed)?;
-        for (src_id, dst_id) in found_route_pairs {
-            writeln!(writer, "{} {} {}", src_id, dst_id, 1)?;
-        }
-
-        Ok(())
+        let entries: Vec<_> = found_route_pairs
+            .into_iter()
+            .map(|(src, dst)| RouteEntry::new(RoutePair { src, dst }, 1))
+            .collect();
+        super::Writer::write_entries_with_description(
+            graph,
+            &entries,
+            &writing_cfg.file,
+            "random routes",
+            Some(self.seed),
+        )
     }
 }
@@ -0,0 +1,84 @@
+use crate::{
+    configs,
+    helpers::err,
+    network::{Graph, NodeIdx, RouteEntry, RoutePair, StronglyConnectedComponents},
+};
+use rand::{
+    distributions::{Distribution, Uniform, WeightedIndex},
+    SeedableRng,
+};
+use std::collections::BTreeMap;
+
+/// Samples nodes (uniformly or proportional to node-weights) and checks whether routes between them exist.
+pub struct Sampler {
+    components: StronglyConnectedComponents,
+    die: NodeDie,
+    pub rng: rand_pcg::Pcg32,
+}
+
+enum NodeDie {
+    Uniform(Uniform<usize>),
+    Weighted(WeightedIndex<f64>),
+}
+
+impl Sampler {
+    pub fn new(graph: &Graph, seed: u64, node_weights: Option<Vec<f64>>) -> err::Result<Sampler> {
+        let die = match node_weights {
+            Some(node_weights) => match WeightedIndex::new(node_weights) {
+                Ok(die) => NodeDie::Weighted(die),
+                Err(e) => {
+                    return Err(format!(
+                        "The node-weights can't be used for sampling due to error: {}",
+                        e
+                    )
+                    .into())
+                }
+            },
+            None => {
+                if graph.nodes().count() == 0 {
+                    return Err("The graph should have nodes for sampling route-pairs.".into());
+                }
+                NodeDie::Uniform(Uniform::from(0..graph.nodes().count()))
+            }
+        };
+
+        Ok(Sampler {
+            components: StronglyConnectedComponents::new(graph),
+            die,
+            rng: rand_pcg::Pcg32::seed_from_u64(seed),
+        })
+    }
+
+    pub fn sample_node(&mut self) -> NodeIdx {
+        match &self.die {
+            NodeDie::Uniform(die) => NodeIdx(die.sample(&mut self.rng)),
+            NodeDie::Weighted(die) => NodeIdx(die.sample(&mut self.rng)),
+        }
+    }
+
+    /// Returns true, if the nodes differ and a path between them exists.
+    pub fn is_routable(&self, src_idx: NodeIdx, dst_idx: NodeIdx) -> bool {
+        src_idx != dst_idx && self.components.is_reachable(src_idx, dst_idx)
+    }
+}
+
+/// Writes the route-pairs (with their counts) into the writing-config's file.
+pub fn write(
+    graph: &Graph,
+    writing_cfg: &configs::writing::routing::Config,
+    description: &str,
+    seed: u64,
+    route_pairs: &BTreeMap<(i64, i64), usize>,
+) -> err::Feedback {
+    let entries: Vec<_> = route_pairs
+        .iter()
+        .map(|(&(src, dst), &count)| RouteEntry::new(RoutePair { src, dst }, count))
+        .collect();
+    super::Writer::write_entries_with_description(
+        graph,
+        &entries,
+        &writing_cfg.file,
+        description,
+        Some(seed),
+    )
+}
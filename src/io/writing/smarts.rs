@@ -1,23 +1,72 @@
 use crate::{
-    configs::writing::smarts::Config as WritingConfig, defaults, helpers::err,
-    io::SupportingFileExts, network::Graph, routing::paths::Path,
+    configs::{
+        self,
+        writing::smarts::{Config as WritingConfig, Departures, Share},
+    },
+    defaults,
+    helpers::err,
+    io::{csv, SupportingFileExts},
+    network::Graph,
+    routing::paths::Path,
+};
+use kissunits::time::Seconds;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng, SeedableRng,
 };
 use std::{
     fs::OpenOptions,
-    io::{BufWriter, Write},
+    io::{BufReader, BufWriter, Write},
+    path,
 };
 
 pub struct Writer;
 
 impl Writer {
     /// Route-File-Format from [SMARTS-homepage](https://projects.eng.unimelb.edu.au/smarts/documentation/)
+    ///
+    /// Every path refers to a vehicle, starting at the given departure (in seconds).
+    /// Vehicles without departure start at a departure drawn from the configured distribution,
+    /// or at the default start-time, if no distribution is configured.
+    /// Vehicle-types and driver-profiles are drawn by their shares.
     pub fn write(
-        chosen_paths: &[Path],
+        chosen_paths: &[(Path, Option<Seconds>)],
         graph: &Graph,
         writing_cfg: &WritingConfig,
     ) -> err::Feedback {
         // prepare
 
+        let vehicles = &writing_cfg.vehicles;
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(writing_cfg.seed);
+        let types = weighted_index(&vehicles.types)?;
+        let driver_profiles = weighted_index(&vehicles.driver_profiles)?;
+        let departures = match &vehicles.departures {
+            Some(Departures::Empirical { file }) => {
+                let bins = parse_bins(file)?;
+                let weights = match WeightedIndex::new(bins.iter().map(|&(_, _, weight)| weight)) {
+                    Ok(weights) => weights,
+                    Err(e) => {
+                        return Err(format!(
+                            "The weights of the departure-bins in {} are invalid due to error: {}",
+                            file.display(),
+                            e
+                        )
+                        .into())
+                    }
+                };
+                Some(DepartureSampler::Empirical { bins, weights })
+            }
+            Some(Departures::Uniform { from, to }) => Some(DepartureSampler::Uniform {
+                from: *from,
+                to: *to,
+            }),
+            Some(Departures::Normal { mean, std_dev }) => Some(DepartureSampler::Normal {
+                mean: *mean,
+                std_dev: *std_dev,
+            }),
+            None => None,
+        };
+
         let output_file = OpenOptions::new()
             .write(true)
             .create_new(true)
@@ -37,17 +86,21 @@ impl Writer {
         )?;
         writeln!(writer, "<data>")?;
 
-        for path in chosen_paths.iter() {
+        for (path, departure) in chosen_paths.iter() {
             let id = format!("{}->{}", nodes.id(path.src_idx()), nodes.id(path.dst_idx()));
 
             // every path refers to a vehicle
+            let vehicle_type = &vehicles.types[types.sample(&mut rng)].id;
+            let driver_profile = &vehicles.driver_profiles[driver_profiles.sample(&mut rng)].id;
+            let start_time = match (departure, &departures) {
+                (Some(departure), _) => departure.0.to_string(),
+                (None, Some(departures)) => departures.sample(&mut rng).0.to_string(),
+                (None, None) => String::from(defaults::smarts::route_file_format::START_TIME),
+            };
             writeln!(
                 writer,
                 "<vehicle id=\"{}\" type=\"{}\" start_time=\"{}\" driverProfile=\"{}\">",
-                id,
-                defaults::smarts::route_file_format::VEHICLE_TYPE,
-                defaults::smarts::route_file_format::START_TIME,
-                defaults::smarts::route_file_format::DRIVER_PROFILE
+                id, vehicle_type, start_time, driver_profile
             )?;
 
             let mut is_first = true;
@@ -67,11 +120,138 @@ impl Writer {
         }
 
         writeln!(writer, "</data>")?;
+        writer.flush()?;
 
         Ok(())
     }
 }
 
+fn weighted_index(shares: &[Share]) -> err::Result<WeightedIndex<f64>> {
+    match WeightedIndex::new(shares.iter().map(|share| share.share)) {
+        Ok(weighted_index) => Ok(weighted_index),
+        Err(e) => Err(format!("The SMARTS-shares are invalid due to error: {}", e).into()),
+    }
+}
+
+/// Reads the bins of empirical departures from a csv-file,
+/// whose header-line names the columns `from`, `to` and `weight`.
+fn parse_bins(file: &path::Path) -> err::Result<Vec<(f64, f64, f64)>> {
+    let input_file = match OpenOptions::new().read(true).open(file) {
+        Ok(input_file) => input_file,
+        Err(e) => {
+            return Err(format!("Couldn't open {} due to error: {}", file.display(), e).into())
+        }
+    };
+    let mut records = csv::Records::new(
+        BufReader::new(input_file),
+        file,
+        Some(configs::csv::Config::default()),
+    );
+
+    let header = match records.next().transpose()? {
+        Some(header) => header,
+        None => return Err(format!("{} should contain departure-bins.", file.display()).into()),
+    };
+    let col_idxs = ["from", "to", "weight"]
+        .iter()
+        .map(|name| {
+            header
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "The column {} is not in the header-line of {}.",
+                        name,
+                        file.display()
+                    ))
+                })
+        })
+        .collect::<err::Result<Vec<usize>>>()?;
+
+    let mut bins = Vec::new();
+    for record in records {
+        let record = record?;
+        let values = col_idxs
+            .iter()
+            .map(|&col_idx| record.get(col_idx)?.trim().parse::<f64>().ok())
+            .collect::<Option<Vec<f64>>>();
+        match values.as_deref() {
+            Some(&[from, to, weight])
+                if from.is_finite()
+                    && to.is_finite()
+                    && 0.0 <= from
+                    && from <= to
+                    && weight.is_finite()
+                    && weight >= 0.0 =>
+            {
+                bins.push((from, to, weight))
+            }
+            _ => {
+                return Err(format!(
+                    "The record {:?} of {} should be a bin with 0 <= from <= to and non-negative weight.",
+                    record,
+                    file.display()
+                )
+                .into())
+            }
+        }
+    }
+
+    if bins.is_empty() {
+        return Err(format!("{} should contain departure-bins.", file.display()).into());
+    }
+    Ok(bins)
+}
+
+enum DepartureSampler {
+    Uniform {
+        from: Seconds,
+        to: Seconds,
+    },
+    Normal {
+        mean: Seconds,
+        std_dev: Seconds,
+    },
+    Empirical {
+        bins: Vec<(f64, f64, f64)>,
+        weights: WeightedIndex<f64>,
+    },
+}
+
+impl DepartureSampler {
+    fn sample<R: Rng>(&self, rng: &mut R) -> Seconds {
+        match self {
+            DepartureSampler::Uniform { from, to } => Seconds(uniform(from.0, to.0, rng)),
+            DepartureSampler::Normal { mean, std_dev } => {
+                // Negative departures are resampled, truncating the distribution at 0.
+                // This terminates quickly, because the mean is non-negative.
+                loop {
+                    // Box-Muller-transform, where 1 - u lies in (0, 1]
+                    let u: f64 = rng.gen();
+                    let v: f64 = rng.gen();
+                    let z = (-2.0 * (1.0 - u).ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+                    let departure = mean.0 + std_dev.0 * z;
+                    if departure >= 0.0 {
+                        break Seconds(departure);
+                    }
+                }
+            }
+            DepartureSampler::Empirical { bins, weights } => {
+                let (from, to, _) = bins[weights.sample(rng)];
+                Seconds(uniform(from, to, rng))
+            }
+        }
+    }
+}
+
+fn uniform<R: Rng>(from: f64, to: f64, rng: &mut R) -> f64 {
+    if from < to {
+        rng.gen_range(from, to)
+    } else {
+        from
+    }
+}
+
 impl SupportingFileExts for Writer {
     fn supported_exts<'a>() -> &'a [&'a str] {
         &["csv"]
@@ -0,0 +1,227 @@
+use crate::{
+    configs::{
+        parsing::edges::metrics::{Dimension, UnitInfo},
+        writing::sumo::Config as WritingConfig,
+    },
+    defaults::sumo as format,
+    helpers::err,
+    io::SupportingFileExts,
+    network::{EdgeIdx, Graph, MetricIdx},
+    routing::paths::Path,
+};
+use kissunits::time::Seconds;
+use log::info;
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path,
+};
+
+/// Writes [plain-xml-networks](https://sumo.dlr.de/docs/Networks/PlainXML.html) and
+/// [routes](https://sumo.dlr.de/docs/Definition_of_Vehicles%2C_Vehicle_Types%2C_and_Routes.html) for SUMO.
+///
+/// Nodes are written with their coordinates as (x, y) = (lon, lat),
+/// hence `netconvert` has to project them, e.g. via
+/// `netconvert --node-files graph.nod.xml --edge-files graph.edg.xml --proj.utm --output-file graph.net.xml`.
+/// Edges are identified by their edge-ids (or edge-indices, if the graph has no edge-ids),
+/// so routes and networks have to be written from the same graph.
+pub struct Writer;
+
+impl Writer {
+    /// Shortcuts are not written, and normalized metrics are denormalized.
+    pub fn write_network(graph: &Graph, writing_cfg: &WritingConfig) -> err::Feedback {
+        info!(
+            "START Write the graph as SUMO-network {} and {}",
+            writing_cfg.nodes_file.display(),
+            writing_cfg.edges_file.display()
+        );
+
+        // get metrics
+
+        let metrics_cfg = &graph.cfg().edges.metrics;
+        let distance_idx = metrics_cfg.try_idx_of(&writing_cfg.metrics.distance)?;
+        let distance_unit = &metrics_cfg.units[*distance_idx];
+        if distance_unit.dimension() != Dimension::LENGTH {
+            return Err(format!(
+                "The metric {} should be a distance, but is given in {:?}.",
+                writing_cfg.metrics.distance, distance_unit
+            )
+            .into());
+        }
+        let kmph_idx = metrics_cfg.try_idx_of(&writing_cfg.metrics.kmph)?;
+        let speed_unit = &metrics_cfg.units[*kmph_idx];
+        if speed_unit.dimension() != Dimension::SPEED {
+            return Err(format!(
+                "The metric {} should be a speed, but is given in {:?}.",
+                writing_cfg.metrics.kmph, speed_unit
+            )
+            .into());
+        }
+        let lane_count_idx = match &writing_cfg.metrics.lane_count {
+            Some(lane_count) => {
+                let lane_count_idx = metrics_cfg.try_idx_of(lane_count)?;
+                if metrics_cfg.units[*lane_count_idx] != UnitInfo::LaneCount {
+                    return Err(format!("The metric {} should be a lane-count.", lane_count).into());
+                }
+                Some(lane_count_idx)
+            }
+            None => None,
+        };
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let metrics = graph.metrics();
+        let value = |edge_idx: EdgeIdx, metric_idx: MetricIdx| {
+            metrics.get_value(edge_idx, metric_idx) * metrics.mean(metric_idx).unwrap_or(1.0)
+        };
+
+        // write nodes
+
+        let mut writer = create(&writing_cfg.nodes_file)?;
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(writer, "<nodes>")?;
+        for node_idx in nodes.iter() {
+            let coord = nodes.coord(node_idx);
+            writeln!(
+                writer,
+                "    <node id=\"{}\" x=\"{}\" y=\"{}\"/>",
+                nodes.id(node_idx),
+                coord.lon,
+                coord.lat
+            )?;
+        }
+        writeln!(writer, "</nodes>")?;
+        writer.flush()?;
+
+        // write edges
+
+        let mut writer = create(&writing_cfg.edges_file)?;
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(writer, "<edges>")?;
+        for edge_idx in fwd_edges.iter() {
+            if fwd_edges.is_shortcut(edge_idx) {
+                continue;
+            }
+
+            let meters =
+                distance_unit.try_convert(&UnitInfo::Meters, value(edge_idx, distance_idx))?;
+            // SUMO expects meters per second
+            let speed =
+                speed_unit.try_convert(&UnitInfo::MetersPerSecond, value(edge_idx, kmph_idx))?;
+            let lane_count = lane_count_idx.map_or(format::network::LANE_COUNT, |lane_count_idx| {
+                (value(edge_idx, lane_count_idx).round() as usize).max(1)
+            });
+
+            writeln!(
+                writer,
+                "    <edge id=\"{}\" from=\"{}\" to=\"{}\" numLanes=\"{}\" speed=\"{}\" length=\"{}\"/>",
+                edge_id(graph, edge_idx)?,
+                nodes.id(bwd_edges.dst_idx(edge_idx)),
+                nodes.id(fwd_edges.dst_idx(edge_idx)),
+                lane_count,
+                speed,
+                meters
+            )?;
+        }
+        writeln!(writer, "</edges>")?;
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+
+    /// Every path refers to a vehicle, starting at the given departure (in seconds).
+    ///
+    /// Vehicles without departure start at the default depart-time.
+    /// SUMO expects vehicles sorted by departure, hence they are sorted (stable) before writing.
+    pub fn write_routes(
+        chosen_paths: &[(Path, Option<Seconds>)],
+        graph: &Graph,
+        file: &path::Path,
+    ) -> err::Feedback {
+        info!(
+            "START Write {} vehicles as SUMO-routes {}",
+            chosen_paths.len(),
+            file.display()
+        );
+
+        let mut vehicles = chosen_paths
+            .iter()
+            .map(|(path, departure)| {
+                let path = path.clone().try_flatten(graph)?;
+                let edge_ids = path
+                    .iter()
+                    .map(|&edge_idx| edge_id(graph, edge_idx))
+                    .collect::<err::Result<Vec<String>>>()?;
+                Ok((
+                    departure.map_or(format::route_file_format::DEPART, |departure| departure.0),
+                    edge_ids,
+                ))
+            })
+            .collect::<err::Result<Vec<(f64, Vec<String>)>>>()?;
+        vehicles.sort_by(|(departure_a, _), (departure_b, _)| {
+            departure_a
+                .partial_cmp(departure_b)
+                .expect("Departures should be comparable.")
+        });
+
+        let mut writer = create(file)?;
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(writer, "<routes>")?;
+        writeln!(
+            writer,
+            "    <vType id=\"{}\" vClass=\"{}\"/>",
+            format::route_file_format::VEHICLE_TYPE,
+            format::route_file_format::VEHICLE_CLASS
+        )?;
+        for (vehicle_id, (departure, edge_ids)) in vehicles.iter().enumerate() {
+            // SUMO can't route vehicles without edges
+            if edge_ids.is_empty() {
+                continue;
+            }
+
+            writeln!(
+                writer,
+                "    <vehicle id=\"{}\" type=\"{}\" depart=\"{:.2}\">",
+                vehicle_id,
+                format::route_file_format::VEHICLE_TYPE,
+                departure
+            )?;
+            writeln!(writer, "        <route edges=\"{}\"/>", edge_ids.join(" "))?;
+            writeln!(writer, "    </vehicle>")?;
+        }
+        writeln!(writer, "</routes>")?;
+        writer.flush()?;
+
+        info!("FINISHED");
+        Ok(())
+    }
+}
+
+impl SupportingFileExts for Writer {
+    fn supported_exts<'a>() -> &'a [&'a str] {
+        &["xml"]
+    }
+}
+
+/// Routes refer to the edges of the written network by their ids,
+/// so edge-ids are required (edge-indices differ between graphs, e.g. with and without shortcuts).
+fn edge_id(graph: &Graph, edge_idx: EdgeIdx) -> err::Result<String> {
+    match graph.fwd_edges().try_id(edge_idx) {
+        Some(edge_id) => Ok(edge_id.to_string()),
+        None => Err(format!(
+            "The edge of edge-idx {} has no id, but SUMO-edges are written with their ids.",
+            *edge_idx
+        )
+        .into()),
+    }
+}
+
+fn create(file: &path::Path) -> err::Result<BufWriter<std::fs::File>> {
+    Writer::find_supported_ext(file)?;
+    match OpenOptions::new().write(true).create_new(true).open(file) {
+        Ok(output_file) => Ok(BufWriter::new(output_file)),
+        Err(e) => Err(format!("Couldn't open {} due to error: {}", file.display(), e).into()),
+    }
+}
@@ -1,4 +1,6 @@
 pub mod approximating;
+#[cfg(feature = "gpl")]
+pub mod balancing;
 pub mod configs;
 pub mod defaults;
 pub mod helpers;
@@ -0,0 +1,144 @@
+use crate::network::{Graph, NodeIdx};
+use std::{cmp::min, collections::HashSet};
+
+/// The strongly connected components of a graph, computed once via Tarjan's algorithm (without recursion).
+///
+/// Components are numbered in reverse topological order,
+/// so every edge leads from a component to the same or a smaller component.
+/// Edges are considered independent of their metrics.
+pub struct StronglyConnectedComponents {
+    component_of: Vec<usize>,
+    /// The condensation's edges, leading from a component to smaller components (sorted).
+    successors: Vec<Vec<usize>>,
+    count: usize,
+}
+
+impl StronglyConnectedComponents {
+    pub fn new(graph: &Graph) -> StronglyConnectedComponents {
+        const UNVISITED: usize = usize::MAX;
+
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+
+        let mut next_index = 0;
+        let mut indices = vec![UNVISITED; nodes.count()];
+        let mut lowlinks = vec![UNVISITED; nodes.count()];
+        let mut is_on_stack = vec![false; nodes.count()];
+        let mut stack = Vec::new();
+        let mut component_of = vec![UNVISITED; nodes.count()];
+        let mut count = 0;
+
+        for root_idx in nodes.iter() {
+            if indices[*root_idx] != UNVISITED {
+                continue;
+            }
+
+            // The call-stack replaces recursion, remembering every node's remaining leaving edges.
+            indices[*root_idx] = next_index;
+            lowlinks[*root_idx] = next_index;
+            next_index += 1;
+            stack.push(root_idx);
+            is_on_stack[*root_idx] = true;
+            let mut call_stack = vec![(root_idx, fwd_edges.starting_from(root_idx))];
+
+            while let Some((node_idx, leaving_edges)) = call_stack.last_mut() {
+                let node_idx = *node_idx;
+                let next_dst_idx = leaving_edges
+                    .next()
+                    .map(|leaving_edge| leaving_edge.dst_idx());
+
+                if let Some(dst_idx) = next_dst_idx {
+                    if indices[*dst_idx] == UNVISITED {
+                        indices[*dst_idx] = next_index;
+                        lowlinks[*dst_idx] = next_index;
+                        next_index += 1;
+                        stack.push(dst_idx);
+                        is_on_stack[*dst_idx] = true;
+                        call_stack.push((dst_idx, fwd_edges.starting_from(dst_idx)));
+                    } else if is_on_stack[*dst_idx] {
+                        lowlinks[*node_idx] = min(lowlinks[*node_idx], indices[*dst_idx]);
+                    }
+                    continue;
+                }
+
+                // all leaving edges are processed
+                call_stack.pop();
+                if let Some((parent_idx, _leaving_edges)) = call_stack.last() {
+                    lowlinks[**parent_idx] = min(lowlinks[**parent_idx], lowlinks[*node_idx]);
+                }
+
+                // node is root of a component
+                if lowlinks[*node_idx] == indices[*node_idx] {
+                    while let Some(member_idx) = stack.pop() {
+                        is_on_stack[*member_idx] = false;
+                        component_of[*member_idx] = count;
+                        if member_idx == node_idx {
+                            break;
+                        }
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        // condense the graph
+        let mut successors = vec![Vec::new(); count];
+        for node_idx in nodes.iter() {
+            let component = component_of[*node_idx];
+            for leaving_edge in fwd_edges.starting_from(node_idx) {
+                let dst_component = component_of[*leaving_edge.dst_idx()];
+                if dst_component != component {
+                    successors[component].push(dst_component);
+                }
+            }
+        }
+        for component_successors in successors.iter_mut() {
+            component_successors.sort_unstable();
+            component_successors.dedup();
+        }
+
+        StronglyConnectedComponents {
+            component_of,
+            successors,
+            count,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn component_of(&self, idx: NodeIdx) -> usize {
+        self.component_of[*idx]
+    }
+
+    /// Returns true if a path from the src-node to the dst-node exists.
+    ///
+    /// Nodes of the same component reach each other, and due to the components' topological order,
+    /// no path leads to a bigger component.
+    /// Otherwise, the condensation is searched, skipping components smaller than the dst-component.
+    pub fn is_reachable(&self, src_idx: NodeIdx, dst_idx: NodeIdx) -> bool {
+        let src_component = self.component_of(src_idx);
+        let dst_component = self.component_of(dst_idx);
+
+        if src_component == dst_component {
+            return true;
+        } else if src_component < dst_component {
+            return false;
+        }
+
+        let mut is_visited = HashSet::new();
+        let mut stack = vec![src_component];
+        while let Some(component) = stack.pop() {
+            for &successor in &self.successors[component] {
+                if successor == dst_component {
+                    return true;
+                }
+                if successor > dst_component && is_visited.insert(successor) {
+                    stack.push(successor);
+                }
+            }
+        }
+        false
+    }
+}
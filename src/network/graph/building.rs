@@ -1,24 +1,103 @@
-use super::{EdgeIdx, Graph, NodeIdx};
+use super::{spilling::Runs, EdgeIdx, Graph, NodeIdx};
 use crate::{
     approximating::Approx,
-    configs::parsing::{self, generating},
+    configs::parsing::{self, generating, storage},
     defaults::{
         self,
         capacity::{self, DimVec},
         routing::IS_USING_CH_LEVEL_SPEEDUP,
     },
     helpers::{self, err, MemSize},
-    io,
 };
 use kissunits::geo::Coordinate;
 use log::{debug, info, trace};
 use progressing::{mapping::Bar as MappingBar, Baring};
+use rayon::prelude::*;
 use smallvec::smallvec;
 use std::{
-    cmp::{min, Reverse},
+    cmp::{max, min, Reverse},
     mem,
 };
 
+fn is_sorted_by_key<T, K, F>(slice: &[T], key: F) -> bool
+where
+    T: Sync,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    slice
+        .par_windows(2)
+        .all(|pair| key(&pair[0]) <= key(&pair[1]))
+}
+
+/// Chunk-size for splitting work onto all threads
+fn par_chunk_size(len: usize) -> usize {
+    max(1, len / rayon::current_num_threads())
+}
+
+/// Returns the offset-array (with `node_count + 1` entries) of the given edges, which are sorted by their src-idx.
+fn par_offsets<T, F>(sorted_edges: &[T], node_count: usize, src_idx_of: F) -> Vec<usize>
+where
+    T: Sync,
+    F: Fn(&T) -> NodeIdx + Sync,
+{
+    // count leaving edges per node
+    // -> leaving edges are consecutive due to sorting, so every chunk only remembers its runs
+    let chunk_runs: Vec<Vec<(NodeIdx, usize)>> = sorted_edges
+        .par_chunks(par_chunk_size(sorted_edges.len()))
+        .map(|chunk| {
+            let mut runs: Vec<(NodeIdx, usize)> = Vec::new();
+            for edge in chunk {
+                let src_idx = src_idx_of(edge);
+                match runs.last_mut() {
+                    Some((run_src_idx, count)) if *run_src_idx == src_idx => *count += 1,
+                    _ => runs.push((src_idx, 1)),
+                }
+            }
+            runs
+        })
+        .collect();
+    let mut degrees = vec![0; node_count];
+    for (src_idx, count) in chunk_runs.into_iter().flatten() {
+        degrees[*src_idx] += count;
+    }
+
+    par_prefix_sums(&degrees)
+}
+
+/// Returns the exclusive prefix-sums of the given values, followed by the total sum.
+fn par_prefix_sums(values: &[usize]) -> Vec<usize> {
+    let chunk_size = par_chunk_size(values.len());
+
+    // sum up chunks in parallel, then offset every chunk by its predecessors
+    let chunk_sums: Vec<usize> = values
+        .par_chunks(chunk_size)
+        .map(|chunk| chunk.iter().sum())
+        .collect();
+    let mut total_sum = 0;
+    let chunk_offsets: Vec<usize> = chunk_sums
+        .into_iter()
+        .map(|chunk_sum| {
+            let chunk_offset = total_sum;
+            total_sum += chunk_sum;
+            chunk_offset
+        })
+        .collect();
+
+    let mut prefix_sums = vec![total_sum; values.len() + 1];
+    prefix_sums[..values.len()]
+        .par_chunks_mut(chunk_size)
+        .zip(values.par_chunks(chunk_size))
+        .zip(chunk_offsets.into_par_iter())
+        .for_each(|((prefix_sums, values), mut sum)| {
+            for (prefix_sum, value) in prefix_sums.iter_mut().zip(values) {
+                *prefix_sum = sum;
+                sum += value;
+            }
+        });
+    prefix_sums
+}
+
 /// private stuff for graph-building
 impl Graph {
     fn new(cfg: parsing::Config) -> Graph {
@@ -26,6 +105,7 @@ impl Graph {
             cfg,
             // nodes
             node_ids: Vec::new(),
+            node_ids_to_idx_map: Vec::new(),
             // node-metrics
             node_coords: Vec::new(),
             node_ch_levels: Vec::new(),
@@ -42,15 +122,19 @@ impl Graph {
             // edge-ids
             edge_ids: Vec::new(),
             edge_ids_to_idx_map: Vec::new(),
+            way_ids_to_idx_map: Vec::new(),
             // shortcuts (contraction-hierarchies)
             sc_offsets: Vec::new(),
             sc_edges: Vec::new(),
+            is_ch_stale: false,
+            compact: None,
         }
     }
 
     /// Optimizes capacity of used data-structures.
-    fn shrink_to_fit(&mut self) {
+    pub(super) fn shrink_to_fit(&mut self) {
         self.node_ids.shrink_to_fit();
+        self.node_ids_to_idx_map.shrink_to_fit();
         self.node_coords.shrink_to_fit();
         self.fwd_dsts.shrink_to_fit();
         self.fwd_offsets.shrink_to_fit();
@@ -61,12 +145,13 @@ impl Graph {
         self.metrics.shrink_to_fit();
         self.edge_ids.shrink_to_fit();
         self.edge_ids_to_idx_map.shrink_to_fit();
+        self.way_ids_to_idx_map.shrink_to_fit();
         self.sc_offsets.shrink_to_fit();
         self.sc_edges.shrink_to_fit();
     }
 
     /// The provided edge is interpreted as forward-edge.
-    fn add_metrics(&mut self, proto_edge: &mut ProtoEdgeB) -> err::Feedback {
+    fn correct_metrics(&self, proto_edge: &mut ProtoEdgeB) {
         let cfg = &self.cfg;
 
         for metric_idx in 0..proto_edge.metrics.len() {
@@ -81,10 +166,6 @@ impl Graph {
                 proto_edge.metrics[metric_idx] = defaults::accuracy::F64_ABS;
             }
         }
-
-        self.metrics.push(proto_edge.metrics.clone());
-
-        Ok(())
     }
 }
 
@@ -115,6 +196,8 @@ impl ProtoShortcut {
 #[derive(Debug)]
 pub struct ProtoEdge {
     pub id: Option<usize>,
+    /// The osm-way this edge belongs to (if known), needed for updating the graph later.
+    pub way_id: Option<i64>,
     pub src_id: i64,
     pub dst_id: i64,
     pub metrics: DimVec<f64>,
@@ -137,6 +220,8 @@ impl MemSize for ProtoEdge {
     fn mem_size_b() -> usize {
         // id: usize
         mem::size_of::<Option<usize>>()
+        // way_id: i64
+        + mem::size_of::<Option<i64>>()
         // src_id: i64
         // dst_id: i64
         + 2 * mem::size_of::<i64>()
@@ -148,6 +233,7 @@ impl MemSize for ProtoEdge {
 struct ProtoEdgeA {
     pub idx: usize,
     pub id: Option<usize>,
+    pub way_id: Option<i64>,
     pub src_id: i64,
     pub dst_id: i64,
     pub metrics: DimVec<f64>,
@@ -157,6 +243,7 @@ struct ProtoEdgeA {
 struct ProtoEdgeB {
     pub idx: usize,
     pub id: Option<usize>,
+    pub way_id: Option<i64>,
     pub src_idx: NodeIdx,
     pub dst_idx: NodeIdx,
     pub metrics: DimVec<f64>,
@@ -169,6 +256,8 @@ impl MemSize for ProtoEdgeB {
         mem::size_of::<usize>()
         // id: usize
         +mem::size_of::<Option<usize>>()
+        // way_id: i64
+        + mem::size_of::<Option<i64>>()
         // src_idx
         // dst_idx
         + 2 * mem::size_of::<usize>()
@@ -191,8 +280,12 @@ struct ProtoEdgeC {
 pub struct EdgeBuilder {
     cfg: parsing::Config,
     node_ids: Vec<i64>,
+    // length of node-ids after last sorting, only needed when streaming
+    sorted_node_count: usize,
     proto_edges: Vec<ProtoEdgeA>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    // only used when streaming
+    runs: Option<Runs>,
 }
 
 impl EdgeBuilder {
@@ -240,12 +333,34 @@ impl EdgeBuilder {
         }
 
         // add edges
+        if let Some(runs) = &mut self.runs {
+            if sc_edges.is_some() {
+                return Err(err::Msg::from(
+                    "Shortcuts are not supported when streaming proto-edges.",
+                ));
+            }
+
+            // Node-ids are needed for all edges, so remove duplicates from time to time.
+            if self.node_ids.len()
+                > 2 * self
+                    .sorted_node_count
+                    .max(defaults::parsing::streaming::MIN_NODE_IDS_FOR_DEDUP)
+            {
+                self.node_ids.sort_unstable();
+                self.node_ids.dedup();
+                self.sorted_node_count = self.node_ids.len();
+            }
+
+            return runs.push(proto_edge);
+        }
+
         let idx = self.proto_edges.len();
         if let Some(sc_edges) = sc_edges {
             // save index to shortcut in proto-edge
             self.proto_edges.push(ProtoEdgeA {
                 idx,
                 id: proto_edge.id,
+                way_id: proto_edge.way_id,
                 src_id: proto_edge.src_id,
                 dst_id: proto_edge.dst_id,
                 metrics: proto_edge.metrics,
@@ -256,6 +371,7 @@ impl EdgeBuilder {
             self.proto_edges.push(ProtoEdgeA {
                 idx,
                 id: proto_edge.id,
+                way_id: proto_edge.way_id,
                 src_id: proto_edge.src_id,
                 dst_id: proto_edge.dst_id,
                 metrics: proto_edge.metrics,
@@ -281,22 +397,62 @@ impl EdgeBuilder {
         node_ch_levels.shrink_to_fit();
         NodeBuilder {
             cfg: self.cfg,
+            needed_nodes: NeededNodes::new(&self.node_ids),
             node_ids: self.node_ids,
             node_coords,
             node_ch_levels,
             proto_edges: self.proto_edges,
             proto_shortcuts: self.proto_shortcuts,
+            runs: self.runs,
         }
     }
 }
 
+/// Bitset of hashed node-ids, marking the nodes needed by edges.
+///
+/// Node-ids are too sparse for indexing a bitset directly, so they are hashed (Fibonacci-hashing).
+/// Hence, there are no false negatives, but set bits have to be confirmed.
+struct NeededNodes {
+    bits: Vec<u64>,
+    shift: u32,
+}
+
+impl NeededNodes {
+    fn new(node_ids: &[i64]) -> NeededNodes {
+        let bit_count = max(
+            64,
+            (defaults::parsing::BITS_PER_NEEDED_NODE * node_ids.len()).next_power_of_two(),
+        );
+        let mut needed_nodes = NeededNodes {
+            bits: vec![0; bit_count / 64],
+            shift: 64 - bit_count.trailing_zeros(),
+        };
+        for &node_id in node_ids {
+            let bit = needed_nodes.bit_of(node_id);
+            needed_nodes.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        needed_nodes
+    }
+
+    fn bit_of(&self, node_id: i64) -> usize {
+        ((node_id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> self.shift) as usize
+    }
+
+    fn may_contain(&self, node_id: i64) -> bool {
+        let bit = self.bit_of(node_id);
+        (self.bits[bit / 64] >> (bit % 64)) & 1 == 1
+    }
+}
+
 pub struct NodeBuilder {
     cfg: parsing::Config,
     node_ids: Vec<i64>,
+    needed_nodes: NeededNodes,
     node_coords: Vec<Option<Coordinate>>,
     node_ch_levels: Vec<usize>,
     proto_edges: Vec<ProtoEdgeA>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    runs: Option<Runs>,
 }
 
 impl NodeBuilder {
@@ -304,8 +460,17 @@ impl NodeBuilder {
         &self.cfg
     }
 
+    /// Returns true, if the node is part of an edge.
+    /// Most other nodes are rejected in O(1), so parsers can skip them before creating their proto-nodes.
+    pub fn is_needed(&self, node_id: i64) -> bool {
+        self.needed_nodes.may_contain(node_id) && self.node_ids.binary_search(&node_id).is_ok()
+    }
+
     /// Returns true if node is part of edge and hence has been added.
     pub fn insert(&mut self, proto_node: ProtoNode) -> bool {
+        if !self.needed_nodes.may_contain(proto_node.id) {
+            return false;
+        }
         if let Ok(idx) = self.node_ids.binary_search(&proto_node.id) {
             self.node_coords[idx] = Some(proto_node.coord);
             if let Some(ch_level) = proto_node.ch_level {
@@ -325,6 +490,7 @@ impl NodeBuilder {
             node_ch_levels: self.node_ch_levels,
             proto_edges: self.proto_edges,
             proto_shortcuts: self.proto_shortcuts,
+            runs: self.runs,
         })
     }
 }
@@ -336,15 +502,21 @@ pub struct GraphBuilder {
     node_ch_levels: Vec<usize>,
     proto_edges: Vec<ProtoEdgeA>,
     proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    runs: Option<Runs>,
 }
 
 impl GraphBuilder {
     pub fn new(cfg: parsing::Config) -> EdgeBuilder {
+        let runs = cfg.streaming.as_ref().map(|streaming_cfg| {
+            Runs::new(&streaming_cfg.tmp_dir, streaming_cfg.max_edges_per_run)
+        });
         EdgeBuilder {
             cfg,
             node_ids: Vec::new(),
+            sorted_node_count: 0,
             proto_edges: Vec::new(),
             proto_shortcuts: Vec::new(),
+            runs,
         }
     }
 
@@ -355,7 +527,7 @@ impl GraphBuilder {
         info!(
             "START Finalize graph with {} proto-nodes and {} proto-edges.",
             self.node_ids.len(),
-            self.proto_edges.len()
+            self.proto_edges.len() + self.runs.as_ref().map_or(0, |runs| runs.edge_count())
         );
         let mut graph = Graph::new(self.cfg);
 
@@ -382,392 +554,23 @@ impl GraphBuilder {
         }
 
         //----------------------------------------------------------------------------------------//
-        // replace edges' node-ids by node-indizes for better performance
+        // build forward-edges with their metrics, ids and shortcuts
 
-        info!("DO Replace edges' node-ids by node-indizes.");
-        let mut proto_edges = {
-            let nodes = graph.nodes();
-
-            let mut new_proto_edges = vec![];
-
-            let mut progress_bar = MappingBar::with_range(0, self.proto_edges.len()).timed();
-            info!("{}", progress_bar);
-
-            // Work off proto-edges in chunks to keep memory-usage lower.
-            let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoEdgeB::mem_size_b();
-            debug!("max-chunk-size: {}", max_chunk_size);
-
-            // sort reversed to make splice efficient
-            self.proto_edges.reverse();
-            while self.proto_edges.len() > 0 {
-                // Get chunk from proto-edges.
-                // Reverse chunk because proto-egdes is sorted reversed to make splice efficient.
-                let chunk: Vec<_> = self
-                    .proto_edges
-                    .splice(
-                        (self.proto_edges.len() - min(self.proto_edges.len(), max_chunk_size))..,
-                        vec![],
-                    )
-                    .rev()
-                    .collect();
-
-                // allocate new memory-needs
-                self.proto_edges.shrink_to_fit();
-                new_proto_edges.reserve_exact(chunk.len());
-                debug!("chunk-len: {}", chunk.len());
-
-                for edge in chunk.into_iter() {
-                    new_proto_edges.push(ProtoEdgeB {
-                        idx: edge.idx,
-                        id: edge.id,
-                        src_idx: nodes.idx_from(edge.src_id).expect(&format!(
-                            "The given src-id `{:?}` doesn't exist as node",
-                            edge.src_id
-                        )),
-                        dst_idx: nodes.idx_from(edge.dst_id).expect(&format!(
-                            "The given dst-id `{:?}` doesn't exist as node",
-                            edge.dst_id
-                        )),
-                        metrics: edge.metrics,
-                        sc_edges: edge.sc_edges,
-                    });
-
-                    // print progress
-                    progress_bar.add(1usize);
-                    if progress_bar.has_progressed_significantly() {
-                        progress_bar.remember_significant_progress();
-                        info!("{}", progress_bar);
-                    }
-                }
-            }
-            progress_bar.set(new_proto_edges.len());
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
-            }
-            // reduce and optimize memory-usage
-            new_proto_edges.shrink_to_fit();
-
-            new_proto_edges
-        };
-
-        //----------------------------------------------------------------------------------------//
-        // sort forward-edges by ascending src-id, then by ascending dst-id -> offset-array
-
-        info!("DO Sort proto-forward-edges by their src/dst-IDs.");
-        {
-            // - memory-peak is here when sorting
-            // - sort by src-id, then level of dst, then dst-id
-            //   -> branch prediction in dijkstra when breaking after level is reached
-            if !IS_USING_CH_LEVEL_SPEEDUP {
-                proto_edges.sort_by_key(|edge| (edge.src_idx, edge.dst_idx, edge.id));
-            } else {
-                let nodes = graph.nodes();
-                proto_edges.sort_by_key(|edge| {
-                    (
-                        edge.src_idx,
-                        Reverse(nodes.level(edge.dst_idx)),
-                        edge.dst_idx,
-                        edge.id,
-                    )
-                });
-            }
+        if let Some(runs) = self.runs.take() {
+            // Runs are sorted already, so they are merged into the forward-edges directly.
+            store_merged_edges(&mut graph, runs)?;
+        } else {
+            store_proto_edges(self.proto_edges, self.proto_shortcuts, &mut graph)?;
         }
 
-        //----------------------------------------------------------------------------------------//
-        // shortcuts: map usize to EdgeIdx
-        // This has to be done before removing duplicates, because the usize-values depend on len()
-
-        info!("DO Remap ch-shortcut-indices according to new sorted edges.");
-        {
-            // create mapping: old-idx -> new-idx
-            let mut new_indices: Vec<usize> = vec![0; proto_edges.len()];
-            proto_edges
+        if graph.metrics.par_iter().any(|metrics| {
+            metrics
                 .iter()
-                .enumerate()
-                .for_each(|(new_idx, edge)| new_indices[edge.idx] = new_idx);
-            // update shortcuts due to new sorted proto-edges
-            for edge in proto_edges.iter() {
-                if let Some(sc_idx) = edge.sc_edges {
-                    let shortcuts = &mut self.proto_shortcuts[sc_idx];
-                    shortcuts[0] = EdgeIdx(new_indices[*shortcuts[0]]);
-                    shortcuts[1] = EdgeIdx(new_indices[*shortcuts[1]]);
-                }
-            }
-        }
-
-        //----------------------------------------------------------------------------------------//
-        // remove duplicates
-        // This should be done before doing metric to save memory.
-
-        info!("DO Remove duplicated proto-edges and correct remaining ch-shortcuts");
-        // count shortcut-edges for later
-        let mut sc_count = 0;
-        {
-            // duplicate is e.g. the edge
-            // node-id 314074041 -> node-id 283494218
-            // which is part of two ways
-
-            let mut removed_indices = Vec::new();
-
-            let mut w = 1;
-            for r in 1..proto_edges.len() {
-                // compare edge[w-1] and edge[r]
-                let is_duplicate = {
-                    let e0 = &proto_edges[w - 1];
-                    let e1 = &proto_edges[r];
-                    let mut is_eq = true;
-
-                    // compare src-id and dst-id, then metrics approximately
-                    is_eq &= e0.id == e1.id;
-                    is_eq &= (e0.src_idx, e0.dst_idx) == (e1.src_idx, e1.dst_idx);
-                    if is_eq {
-                        for (e0_metric, e1_metric) in e0.metrics.iter().zip(e1.metrics.iter()) {
-                            if Approx(e0_metric) == Approx(e1_metric) {
-                                continue;
-                            }
-                            // values are different
-                            is_eq = false;
-                            break;
-                        }
-                    }
-
-                    is_eq
-                };
-
-                // if duplicate
-                // -> inc r
-                // -> remember index for updating shortcuts
-                if is_duplicate {
-                    // replace r by w-1
-                    removed_indices.push(r);
-                }
-                // if not a duplicate
-                // -> swap edge[w] and edge[r]
-                // -> inc w and inc r
-                else {
-                    proto_edges.swap(w, r);
-                    w += 1;
-                }
-            }
-
-            proto_edges.truncate(proto_edges.len() - removed_indices.len());
-
-            // correct remaining shortcuts
-            // -> decrement every index, that is at least as high as a removed-idx
-            // This works because the original list has been sorted, meaning duplicates are laying
-            // next to each other.
-            // Thus decrementing corrects every value.
-            for edge in proto_edges.iter() {
-                if let Some(sc_idx) = edge.sc_edges {
-                    let shortcuts = &mut self.proto_shortcuts[sc_idx];
-                    sc_count += 1;
-                    for removed_idx in removed_indices.iter().rev() {
-                        for shortcut in shortcuts.iter_mut().filter(|sc| ***sc >= *removed_idx) {
-                            **shortcut -= 1;
-                        }
-                    }
-                }
-            }
-            info!("Removed {} duplicates.", removed_indices.len());
-        }
-
-        //----------------------------------------------------------------------------------------//
-        // build metrics
-        // If metrics are built before indices and offsets are built, the total need of memory while
-        // building is reduced.
-
-        info!("START Store metrics.");
-        let mut new_sc_edges = Vec::with_capacity(sc_count);
-        let mut proto_edges = {
-            let mut new_proto_edges = vec![];
-
-            let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
-            let mut edge_idx: usize = 0;
-
-            // Work off proto-edges in chunks to keep memory-usage lower.
-            let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoShortcut::mem_size_b();
-            debug!("max-chunk-size: {}", max_chunk_size);
-            // init metrics
-            graph.metrics = Vec::new();
-            debug!(
-                "initial graph-metric-capacity: {}",
-                graph.metrics.capacity()
-            );
-
-            // sort reversed to make splice efficient
-            proto_edges.reverse();
-            while proto_edges.len() > 0 {
-                // Get chunk from proto-edges.
-                // Reverse chunk because proto-egdes is sorted reversed to make splice efficient.
-                let chunk: Vec<_> = proto_edges
-                    .splice(
-                        (proto_edges.len() - min(proto_edges.len(), max_chunk_size))..,
-                        vec![],
-                    )
-                    .rev()
-                    .collect();
-
-                // allocate new memory-needs
-                proto_edges.shrink_to_fit();
-                graph.metrics.reserve_exact(chunk.len());
-                new_proto_edges.reserve_exact(chunk.len());
-                debug!("chunk-len: {}", chunk.len());
-                debug!("graph-metric-capacity: {}", graph.metrics.capacity());
-
-                for mut edge in chunk.into_iter() {
-                    // add to graph and remember ids
-                    // -> nodes are needed to be finished here to map NodeId -> NodeIdx
-                    graph.add_metrics(&mut edge)?;
-                    new_proto_edges.push(ProtoEdgeC {
-                        src_idx: edge.src_idx,
-                        dst_idx: edge.dst_idx,
-                        idx: 0, // used later for offset-arrays
-                        id: edge.id,
-                    });
-
-                    // remember sc-edges for setting offsets later
-                    if let Some(sc_idx) = edge.sc_edges {
-                        new_sc_edges.push((edge_idx, self.proto_shortcuts[sc_idx]));
-                    }
-
-                    // print progress
-                    progress_bar.set(edge_idx);
-                    if progress_bar.has_progressed_significantly() {
-                        progress_bar.remember_significant_progress();
-                        info!("{}", progress_bar);
-                    }
-
-                    // update edge-idx
-                    edge_idx += 1;
-                }
-            }
-            progress_bar.set(edge_idx);
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
-            }
-            // reduce and optimize memory-usage
-            graph.shrink_to_fit();
-            new_proto_edges.shrink_to_fit();
-            // last node needs an upper bound as well for `leaving_edges(...)`
-
-            new_proto_edges
-        };
-
-        for metrics in &graph.metrics {
-            for metric in metrics {
-                if metric < &defaults::accuracy::F64_ABS {
-                    return Err(err::Msg::from(
-                        "A metric is smaller than accuracy allows it.",
-                    ));
-                }
-            }
-        }
-
-        //----------------------------------------------------------------------------------------//
-        // set ch-shortcut-offsets
-        // do it here to reduce total memory-needs by processing metrics first
-        //
-        // Why offsets for m edges? Assume one memory-unit to equal one usize.
-        //
-        // Storing all shortcuts (or None) in a vector of Option<[usize; usize]> results in
-        // a memory-consumption of `2*m`, even if the graph has no shortcuts at all.
-        //
-        // Storing all k shortcuts in a separate vector results in
-        // a memory-consumption `2*k + m`. This saves `m - 2*k` memory, which is especially low when
-        // the graph has no shortcuts at all (k=0). Besides that, the sc-edge-indices doesn't need
-        // being wrapped by Option.
-
-        info!("DO Create ch-shortcut-offsets-array");
-        {
-            graph.sc_offsets = vec![new_sc_edges.len(); proto_edges.len() + 1];
-            graph.sc_edges = Vec::with_capacity(sc_count);
-            let mut sc_offset = 0;
-            for edge_idx in 0..proto_edges.len() {
-                // Since sc-offsets have been initialized with the last offset,
-                // everything is already correct when this point is reached.
-                if sc_offset == new_sc_edges.len() {
-                    break;
-                }
-
-                let (sc_edge_idx, sc_edges) = new_sc_edges[sc_offset];
-
-                // update shortcut-offset
-                graph.sc_offsets[edge_idx] = sc_offset;
-
-                // if this was a shortcut-edge
-                // -> increase offset for next edges
-                if edge_idx == sc_edge_idx {
-                    graph.sc_edges.push(sc_edges);
-                    sc_offset += 1;
-                }
-            }
-        }
-
-        //----------------------------------------------------------------------------------------//
-        // build forward-offset-array and edges
-
-        // logging
-        info!("START Create the forward-offset-array and the forward-mapping.");
-        {
-            let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
-            // start looping
-            let mut src_idx = NodeIdx(0);
-            let mut offset = 0;
-            graph.fwd_offsets.push(offset);
-            // high-level-idea
-            // count offset for each proto_edge (sorted)
-            // and apply offset as far as src doesn't change
-            let mut edge_idx = 0;
-            for proto_edge in proto_edges.iter_mut() {
-                // Add edge-idx here to remember it for indirect mapping bwd->fwd.
-                // Update it at the end of the loop.
-                proto_edge.idx = edge_idx;
-
-                // do not swap src and dst since this is a forward-edge
-                let edge_src_idx = proto_edge.src_idx;
-                let edge_dst_idx = proto_edge.dst_idx;
-
-                // If coming edges have new src, then update offset of new src.
-                // Loop because of nodes with no leaving edges.
-                // Nodes of id y with no leaving edge must have the same offset as the node of id (y+1)
-                // to remember it.
-                while src_idx != edge_src_idx.into() {
-                    *src_idx += 1;
-                    graph.fwd_offsets.push(offset);
-                }
-                offset += 1;
-                graph.bwd_dsts.push(edge_src_idx);
-                graph.fwd_dsts.push(edge_dst_idx);
-                // mapping fwd to fwd is just the identity
-                graph.fwd_to_fwd_map.push(EdgeIdx(edge_idx));
-                // edge-ids
-                graph.edge_ids.push(proto_edge.id);
-                if let Some(id) = proto_edge.id {
-                    graph.edge_ids_to_idx_map.push((id, EdgeIdx(edge_idx)));
-                }
-
-                // print progress
-                progress_bar.set(edge_idx);
-                if progress_bar.has_progressed_significantly() {
-                    progress_bar.remember_significant_progress();
-                    info!("{}", progress_bar);
-                }
-
-                // update edge-idx
-                edge_idx += 1;
-            }
-            // last node needs an upper bound as well for `leaving_edges(...)`
-            graph.fwd_offsets.push(offset);
-            progress_bar.set(offset);
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
-            }
-            // reduce and optimize memory-usage
-            // already dropped via iterator: drop(self.proto_edges);
-            graph.shrink_to_fit();
+                .any(|metric| metric < &defaults::accuracy::F64_ABS)
+        }) {
+            return Err(err::Msg::from(
+                "A metric is smaller than accuracy allows it.",
+            ));
         }
 
         // cleanup and sort by edge-ids for finding the edge-idx with a given id
@@ -777,7 +580,7 @@ impl GraphBuilder {
             info!("DO Sort mapping from edge-ids to indices.");
             graph
                 .edge_ids_to_idx_map
-                .sort_unstable_by_key(|&(id, _idx)| id);
+                .par_sort_unstable_by_key(|&(id, _idx)| id);
             graph.edge_ids_to_idx_map.dedup_by_key(|&mut (id, _idx)| id);
             if graph.edge_ids_to_idx_map.len() != old_len {
                 return Err(err::Msg::from(
@@ -810,18 +613,26 @@ impl GraphBuilder {
         //----------------------------------------------------------------------------------------//
         // sort backward-edges by ascending dst-id, then by ascending src-id -> offset-array
 
-        info!("DO Sort proto-backward-edges by their dst/src-IDs.");
+        info!("DO Sort backward-edges by their dst/src-IDs.");
+        let mut bwd_to_fwd_map: Vec<EdgeIdx> = (0..graph.fwd_dsts.len())
+            .into_par_iter()
+            .map(EdgeIdx)
+            .collect();
         {
+            // Forward-edges are stored already, so only their indices are sorted.
+            let (fwd_dsts, bwd_dsts) = (&graph.fwd_dsts, &graph.bwd_dsts);
             if !IS_USING_CH_LEVEL_SPEEDUP {
-                proto_edges.sort_by_key(|edge| (edge.dst_idx, edge.src_idx));
+                bwd_to_fwd_map
+                    .par_sort_by_key(|&edge_idx| (fwd_dsts[*edge_idx], bwd_dsts[*edge_idx]));
             } else {
                 let nodes = graph.nodes();
-                proto_edges.sort_by_key(|edge| {
+                let edge_ids = &graph.edge_ids;
+                bwd_to_fwd_map.par_sort_by_key(|&edge_idx| {
                     (
-                        edge.dst_idx,
-                        Reverse(nodes.level(edge.src_idx)),
-                        edge.src_idx,
-                        edge.id,
+                        fwd_dsts[*edge_idx],
+                        Reverse(nodes.level(bwd_dsts[*edge_idx])),
+                        bwd_dsts[*edge_idx],
+                        edge_ids[*edge_idx],
                     )
                 });
             }
@@ -830,59 +641,20 @@ impl GraphBuilder {
         //----------------------------------------------------------------------------------------//
         // build backward-offset-array
 
-        info!("START Create the backward-offset-array.");
+        info!("DO Create the backward-offset-array.");
         {
-            let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
-            // start looping
-            let mut src_idx = NodeIdx(0);
-            let mut offset = 0;
-            graph.bwd_offsets.push(offset);
-            // high-level-idea
-            // count offset for each proto_edge (sorted) and apply offset as far as src doesn't change
-            for edge_idx in 0..proto_edges.len() {
-                let proto_edge = &mut proto_edges[edge_idx];
-
-                // swap src and dst since this is the backward-edge
-                let edge_src_idx = proto_edge.dst_idx;
-
-                // If coming edges have new src, then update offset of new src.
-                // Loop because of nodes with no leaving edges.
-                // Nodes of id y with no leaving edge must have the same offset as the node of id (y+1)
-                // to remember it.
-                while src_idx != edge_src_idx {
-                    *src_idx += 1;
-                    graph.bwd_offsets.push(offset);
-                }
-                offset += 1;
-                // For the backward-mapping, bwd-indices have been remembered above,
-                // but applied to forward-sorted-edges.
-                // Now, that's used to generate the mapping from backward to forward,
-                // which is needed for the offset-arrays.
-                graph.bwd_to_fwd_map.push(EdgeIdx(proto_edge.idx));
+            // swap src and dst since these are the backward-edges
+            let fwd_dsts = &graph.fwd_dsts;
+            graph.bwd_offsets = par_offsets(&bwd_to_fwd_map, graph.node_ids.len(), |&edge_idx| {
+                fwd_dsts[*edge_idx]
+            });
+            // The sorted indices are the mapping from backward to forward,
+            // which is needed for the offset-arrays.
+            graph.bwd_to_fwd_map = bwd_to_fwd_map;
 
-                // print progress
-                progress_bar.set(edge_idx);
-                if progress_bar.has_progressed_significantly() {
-                    progress_bar.remember_significant_progress();
-                    info!("{}", progress_bar);
-                }
-            }
-            // last node needs an upper bound as well for `leaving_edges(...)`
-            debug_assert_eq!(
-                offset,
-                proto_edges.len(),
-                "Last offset-value should be as big as the number of proto-edges."
-            );
-            graph.bwd_offsets.push(offset);
-            progress_bar.set(graph.fwd_dsts.len());
-            if progress_bar.has_progressed_significantly() {
-                progress_bar.remember_significant_progress();
-                info!("{}", progress_bar);
-            }
             // reduce and optimize memory-usage
             graph.shrink_to_fit();
         }
-
         //----------------------------------------------------------------------------------------//
         // generate new metrics
 
@@ -959,6 +731,14 @@ impl GraphBuilder {
                                 id: new_id,
                             },
                     }
+                    | generating::edges::Category::Formula {
+                        expression: _,
+                        result:
+                            generating::edges::metrics::Category {
+                                unit: _,
+                                id: new_id,
+                            },
+                    }
                     | generating::edges::Category::Custom {
                         unit: _,
                         id: new_id,
@@ -991,8 +771,10 @@ impl GraphBuilder {
                     generating::edges::Category::Merge {
                         from: _,
                         is_file_with_header: _,
-                        edge_id: _,
+                        key: _,
+                        policies: _,
                         edges_info: _,
+                        csv: _,
                     }
                     | generating::edges::Category::Convert { from: _, to: _ } => {
                         // do not check because it's in-place, so duplicates would be removed.
@@ -1141,29 +923,19 @@ impl GraphBuilder {
                             .edges
                             .categories
                             .push(parsing::edges::Category::Metric {
-                                unit: parsing::edges::metrics::UnitInfo::from(*unit),
+                                unit: *unit,
                                 id: id.clone(),
                             });
-                        graph.cfg.edges.metrics.units.push((*unit).into());
+                        graph.cfg.edges.metrics.units.push(*unit);
                         graph.cfg.edges.metrics.ids.push(id.clone());
                     }
                     generating::edges::Category::Haversine { unit, id } => {
                         // check unit
 
-                        if !match unit {
-                            generating::edges::metrics::UnitInfo::Meters => false,
-                            generating::edges::metrics::UnitInfo::Kilometers => true,
-                            generating::edges::metrics::UnitInfo::Seconds => false,
-                            generating::edges::metrics::UnitInfo::Minutes => false,
-                            generating::edges::metrics::UnitInfo::Hours => false,
-                            generating::edges::metrics::UnitInfo::KilometersPerHour => false,
-                            generating::edges::metrics::UnitInfo::LaneCount => false,
-                            generating::edges::metrics::UnitInfo::F64 => false,
-                        } {
+                        if unit.dimension() != parsing::edges::metrics::Dimension::LENGTH {
                             return Err(format!(
-                                "Haversine creates {:?}, but you may convert \
-                                the resulting value afterwards.",
-                                generating::edges::metrics::UnitInfo::Kilometers
+                                "Haversine creates a distance, hence can't be stored in {:?}.",
+                                unit
                             )
                             .into());
                         }
@@ -1200,10 +972,10 @@ impl GraphBuilder {
                             .edges
                             .categories
                             .push(parsing::edges::Category::Metric {
-                                unit: parsing::edges::metrics::UnitInfo::from(*unit),
+                                unit: *unit,
                                 id: id.clone(),
                             });
-                        graph.cfg.edges.metrics.units.push((*unit).into());
+                        graph.cfg.edges.metrics.units.push(*unit);
                         graph.cfg.edges.metrics.ids.push(id.clone());
                     }
                     generating::edges::Category::Copy { from, to } => {
@@ -1232,10 +1004,10 @@ impl GraphBuilder {
                             .edges
                             .categories
                             .push(parsing::edges::Category::Metric {
-                                unit: parsing::edges::metrics::UnitInfo::from(to.unit),
+                                unit: to.unit,
                                 id: to.id.clone(),
                             });
-                        graph.cfg.edges.metrics.units.push(to.unit.into());
+                        graph.cfg.edges.metrics.units.push(to.unit);
                         graph.cfg.edges.metrics.ids.push(to.id.clone());
                     }
                     generating::edges::Category::Convert { from, to } => {
@@ -1270,14 +1042,14 @@ impl GraphBuilder {
                                     id: old_id,
                                 } => {
                                     if old_id == &from.id {
-                                        *old_unit = to.unit.into();
+                                        *old_unit = to.unit;
                                         *old_id = to.id.clone();
                                     }
                                 }
                                 parsing::edges::Category::Meta { info: _, id: _ }
                                 | parsing::edges::Category::Ignored => (),
                             });
-                        graph.cfg.edges.metrics.units[*metric_idx] = to.unit.into();
+                        graph.cfg.edges.metrics.units[*metric_idx] = to.unit;
                         graph.cfg.edges.metrics.ids[*metric_idx] = to.id.clone();
                     }
                     generating::edges::Category::Calc { result, a, b } => {
@@ -1310,82 +1082,68 @@ impl GraphBuilder {
                             .edges
                             .categories
                             .push(parsing::edges::Category::Metric {
-                                unit: parsing::edges::metrics::UnitInfo::from(result.unit),
+                                unit: result.unit,
+                                id: result.id.clone(),
+                            });
+                        graph.cfg.edges.metrics.units.push(result.unit);
+                        graph.cfg.edges.metrics.ids.push(result.id.clone());
+                    }
+                    generating::edges::Category::Formula { result, expression } => {
+                        // check units before looping over all edges
+
+                        let bound_expression =
+                            expression.try_bind(&graph.cfg.edges.metrics, &result.unit)?;
+                        let factors: Vec<f64> = graph
+                            .cfg
+                            .edges
+                            .metrics
+                            .units
+                            .iter()
+                            .map(|unit| unit.factor())
+                            .collect();
+                        for edge_idx in 0..graph.metrics.len() {
+                            let new_raw_value =
+                                bound_expression.eval(&graph.metrics[edge_idx], &factors);
+
+                            // e.g. divisions by zero would break the ordering of costs
+                            if !new_raw_value.is_finite() {
+                                return Err(format!(
+                                    "The formula {} of {} results in {} for the edge with id {}.",
+                                    expression,
+                                    result.id,
+                                    new_raw_value,
+                                    graph.fwd_edges().id(EdgeIdx(edge_idx))
+                                )
+                                .into());
+                            }
+
+                            // update graph
+
+                            graph.metrics[edge_idx].push(new_raw_value);
+                        }
+
+                        // update config
+
+                        graph
+                            .cfg
+                            .edges
+                            .categories
+                            .push(parsing::edges::Category::Metric {
+                                unit: result.unit,
                                 id: result.id.clone(),
                             });
-                        graph.cfg.edges.metrics.units.push(result.unit.into());
+                        graph.cfg.edges.metrics.units.push(result.unit);
                         graph.cfg.edges.metrics.ids.push(result.id.clone());
                     }
                     generating::edges::Category::Merge {
                         from,
                         is_file_with_header,
-                        edge_id,
+                        key,
+                        policies,
                         edges_info,
+                        csv,
                     } => {
-                        // open file
-
-                        let reader =
-                            io::network::edges::Parser::new_reader(from, *is_file_with_header)?;
-
-                        // parse edge-id and metric
-
-                        for line in reader {
-                            let params: Vec<&str> = line.split_whitespace().collect();
-
-                            // get edge-idx
-
-                            let mut edge_idx = None;
-                            for (col_idx, category) in edges_info.iter().enumerate() {
-                                match category {
-                                    generating::edges::merge::Category::Id(id) => {
-                                        let param = params[col_idx];
-
-                                        if id == edge_id {
-                                            let raw_edge_id =
-                                                param.parse::<usize>().ok().ok_or(format!(
-                                                    "{}{}{}",
-                                                    "Parsing edge-id '",
-                                                    param,
-                                                    "' (should be usize) didn't work."
-                                                ))?;
-                                            edge_idx =
-                                                Some(graph.fwd_edges().try_idx_from(raw_edge_id)?);
-                                            break;
-                                        }
-                                    }
-                                    generating::edges::merge::Category::Ignored => continue,
-                                }
-                            }
-                            let edge_idx = edge_idx.ok_or(err::Msg::from(format!(
-                                "The expected edge-id {} is not in the edges-info-file.",
-                                edge_id,
-                            )))?;
-
-                            // update graph with data
-
-                            for (col_idx, category) in edges_info.iter().enumerate() {
-                                match category {
-                                    generating::edges::merge::Category::Id(id) => {
-                                        if id == edge_id {
-                                            continue;
-                                        }
-
-                                        let metric_idx = graph.cfg.edges.metrics.idx_of(id);
-
-                                        let param = params[col_idx];
-                                        if let Ok(raw_value) = param.parse::<f64>() {
-                                            graph.metrics[*edge_idx][*metric_idx] = raw_value;
-                                        } else {
-                                            return Err(err::Msg::from(format!(
-                                                "Parsing '{}' didn't work.",
-                                                param
-                                            )));
-                                        };
-                                    }
-                                    generating::edges::merge::Category::Ignored => continue,
-                                }
-                            }
-                        }
+                        graph.merge(from, *is_file_with_header, key, policies, edges_info, *csv)?;
 
                         // update config
                         // -> already up-to-date since just floats has been replaced
@@ -1454,7 +1212,558 @@ impl GraphBuilder {
             graph.means = Some(means);
         }
 
+        if graph.cfg.storage.layout == storage::Layout::Compact {
+            graph.compact()?;
+        }
+
         info!("FINISHED Finalizing graph has finished.");
         Ok(graph)
     }
 }
+
+/// Replaces the proto-edges' node-ids, sorts them and stores them (with their metrics, ids and shortcuts) as forward-edges.
+fn store_proto_edges(
+    mut old_proto_edges: Vec<ProtoEdgeA>,
+    mut proto_shortcuts: Vec<[EdgeIdx; 2]>,
+    graph: &mut Graph,
+) -> err::Feedback {
+    //----------------------------------------------------------------------------------------//
+    // replace edges' node-ids by node-indizes for better performance
+    let mut proto_edges = {
+        let nodes = graph.nodes();
+
+        let mut new_proto_edges = vec![];
+
+        let mut progress_bar = MappingBar::with_range(0, old_proto_edges.len()).timed();
+        info!("{}", progress_bar);
+
+        // Work off proto-edges in chunks to keep memory-usage lower.
+        let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoEdgeB::mem_size_b();
+        debug!("max-chunk-size: {}", max_chunk_size);
+
+        // sort reversed to make splice efficient
+        old_proto_edges.reverse();
+        while old_proto_edges.len() > 0 {
+            // Get chunk from proto-edges.
+            // Reverse chunk because proto-egdes is sorted reversed to make splice efficient.
+            let chunk: Vec<_> = old_proto_edges
+                .splice(
+                    (old_proto_edges.len() - min(old_proto_edges.len(), max_chunk_size))..,
+                    vec![],
+                )
+                .rev()
+                .collect();
+
+            // allocate new memory-needs
+            old_proto_edges.shrink_to_fit();
+            new_proto_edges.reserve_exact(chunk.len());
+            debug!("chunk-len: {}", chunk.len());
+
+            progress_bar.add(chunk.len());
+            new_proto_edges.par_extend(chunk.into_par_iter().map(|edge| ProtoEdgeB {
+                idx: edge.idx,
+                id: edge.id,
+                way_id: edge.way_id,
+                src_idx: nodes.idx_from(edge.src_id).unwrap_or_else(|_| {
+                    panic!("The given src-id `{:?}` doesn't exist as node", edge.src_id)
+                }),
+                dst_idx: nodes.idx_from(edge.dst_id).unwrap_or_else(|_| {
+                    panic!("The given dst-id `{:?}` doesn't exist as node", edge.dst_id)
+                }),
+                metrics: edge.metrics,
+                sc_edges: edge.sc_edges,
+            }));
+
+            // print progress
+            if progress_bar.has_progressed_significantly() {
+                progress_bar.remember_significant_progress();
+                info!("{}", progress_bar);
+            }
+        }
+        progress_bar.set(new_proto_edges.len());
+        if progress_bar.has_progressed_significantly() {
+            progress_bar.remember_significant_progress();
+            info!("{}", progress_bar);
+        }
+        // reduce and optimize memory-usage
+        new_proto_edges.shrink_to_fit();
+
+        new_proto_edges
+    };
+
+    //----------------------------------------------------------------------------------------//
+    // sort forward-edges by ascending src-id, then by ascending dst-id -> offset-array
+
+    info!("DO Sort proto-forward-edges by their src/dst-IDs.");
+    {
+        // - memory-peak is here when sorting
+        //   -> proto-edges of sorted map-files are sorted already, so sorting is skipped if possible
+        // - sort by src-id, then level of dst, then dst-id
+        //   -> branch prediction in dijkstra when breaking after level is reached
+        // - sorting in parallel is stable as well, keeping the order of equal edges
+        if !IS_USING_CH_LEVEL_SPEEDUP {
+            let key = |edge: &ProtoEdgeB| (edge.src_idx, edge.dst_idx, edge.id);
+            if !is_sorted_by_key(&proto_edges, key) {
+                proto_edges.par_sort_by_key(key);
+            }
+        } else {
+            let nodes = graph.nodes();
+            let key = |edge: &ProtoEdgeB| {
+                (
+                    edge.src_idx,
+                    Reverse(nodes.level(edge.dst_idx)),
+                    edge.dst_idx,
+                    edge.id,
+                )
+            };
+            if !is_sorted_by_key(&proto_edges, key) {
+                proto_edges.par_sort_by_key(key);
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------//
+    // shortcuts: map usize to EdgeIdx
+    // This has to be done before removing duplicates, because the usize-values depend on len()
+
+    info!("DO Remap ch-shortcut-indices according to new sorted edges.");
+    {
+        // create mapping: old-idx -> new-idx
+        let mut new_indices: Vec<usize> = vec![0; proto_edges.len()];
+        proto_edges
+            .iter()
+            .enumerate()
+            .for_each(|(new_idx, edge)| new_indices[edge.idx] = new_idx);
+        // update shortcuts due to new sorted proto-edges
+        for edge in proto_edges.iter() {
+            if let Some(sc_idx) = edge.sc_edges {
+                let shortcuts = &mut proto_shortcuts[sc_idx];
+                shortcuts[0] = EdgeIdx(new_indices[*shortcuts[0]]);
+                shortcuts[1] = EdgeIdx(new_indices[*shortcuts[1]]);
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------//
+    // remove duplicates
+    // This should be done before doing metric to save memory.
+
+    info!("DO Remove duplicated proto-edges and correct remaining ch-shortcuts");
+    // count shortcut-edges for later
+    let mut sc_count = 0;
+    {
+        // duplicate is e.g. the edge
+        // node-id 314074041 -> node-id 283494218
+        // which is part of two ways
+
+        let mut removed_indices = Vec::new();
+
+        // Remember the osm-ways of all edges, also of the removed duplicates,
+        // because a duplicate could be part of multiple ways.
+        // The edge-indices are final, since metrics are stored in this order.
+        if let Some(way_id) = proto_edges.first().and_then(|edge| edge.way_id) {
+            graph.way_ids_to_idx_map.push((way_id, EdgeIdx(0)));
+        }
+
+        let mut w = 1;
+        for r in 1..proto_edges.len() {
+            // compare edge[w-1] and edge[r]
+            let is_duplicate = {
+                let e0 = &proto_edges[w - 1];
+                let e1 = &proto_edges[r];
+                let mut is_eq = true;
+
+                // compare src-id and dst-id, then metrics approximately
+                is_eq &= e0.id == e1.id;
+                is_eq &= (e0.src_idx, e0.dst_idx) == (e1.src_idx, e1.dst_idx);
+                if is_eq {
+                    for (e0_metric, e1_metric) in e0.metrics.iter().zip(e1.metrics.iter()) {
+                        if Approx(e0_metric) == Approx(e1_metric) {
+                            continue;
+                        }
+                        // values are different
+                        is_eq = false;
+                        break;
+                    }
+                }
+
+                is_eq
+            };
+
+            // if duplicate
+            // -> inc r
+            // -> remember index for updating shortcuts
+            if is_duplicate {
+                // replace r by w-1
+                removed_indices.push(r);
+                if let Some(way_id) = proto_edges[r].way_id {
+                    graph.way_ids_to_idx_map.push((way_id, EdgeIdx(w - 1)));
+                }
+            }
+            // if not a duplicate
+            // -> swap edge[w] and edge[r]
+            // -> inc w and inc r
+            else {
+                proto_edges.swap(w, r);
+                if let Some(way_id) = proto_edges[w].way_id {
+                    graph.way_ids_to_idx_map.push((way_id, EdgeIdx(w)));
+                }
+                w += 1;
+            }
+        }
+
+        proto_edges.truncate(proto_edges.len() - removed_indices.len());
+        graph.way_ids_to_idx_map.sort_unstable();
+        graph.way_ids_to_idx_map.dedup();
+
+        // correct remaining shortcuts
+        // -> decrement every index, that is at least as high as a removed-idx
+        // This works because the original list has been sorted, meaning duplicates are laying
+        // next to each other.
+        // Thus decrementing corrects every value.
+        for edge in proto_edges.iter() {
+            if let Some(sc_idx) = edge.sc_edges {
+                let shortcuts = &mut proto_shortcuts[sc_idx];
+                sc_count += 1;
+                for removed_idx in removed_indices.iter().rev() {
+                    for shortcut in shortcuts.iter_mut().filter(|sc| ***sc >= *removed_idx) {
+                        **shortcut -= 1;
+                    }
+                }
+            }
+        }
+        info!("Removed {} duplicates.", removed_indices.len());
+    }
+
+    //----------------------------------------------------------------------------------------//
+    // build metrics
+    // If metrics are built before indices and offsets are built, the total need of memory while
+    // building is reduced.
+
+    info!("START Store metrics.");
+    let mut new_sc_edges = Vec::with_capacity(sc_count);
+    let mut proto_edges = {
+        let mut new_proto_edges = vec![];
+
+        let mut progress_bar = MappingBar::with_range(0, proto_edges.len()).timed();
+        let mut edge_idx: usize = 0;
+
+        // Work off proto-edges in chunks to keep memory-usage lower.
+        let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoShortcut::mem_size_b();
+        debug!("max-chunk-size: {}", max_chunk_size);
+        // init metrics
+        graph.metrics = Vec::new();
+        debug!(
+            "initial graph-metric-capacity: {}",
+            graph.metrics.capacity()
+        );
+
+        // sort reversed to make splice efficient
+        proto_edges.reverse();
+        while proto_edges.len() > 0 {
+            // Get chunk from proto-edges.
+            // Reverse chunk because proto-egdes is sorted reversed to make splice efficient.
+            let chunk: Vec<_> = proto_edges
+                .splice(
+                    (proto_edges.len() - min(proto_edges.len(), max_chunk_size))..,
+                    vec![],
+                )
+                .rev()
+                .collect();
+
+            // allocate new memory-needs
+            proto_edges.shrink_to_fit();
+            graph.metrics.reserve_exact(chunk.len());
+            new_proto_edges.reserve_exact(chunk.len());
+            debug!("chunk-len: {}", chunk.len());
+            debug!("graph-metric-capacity: {}", graph.metrics.capacity());
+
+            // correct metrics in parallel
+            // -> nodes are needed to be finished here to map NodeId -> NodeIdx
+            let mut chunk = chunk;
+            chunk
+                .par_iter_mut()
+                .for_each(|edge| graph.correct_metrics(edge));
+
+            for edge in chunk.into_iter() {
+                // add to graph and remember ids
+                graph.metrics.push(edge.metrics);
+                new_proto_edges.push(ProtoEdgeC {
+                    src_idx: edge.src_idx,
+                    dst_idx: edge.dst_idx,
+                    idx: 0, // used later for offset-arrays
+                    id: edge.id,
+                });
+
+                // remember sc-edges for setting offsets later
+                if let Some(sc_idx) = edge.sc_edges {
+                    new_sc_edges.push((edge_idx, proto_shortcuts[sc_idx]));
+                }
+
+                // print progress
+                progress_bar.set(edge_idx);
+                if progress_bar.has_progressed_significantly() {
+                    progress_bar.remember_significant_progress();
+                    info!("{}", progress_bar);
+                }
+
+                // update edge-idx
+                edge_idx += 1;
+            }
+        }
+        progress_bar.set(edge_idx);
+        if progress_bar.has_progressed_significantly() {
+            progress_bar.remember_significant_progress();
+            info!("{}", progress_bar);
+        }
+        // reduce and optimize memory-usage
+        graph.shrink_to_fit();
+        new_proto_edges.shrink_to_fit();
+        // last node needs an upper bound as well for `leaving_edges(...)`
+
+        new_proto_edges
+    };
+
+    //----------------------------------------------------------------------------------------//
+    // set ch-shortcut-offsets
+    // do it here to reduce total memory-needs by processing metrics first
+    //
+    // Why offsets for m edges? Assume one memory-unit to equal one usize.
+    //
+    // Storing all shortcuts (or None) in a vector of Option<[usize; usize]> results in
+    // a memory-consumption of `2*m`, even if the graph has no shortcuts at all.
+    //
+    // Storing all k shortcuts in a separate vector results in
+    // a memory-consumption `2*k + m`. This saves `m - 2*k` memory, which is especially low when
+    // the graph has no shortcuts at all (k=0). Besides that, the sc-edge-indices doesn't need
+    // being wrapped by Option.
+
+    info!("DO Create ch-shortcut-offsets-array");
+    {
+        graph.sc_offsets = vec![new_sc_edges.len(); proto_edges.len() + 1];
+        graph.sc_edges = Vec::with_capacity(sc_count);
+        let mut sc_offset = 0;
+        for edge_idx in 0..proto_edges.len() {
+            // Since sc-offsets have been initialized with the last offset,
+            // everything is already correct when this point is reached.
+            if sc_offset == new_sc_edges.len() {
+                break;
+            }
+
+            let (sc_edge_idx, sc_edges) = new_sc_edges[sc_offset];
+
+            // update shortcut-offset
+            graph.sc_offsets[edge_idx] = sc_offset;
+
+            // if this was a shortcut-edge
+            // -> increase offset for next edges
+            if edge_idx == sc_edge_idx {
+                graph.sc_edges.push(sc_edges);
+                sc_offset += 1;
+            }
+        }
+    }
+
+    //----------------------------------------------------------------------------------------//
+    // build forward-offset-array and edges
+
+    // logging
+    info!("DO Create the forward-offset-array and the forward-mapping.");
+    {
+        // Add edge-idx here to remember it for indirect mapping bwd->fwd.
+        proto_edges
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(edge_idx, proto_edge)| proto_edge.idx = edge_idx);
+
+        // Nodes with no leaving edges have the same offset as their successor.
+        graph.fwd_offsets = par_offsets(&proto_edges, graph.node_ids.len(), |proto_edge| {
+            proto_edge.src_idx
+        });
+        // do not swap src and dst since these are forward-edges
+        graph.bwd_dsts = proto_edges
+            .par_iter()
+            .map(|proto_edge| proto_edge.src_idx)
+            .collect();
+        graph.fwd_dsts = proto_edges
+            .par_iter()
+            .map(|proto_edge| proto_edge.dst_idx)
+            .collect();
+        // mapping fwd to fwd is just the identity
+        graph.fwd_to_fwd_map = (0..proto_edges.len())
+            .into_par_iter()
+            .map(EdgeIdx)
+            .collect();
+        // edge-ids
+        graph.edge_ids = proto_edges
+            .par_iter()
+            .map(|proto_edge| proto_edge.id)
+            .collect();
+        graph.edge_ids_to_idx_map = proto_edges
+            .par_iter()
+            .filter_map(|proto_edge| Some((proto_edge.id?, EdgeIdx(proto_edge.idx))))
+            .collect();
+
+        // reduce and optimize memory-usage
+        graph.shrink_to_fit();
+    }
+
+    Ok(())
+}
+
+/// Merges the sorted runs (k-way) and stores the proto-edges as forward-edges (with their metrics and ids) on the fly, without collecting all proto-edges.
+///
+/// Merged proto-edges are sorted by (src-id, dst-id, id), hence by src-idx as well.
+/// Only the leaving edges of one node are buffered, to sort them by their dsts' ch-levels and to remove duplicates, like it's done in memory.
+/// Runs don't contain shortcuts.
+fn store_merged_edges(graph: &mut Graph, runs: Runs) -> err::Feedback {
+    info!("DO Merge spilled runs of proto-edges into forward-edges.");
+    let mut merger = runs.merge()?;
+
+    let mut progress_bar = MappingBar::with_range(0, merger.edge_count()).timed();
+    info!("{}", progress_bar);
+
+    let mut degrees = vec![0; graph.node_ids.len()];
+    let mut removed_count = 0;
+    // leaving edges of the current src-node
+    let mut leaving_edges: Vec<ProtoEdgeB> = Vec::new();
+
+    // Merging is sequential, but node-ids are replaced in parallel chunks.
+    let max_chunk_size = capacity::MAX_BYTE_PER_CHUNK / ProtoEdgeB::mem_size_b();
+    debug!("max-chunk-size: {}", max_chunk_size);
+    let mut chunk = Vec::with_capacity(max_chunk_size);
+    loop {
+        let next_edge = merger.next()?;
+        let is_merged = next_edge.is_none();
+        if let Some(edge) = next_edge {
+            chunk.push(edge);
+        }
+        if chunk.len() < max_chunk_size && !is_merged {
+            continue;
+        }
+
+        progress_bar.add(chunk.len());
+        let new_edges: Vec<ProtoEdgeB> = {
+            let nodes = graph.nodes();
+            mem::take(&mut chunk)
+                .into_par_iter()
+                .map(|edge| ProtoEdgeB {
+                    idx: 0, // not needed, since there are no shortcuts
+                    id: edge.id,
+                    way_id: edge.way_id,
+                    src_idx: nodes.idx_from(edge.src_id).unwrap_or_else(|_| {
+                        panic!("The given src-id `{:?}` doesn't exist as node", edge.src_id)
+                    }),
+                    dst_idx: nodes.idx_from(edge.dst_id).unwrap_or_else(|_| {
+                        panic!("The given dst-id `{:?}` doesn't exist as node", edge.dst_id)
+                    }),
+                    metrics: edge.metrics,
+                    sc_edges: None,
+                })
+                .collect()
+        };
+        for edge in new_edges {
+            if leaving_edges
+                .last()
+                .is_some_and(|last| last.src_idx != edge.src_idx)
+            {
+                removed_count += store_leaving_edges(graph, &mut leaving_edges, &mut degrees);
+            }
+            leaving_edges.push(edge);
+        }
+
+        // print progress
+        if progress_bar.has_progressed_significantly() {
+            progress_bar.remember_significant_progress();
+            info!("{}", progress_bar);
+        }
+
+        if is_merged {
+            break;
+        }
+    }
+    removed_count += store_leaving_edges(graph, &mut leaving_edges, &mut degrees);
+    progress_bar.set(merger.edge_count());
+    if progress_bar.has_progressed_significantly() {
+        progress_bar.remember_significant_progress();
+        info!("{}", progress_bar);
+    }
+    info!("Removed {} duplicates.", removed_count);
+
+    // Nodes with no leaving edges have the same offset as their successor.
+    graph.fwd_offsets = par_prefix_sums(&degrees);
+    // mapping fwd to fwd is just the identity
+    graph.fwd_to_fwd_map = (0..graph.fwd_dsts.len())
+        .into_par_iter()
+        .map(EdgeIdx)
+        .collect();
+    graph.sc_offsets = vec![0; graph.fwd_dsts.len() + 1];
+    graph.way_ids_to_idx_map.sort_unstable();
+    graph.way_ids_to_idx_map.dedup();
+
+    // reduce and optimize memory-usage
+    graph.shrink_to_fit();
+
+    Ok(())
+}
+
+/// Sorts the given leaving edges of one node, removes duplicates and appends the remaining edges to the graph's forward-edges.
+///
+/// Returns the number of removed duplicates.
+fn store_leaving_edges(
+    graph: &mut Graph,
+    leaving_edges: &mut Vec<ProtoEdgeB>,
+    degrees: &mut [usize],
+) -> usize {
+    // sorting is stable, keeping the order of equal edges
+    if IS_USING_CH_LEVEL_SPEEDUP {
+        let nodes = graph.nodes();
+        leaving_edges
+            .sort_by_key(|edge| (Reverse(nodes.level(edge.dst_idx)), edge.dst_idx, edge.id));
+    }
+
+    let mut removed_count = 0;
+    let mut last: Option<ProtoEdgeB> = None;
+    for mut edge in leaving_edges.drain(..) {
+        // compare ids and dst, then metrics approximately (before correcting them)
+        let is_duplicate = last.as_ref().is_some_and(|last| {
+            last.id == edge.id
+                && last.dst_idx == edge.dst_idx
+                && last
+                    .metrics
+                    .iter()
+                    .zip(edge.metrics.iter())
+                    .all(|(a, b)| Approx(a) == Approx(b))
+        });
+        if is_duplicate {
+            removed_count += 1;
+            if let Some(way_id) = edge.way_id {
+                let edge_idx = EdgeIdx(graph.fwd_dsts.len() - 1);
+                graph.way_ids_to_idx_map.push((way_id, edge_idx));
+            }
+            continue;
+        }
+
+        let edge_idx = EdgeIdx(graph.fwd_dsts.len());
+        if let Some(way_id) = edge.way_id {
+            graph.way_ids_to_idx_map.push((way_id, edge_idx));
+        }
+        if let Some(id) = edge.id {
+            graph.edge_ids_to_idx_map.push((id, edge_idx));
+        }
+        graph.edge_ids.push(edge.id);
+        graph.fwd_dsts.push(edge.dst_idx);
+        // do not swap src and dst since these are forward-edges
+        graph.bwd_dsts.push(edge.src_idx);
+        degrees[*edge.src_idx] += 1;
+
+        let raw_metrics = edge.metrics.clone();
+        graph.correct_metrics(&mut edge);
+        graph
+            .metrics
+            .push(mem::replace(&mut edge.metrics, raw_metrics));
+        last = Some(edge);
+    }
+
+    removed_count
+}
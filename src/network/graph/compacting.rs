@@ -0,0 +1,784 @@
+use super::{EdgeIdx, Graph, MetricIdx, NodeIdx};
+use crate::{
+    configs::parsing::storage,
+    defaults::{self, capacity::DimVec},
+    helpers::{self, err},
+};
+use log::info;
+use std::{borrow::Cow, convert::TryFrom};
+
+/// Values, which are stored as `u32` in compact graphs.
+pub(super) trait Compactable: Copy {
+    fn to_usize(self) -> usize;
+
+    fn from_usize(value: usize) -> Self;
+}
+
+impl Compactable for usize {
+    fn to_usize(self) -> usize {
+        self
+    }
+
+    fn from_usize(value: usize) -> usize {
+        value
+    }
+}
+
+impl Compactable for NodeIdx {
+    fn to_usize(self) -> usize {
+        *self
+    }
+
+    fn from_usize(value: usize) -> NodeIdx {
+        NodeIdx(value)
+    }
+}
+
+impl Compactable for EdgeIdx {
+    fn to_usize(self) -> usize {
+        *self
+    }
+
+    fn from_usize(value: usize) -> EdgeIdx {
+        EdgeIdx(value)
+    }
+}
+
+fn try_compact<T: Compactable>(value: T, name: &str) -> err::Result<u32> {
+    match u32::try_from(value.to_usize()) {
+        Ok(value) => Ok(value),
+        Err(_) => Err(format!(
+            "The {} {} is too big for a compact graph.",
+            name,
+            value.to_usize()
+        )
+        .into()),
+    }
+}
+
+fn try_compact_all<T: Compactable>(values: &[T], name: &str) -> err::Result<Vec<u32>> {
+    values
+        .iter()
+        .map(|&value| try_compact(value, name))
+        .collect()
+}
+
+/// Compacts a value, which has been checked before (see `Graph::check_compactable(...)`).
+fn compact_checked<T: Compactable>(value: T) -> u32 {
+    u32::try_from(value.to_usize()).expect("Value should have been checked to fit into u32.")
+}
+
+/// Keeps the values marked in `is_kept` in their order.
+fn retain<T>(values: &mut Vec<T>, is_kept: &[bool]) {
+    let mut is_kept = is_kept.iter();
+    values.retain(|_| *is_kept.next().unwrap_or(&true));
+}
+
+fn widen_all<T: Compactable>(values: &[u32]) -> Vec<T> {
+    values
+        .iter()
+        .map(|&value| T::from_usize(value as usize))
+        .collect()
+}
+
+/// Replaces the wide data of a graph, when the graph is compacted.
+/// Nodes' ids and coordinates, the mapping of osm-ways and shortcuts' edges keep their wide types.
+#[derive(Debug)]
+pub(super) struct CompactStorage {
+    node_ch_levels: Vec<u32>,
+    fwd_dsts: Vec<u32>,
+    fwd_offsets: Vec<u32>,
+    fwd_to_fwd_map: Vec<u32>,
+    bwd_dsts: Vec<u32>,
+    bwd_offsets: Vec<u32>,
+    bwd_to_fwd_map: Vec<u32>,
+    metrics: CompactMetrics,
+    edge_ids: CompactEdgeIds,
+    edge_ids_to_idx_map: Vec<(u32, u32)>,
+    sc_offsets: Vec<u32>,
+}
+
+impl CompactStorage {
+    pub(super) fn metric_slice_mut(&mut self) -> MetricSliceMut<'_> {
+        MetricSliceMut::Compact(&mut self.metrics)
+    }
+}
+
+impl Graph {
+    /// Returns true, if the graph is stored in the compact layout (see parsing-config).
+    pub fn is_compact(&self) -> bool {
+        self.compact.is_some()
+    }
+
+    /// Converts the graph into the compact layout.
+    /// If the graph is too big for 32-bit-indices, an error is returned and the graph remains unchanged.
+    pub(super) fn compact(&mut self) -> err::Feedback {
+        if self.is_compact() {
+            return Ok(());
+        }
+        info!("DO Compact graph");
+
+        let compact = CompactStorage {
+            node_ch_levels: try_compact_all(&self.node_ch_levels, "ch-level")?,
+            fwd_dsts: try_compact_all(&self.fwd_dsts, "node-idx")?,
+            fwd_offsets: try_compact_all(&self.fwd_offsets, "offset")?,
+            fwd_to_fwd_map: try_compact_all(&self.fwd_to_fwd_map, "edge-idx")?,
+            bwd_dsts: try_compact_all(&self.bwd_dsts, "node-idx")?,
+            bwd_offsets: try_compact_all(&self.bwd_offsets, "offset")?,
+            bwd_to_fwd_map: try_compact_all(&self.bwd_to_fwd_map, "edge-idx")?,
+            metrics: CompactMetrics::new(
+                &self.metrics,
+                self.cfg.edges.metrics.units.len(),
+                self.cfg.storage.metric_precision,
+            ),
+            edge_ids: CompactEdgeIds::try_from(&self.edge_ids)?,
+            edge_ids_to_idx_map: self
+                .edge_ids_to_idx_map
+                .iter()
+                .map(|&(id, idx)| Ok((try_compact(id, "edge-id")?, try_compact(idx, "edge-idx")?)))
+                .collect::<err::Result<_>>()?,
+            sc_offsets: try_compact_all(&self.sc_offsets, "sc-offset")?,
+        };
+
+        // replace wide data
+        self.node_ch_levels = Vec::new();
+        self.fwd_dsts = Vec::new();
+        self.fwd_offsets = Vec::new();
+        self.fwd_to_fwd_map = Vec::new();
+        self.bwd_dsts = Vec::new();
+        self.bwd_offsets = Vec::new();
+        self.bwd_to_fwd_map = Vec::new();
+        self.metrics = Vec::new();
+        self.edge_ids = Vec::new();
+        self.edge_ids_to_idx_map = Vec::new();
+        self.sc_offsets = Vec::new();
+        self.compact = Some(Box::new(compact));
+
+        Ok(())
+    }
+
+    /// Converts the graph back into the wide layout, e.g. for updating it.
+    pub(super) fn widen(&mut self) {
+        let compact = match self.compact.take() {
+            Some(compact) => compact,
+            None => return,
+        };
+        info!("DO Widen graph");
+
+        self.node_ch_levels = widen_all(&compact.node_ch_levels);
+        self.fwd_dsts = widen_all(&compact.fwd_dsts);
+        self.fwd_offsets = widen_all(&compact.fwd_offsets);
+        self.fwd_to_fwd_map = widen_all(&compact.fwd_to_fwd_map);
+        self.bwd_dsts = widen_all(&compact.bwd_dsts);
+        self.bwd_offsets = widen_all(&compact.bwd_offsets);
+        self.bwd_to_fwd_map = widen_all(&compact.bwd_to_fwd_map);
+        self.metrics = (0..compact.metrics.edge_count)
+            .map(|edge_idx| compact.metrics.get(EdgeIdx(edge_idx)))
+            .collect();
+        self.edge_ids = (0..compact.edge_ids.ids.len())
+            .map(|edge_idx| compact.edge_ids.get(edge_idx))
+            .collect();
+        self.edge_ids_to_idx_map = compact
+            .edge_ids_to_idx_map
+            .iter()
+            .map(|&(id, idx)| (id as usize, EdgeIdx(idx as usize)))
+            .collect();
+        self.sc_offsets = widen_all(&compact.sc_offsets);
+    }
+
+    /// Checks whether a graph of the given size fits into the compact layout, if the graph is compact.
+    /// This is needed before changing a compact graph, because changes can't be undone.
+    pub(super) fn check_compactable(
+        &self,
+        node_count: usize,
+        edge_count: usize,
+        sc_edge_count: usize,
+        max_edge_id: Option<usize>,
+    ) -> err::Feedback {
+        if !self.is_compact() {
+            return Ok(());
+        }
+
+        try_compact(node_count, "node-count")?;
+        try_compact(edge_count, "edge-count")?;
+        try_compact(sc_edge_count, "sc-edge-count")?;
+        if let Some(max_edge_id) = max_edge_id {
+            try_compact(max_edge_id, "edge-id")?;
+        }
+        Ok(())
+    }
+
+    /// Appends the ch-levels of new nodes with the default level.
+    pub(super) fn append_node_ch_levels(&mut self, count: usize) {
+        let level = defaults::network::nodes::LEVEL;
+        match &mut self.compact {
+            Some(compact) => compact
+                .node_ch_levels
+                .extend((0..count).map(|_| compact_checked(level))),
+            None => self.node_ch_levels.extend((0..count).map(|_| level)),
+        }
+    }
+
+    /// Removes the edges, which are not marked in `is_kept`, from all per-edge-arrays and appends the given edges.
+    /// Remaining edges keep their order, so their indices shift only by the number of removed edges before them.
+    ///
+    /// Offset-arrays, to-fwd-idx-arrays and mappings are not touched (see `replace_edge_indices(...)`).
+    pub(super) fn retain_and_append_edges(
+        &mut self,
+        is_kept: &[bool],
+        appended_edges: Vec<AppendedEdge>,
+    ) {
+        match &mut self.compact {
+            Some(compact) => {
+                retain(&mut compact.fwd_dsts, is_kept);
+                retain(&mut compact.bwd_dsts, is_kept);
+                compact.fwd_dsts.extend(
+                    appended_edges
+                        .iter()
+                        .map(|edge| compact_checked(edge.dst_idx)),
+                );
+                compact.bwd_dsts.extend(
+                    appended_edges
+                        .iter()
+                        .map(|edge| compact_checked(edge.src_idx)),
+                );
+                compact
+                    .edge_ids
+                    .retain_and_append(is_kept, appended_edges.iter().map(|edge| edge.id));
+                compact.metrics.retain_and_append(
+                    is_kept,
+                    &appended_edges
+                        .into_iter()
+                        .map(|edge| edge.metrics)
+                        .collect::<Vec<_>>(),
+                );
+            }
+            None => {
+                retain(&mut self.fwd_dsts, is_kept);
+                retain(&mut self.bwd_dsts, is_kept);
+                retain(&mut self.edge_ids, is_kept);
+                retain(&mut self.metrics, is_kept);
+                for edge in appended_edges.into_iter() {
+                    self.fwd_dsts.push(edge.dst_idx);
+                    self.bwd_dsts.push(edge.src_idx);
+                    self.edge_ids.push(edge.id);
+                    self.metrics.push(edge.metrics);
+                }
+            }
+        }
+    }
+
+    /// Replaces the arrays pointing to edge-indices, which have been built in the graph's layout.
+    pub(super) fn replace_edge_indices(
+        &mut self,
+        fwd_indices: (IdxVec<usize>, IdxVec<EdgeIdx>),
+        bwd_indices: (IdxVec<usize>, IdxVec<EdgeIdx>),
+        sc_offsets: IdxVec<usize>,
+        edge_ids_to_idx_map: Vec<(usize, EdgeIdx)>,
+    ) {
+        let ((fwd_offsets, fwd_to_fwd_map), (bwd_offsets, bwd_to_fwd_map)) =
+            (fwd_indices, bwd_indices);
+        match &mut self.compact {
+            Some(compact) => {
+                compact.fwd_offsets = fwd_offsets.into_compact();
+                compact.fwd_to_fwd_map = fwd_to_fwd_map.into_compact();
+                compact.bwd_offsets = bwd_offsets.into_compact();
+                compact.bwd_to_fwd_map = bwd_to_fwd_map.into_compact();
+                compact.sc_offsets = sc_offsets.into_compact();
+                compact.edge_ids_to_idx_map = edge_ids_to_idx_map
+                    .into_iter()
+                    .map(|(id, idx)| (compact_checked(id), compact_checked(idx)))
+                    .collect();
+            }
+            None => {
+                self.fwd_offsets = fwd_offsets.into_wide();
+                self.fwd_to_fwd_map = fwd_to_fwd_map.into_wide();
+                self.bwd_offsets = bwd_offsets.into_wide();
+                self.bwd_to_fwd_map = bwd_to_fwd_map.into_wide();
+                self.sc_offsets = sc_offsets.into_wide();
+                self.edge_ids_to_idx_map = edge_ids_to_idx_map;
+            }
+        }
+    }
+
+    pub(super) fn node_ch_levels(&self) -> IdxSlice<'_, usize> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.node_ch_levels),
+            None => IdxSlice::Wide(&self.node_ch_levels),
+        }
+    }
+
+    pub(super) fn fwd_dsts(&self) -> IdxSlice<'_, NodeIdx> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.fwd_dsts),
+            None => IdxSlice::Wide(&self.fwd_dsts),
+        }
+    }
+
+    pub(super) fn fwd_offsets(&self) -> IdxSlice<'_, usize> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.fwd_offsets),
+            None => IdxSlice::Wide(&self.fwd_offsets),
+        }
+    }
+
+    pub(super) fn fwd_to_fwd_map(&self) -> IdxSlice<'_, EdgeIdx> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.fwd_to_fwd_map),
+            None => IdxSlice::Wide(&self.fwd_to_fwd_map),
+        }
+    }
+
+    pub(super) fn bwd_dsts(&self) -> IdxSlice<'_, NodeIdx> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.bwd_dsts),
+            None => IdxSlice::Wide(&self.bwd_dsts),
+        }
+    }
+
+    pub(super) fn bwd_offsets(&self) -> IdxSlice<'_, usize> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.bwd_offsets),
+            None => IdxSlice::Wide(&self.bwd_offsets),
+        }
+    }
+
+    pub(super) fn bwd_to_fwd_map(&self) -> IdxSlice<'_, EdgeIdx> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.bwd_to_fwd_map),
+            None => IdxSlice::Wide(&self.bwd_to_fwd_map),
+        }
+    }
+
+    pub(super) fn metric_slice(&self) -> MetricSlice<'_> {
+        match &self.compact {
+            Some(compact) => MetricSlice::Compact(&compact.metrics),
+            None => MetricSlice::Wide(&self.metrics),
+        }
+    }
+
+    pub(super) fn edge_id_slice(&self) -> EdgeIdSlice<'_> {
+        match &self.compact {
+            Some(compact) => EdgeIdSlice::Compact(&compact.edge_ids),
+            None => EdgeIdSlice::Wide(&self.edge_ids),
+        }
+    }
+
+    pub(super) fn edge_id_map(&self) -> EdgeIdMap<'_> {
+        match &self.compact {
+            Some(compact) => EdgeIdMap::Compact(&compact.edge_ids_to_idx_map),
+            None => EdgeIdMap::Wide(&self.edge_ids_to_idx_map),
+        }
+    }
+
+    pub(super) fn sc_offsets(&self) -> IdxSlice<'_, usize> {
+        match &self.compact {
+            Some(compact) => IdxSlice::Compact(&compact.sc_offsets),
+            None => IdxSlice::Wide(&self.sc_offsets),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------------------------//
+// views on wide or compact data
+
+#[derive(Copy, Clone, Debug)]
+pub(super) enum IdxSlice<'a, T> {
+    Wide(&'a [T]),
+    Compact(&'a [u32]),
+}
+
+impl<'a, T: Compactable> IdxSlice<'a, T> {
+    pub fn get(&self, idx: usize) -> T {
+        match self {
+            IdxSlice::Wide(values) => values[idx],
+            IdxSlice::Compact(values) => T::from_usize(values[idx] as usize),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            IdxSlice::Wide(values) => values.len(),
+            IdxSlice::Compact(values) => values.len(),
+        }
+    }
+}
+
+/// Growable counterpart of `IdxSlice`, which is built in the graph's layout.
+#[derive(Debug)]
+pub(super) enum IdxVec<T> {
+    Wide(Vec<T>),
+    Compact(Vec<u32>),
+}
+
+const LAYOUT_MISMATCH: &str = "Values should have been built in the graph's layout.";
+
+impl<T: Compactable> IdxVec<T> {
+    pub fn with_capacity(is_compact: bool, capacity: usize) -> IdxVec<T> {
+        if is_compact {
+            IdxVec::Compact(Vec::with_capacity(capacity))
+        } else {
+            IdxVec::Wide(Vec::with_capacity(capacity))
+        }
+    }
+
+    /// Values of compact graphs have to be checked before (see `Graph::check_compactable(...)`).
+    pub fn push(&mut self, value: T) {
+        match self {
+            IdxVec::Wide(values) => values.push(value),
+            IdxVec::Compact(values) => values.push(compact_checked(value)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            IdxVec::Wide(values) => values.len(),
+            IdxVec::Compact(values) => values.len(),
+        }
+    }
+
+    fn into_wide(self) -> Vec<T> {
+        match self {
+            IdxVec::Wide(values) => values,
+            IdxVec::Compact(_) => panic!("{}", LAYOUT_MISMATCH),
+        }
+    }
+
+    fn into_compact(self) -> Vec<u32> {
+        match self {
+            IdxVec::Wide(_) => panic!("{}", LAYOUT_MISMATCH),
+            IdxVec::Compact(values) => values,
+        }
+    }
+}
+
+/// An edge, which is appended to the graph's edges, e.g. when applying changes.
+pub(super) struct AppendedEdge {
+    pub src_idx: NodeIdx,
+    pub dst_idx: NodeIdx,
+    pub id: Option<usize>,
+    pub metrics: DimVec<f64>,
+}
+
+/// Edge-ids as bitset, marking edges with id, and the ids themselves.
+#[derive(Debug)]
+pub(super) struct CompactEdgeIds {
+    has_id: Vec<u64>,
+    ids: Vec<u32>,
+}
+
+impl CompactEdgeIds {
+    fn get(&self, edge_idx: usize) -> Option<usize> {
+        if (self.has_id[edge_idx / 64] >> (edge_idx % 64)) & 1 == 1 {
+            Some(self.ids[edge_idx] as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Ids of appended edges have to be checked before (see `Graph::check_compactable(...)`).
+    fn retain_and_append(
+        &mut self,
+        is_kept: &[bool],
+        appended_ids: impl Iterator<Item = Option<usize>>,
+    ) {
+        let edge_ids = (0..self.ids.len())
+            .filter(|&edge_idx| is_kept[edge_idx])
+            .map(|edge_idx| self.get(edge_idx))
+            .chain(appended_ids)
+            .collect::<Vec<_>>();
+
+        let mut has_id = vec![0; edge_ids.len().div_ceil(64)];
+        let mut ids = Vec::with_capacity(edge_ids.len());
+        for (edge_idx, edge_id) in edge_ids.into_iter().enumerate() {
+            match edge_id {
+                Some(edge_id) => {
+                    has_id[edge_idx / 64] |= 1 << (edge_idx % 64);
+                    ids.push(compact_checked(edge_id));
+                }
+                None => ids.push(0),
+            }
+        }
+        self.has_id = has_id;
+        self.ids = ids;
+    }
+}
+
+impl TryFrom<&Vec<Option<usize>>> for CompactEdgeIds {
+    type Error = err::Msg;
+
+    fn try_from(edge_ids: &Vec<Option<usize>>) -> err::Result<CompactEdgeIds> {
+        let mut has_id = vec![0; edge_ids.len().div_ceil(64)];
+        let mut ids = Vec::with_capacity(edge_ids.len());
+        for (edge_idx, edge_id) in edge_ids.iter().enumerate() {
+            match edge_id {
+                Some(edge_id) => {
+                    has_id[edge_idx / 64] |= 1 << (edge_idx % 64);
+                    ids.push(try_compact(*edge_id, "edge-id")?);
+                }
+                None => ids.push(0),
+            }
+        }
+        Ok(CompactEdgeIds { has_id, ids })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(super) enum EdgeIdSlice<'a> {
+    Wide(&'a [Option<usize>]),
+    Compact(&'a CompactEdgeIds),
+}
+
+impl<'a> EdgeIdSlice<'a> {
+    pub fn get(&self, edge_idx: usize) -> Option<usize> {
+        match self {
+            EdgeIdSlice::Wide(edge_ids) => edge_ids[edge_idx],
+            EdgeIdSlice::Compact(edge_ids) => edge_ids.get(edge_idx),
+        }
+    }
+}
+
+/// Mapping from edge-ids to edge-indices, sorted by edge-ids
+#[derive(Copy, Clone, Debug)]
+pub(super) enum EdgeIdMap<'a> {
+    Wide(&'a [(usize, EdgeIdx)]),
+    Compact(&'a [(u32, u32)]),
+}
+
+impl<'a> EdgeIdMap<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            EdgeIdMap::Wide(map) => map.len(),
+            EdgeIdMap::Compact(map) => map.len(),
+        }
+    }
+
+    /// Returns the `i`-th entry (edge-id, edge-idx) of the mapping.
+    pub fn get(&self, i: usize) -> (usize, EdgeIdx) {
+        match self {
+            EdgeIdMap::Wide(map) => map[i],
+            EdgeIdMap::Compact(map) => (map[i].0 as usize, EdgeIdx(map[i].1 as usize)),
+        }
+    }
+
+    pub fn find(&self, id: usize) -> Option<EdgeIdx> {
+        match self {
+            EdgeIdMap::Wide(map) => map
+                .binary_search_by_key(&id, |&(edge_id, _edge_idx)| edge_id)
+                .ok()
+                .map(|i| map[i].1),
+            EdgeIdMap::Compact(map) => {
+                let id = u32::try_from(id).ok()?;
+                map.binary_search_by_key(&id, |&(edge_id, _edge_idx)| edge_id)
+                    .ok()
+                    .map(|i| EdgeIdx(map[i].1 as usize))
+            }
+        }
+    }
+}
+
+/// Metrics as struct-of-arrays, so all values of one metric are stored consecutively.
+#[derive(Debug)]
+pub(super) struct CompactMetrics {
+    edge_count: usize,
+    values: MetricValues,
+}
+
+#[derive(Debug)]
+enum MetricValues {
+    F64(Vec<f64>),
+    F32(Vec<f32>),
+}
+
+impl CompactMetrics {
+    fn new(metrics: &[DimVec<f64>], dim: usize, precision: storage::Precision) -> CompactMetrics {
+        let edge_count = metrics.len();
+        let values = (0..dim).flat_map(|metric_idx| {
+            metrics
+                .iter()
+                .map(move |edge_metrics| edge_metrics[metric_idx])
+        });
+
+        CompactMetrics {
+            edge_count,
+            values: match precision {
+                storage::Precision::F64 => MetricValues::F64(values.collect()),
+                storage::Precision::F32 => {
+                    MetricValues::F32(values.map(|value| value as f32).collect())
+                }
+            },
+        }
+    }
+
+    fn dim(&self) -> usize {
+        if self.edge_count == 0 {
+            return 0;
+        }
+
+        match &self.values {
+            MetricValues::F64(values) => values.len() / self.edge_count,
+            MetricValues::F32(values) => values.len() / self.edge_count,
+        }
+    }
+
+    fn get_value(&self, edge_idx: EdgeIdx, metric_idx: MetricIdx) -> f64 {
+        let i = *metric_idx * self.edge_count + *edge_idx;
+        match &self.values {
+            MetricValues::F64(values) => values[i],
+            MetricValues::F32(values) => values[i] as f64,
+        }
+    }
+
+    fn set_value(&mut self, edge_idx: EdgeIdx, metric_idx: MetricIdx, value: f64) {
+        let i = *metric_idx * self.edge_count + *edge_idx;
+        match &mut self.values {
+            MetricValues::F64(values) => values[i] = value,
+            MetricValues::F32(values) => values[i] = value as f32,
+        }
+    }
+
+    fn get(&self, edge_idx: EdgeIdx) -> DimVec<f64> {
+        (0..self.dim())
+            .map(|metric_idx| self.get_value(edge_idx, MetricIdx(metric_idx)))
+            .collect()
+    }
+
+    /// Keeps the edges marked in `is_kept` and appends the given edges' metrics to every metric's column.
+    fn retain_and_append(&mut self, is_kept: &[bool], appended_metrics: &[DimVec<f64>]) {
+        let dim = match appended_metrics.first() {
+            Some(metrics) => metrics.len(),
+            None => self.dim(),
+        };
+        let old_edge_count = self.edge_count;
+        let edge_count =
+            is_kept.iter().filter(|&&is_kept| is_kept).count() + appended_metrics.len();
+        let appended_column = |metric_idx: usize| {
+            appended_metrics
+                .iter()
+                .map(move |edge_metrics| edge_metrics[metric_idx])
+        };
+
+        // every column has to grow, so the columns are rebuilt
+        match &mut self.values {
+            MetricValues::F64(values) => {
+                let mut new_values = Vec::with_capacity(dim * edge_count);
+                for metric_idx in 0..dim {
+                    let column = &values[(metric_idx * old_edge_count)..][..old_edge_count];
+                    new_values.extend(
+                        column
+                            .iter()
+                            .enumerate()
+                            .filter(|&(i, _value)| is_kept[i])
+                            .map(|(_i, &value)| value),
+                    );
+                    new_values.extend(appended_column(metric_idx));
+                }
+                *values = new_values;
+            }
+            MetricValues::F32(values) => {
+                let mut new_values = Vec::with_capacity(dim * edge_count);
+                for metric_idx in 0..dim {
+                    let column = &values[(metric_idx * old_edge_count)..][..old_edge_count];
+                    new_values.extend(
+                        column
+                            .iter()
+                            .enumerate()
+                            .filter(|&(i, _value)| is_kept[i])
+                            .map(|(_i, &value)| value),
+                    );
+                    new_values.extend(appended_column(metric_idx).map(|value| value as f32));
+                }
+                *values = new_values;
+            }
+        }
+        self.edge_count = edge_count;
+    }
+
+    /// Reads the edge's values from every metric's column, without collecting them.
+    fn dot_product(&self, edge_idx: EdgeIdx, alphas: &[f64]) -> f64 {
+        alphas
+            .iter()
+            .enumerate()
+            .fold(0.0, |start, (metric_idx, alpha)| {
+                start + alpha * self.get_value(edge_idx, MetricIdx(metric_idx))
+            })
+    }
+}
+
+const NO_WIDE_METRICS: &str = "Metrics of compact graphs can't be referenced per edge. \
+                               Please use get(...), get_value(...) or dot_product(...) instead.";
+
+#[derive(Copy, Clone, Debug)]
+pub(super) enum MetricSlice<'a> {
+    Wide(&'a [DimVec<f64>]),
+    Compact(&'a CompactMetrics),
+}
+
+impl<'a> MetricSlice<'a> {
+    /// Metrics of wide graphs are borrowed, metrics of compact graphs are collected.
+    pub fn get(&self, edge_idx: EdgeIdx) -> Cow<'a, DimVec<f64>> {
+        match *self {
+            MetricSlice::Wide(metrics) => Cow::Borrowed(&metrics[*edge_idx]),
+            MetricSlice::Compact(metrics) => Cow::Owned(metrics.get(edge_idx)),
+        }
+    }
+
+    /// Returns the metrics of wide graphs, which can be referenced per edge.
+    pub fn wide(&self) -> &'a [DimVec<f64>] {
+        match *self {
+            MetricSlice::Wide(metrics) => metrics,
+            MetricSlice::Compact(_) => panic!("{}", NO_WIDE_METRICS),
+        }
+    }
+
+    pub fn get_value(&self, edge_idx: EdgeIdx, metric_idx: MetricIdx) -> f64 {
+        match self {
+            MetricSlice::Wide(metrics) => metrics[*edge_idx][*metric_idx],
+            MetricSlice::Compact(metrics) => metrics.get_value(edge_idx, metric_idx),
+        }
+    }
+
+    pub fn dot_product(&self, edge_idx: EdgeIdx, alphas: &[f64]) -> f64 {
+        match self {
+            MetricSlice::Wide(metrics) => helpers::dot_product(alphas, &metrics[*edge_idx]),
+            MetricSlice::Compact(metrics) => metrics.dot_product(edge_idx, alphas),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) enum MetricSliceMut<'a> {
+    Wide(&'a mut [DimVec<f64>]),
+    Compact(&'a mut CompactMetrics),
+}
+
+impl<'a> MetricSliceMut<'a> {
+    pub fn as_slice(&self) -> MetricSlice<'_> {
+        match self {
+            MetricSliceMut::Wide(metrics) => MetricSlice::Wide(metrics),
+            MetricSliceMut::Compact(metrics) => MetricSlice::Compact(metrics),
+        }
+    }
+
+    /// Returns the metrics of wide graphs, which can be referenced per edge.
+    pub fn wide(&self) -> &[DimVec<f64>] {
+        match self {
+            MetricSliceMut::Wide(metrics) => metrics,
+            MetricSliceMut::Compact(_) => panic!("{}", NO_WIDE_METRICS),
+        }
+    }
+
+    pub fn wide_mut(&mut self) -> &mut [DimVec<f64>] {
+        match self {
+            MetricSliceMut::Wide(metrics) => metrics,
+            MetricSliceMut::Compact(_) => panic!("{}", NO_WIDE_METRICS),
+        }
+    }
+
+    pub fn set_value(&mut self, edge_idx: EdgeIdx, metric_idx: MetricIdx, value: f64) {
+        match self {
+            MetricSliceMut::Wide(metrics) => metrics[*edge_idx][*metric_idx] = value,
+            MetricSliceMut::Compact(metrics) => metrics.set_value(edge_idx, metric_idx, value),
+        }
+    }
+}
@@ -0,0 +1,762 @@
+use super::{EdgeIdx, Graph, NodeAccessor, NodeIdx};
+use crate::{
+    approximating::Approx,
+    defaults::{accuracy, capacity::DimVec, routing::IS_USING_CH_LEVEL_SPEEDUP},
+    helpers::{self, err},
+};
+use log::info;
+use std::{
+    cmp::{Ordering, Reverse},
+    mem,
+};
+
+/// Metric-independent preprocessing of a customizable contraction-hierarchy (CCH).
+///
+/// Nodes are ordered by nested dissection, recursively separating the graph by the nodes' coordinates.
+/// Contracting the nodes in this order without witness-searches results in a chordal supergraph, whose arcs are independent of any metric.
+/// Hence, after metrics have changed (e.g. via `graph.metrics_mut()`), shortcuts can be recomputed via `Graph::customize(...)` instead of contracting the graph again.
+pub struct CustomizableCh {
+    // rank (resulting ch-level) of every node-idx
+    ranks: Vec<usize>,
+    // node-idx of every rank
+    order: Vec<NodeIdx>,
+    // upward arcs of the chordal supergraph as offset-graph over ranks, heads sorted by rank
+    up_offsets: Vec<usize>,
+    up_heads: Vec<usize>,
+}
+
+impl CustomizableCh {
+    /// Shortcuts of the graph are ignored, so a contracted graph can be preprocessed as well.
+    pub fn preprocess(graph: &Graph) -> CustomizableCh {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        info!(
+            "START Preprocess customizable ch of {} nodes and {} edges.",
+            nodes.count(),
+            fwd_edges.count()
+        );
+
+        // undirected neighbours, ignoring shortcuts and loops
+
+        let mut neighbours: Vec<Vec<NodeIdx>> = vec![Vec::new(); nodes.count()];
+        for src_idx in &nodes {
+            for edge in fwd_edges.starting_from(src_idx) {
+                if edge.is_shortcut() || edge.dst_idx() == src_idx {
+                    continue;
+                }
+                neighbours[*src_idx].push(edge.dst_idx());
+                neighbours[*edge.dst_idx()].push(src_idx);
+            }
+        }
+        for node_neighbours in neighbours.iter_mut() {
+            node_neighbours.sort_unstable();
+            node_neighbours.dedup();
+        }
+
+        info!("DO Order nodes by nested dissection.");
+        let order = NestedDissection::new(&nodes, &neighbours).order();
+        let mut ranks = vec![0; nodes.count()];
+        for (rank, node_idx) in order.iter().enumerate() {
+            ranks[**node_idx] = rank;
+        }
+
+        info!("DO Contract nodes to get the chordal supergraph.");
+        let mut upper_neighbours: Vec<Vec<usize>> = vec![Vec::new(); nodes.count()];
+        for (node_idx, node_neighbours) in neighbours.into_iter().enumerate() {
+            let rank = ranks[node_idx];
+            upper_neighbours[rank].extend(
+                node_neighbours
+                    .into_iter()
+                    .map(|neighbour_idx| ranks[*neighbour_idx])
+                    .filter(|&neighbour_rank| neighbour_rank > rank),
+            );
+        }
+        let mut up_offsets = Vec::with_capacity(nodes.count() + 1);
+        let mut up_heads = Vec::new();
+        up_offsets.push(0);
+        for rank in 0..nodes.count() {
+            let mut heads = mem::take(&mut upper_neighbours[rank]);
+            heads.sort_unstable();
+            heads.dedup();
+
+            // Contracting a node connects all its upper neighbours with each other.
+            // It's sufficient to pass them to the lowest one, which passes them on when contracted.
+            if let Some((&parent, others)) = heads.split_first() {
+                upper_neighbours[parent].extend_from_slice(others);
+            }
+
+            up_heads.extend(heads);
+            up_offsets.push(up_heads.len());
+        }
+
+        info!(
+            "FINISHED Preprocessed customizable ch with {} arcs.",
+            up_heads.len()
+        );
+        CustomizableCh {
+            ranks,
+            order,
+            up_offsets,
+            up_heads,
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// Number of (undirected) arcs of the chordal supergraph
+    pub fn arc_count(&self) -> usize {
+        self.up_heads.len()
+    }
+
+    /// Returns the ch-level, a node gets when customizing.
+    pub fn rank(&self, node_idx: NodeIdx) -> usize {
+        self.ranks[*node_idx]
+    }
+
+    fn arc_idx(&self, lower_rank: usize, upper_rank: usize) -> Option<usize> {
+        let offset = self.up_offsets[lower_rank];
+        self.up_heads[offset..self.up_offsets[lower_rank + 1]]
+            .binary_search(&upper_rank)
+            .ok()
+            .map(|i| offset + i)
+    }
+}
+
+struct NestedDissection<'a> {
+    nodes: &'a NodeAccessor<'a>,
+    neighbours: &'a [Vec<NodeIdx>],
+    // marks nodes of the upper part when separating
+    marks: Vec<usize>,
+    next_mark: usize,
+}
+
+impl<'a> NestedDissection<'a> {
+    fn new(nodes: &'a NodeAccessor<'a>, neighbours: &'a [Vec<NodeIdx>]) -> NestedDissection<'a> {
+        NestedDissection {
+            nodes,
+            neighbours,
+            marks: vec![0; nodes.count()],
+            next_mark: 1,
+        }
+    }
+
+    /// Returns all node-indices, ordered by ascending rank.
+    fn order(mut self) -> Vec<NodeIdx> {
+        let mut order = Vec::with_capacity(self.nodes.count());
+        self.dissect(self.nodes.iter().collect(), &mut order);
+        order
+    }
+
+    /// Splits the part at the median of its coordinates (trying several directions) and takes the nodes of the lower half, which have neighbours in the upper half, as separator.
+    /// Both remaining halves are ordered recursively, followed by the separator.
+    fn dissect(&mut self, part: Vec<NodeIdx>, order: &mut Vec<NodeIdx>) {
+        if part.len() <= 1 {
+            order.extend(part);
+            return;
+        }
+
+        let projections: [fn(f64, f64) -> f64; 4] = [
+            |lat, _lon| lat,
+            |_lat, lon| lon,
+            |lat, lon| lat + lon,
+            |lat, lon| lat - lon,
+        ];
+        let mut best: Option<(Vec<NodeIdx>, Vec<NodeIdx>, Vec<NodeIdx>)> = None;
+        for project in projections.iter() {
+            let mut sorted_part: Vec<(f64, NodeIdx)> = part
+                .iter()
+                .map(|&node_idx| {
+                    let coord = self.nodes.coord(node_idx);
+                    (project(coord.lat, coord.lon), node_idx)
+                })
+                .collect();
+            // node-idx breaks ties to be deterministic
+            sorted_part.sort_by(|(a, a_idx), (b, b_idx)| {
+                a.partial_cmp(b)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a_idx.cmp(b_idx))
+            });
+            let (lower, upper) = sorted_part.split_at(part.len() / 2);
+
+            let mark = self.next_mark;
+            self.next_mark += 1;
+            for &(_, node_idx) in upper {
+                self.marks[*node_idx] = mark;
+            }
+            let (separator, lower): (Vec<NodeIdx>, Vec<NodeIdx>) = lower
+                .iter()
+                .map(|&(_, node_idx)| node_idx)
+                .partition(|node_idx| {
+                    self.neighbours[**node_idx]
+                        .iter()
+                        .any(|neighbour_idx| self.marks[**neighbour_idx] == mark)
+                });
+
+            let is_better = match &best {
+                Some((_, _, best_separator)) => separator.len() < best_separator.len(),
+                None => true,
+            };
+            if is_better {
+                let upper = upper.iter().map(|&(_, node_idx)| node_idx).collect();
+                best = Some((lower, upper, separator));
+            }
+        }
+
+        let (lower, upper, separator) = best.expect("At least one projection should be tried.");
+        self.dissect(lower, order);
+        self.dissect(upper, order);
+        order.extend(separator);
+    }
+}
+
+/// Edge or shortcut, that is a candidate for being the connection of an arc in one direction
+#[derive(Clone, Debug)]
+struct Candidate {
+    metrics: DimVec<f64>,
+    origin: Origin,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Origin {
+    Edge(EdgeIdx),
+    Shortcut([CandidateRef; 2]),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CandidateRef {
+    is_upwards: bool,
+    arc_idx: usize,
+    pos: usize,
+}
+
+/// Returns true, if a is at most as expensive as b in every considered metric.
+fn is_dominating(a: &DimVec<f64>, b: &DimVec<f64>, is_considered: &[bool]) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .zip(is_considered.iter())
+        .all(|((a, b), &is_considered)| !is_considered || Approx(a) <= Approx(b))
+}
+
+/// Adds the candidate, if it's not dominated, and removes all candidates dominated by the new one.
+fn insert_pareto(candidates: &mut Vec<Candidate>, candidate: Candidate, is_considered: &[bool]) {
+    if candidates
+        .iter()
+        .any(|other| is_dominating(&other.metrics, &candidate.metrics, is_considered))
+    {
+        return;
+    }
+    candidates.retain(|other| !is_dominating(&candidate.metrics, &other.metrics, is_considered));
+    candidates.push(candidate);
+}
+
+/// Removes all candidates, which are dominated by a convex combination of other candidates.
+///
+/// Remaining candidates form the lower convex hull wrt the considered metrics, hence they are exactly the candidates, which are optimal for some alphas.
+/// Keeping every pareto-optimal candidate instead would blow up with multiple considered metrics.
+fn prune_to_convex_hull(candidates: &mut Vec<Candidate>, is_considered: &[bool]) {
+    // pareto-optimality is sufficient for less than 3 candidates
+    if candidates.len() < 3 {
+        return;
+    }
+
+    let mut i = 0;
+    while i < candidates.len() {
+        let others: Vec<&DimVec<f64>> = candidates
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, other)| &other.metrics)
+            .collect();
+        if is_dominated_by_convex_combination(&candidates[i].metrics, &others, is_considered) {
+            candidates.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Checks via phase 1 of the simplex-algorithm, whether some convex combination of the others is at most as expensive as the candidate in every considered metric.
+///
+/// The lp is `sum_j(lambda_j * others_j) + slacks = candidate` with `sum_j(lambda_j) = 1`, whose feasibility is found by minimizing an artificial variable of the last row.
+fn is_dominated_by_convex_combination(
+    candidate: &DimVec<f64>,
+    others: &[&DimVec<f64>],
+    is_considered: &[bool],
+) -> bool {
+    let dims: Vec<usize> = (0..candidate.len()).filter(|&i| is_considered[i]).collect();
+    let (row_count, lambda_count) = (dims.len() + 1, others.len());
+    // lambdas, slacks, artificial and rhs
+    let col_count = lambda_count + dims.len() + 1;
+    let rhs_col = col_count;
+    let eps = 1e-9;
+
+    let mut tableau = vec![vec![0.0; col_count + 1]; row_count];
+    for (row, &dim) in dims.iter().enumerate() {
+        for (j, other) in others.iter().enumerate() {
+            tableau[row][j] = other[dim];
+        }
+        tableau[row][lambda_count + row] = 1.0;
+        tableau[row][rhs_col] = candidate[dim] + accuracy::F64_ABS;
+    }
+    for value in tableau[row_count - 1].iter_mut().take(lambda_count) {
+        *value = 1.0;
+    }
+    tableau[row_count - 1][col_count - 1] = 1.0;
+    tableau[row_count - 1][rhs_col] = 1.0;
+    let mut basis: Vec<usize> = (lambda_count..col_count).collect();
+
+    // artificial = costs[rhs_col] + sum_j(costs[j] * x_j) for non-basic x_j
+    let mut costs = vec![0.0; col_count + 1];
+    for cost in costs.iter_mut().take(lambda_count) {
+        *cost = -1.0;
+    }
+    costs[rhs_col] = 1.0;
+
+    // Bland's rule prevents cycling, but the guard is kept for numerical safety
+    for _ in 0..(10 * col_count) {
+        if costs[rhs_col] <= eps {
+            return true;
+        }
+
+        let entering = match (0..col_count).find(|&j| costs[j] < -eps) {
+            Some(entering) => entering,
+            None => return false,
+        };
+        let leaving_row = match (0..row_count)
+            .filter(|&row| tableau[row][entering] > eps)
+            .min_by(|&a, &b| {
+                let ratio_a = tableau[a][rhs_col] / tableau[a][entering];
+                let ratio_b = tableau[b][rhs_col] / tableau[b][entering];
+                ratio_a
+                    .partial_cmp(&ratio_b)
+                    .unwrap_or(Ordering::Equal)
+                    .then(basis[a].cmp(&basis[b]))
+            }) {
+            Some(leaving_row) => leaving_row,
+            // unbounded, which can't happen for phase 1
+            None => return false,
+        };
+
+        let pivot = tableau[leaving_row][entering];
+        for value in tableau[leaving_row].iter_mut() {
+            *value /= pivot;
+        }
+        let pivot_row = tableau[leaving_row].clone();
+        for (row, values) in tableau.iter_mut().enumerate() {
+            let factor = values[entering];
+            if row != leaving_row && factor != 0.0 {
+                for (value, pivot_value) in values.iter_mut().zip(pivot_row.iter()) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+        let factor = costs[entering];
+        for (j, (cost, pivot_value)) in costs.iter_mut().zip(pivot_row.iter()).enumerate() {
+            if j == rhs_col {
+                *cost += factor * pivot_value;
+            } else {
+                *cost -= factor * pivot_value;
+            }
+        }
+        basis[leaving_row] = entering;
+    }
+
+    false
+}
+
+enum CustomizedOrigin {
+    Kept(EdgeIdx),
+    Shortcut {
+        metrics: DimVec<f64>,
+        sc_positions: [usize; 2],
+    },
+}
+
+struct CustomizedEdge {
+    src_idx: NodeIdx,
+    dst_idx: NodeIdx,
+    id: Option<usize>,
+    origin: CustomizedOrigin,
+}
+
+/// public stuff for customizing the graph
+impl Graph {
+    /// Replaces all shortcuts and ch-levels by the ones of the given customizable ch, using the current metrics.
+    ///
+    /// For every arc of the ch, all shortcuts are kept, which are optimal for some alphas being positive only for the metrics of positive alpha.
+    /// Hence the customized graph supports ch-routing for all alphas, which are positive only for these metrics (e.g. when explorating).
+    /// Remaining edges keep their ids, but edge-indices change.
+    /// If an error occurs, the graph is unchanged.
+    pub fn customize(&mut self, cch: &CustomizableCh, alphas: &[f64]) -> err::Feedback {
+        // shortcuts are customized in the wide layout
+        let is_compact = self.is_compact();
+        self.widen();
+        let result = self.customize_wide(cch, alphas);
+        if is_compact {
+            self.compact()?;
+        }
+        result
+    }
+}
+
+/// private stuff for customizing the graph
+impl Graph {
+    fn customize_wide(&mut self, cch: &CustomizableCh, alphas: &[f64]) -> err::Feedback {
+        if cch.node_count() != self.node_ids.len() {
+            return Err(format!(
+                "The customizable ch has been preprocessed for {} nodes, but the graph has {} nodes.",
+                cch.node_count(),
+                self.node_ids.len()
+            )
+            .into());
+        }
+        if alphas.len() != self.metrics().dim() {
+            return Err(format!(
+                "The graph has {} metrics, but {} alphas are given.",
+                self.metrics().dim(),
+                alphas.len()
+            )
+            .into());
+        }
+        let is_considered: Vec<bool> = alphas.iter().map(|alpha| alpha > &0.0).collect();
+
+        info!(
+            "START Customize {} arcs for metrics {:?} with alphas {:?}.",
+            cch.arc_count(),
+            self.cfg.edges.metrics.ids,
+            alphas
+        );
+
+        //----------------------------------------------------------------------------------------//
+        // add edges as candidates of their arcs
+
+        info!("DO Add edges to the arcs of the customizable ch.");
+        let old_edge_count = self.fwd_dsts.len();
+        let mut kept_indices = Vec::with_capacity(old_edge_count);
+        let mut up_candidates: Vec<Vec<Candidate>> = vec![Vec::new(); cch.arc_count()];
+        let mut down_candidates: Vec<Vec<Candidate>> = vec![Vec::new(); cch.arc_count()];
+        {
+            let fwd_edges = self.fwd_edges();
+            for edge_idx in (0..old_edge_count).map(EdgeIdx) {
+                if fwd_edges.is_shortcut(edge_idx) {
+                    continue;
+                }
+                kept_indices.push(edge_idx);
+
+                let src_rank = cch.rank(self.bwd_dsts[*edge_idx]);
+                let dst_rank = cch.rank(self.fwd_dsts[*edge_idx]);
+                if src_rank == dst_rank {
+                    // loops don't help any shortest path
+                    continue;
+                }
+
+                let (is_upwards, lower_rank, upper_rank) = if src_rank < dst_rank {
+                    (true, src_rank, dst_rank)
+                } else {
+                    (false, dst_rank, src_rank)
+                };
+                let arc_idx = cch.arc_idx(lower_rank, upper_rank).ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "The edge (id:{}->id:{}) has no arc in the customizable ch. \
+                         Probably, the graph has changed after preprocessing.",
+                        self.node_ids[*self.bwd_dsts[*edge_idx]],
+                        self.node_ids[*self.fwd_dsts[*edge_idx]]
+                    ))
+                })?;
+                let candidates = if is_upwards {
+                    &mut up_candidates[arc_idx]
+                } else {
+                    &mut down_candidates[arc_idx]
+                };
+                insert_pareto(
+                    candidates,
+                    Candidate {
+                        metrics: self.metrics[*edge_idx].clone(),
+                        origin: Origin::Edge(edge_idx),
+                    },
+                    &is_considered,
+                );
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // customize arcs bottom-up via their lower triangles
+
+        info!("DO Customize arcs bottom-up via lower triangles.");
+        // Candidates of an arc are final, when its lower node is processed,
+        // since all its lower triangles contain lower nodes.
+        for rank in 0..cch.node_count() {
+            let arc_indices = cch.up_offsets[rank]..cch.up_offsets[rank + 1];
+            for arc_idx in arc_indices.clone() {
+                prune_to_convex_hull(&mut up_candidates[arc_idx], &is_considered);
+                prune_to_convex_hull(&mut down_candidates[arc_idx], &is_considered);
+            }
+            for first_arc_idx in arc_indices.clone() {
+                for second_arc_idx in arc_indices.clone() {
+                    if first_arc_idx == second_arc_idx {
+                        continue;
+                    }
+
+                    // path: first head -> node -> second head
+                    let first_head = cch.up_heads[first_arc_idx];
+                    let second_head = cch.up_heads[second_arc_idx];
+                    let mut new_candidates = Vec::new();
+                    for (first_pos, first) in down_candidates[first_arc_idx].iter().enumerate() {
+                        for (second_pos, second) in up_candidates[second_arc_idx].iter().enumerate()
+                        {
+                            new_candidates.push(Candidate {
+                                metrics: helpers::add(&first.metrics, &second.metrics),
+                                origin: Origin::Shortcut([
+                                    CandidateRef {
+                                        is_upwards: false,
+                                        arc_idx: first_arc_idx,
+                                        pos: first_pos,
+                                    },
+                                    CandidateRef {
+                                        is_upwards: true,
+                                        arc_idx: second_arc_idx,
+                                        pos: second_pos,
+                                    },
+                                ]),
+                            });
+                        }
+                    }
+
+                    let candidates = if first_head < second_head {
+                        &mut up_candidates[cch
+                            .arc_idx(first_head, second_head)
+                            .expect("Upper neighbours of a node should be connected.")]
+                    } else {
+                        &mut down_candidates[cch
+                            .arc_idx(second_head, first_head)
+                            .expect("Upper neighbours of a node should be connected.")]
+                    };
+                    for candidate in new_candidates {
+                        insert_pareto(candidates, candidate, &is_considered);
+                    }
+                }
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // collect kept edges and new shortcuts
+
+        info!("DO Collect kept edges and new shortcuts.");
+        let mut customized_edges = Vec::with_capacity(kept_indices.len());
+        // position in customized edges, before being sorted
+        let mut old_to_pos = vec![None; old_edge_count];
+        for &edge_idx in kept_indices.iter() {
+            old_to_pos[*edge_idx] = Some(customized_edges.len());
+            customized_edges.push(CustomizedEdge {
+                src_idx: self.bwd_dsts[*edge_idx],
+                dst_idx: self.fwd_dsts[*edge_idx],
+                id: self.edge_ids[*edge_idx],
+                origin: CustomizedOrigin::Kept(edge_idx),
+            });
+        }
+
+        // positions of all candidates, shortcuts are appended
+        let mut candidate_positions = [
+            vec![Vec::new(); cch.arc_count()],
+            vec![Vec::new(); cch.arc_count()],
+        ];
+        let mut shortcut_count = 0;
+        for (direction, all_candidates) in [&down_candidates, &up_candidates].iter().enumerate() {
+            for (arc_idx, candidates) in all_candidates.iter().enumerate() {
+                candidate_positions[direction][arc_idx] = candidates
+                    .iter()
+                    .map(|candidate| match candidate.origin {
+                        Origin::Edge(edge_idx) => {
+                            old_to_pos[*edge_idx].expect("Candidate-edges should be kept.")
+                        }
+                        Origin::Shortcut(_) => {
+                            shortcut_count += 1;
+                            kept_indices.len() + shortcut_count - 1
+                        }
+                    })
+                    .collect();
+            }
+        }
+        let pos_of = |candidate_ref: &CandidateRef| {
+            candidate_positions[candidate_ref.is_upwards as usize][candidate_ref.arc_idx]
+                [candidate_ref.pos]
+        };
+        for (direction, all_candidates) in [&mut down_candidates, &mut up_candidates]
+            .iter_mut()
+            .enumerate()
+        {
+            for lower_rank in 0..cch.node_count() {
+                for arc_idx in cch.up_offsets[lower_rank]..cch.up_offsets[lower_rank + 1] {
+                    let (src_idx, dst_idx) = {
+                        let lower_idx = cch.order[lower_rank];
+                        let upper_idx = cch.order[cch.up_heads[arc_idx]];
+                        if direction == 1 {
+                            (lower_idx, upper_idx)
+                        } else {
+                            (upper_idx, lower_idx)
+                        }
+                    };
+                    for candidate in mem::take(&mut all_candidates[arc_idx]) {
+                        if let Origin::Shortcut(sc_refs) = candidate.origin {
+                            customized_edges.push(CustomizedEdge {
+                                src_idx,
+                                dst_idx,
+                                id: None,
+                                origin: CustomizedOrigin::Shortcut {
+                                    metrics: candidate.metrics,
+                                    sc_positions: [pos_of(&sc_refs[0]), pos_of(&sc_refs[1])],
+                                },
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // sort edges like a finalized graph
+
+        info!("DO Sort customized edges.");
+        let node_ch_levels = cch.ranks.clone();
+        let mut fwd_order: Vec<usize> = (0..customized_edges.len()).collect();
+        if !IS_USING_CH_LEVEL_SPEEDUP {
+            fwd_order.sort_by_key(|&pos| {
+                let edge = &customized_edges[pos];
+                (edge.src_idx, edge.dst_idx, edge.id)
+            });
+        } else {
+            fwd_order.sort_by_key(|&pos| {
+                let edge = &customized_edges[pos];
+                (
+                    edge.src_idx,
+                    Reverse(node_ch_levels[*edge.dst_idx]),
+                    edge.dst_idx,
+                    edge.id,
+                )
+            });
+        }
+        let mut pos_to_idx = vec![EdgeIdx(0); customized_edges.len()];
+        for (edge_idx, &pos) in fwd_order.iter().enumerate() {
+            pos_to_idx[pos] = EdgeIdx(edge_idx);
+        }
+
+        let customized_edge_of = |edge_idx: EdgeIdx| &customized_edges[fwd_order[*edge_idx]];
+        let mut bwd_order: Vec<EdgeIdx> = (0..fwd_order.len()).map(EdgeIdx).collect();
+        if !IS_USING_CH_LEVEL_SPEEDUP {
+            bwd_order.sort_by_key(|&edge_idx| {
+                let edge = customized_edge_of(edge_idx);
+                (edge.dst_idx, edge.src_idx)
+            });
+        } else {
+            bwd_order.sort_by_key(|&edge_idx| {
+                let edge = customized_edge_of(edge_idx);
+                (
+                    edge.dst_idx,
+                    Reverse(node_ch_levels[*edge.src_idx]),
+                    edge.src_idx,
+                    edge.id,
+                )
+            });
+        }
+
+        // edge-ids and way-ids
+
+        let mut edge_ids = Vec::with_capacity(fwd_order.len());
+        let mut edge_ids_to_idx_map = Vec::with_capacity(self.edge_ids_to_idx_map.len());
+        for (edge_idx, &pos) in fwd_order.iter().enumerate() {
+            let id = customized_edges[pos].id;
+            edge_ids.push(id);
+            if let Some(id) = id {
+                edge_ids_to_idx_map.push((id, EdgeIdx(edge_idx)));
+            }
+        }
+        edge_ids_to_idx_map.sort_unstable_by_key(|&(id, _idx)| id);
+
+        let mut way_ids_to_idx_map: Vec<(i64, EdgeIdx)> = self
+            .way_ids_to_idx_map
+            .iter()
+            .filter_map(|&(way_id, old_idx)| {
+                old_to_pos[*old_idx].map(|pos| (way_id, pos_to_idx[pos]))
+            })
+            .collect();
+        way_ids_to_idx_map.sort_unstable();
+
+        //----------------------------------------------------------------------------------------//
+        // Nothing can fail anymore, so the graph can be updated.
+
+        info!("DO Update offset-arrays, metrics and shortcuts.");
+        let mut old_metrics = mem::take(&mut self.metrics);
+        let mut fwd_dsts = Vec::with_capacity(fwd_order.len());
+        let mut bwd_dsts = Vec::with_capacity(fwd_order.len());
+        let mut fwd_offsets = Vec::with_capacity(self.node_ids.len() + 1);
+        let mut metrics = Vec::with_capacity(fwd_order.len());
+        let mut sc_offsets = Vec::with_capacity(fwd_order.len() + 1);
+        let mut sc_edges = Vec::with_capacity(shortcut_count);
+        fwd_offsets.push(0);
+        for (edge_idx, &pos) in fwd_order.iter().enumerate() {
+            let edge = &mut customized_edges[pos];
+
+            // offsets of nodes without leaving edges are the offset of the next node
+            while fwd_offsets.len() <= *edge.src_idx {
+                fwd_offsets.push(edge_idx);
+            }
+            fwd_dsts.push(edge.dst_idx);
+            bwd_dsts.push(edge.src_idx);
+
+            sc_offsets.push(sc_edges.len());
+            match &mut edge.origin {
+                CustomizedOrigin::Kept(old_idx) => {
+                    metrics.push(mem::take(&mut old_metrics[**old_idx]));
+                }
+                CustomizedOrigin::Shortcut {
+                    metrics: sc_metrics,
+                    sc_positions,
+                } => {
+                    metrics.push(mem::take(sc_metrics));
+                    sc_edges.push([pos_to_idx[sc_positions[0]], pos_to_idx[sc_positions[1]]]);
+                }
+            }
+        }
+        while fwd_offsets.len() <= self.node_ids.len() {
+            fwd_offsets.push(fwd_order.len());
+        }
+        sc_offsets.push(sc_edges.len());
+
+        let mut bwd_offsets = Vec::with_capacity(self.node_ids.len() + 1);
+        bwd_offsets.push(0);
+        for (offset, &edge_idx) in bwd_order.iter().enumerate() {
+            let dst_idx = fwd_dsts[*edge_idx];
+            while bwd_offsets.len() <= *dst_idx {
+                bwd_offsets.push(offset);
+            }
+        }
+        while bwd_offsets.len() <= self.node_ids.len() {
+            bwd_offsets.push(bwd_order.len());
+        }
+
+        self.node_ch_levels = node_ch_levels;
+        self.fwd_dsts = fwd_dsts;
+        self.fwd_offsets = fwd_offsets;
+        self.fwd_to_fwd_map = (0..fwd_order.len()).map(EdgeIdx).collect();
+        self.bwd_dsts = bwd_dsts;
+        self.bwd_offsets = bwd_offsets;
+        self.bwd_to_fwd_map = bwd_order;
+        self.metrics = metrics;
+        self.edge_ids = edge_ids;
+        self.edge_ids_to_idx_map = edge_ids_to_idx_map;
+        self.way_ids_to_idx_map = way_ids_to_idx_map;
+        self.sc_offsets = sc_offsets;
+        self.sc_edges = sc_edges;
+        self.is_ch_stale = false;
+        self.shrink_to_fit();
+
+        info!(
+            "FINISHED Customized {} shortcuts for {} edges.",
+            shortcut_count,
+            kept_indices.len()
+        );
+        Ok(())
+    }
+}
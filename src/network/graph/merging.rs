@@ -0,0 +1,348 @@
+use super::{EdgeIdx, Graph};
+use crate::{
+    configs::{csv, parsing::generating::edges::merge, SimpleId},
+    defaults,
+    helpers::err,
+    io,
+    network::{locating, EdgeLocator, MetricIdx},
+};
+use kissunits::{distance::Kilometers, geo::Coordinate};
+use log::warn;
+use std::path::Path;
+
+/// Counts, how often a policy has been applied.
+struct Occurrences<'a> {
+    policy: merge::Policy,
+    description: &'a str,
+    count: usize,
+}
+
+impl<'a> Occurrences<'a> {
+    fn new(policy: merge::Policy, description: &'a str) -> Occurrences<'a> {
+        Occurrences {
+            policy,
+            description,
+            count: 0,
+        }
+    }
+
+    /// Returns an error, if the policy doesn't allow this occurrence.
+    fn add(&mut self, what: &dyn Fn() -> String) -> err::Feedback {
+        self.count += 1;
+        match self.policy {
+            merge::Policy::Error => Err(format!("{} {}", what(), self.description).into()),
+            merge::Policy::Warn | merge::Policy::Ignore | merge::Policy::Default(_) => Ok(()),
+        }
+    }
+
+    fn warn(&self, from: &Path) {
+        if self.count > 0 {
+            if let merge::Policy::Warn = self.policy {
+                warn!(
+                    "{} time(s) while merging {}: {}",
+                    self.count,
+                    from.display(),
+                    self.description
+                );
+            }
+        }
+    }
+}
+
+impl Graph {
+    /// Merges the edges-info of the given file into the graph's metrics,
+    /// by joining the file's rows with the graph's edges according to the key.
+    pub(super) fn merge(
+        &mut self,
+        from: &Path,
+        is_file_with_header: bool,
+        key: &merge::Key,
+        policies: &merge::Policies,
+        edges_info: &[merge::Category],
+        csv: Option<csv::Config>,
+    ) -> err::Feedback {
+        // open file
+
+        let (header, records) =
+            io::network::edges::Parser::new_reader(&from, is_file_with_header, csv)?;
+
+        // get column-indices of ids
+        // Csv-files with header-line are merged by column-names,
+        // other files by column-positions.
+
+        let columns: Vec<(usize, &SimpleId)> = match (csv, &header) {
+            (Some(_), Some(header)) => edges_info
+                .iter()
+                .filter_map(|category| match category {
+                    merge::Category::Id(id) => Some(id),
+                    merge::Category::Ignored => None,
+                })
+                .map(|id| match header.iter().position(|name| name == &id.0) {
+                    Some(col_idx) => Ok((col_idx, id)),
+                    None => Err(err::Msg::from(format!(
+                        "The column {} is not in the header-line of {}.",
+                        id,
+                        from.display()
+                    ))),
+                })
+                .collect::<err::Result<_>>()?,
+            _ => edges_info
+                .iter()
+                .enumerate()
+                .filter_map(|(col_idx, category)| match category {
+                    merge::Category::Id(id) => Some((col_idx, id)),
+                    merge::Category::Ignored => None,
+                })
+                .collect(),
+        };
+        let key_ids = key.ids();
+        let col_idx_of = |key_id: &SimpleId| {
+            columns
+                .iter()
+                .find(|(_col_idx, id)| *id == key_id)
+                .map(|(col_idx, _id)| *col_idx)
+                .ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "The expected key-column {} is not in the edges-info-file.",
+                        key_id,
+                    ))
+                })
+        };
+        let key_col_idxs: Vec<usize> = key_ids
+            .iter()
+            .map(|key_id| col_idx_of(key_id))
+            .collect::<err::Result<_>>()?;
+        let metric_columns: Vec<(usize, MetricIdx)> = columns
+            .iter()
+            .filter(|(_col_idx, id)| !key_ids.contains(id))
+            .map(|(col_idx, id)| Ok((*col_idx, self.cfg.edges.metrics.try_idx_of(id)?)))
+            .collect::<err::Result<_>>()?;
+
+        // prepare joining
+
+        let edge_locator = match key {
+            merge::Key::Nearest { .. } => Some(EdgeLocator::new(self)),
+            merge::Key::EdgeId(_) | merge::Key::NodeIds { .. } | merge::Key::Way { .. } => None,
+        };
+        if let merge::Key::Way { .. } = key {
+            if self.way_ids_to_idx_map.is_empty() {
+                return Err(format!(
+                    "The edges-info {} should be merged by way-ids, but the graph has no ways.",
+                    from.display()
+                )
+                .into());
+            }
+        }
+
+        let mut unmatched_rows = Occurrences::new(
+            policies.unmatched_rows,
+            "A row of the edges-info doesn't match any edge.",
+        );
+        let mut unmatched_edges = Occurrences::new(
+            policies.unmatched_edges,
+            "An edge isn't matched by any row of the edges-info.",
+        );
+        let mut duplicates = Occurrences::new(
+            policies.duplicates,
+            "An edge is matched by multiple rows of the edges-info.",
+        );
+        // number of rows matching the edge
+        let mut matches = vec![0usize; self.fwd_edges().count()];
+
+        // parse key and metrics
+
+        for params in records {
+            let params = params?;
+            let param = |col_idx: usize| {
+                params.get(col_idx).ok_or_else(|| {
+                    err::Msg::from(format!(
+                        "The record {:?} of {} should have at least {} columns.",
+                        params,
+                        from.display(),
+                        col_idx + 1
+                    ))
+                })
+            };
+            let key_params: Vec<&String> = key_col_idxs
+                .iter()
+                .map(|col_idx| param(*col_idx))
+                .collect::<err::Result<_>>()?;
+
+            // get edge-indices
+
+            let edge_idxs = self.join(key, &key_params, edge_locator.as_ref())?;
+            if edge_idxs.is_empty() {
+                unmatched_rows.add(&|| format!("The row {:?} is unmatched.", params))?;
+                continue;
+            }
+
+            // parse metrics
+
+            let mut values = Vec::with_capacity(metric_columns.len());
+            for (col_idx, metric_idx) in metric_columns.iter() {
+                let param = param(*col_idx)?;
+                if let Ok(raw_value) = param.parse::<f64>() {
+                    values.push((*metric_idx, raw_value));
+                } else {
+                    return Err(err::Msg::from(format!("Parsing '{}' didn't work.", param)));
+                };
+            }
+
+            // update graph with data
+
+            for edge_idx in edge_idxs {
+                matches[*edge_idx] += 1;
+                if matches[*edge_idx] == 2 {
+                    duplicates.add(&|| {
+                        format!(
+                            "The edge with id {} is matched again by the row {:?}.",
+                            self.fwd_edges().id(edge_idx),
+                            params
+                        )
+                    })?;
+                }
+
+                for (metric_idx, raw_value) in values.iter() {
+                    self.metrics[*edge_idx][**metric_idx] = *raw_value;
+                }
+            }
+        }
+
+        // apply policies to edges
+
+        for edge_idx in self.fwd_edges().iter() {
+            let default_value = match matches[*edge_idx] {
+                0 => {
+                    if self.fwd_edges().is_shortcut(edge_idx) {
+                        continue;
+                    }
+                    unmatched_edges.add(&|| {
+                        format!(
+                            "The edge with id {} is unmatched.",
+                            self.fwd_edges().id(edge_idx)
+                        )
+                    })?;
+                    policies.unmatched_edges
+                }
+                1 => continue,
+                _ => policies.duplicates,
+            };
+            if let merge::Policy::Default(value) = default_value {
+                for (_col_idx, metric_idx) in metric_columns.iter() {
+                    self.metrics[*edge_idx][**metric_idx] = value;
+                }
+            }
+        }
+
+        for occurrences in &[unmatched_rows, unmatched_edges, duplicates] {
+            occurrences.warn(from);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the edges matching the key's params, which are ordered like the key's ids.
+    fn join(
+        &self,
+        key: &merge::Key,
+        params: &[&String],
+        edge_locator: Option<&EdgeLocator>,
+    ) -> err::Result<Vec<EdgeIdx>> {
+        let fwd_edges = self.fwd_edges();
+
+        match key {
+            merge::Key::EdgeId(_) => {
+                let raw_edge_id = params[0].parse::<usize>().ok().ok_or(format!(
+                    "{}{}{}",
+                    "Parsing edge-id '", params[0], "' (should be usize) didn't work."
+                ))?;
+                // unknown edge-ids are unmatched rows
+                Ok(fwd_edges.try_idx_from(raw_edge_id).into_iter().collect())
+            }
+            merge::Key::NodeIds { .. } => {
+                let nodes = self.nodes();
+                let mut node_idxs = Vec::with_capacity(2);
+                for param in params {
+                    let node_id = param.parse::<i64>().ok().ok_or(format!(
+                        "{}{}{}",
+                        "Parsing node-id '", param, "' (should be i64) didn't work."
+                    ))?;
+                    match nodes.idx_from(node_id) {
+                        Ok(node_idx) => node_idxs.push(node_idx),
+                        Err(_) => return Ok(Vec::new()),
+                    }
+                }
+
+                Ok(fwd_edges
+                    .starting_from(node_idxs[0])
+                    .filter(|half_edge| {
+                        !half_edge.is_shortcut() && half_edge.dst_idx() == node_idxs[1]
+                    })
+                    .map(|half_edge| half_edge.idx())
+                    .collect())
+            }
+            merge::Key::Way { .. } => {
+                let way_id = params[0].parse::<i64>().ok().ok_or(format!(
+                    "{}{}{}",
+                    "Parsing way-id '", params[0], "' (should be i64) didn't work."
+                ))?;
+                let coord = parse_coord(params[1], params[2])?;
+
+                // way-ids are sorted in this "map" (vector)
+                let start = self
+                    .way_ids_to_idx_map
+                    .partition_point(|(id, _edge_idx)| *id < way_id);
+                let end = self
+                    .way_ids_to_idx_map
+                    .partition_point(|(id, _edge_idx)| *id <= way_id);
+
+                let nodes = self.nodes();
+                let bwd_edges = self.bwd_edges();
+                let distances: Vec<(EdgeIdx, f64)> = self.way_ids_to_idx_map[start..end]
+                    .iter()
+                    .map(|(_way_id, edge_idx)| {
+                        let src = nodes.coord(bwd_edges.dst_idx(*edge_idx));
+                        let dst = nodes.coord(fwd_edges.dst_idx(*edge_idx));
+                        (*edge_idx, locating::distance_km(&coord, &src, &dst))
+                    })
+                    .collect();
+                let best_distance = distances
+                    .iter()
+                    .map(|(_edge_idx, distance)| *distance)
+                    .fold(f64::INFINITY, f64::min);
+
+                Ok(distances
+                    .into_iter()
+                    .filter(|(_edge_idx, distance)| {
+                        *distance <= best_distance + defaults::accuracy::F64_ABS
+                    })
+                    .map(|(edge_idx, _distance)| edge_idx)
+                    .collect())
+            }
+            merge::Key::Nearest {
+                lat: _,
+                lon: _,
+                max_distance,
+            } => {
+                let coord = parse_coord(params[0], params[1])?;
+                let edge_locator =
+                    edge_locator.expect("Edge-locator should exist when merging by coordinates.");
+                Ok(edge_locator
+                    .nearest_within(&coord, max_distance.unwrap_or(Kilometers(f64::INFINITY))))
+            }
+        }
+    }
+}
+
+fn parse_coord(lat: &str, lon: &str) -> err::Result<Coordinate> {
+    let parse = |param: &str| {
+        param.parse::<f64>().ok().ok_or(format!(
+            "{}{}{}",
+            "Parsing coordinate '", param, "' (should be f64) didn't work."
+        ))
+    };
+    Ok(Coordinate {
+        lat: parse(lat)?,
+        lon: parse(lon)?,
+    })
+}
@@ -1,10 +1,17 @@
 pub mod building;
+mod compacting;
+pub mod customizing;
 mod indexing;
+mod merging;
+mod spilling;
+pub mod updating;
 pub use indexing::{EdgeIdx, EdgeIdxIterator, MetricIdx, NodeIdx, NodeIdxIterator};
 
 use crate::{configs::parsing::Config, defaults::capacity::DimVec, helpers::err};
+use compacting::{CompactStorage, EdgeIdMap, EdgeIdSlice, IdxSlice, MetricSlice, MetricSliceMut};
 use kissunits::geo::Coordinate;
 use std::{
+    borrow::Cow,
     fmt,
     fmt::Display,
     iter::Iterator,
@@ -78,8 +85,11 @@ use std::{
 #[derive(Debug)]
 pub struct Graph {
     cfg: Config,
-    // nodes, ids sorted
+    // nodes, ids sorted (unless changes have been applied)
     node_ids: Vec<i64>,
+    // mapping from node-id to NodeIdx, sorted by id and without removed nodes
+    // (empty as long as node-ids are sorted and no node has been removed)
+    node_ids_to_idx_map: Vec<(i64, NodeIdx)>,
     // node-metrics
     node_coords: Vec<Coordinate>,
     node_ch_levels: Vec<usize>,
@@ -97,9 +107,16 @@ pub struct Graph {
     // mapping from id to EdgeIdx, sorted by id
     edge_ids: Vec<Option<usize>>,
     edge_ids_to_idx_map: Vec<(usize, EdgeIdx)>,
+    // mapping from osm-way-id to EdgeIdx, sorted by id
+    // (multiple entries per way and edges of multiple ways are possible)
+    way_ids_to_idx_map: Vec<(i64, EdgeIdx)>,
     // shortcuts (contraction-hierarchies)
     sc_offsets: Vec<usize>,
     sc_edges: Vec<[EdgeIdx; 2]>,
+    // true if the graph has been updated after contracting it
+    is_ch_stale: bool,
+    // compact layout (if configured), replacing most of the (then empty) data above
+    compact: Option<Box<CompactStorage>>,
 }
 
 /// public stuff for accessing the (static) graph
@@ -108,36 +125,43 @@ impl Graph {
         &self.cfg
     }
 
+    /// Returns true, if the graph has been contracted, but changed afterwards (e.g. by applying osm-changes).
+    /// Shortcuts and ch-levels of such a graph are not reliable anymore, so the graph has to be contracted again before using ch-routing.
+    pub fn is_ch_stale(&self) -> bool {
+        self.is_ch_stale
+    }
+
     pub fn nodes<'a>(&'a self) -> NodeAccessor<'a> {
         NodeAccessor {
             node_ids: &self.node_ids,
+            node_ids_to_idx_map: &self.node_ids_to_idx_map,
             node_coords: &self.node_coords,
-            node_ch_levels: &self.node_ch_levels,
+            node_ch_levels: self.node_ch_levels(),
         }
     }
 
     pub fn fwd_edges<'a>(&'a self) -> EdgeAccessor<'a> {
         EdgeAccessor {
-            edge_ids: &self.edge_ids,
-            edge_ids_to_idx_map: &self.edge_ids_to_idx_map,
-            edge_dsts: &self.fwd_dsts,
-            offsets: &self.fwd_offsets,
-            xwd_to_fwd_map: &self.fwd_to_fwd_map,
+            edge_ids: self.edge_id_slice(),
+            edge_ids_to_idx_map: self.edge_id_map(),
+            edge_dsts: self.fwd_dsts(),
+            offsets: self.fwd_offsets(),
+            xwd_to_fwd_map: self.fwd_to_fwd_map(),
             metrics: self.metrics(),
-            sc_offsets: &self.sc_offsets,
+            sc_offsets: self.sc_offsets(),
             sc_edges: &self.sc_edges,
         }
     }
 
     pub fn bwd_edges<'a>(&'a self) -> EdgeAccessor<'a> {
         EdgeAccessor {
-            edge_ids: &self.edge_ids,
-            edge_ids_to_idx_map: &self.edge_ids_to_idx_map,
-            edge_dsts: &(self.bwd_dsts),
-            offsets: &(self.bwd_offsets),
-            xwd_to_fwd_map: &(self.bwd_to_fwd_map),
+            edge_ids: self.edge_id_slice(),
+            edge_ids_to_idx_map: self.edge_id_map(),
+            edge_dsts: self.bwd_dsts(),
+            offsets: self.bwd_offsets(),
+            xwd_to_fwd_map: self.bwd_to_fwd_map(),
             metrics: self.metrics(),
-            sc_offsets: &self.sc_offsets,
+            sc_offsets: self.sc_offsets(),
             sc_edges: &self.sc_edges,
         }
     }
@@ -145,15 +169,19 @@ impl Graph {
     pub fn metrics<'a>(&'a self) -> MetricAccessor<'a> {
         MetricAccessor {
             cfg: &self.cfg,
-            metrics: &self.metrics,
+            metrics: self.metric_slice(),
             means: self.means.as_ref(),
         }
     }
 
     pub fn metrics_mut<'a>(&'a mut self) -> MetricAccessorMut<'a> {
+        let metrics = match &mut self.compact {
+            Some(compact) => compact.metric_slice_mut(),
+            None => MetricSliceMut::Wide(&mut self.metrics),
+        };
         MetricAccessorMut {
             cfg: &self.cfg,
-            metrics: &mut self.metrics,
+            metrics,
             means: self.means.as_mut(),
         }
     }
@@ -208,13 +236,13 @@ impl Display for Graph {
             (
                 self.fwd_edges(),
                 self.bwd_edges(),
-                &(self.fwd_offsets),
+                self.fwd_offsets(),
                 "fwd-",
             ),
             (
                 self.bwd_edges(),
                 self.fwd_edges(),
-                &(self.bwd_offsets),
+                self.bwd_offsets(),
                 "bwd-",
             ),
         ];
@@ -237,7 +265,7 @@ impl Display for Graph {
                     let edge_idx = EdgeIdx(j);
                     let src_idx = bwd_edges.dst_idx(edge_idx);
                     let half_edge = fwd_edges.half_edge(edge_idx);
-                    let metrics = half_edge.metrics();
+                    let metrics = fwd_edges.metrics().get(edge_idx);
                     writeln!(
                         f,
                         "{}edge: {{ {}idx: {}, sc-offset: {}, (idx: {})-{:?}->(idx: {}) }}",
@@ -247,7 +275,7 @@ impl Display for Graph {
                             None => String::from(""),
                         },
                         j,
-                        self.sc_offsets[j],
+                        self.sc_offsets().get(j),
                         *src_idx,
                         metrics,
                         *half_edge.dst_idx(),
@@ -274,7 +302,9 @@ impl Display for Graph {
                     writeln!(
                         f,
                         "{}offset: {{ node-id: {}, offset: {} }}",
-                        xwd_prefix, i, xwd_offsets[i]
+                        xwd_prefix,
+                        i,
+                        xwd_offsets.get(i)
                     )?;
                 } else {
                     break;
@@ -285,7 +315,9 @@ impl Display for Graph {
             writeln!(
                 f,
                 "{}offset: {{ __: {}, offset: {} }}",
-                xwd_prefix, i, xwd_offsets[i]
+                xwd_prefix,
+                i,
+                xwd_offsets.get(i)
             )?;
 
             writeln!(f, "")?;
@@ -309,7 +341,7 @@ impl Display for Graph {
                         j = self.sc_edges.len() - 1;
                     }
                     // get edge-idx from sc-edge
-                    while self.sc_offsets[edge_idx] <= j {
+                    while self.sc_offsets().get(edge_idx) <= j {
                         edge_idx += 1;
                     }
                     edge_idx -= 1;
@@ -317,8 +349,8 @@ impl Display for Graph {
                         f,
                         "shortcut: {{ edge-idx: {}, sc-offset: {}, replaced: {:?} }}",
                         edge_idx,
-                        self.sc_offsets[edge_idx],
-                        self.sc_edges[self.sc_offsets[edge_idx]],
+                        self.sc_offsets().get(edge_idx),
+                        self.sc_edges[self.sc_offsets().get(edge_idx)],
                     )?;
                 } else {
                     break;
@@ -399,9 +431,15 @@ impl<'a> HalfEdge<'a> {
         self.edge_accessor.sc_edges(self.idx)
     }
 
+    /// Panics for compact graphs, whose metrics can't be referenced per edge.
     pub fn metrics(&self) -> &DimVec<f64> {
         &self.edge_accessor.metrics[self.idx]
     }
+
+    /// Returns the edge's cost wrt the given alphas, without copying its metrics.
+    pub fn dot_product(&self, alphas: &[f64]) -> f64 {
+        self.edge_accessor.metrics.dot_product(self.idx, alphas)
+    }
 }
 
 impl<'a> Eq for HalfEdge<'a> {}
@@ -417,7 +455,7 @@ impl<'a> Display for HalfEdge<'a> {
         write!(
             f,
             "{{ (src)-{:?}->(idx: {}) }}",
-            self.edge_accessor.metrics[self.idx],
+            self.edge_accessor.metrics.get(self.idx),
             self.dst_idx(),
         )
     }
@@ -428,8 +466,9 @@ impl<'a> Display for HalfEdge<'a> {
 #[derive(Debug)]
 pub struct NodeAccessor<'a> {
     node_ids: &'a Vec<i64>,
+    node_ids_to_idx_map: &'a Vec<(i64, NodeIdx)>,
     node_coords: &'a Vec<Coordinate>,
-    node_ch_levels: &'a Vec<usize>,
+    node_ch_levels: IdxSlice<'a, usize>,
 }
 
 impl IntoIterator for NodeAccessor<'_> {
@@ -455,6 +494,7 @@ impl<'a> NodeAccessor<'a> {
         self.into_iter()
     }
 
+    /// Includes nodes, which have been removed by applying changes (see `is_removed(...)`).
     pub fn count(&self) -> usize {
         self.node_ids.len()
     }
@@ -468,16 +508,33 @@ impl<'a> NodeAccessor<'a> {
     }
 
     pub fn level(&self, idx: NodeIdx) -> usize {
-        self.node_ch_levels[*idx]
+        self.node_ch_levels.get(*idx)
     }
 
     pub fn idx_from(&self, id: i64) -> Result<NodeIdx, NodeIdx> {
-        match self.node_ids.binary_search(&id) {
-            Ok(idx) => Ok(NodeIdx(idx)),
-            Err(idx) => Err(NodeIdx(idx)),
+        if self.node_ids_to_idx_map.is_empty() {
+            return match self.node_ids.binary_search(&id) {
+                Ok(idx) => Ok(NodeIdx(idx)),
+                Err(idx) => Err(NodeIdx(idx)),
+            };
+        }
+
+        // changes have been applied, so node-ids are not sorted anymore
+        match self
+            .node_ids_to_idx_map
+            .binary_search_by_key(&id, |&(node_id, _node_idx)| node_id)
+        {
+            Ok(i) => Ok(self.node_ids_to_idx_map[i].1),
+            Err(i) => Err(NodeIdx(i)),
         }
     }
 
+    /// Returns true, if the node has been removed by applying changes.
+    /// Removed nodes keep their index, but have no edges and can't be found by their id.
+    pub fn is_removed(&self, idx: NodeIdx) -> bool {
+        !self.node_ids_to_idx_map.is_empty() && self.idx_from(self.id(idx)) != Ok(idx)
+    }
+
     pub fn create_from(&self, id: i64) -> Option<Node> {
         let idx = match self.idx_from(id) {
             Ok(idx) => idx,
@@ -503,15 +560,15 @@ impl<'a> NodeAccessor<'a> {
 /// Shallow means that it does only contain references to the graph's data-arrays.
 #[derive(Debug)]
 pub struct EdgeAccessor<'a> {
-    edge_ids: &'a Vec<Option<usize>>,
-    edge_ids_to_idx_map: &'a Vec<(usize, EdgeIdx)>,
-    edge_dsts: &'a Vec<NodeIdx>,
-    offsets: &'a Vec<usize>,
+    edge_ids: EdgeIdSlice<'a>,
+    edge_ids_to_idx_map: EdgeIdMap<'a>,
+    edge_dsts: IdxSlice<'a, NodeIdx>,
+    offsets: IdxSlice<'a, usize>,
     // indirect mapping to save memory
-    xwd_to_fwd_map: &'a Vec<EdgeIdx>,
+    xwd_to_fwd_map: IdxSlice<'a, EdgeIdx>,
     metrics: MetricAccessor<'a>,
     // shortcuts
-    sc_offsets: &'a Vec<usize>,
+    sc_offsets: IdxSlice<'a, usize>,
     sc_edges: &'a Vec<[EdgeIdx; 2]>,
 }
 
@@ -550,25 +607,21 @@ impl<'a> EdgeAccessor<'a> {
     }
 
     pub fn try_id(&self, idx: EdgeIdx) -> Option<usize> {
-        self.edge_ids[*idx]
+        self.edge_ids.get(*idx)
     }
 
     pub fn id(&self, idx: EdgeIdx) -> usize {
-        self.edge_ids[*idx].expect(&format!("Edge-id expected at edge-idx {}.", *idx))
+        self.edge_ids
+            .get(*idx)
+            .expect(&format!("Edge-id expected at edge-idx {}.", *idx))
     }
 
     pub fn try_idx_from(&self, id: usize) -> err::Result<EdgeIdx> {
         // edge-ids are sorted in this "map" (vector)
         // -> mapped from id to edge-idx
-        match self
-            .edge_ids_to_idx_map
-            .binary_search_by_key(&id, |(edge_id, _edge_idx)| *edge_id)
-        {
-            Ok(idx) => {
-                let (_edge_id, edge_idx) = self.edge_ids_to_idx_map[idx];
-                Ok(edge_idx)
-            }
-            Err(_idx) => Err(err::Msg::from(format!(
+        match self.edge_ids_to_idx_map.find(id) {
+            Some(edge_idx) => Ok(edge_idx),
+            None => Err(err::Msg::from(format!(
                 "The provided edge-id {} is expected to be in the graph, but is not.",
                 id
             ))),
@@ -583,7 +636,7 @@ impl<'a> EdgeAccessor<'a> {
     }
 
     pub fn dst_idx(&self, idx: EdgeIdx) -> NodeIdx {
-        self.edge_dsts[*idx]
+        self.edge_dsts.get(*idx)
     }
 
     pub fn metrics(&self) -> &MetricAccessor<'a> {
@@ -592,12 +645,12 @@ impl<'a> EdgeAccessor<'a> {
 
     pub fn is_shortcut(&self, idx: EdgeIdx) -> bool {
         // no overflow due to (len + 1)
-        self.sc_offsets[(*idx) + 1] - self.sc_offsets[*idx] != 0
+        self.sc_offsets.get((*idx) + 1) - self.sc_offsets.get(*idx) != 0
     }
 
     pub fn sc_edges(&self, idx: EdgeIdx) -> Option<&[EdgeIdx; 2]> {
         if self.is_shortcut(idx) {
-            Some(&self.sc_edges[self.sc_offsets[*idx]])
+            Some(&self.sc_edges[self.sc_offsets.get(*idx)])
         } else {
             None
         }
@@ -626,18 +679,21 @@ impl<'a> EdgeAccessor<'a> {
         // Use offset-array to get indices for the graph's edges belonging to the given node
         // (idx + 1) guaranteed by offset-array-length
         // i0 <= i1 <-> node has 0 or more leaving edges
-        (self.offsets[*idx]..self.offsets[*idx + 1])
-            .into_iter()
-            .map(move |i| self.xwd_to_fwd_map[i])
+        (self.offsets.get(*idx)..self.offsets.get(*idx + 1))
+            .map(move |i| self.xwd_to_fwd_map.get(i))
     }
 }
 
 /// A shallow container for accessing metrics.
 /// Shallow means that it does only contain references to the graph's data-arrays.
+///
+/// Indexing returns references and hence panics for compact graphs,
+/// which store metrics metric-major (and maybe with less precision).
+/// The other accessors work for both layouts.
 #[derive(Debug)]
 pub struct MetricAccessor<'a> {
     cfg: &'a Config,
-    metrics: &'a Vec<DimVec<f64>>,
+    metrics: MetricSlice<'a>,
     means: Option<&'a DimVec<f64>>,
 }
 
@@ -649,13 +705,29 @@ impl<'a> MetricAccessor<'a> {
     pub fn mean(&self, idx: MetricIdx) -> Option<f64> {
         Some(self.means?[*idx])
     }
+
+    /// Returns all metrics of the given edge, which are only collected for compact graphs.
+    pub fn get(&self, edge_idx: EdgeIdx) -> Cow<'a, DimVec<f64>> {
+        self.metrics.get(edge_idx)
+    }
+
+    /// Returns one metric of the given edge.
+    pub fn get_value(&self, edge_idx: EdgeIdx, metric_idx: MetricIdx) -> f64 {
+        self.metrics.get_value(edge_idx, metric_idx)
+    }
+
+    /// Returns the given edge's cost wrt the given alphas, reading its metrics in place.
+    /// Prefer this over `get(...)` in hot loops, e.g. when relaxing edges.
+    pub fn dot_product(&self, edge_idx: EdgeIdx, alphas: &[f64]) -> f64 {
+        self.metrics.dot_product(edge_idx, alphas)
+    }
 }
 
 impl<'a> Index<EdgeIdx> for MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.metrics.wide()[*edge_idx]
     }
 }
 
@@ -663,7 +735,7 @@ impl<'a> Index<EdgeIdx> for &MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.metrics.wide()[*edge_idx]
     }
 }
 
@@ -671,7 +743,7 @@ impl<'a> Index<&EdgeIdx> for MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.metrics.wide()[**edge_idx]
     }
 }
 
@@ -679,7 +751,7 @@ impl<'a> Index<&EdgeIdx> for &MetricAccessor<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.metrics.wide()[**edge_idx]
     }
 }
 
@@ -688,7 +760,7 @@ impl<'a> Index<&EdgeIdx> for &MetricAccessor<'a> {
 #[derive(Debug)]
 pub struct MetricAccessorMut<'a> {
     cfg: &'a Config,
-    metrics: &'a mut Vec<DimVec<f64>>,
+    metrics: MetricSliceMut<'a>,
     means: Option<&'a mut DimVec<f64>>,
 }
 
@@ -704,19 +776,34 @@ impl<'a> MetricAccessorMut<'a> {
     pub fn means(&mut self) -> Option<&mut DimVec<f64>> {
         Some(self.means.as_mut()?)
     }
+
+    /// Returns all metrics of the given edge, which are only collected for compact graphs.
+    pub fn get(&self, edge_idx: EdgeIdx) -> Cow<'_, DimVec<f64>> {
+        self.metrics.as_slice().get(edge_idx)
+    }
+
+    /// Returns one metric of the given edge.
+    pub fn get_value(&self, edge_idx: EdgeIdx, metric_idx: MetricIdx) -> f64 {
+        self.metrics.as_slice().get_value(edge_idx, metric_idx)
+    }
+
+    /// Sets one metric of the given edge.
+    pub fn set_value(&mut self, edge_idx: EdgeIdx, metric_idx: MetricIdx, value: f64) {
+        self.metrics.set_value(edge_idx, metric_idx, value)
+    }
 }
 
 impl<'a> Index<EdgeIdx> for MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.metrics.wide()[*edge_idx]
     }
 }
 
 impl<'a> IndexMut<EdgeIdx> for MetricAccessorMut<'a> {
     fn index_mut(&mut self, edge_idx: EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[*edge_idx]
+        &mut self.metrics.wide_mut()[*edge_idx]
     }
 }
 
@@ -724,7 +811,7 @@ impl<'a> Index<EdgeIdx> for &MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.metrics.wide()[*edge_idx]
     }
 }
 
@@ -732,13 +819,13 @@ impl<'a> Index<EdgeIdx> for &mut MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[*edge_idx]
+        &self.metrics.wide()[*edge_idx]
     }
 }
 
 impl<'a> IndexMut<EdgeIdx> for &mut MetricAccessorMut<'a> {
     fn index_mut(&mut self, edge_idx: EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[*edge_idx]
+        &mut self.metrics.wide_mut()[*edge_idx]
     }
 }
 
@@ -746,13 +833,13 @@ impl<'a> Index<&EdgeIdx> for MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.metrics.wide()[**edge_idx]
     }
 }
 
 impl<'a> IndexMut<&EdgeIdx> for MetricAccessorMut<'a> {
     fn index_mut(&mut self, edge_idx: &EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[**edge_idx]
+        &mut self.metrics.wide_mut()[**edge_idx]
     }
 }
 
@@ -760,7 +847,7 @@ impl<'a> Index<&EdgeIdx> for &MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.metrics.wide()[**edge_idx]
     }
 }
 
@@ -768,12 +855,12 @@ impl<'a> Index<&EdgeIdx> for &mut MetricAccessorMut<'a> {
     type Output = DimVec<f64>;
 
     fn index(&self, edge_idx: &EdgeIdx) -> &DimVec<f64> {
-        &self.metrics[**edge_idx]
+        &self.metrics.wide()[**edge_idx]
     }
 }
 
 impl<'a> IndexMut<&EdgeIdx> for &mut MetricAccessorMut<'a> {
     fn index_mut(&mut self, edge_idx: &EdgeIdx) -> &mut DimVec<f64> {
-        &mut self.metrics[**edge_idx]
+        &mut self.metrics.wide_mut()[**edge_idx]
     }
 }
@@ -0,0 +1,268 @@
+use super::building::ProtoEdge;
+use crate::{defaults::capacity::DimVec, helpers::err};
+use log::{debug, warn};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    convert::TryFrom,
+    fs::{self, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    mem,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Sorted runs of proto-edges, which are stored in temporary files to keep memory-usage low while parsing.
+///
+/// Proto-edges are buffered until the buffer is full, which is sorted and written into a new temporary file then.
+/// Proto-edges are sorted by (src-id, dst-id, id), and merging all runs keeps this order.
+/// Equal proto-edges keep their order of insertion, so merging results in the same order as sorting all proto-edges in memory.
+/// The temporary files are removed when the runs are dropped.
+pub(super) struct Runs {
+    tmp_dir: PathBuf,
+    prefix: String,
+    buffer: Vec<ProtoEdge>,
+    max_edges_per_run: usize,
+    files: Vec<PathBuf>,
+    edge_count: usize,
+}
+
+impl Runs {
+    pub fn new<P: AsRef<Path> + ?Sized>(tmp_dir: &P, max_edges_per_run: usize) -> Runs {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or(0);
+
+        Runs {
+            tmp_dir: tmp_dir.as_ref().to_path_buf(),
+            prefix: format!("osmgraphing-{}-{}", process::id(), nanos),
+            buffer: Vec::new(),
+            max_edges_per_run: max_edges_per_run.max(1),
+            files: Vec::new(),
+            edge_count: 0,
+        }
+    }
+
+    /// Number of all proto-edges, including buffered ones
+    pub fn edge_count(&self) -> usize {
+        self.edge_count + self.buffer.len()
+    }
+
+    pub fn push(&mut self, proto_edge: ProtoEdge) -> err::Feedback {
+        self.buffer.push(proto_edge);
+        if self.buffer.len() >= self.max_edges_per_run {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Sorts the buffered proto-edges and writes them into a new temporary file.
+    fn spill(&mut self) -> err::Feedback {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut proto_edges = mem::take(&mut self.buffer);
+
+        proto_edges.sort_by_key(|edge| (edge.src_id, edge.dst_id, edge.id));
+
+        let path = self
+            .tmp_dir
+            .join(format!("{}-{}.run", self.prefix, self.files.len()));
+        debug!(
+            "Spill {} proto-edges into {}",
+            proto_edges.len(),
+            path.display()
+        );
+        let file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                return Err(
+                    format!("Couldn't create {} due to error: {}", path.display(), e).into(),
+                )
+            }
+        };
+        // remember file before writing to remove it in any case
+        self.files.push(path);
+
+        let mut writer = BufWriter::new(file);
+        for proto_edge in proto_edges.iter() {
+            if let Err(e) = write_proto_edge(&mut writer, proto_edge) {
+                return Err(format!("Couldn't write proto-edge due to error: {}", e).into());
+            }
+        }
+        if let Err(e) = writer.flush() {
+            return Err(format!("Couldn't write proto-edges due to error: {}", e).into());
+        }
+
+        self.edge_count += proto_edges.len();
+        Ok(())
+    }
+
+    /// Opens all runs to merge them, after spilling the remaining buffer.
+    pub fn merge(mut self) -> err::Result<Merger> {
+        self.spill()?;
+
+        let mut readers = Vec::with_capacity(self.files.len());
+        for path in self.files.iter() {
+            match OpenOptions::new().read(true).open(path) {
+                Ok(file) => readers.push(BufReader::new(file)),
+                Err(e) => {
+                    return Err(
+                        format!("Couldn't open {} due to error: {}", path.display(), e).into(),
+                    )
+                }
+            }
+        }
+
+        let mut merger = Merger {
+            runs: self,
+            readers,
+            heads: Vec::new(),
+            queue: BinaryHeap::new(),
+        };
+        for run_idx in 0..merger.readers.len() {
+            merger.heads.push(None);
+            merger.pull(run_idx)?;
+        }
+        Ok(merger)
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in self.files.iter() {
+            if let Err(e) = fs::remove_file(path) {
+                warn!("Couldn't remove {} due to error: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+// (src-id, dst-id, id, run-idx)
+type QueueEntry = Reverse<(i64, i64, Option<usize>, usize)>;
+
+/// Merges all runs (k-way), returning the proto-edges in sorted order.
+pub(super) struct Merger {
+    // keep runs to remove their files after merging
+    runs: Runs,
+    readers: Vec<BufReader<fs::File>>,
+    heads: Vec<Option<ProtoEdge>>,
+    // the run-idx breaks ties to keep the order of insertion
+    queue: BinaryHeap<QueueEntry>,
+}
+
+impl Merger {
+    pub fn edge_count(&self) -> usize {
+        self.runs.edge_count()
+    }
+
+    pub fn next(&mut self) -> err::Result<Option<ProtoEdge>> {
+        let run_idx = match self.queue.pop() {
+            Some(Reverse((_src_id, _dst_id, _id, run_idx))) => run_idx,
+            None => return Ok(None),
+        };
+        let proto_edge = self.heads[run_idx].take();
+        self.pull(run_idx)?;
+        Ok(proto_edge)
+    }
+
+    fn pull(&mut self, run_idx: usize) -> err::Feedback {
+        match read_proto_edge(&mut self.readers[run_idx]) {
+            Ok(Some(proto_edge)) => {
+                self.queue.push(Reverse((
+                    proto_edge.src_id,
+                    proto_edge.dst_id,
+                    proto_edge.id,
+                    run_idx,
+                )));
+                self.heads[run_idx] = Some(proto_edge);
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(e) => Err(format!(
+                "Couldn't read proto-edge from {} due to error: {}",
+                self.runs.files[run_idx].display(),
+                e
+            )
+            .into()),
+        }
+    }
+}
+
+// Binary format per proto-edge (little endian):
+// has-id (u8), id (u64), has-way-id (u8), way-id (i64), src-id (i64), dst-id (i64),
+// metric-count (u32), metrics (f64 each)
+
+fn write_proto_edge<W: Write>(writer: &mut W, proto_edge: &ProtoEdge) -> io::Result<()> {
+    writer.write_all(&[proto_edge.id.is_some() as u8])?;
+    writer.write_all(&(proto_edge.id.unwrap_or(0) as u64).to_le_bytes())?;
+    writer.write_all(&[proto_edge.way_id.is_some() as u8])?;
+    writer.write_all(&proto_edge.way_id.unwrap_or(0).to_le_bytes())?;
+    writer.write_all(&proto_edge.src_id.to_le_bytes())?;
+    writer.write_all(&proto_edge.dst_id.to_le_bytes())?;
+    let metric_count = match u32::try_from(proto_edge.metrics.len()) {
+        Ok(metric_count) => metric_count,
+        Err(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} metrics are too many", proto_edge.metrics.len()),
+            ))
+        }
+    };
+    writer.write_all(&metric_count.to_le_bytes())?;
+    for metric in proto_edge.metrics.iter() {
+        writer.write_all(&metric.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Returns None, if the reader is at its end.
+fn read_proto_edge<R: Read>(reader: &mut R) -> io::Result<Option<ProtoEdge>> {
+    let mut byte = [0u8; 1];
+    let mut bytes = [0u8; 8];
+
+    // end of run is only allowed at the start of a proto-edge
+    if reader.read(&mut byte)? == 0 {
+        return Ok(None);
+    }
+    let has_id = byte[0] != 0;
+    reader.read_exact(&mut bytes)?;
+    let id = if has_id {
+        Some(u64::from_le_bytes(bytes) as usize)
+    } else {
+        None
+    };
+
+    reader.read_exact(&mut byte)?;
+    let has_way_id = byte[0] != 0;
+    reader.read_exact(&mut bytes)?;
+    let way_id = if has_way_id {
+        Some(i64::from_le_bytes(bytes))
+    } else {
+        None
+    };
+
+    reader.read_exact(&mut bytes)?;
+    let src_id = i64::from_le_bytes(bytes);
+    reader.read_exact(&mut bytes)?;
+    let dst_id = i64::from_le_bytes(bytes);
+
+    let mut count_bytes = [0u8; 4];
+    reader.read_exact(&mut count_bytes)?;
+    let metric_count = u32::from_le_bytes(count_bytes) as usize;
+    let mut metrics = DimVec::with_capacity(metric_count);
+    for _ in 0..metric_count {
+        reader.read_exact(&mut bytes)?;
+        metrics.push(f64::from_le_bytes(bytes));
+    }
+
+    Ok(Some(ProtoEdge {
+        id,
+        way_id,
+        src_id,
+        dst_id,
+        metrics,
+    }))
+}
@@ -0,0 +1,842 @@
+use super::{
+    building::{GraphBuilder, ProtoEdge, ProtoNode},
+    compacting::{AppendedEdge, IdxVec},
+    EdgeAccessor, EdgeIdx, Graph, MetricIdx, NodeIdx,
+};
+use crate::{
+    approximating::Approx,
+    configs::parsing::{self, generating},
+    defaults::{self, capacity::DimVec, routing::IS_USING_CH_LEVEL_SPEEDUP},
+    helpers::err,
+};
+use kissunits::geo::Coordinate;
+use log::{info, warn};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+type OffsetIndices = (IdxVec<usize>, IdxVec<EdgeIdx>);
+/// appended edges per node as (other node, edge-id, new edge-idx)
+type AppendedPerNode = HashMap<NodeIdx, Vec<(NodeIdx, Option<usize>, EdgeIdx)>>;
+/// key for sorting an offset-range by the other node's ch-level, the other node and the edge-id
+type RangeKey = (Reverse<usize>, NodeIdx, Option<usize>);
+
+/// Changes of osm-data (e.g. parsed from an `*.osc`-file), which can be applied to a finalized graph.
+///
+/// A modified way is handled as removed and created again, hence its id is listed in `removed_way_ids` and its new edges in `edges`.
+#[derive(Debug, Default)]
+pub struct ProtoChanges {
+    /// created or modified nodes
+    pub nodes: Vec<ProtoNode>,
+    pub deleted_node_ids: Vec<i64>,
+    /// modified or deleted ways
+    pub removed_way_ids: Vec<i64>,
+    /// Edges of created or modified ways with their parsed metrics, meaning metrics are not generated yet.
+    pub edges: Vec<ProtoEdge>,
+}
+
+/// An edge, which is not part of the graph yet, or whose metrics have to be generated again.
+struct NewEdge {
+    id: Option<usize>,
+    way_ids: Vec<i64>,
+    src_id: i64,
+    dst_id: i64,
+    metrics: DimVec<f64>,
+    /// Edges, whose nodes have been moved, are recomputed, but keep their id and ways.
+    old_idx: Option<EdgeIdx>,
+}
+
+/// Everything needed for updating the graph, which is prepared before the graph is changed.
+struct Update {
+    moved_nodes: Vec<(NodeIdx, Coordinate)>,
+    appended_nodes: Vec<(i64, Coordinate)>,
+    /// only set if nodes are appended, revived or removed
+    node_ids_to_idx_map: Option<Vec<(i64, NodeIdx)>>,
+    is_kept: Vec<bool>,
+    recomputed_metrics: Vec<(EdgeIdx, DimVec<f64>)>,
+    appended_edges: Vec<AppendedEdge>,
+    fwd_indices: OffsetIndices,
+    bwd_indices: OffsetIndices,
+    edge_ids_to_idx_map: Vec<(usize, EdgeIdx)>,
+    way_ids_to_idx_map: Vec<(i64, EdgeIdx)>,
+    sc_offsets: IdxVec<usize>,
+    sc_edges: Vec<[EdgeIdx; 2]>,
+    is_ch_stale: bool,
+}
+
+/// public stuff for updating the graph
+impl Graph {
+    /// Applies osm-changes to the graph, without parsing the whole map again.
+    ///
+    /// Edges of removed ways or deleted nodes are removed, as well as shortcuts containing them.
+    /// Edges of created or modified ways are added, and their metrics are generated as defined in the provided parsing-config, which should be the config this graph has been parsed with.
+    /// Metrics of edges, whose nodes have been moved, are generated again as well.
+    ///
+    /// Remaining edges keep their ids.
+    /// New edges reuse the id of a removed edge of the same way and nodes, if possible, or get new ids otherwise.
+    /// Clipping is not applied to changes.
+    ///
+    /// Node-indices are stable.
+    /// New nodes are appended and deleted nodes are removed, but keep their index (see `NodeAccessor::is_removed(...)`).
+    /// Nodes, which lose all of their edges, are kept.
+    /// Remaining edges keep their order and new edges are appended, so only the offset-ranges of nodes with removed or new edges are sorted again.
+    /// The graph keeps its layout.
+    ///
+    /// If the graph has been contracted, remaining shortcuts and ch-levels are kept, but the graph is marked as stale (see `is_ch_stale()`).
+    /// If an error occurs, the graph is unchanged.
+    pub fn apply_changes(
+        &mut self,
+        changes: ProtoChanges,
+        parsing_cfg: &parsing::Config,
+    ) -> err::Feedback {
+        info!(
+            "START Apply changes ({} nodes, {} deleted nodes, {} removed ways, {} new edges) to graph.",
+            changes.nodes.len(),
+            changes.deleted_node_ids.len(),
+            changes.removed_way_ids.len(),
+            changes.edges.len()
+        );
+
+        let update = self.prepare_update(changes, parsing_cfg)?;
+        let (removed_count, added_count) = (
+            update.is_kept.iter().filter(|&&is_kept| !is_kept).count(),
+            update.appended_edges.len(),
+        );
+        self.write_update(update);
+
+        info!(
+            "FINISHED Removed {} and added {} edges.",
+            removed_count, added_count
+        );
+        Ok(())
+    }
+}
+
+/// private stuff for updating the graph
+impl Graph {
+    fn prepare_update(
+        &self,
+        changes: ProtoChanges,
+        parsing_cfg: &parsing::Config,
+    ) -> err::Result<Update> {
+        if self.means.is_some() {
+            return Err(err::Msg::from(
+                "The graph's metrics are normalized, hence changes can't be applied.",
+            ));
+        }
+
+        let deleted_node_ids: HashSet<i64> = changes.deleted_node_ids.iter().copied().collect();
+        let removed_way_ids: HashSet<i64> = changes.removed_way_ids.iter().copied().collect();
+        let new_coords: HashMap<i64, Coordinate> = changes
+            .nodes
+            .iter()
+            .map(|proto_node| (proto_node.id, proto_node.coord))
+            .collect();
+        let nodes = self.nodes();
+        let fwd_edges = self.fwd_edges();
+        let bwd_edges = self.bwd_edges();
+        let old_node_count = nodes.count();
+        let old_edge_count = fwd_edges.count();
+        let src_id_of = |edge_idx: EdgeIdx| nodes.id(bwd_edges.dst_idx(edge_idx));
+        let dst_id_of = |edge_idx: EdgeIdx| nodes.id(fwd_edges.dst_idx(edge_idx));
+
+        //----------------------------------------------------------------------------------------//
+        // find edges, which have to be removed or recomputed
+
+        info!("DO Find removed and moved edges.");
+        let mut is_removed = vec![false; old_edge_count];
+        let mut is_recomputed = vec![false; old_edge_count];
+        {
+            // An edge is removed, if all of its ways are removed.
+            // Edges without any way (e.g. from fmi-files) are kept.
+
+            let mut has_way = vec![false; old_edge_count];
+            let mut has_kept_way = vec![false; old_edge_count];
+            for &(way_id, edge_idx) in self.way_ids_to_idx_map.iter() {
+                has_way[*edge_idx] = true;
+                if !removed_way_ids.contains(&way_id) {
+                    has_kept_way[*edge_idx] = true;
+                }
+            }
+
+            let moved_node_ids: HashSet<i64> = changes
+                .nodes
+                .iter()
+                .filter(|proto_node| match nodes.idx_from(proto_node.id) {
+                    Ok(node_idx) => nodes.coord(node_idx) != proto_node.coord,
+                    Err(_) => false,
+                })
+                .map(|proto_node| proto_node.id)
+                .collect();
+
+            for edge_idx in fwd_edges.iter() {
+                let (src_id, dst_id) = (src_id_of(edge_idx), dst_id_of(edge_idx));
+
+                if (has_way[*edge_idx] && !has_kept_way[*edge_idx])
+                    || deleted_node_ids.contains(&src_id)
+                    || deleted_node_ids.contains(&dst_id)
+                {
+                    is_removed[*edge_idx] = true;
+                } else if !fwd_edges.is_shortcut(edge_idx)
+                    && (moved_node_ids.contains(&src_id) || moved_node_ids.contains(&dst_id))
+                {
+                    is_recomputed[*edge_idx] = true;
+                }
+            }
+
+            // Shortcuts of removed or recomputed edges are outdated.
+            // Since shortcuts may consist of shortcuts, repeat until nothing changes.
+
+            let mut has_changed = true;
+            while has_changed {
+                has_changed = false;
+                for edge_idx in fwd_edges.iter() {
+                    if is_removed[*edge_idx] {
+                        continue;
+                    }
+                    if let Some(sc_edges) = fwd_edges.sc_edges(edge_idx) {
+                        if sc_edges
+                            .iter()
+                            .any(|sc_edge| is_removed[**sc_edge] || is_recomputed[**sc_edge])
+                        {
+                            is_removed[*edge_idx] = true;
+                            has_changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // collect new edges
+
+        info!("DO Collect new edges.");
+        let coord_of = |node_id: i64| -> Option<Coordinate> {
+            if let Some(coord) = new_coords.get(&node_id) {
+                return Some(*coord);
+            }
+            if deleted_node_ids.contains(&node_id) {
+                return None;
+            }
+            nodes
+                .idx_from(node_id)
+                .ok()
+                .map(|node_idx| nodes.coord(node_idx))
+        };
+        let mut new_edges = Vec::new();
+        {
+            // Recomputed edges need their parsed metrics, which are still stored in the graph.
+
+            if is_recomputed.iter().any(|&is_recomputed| is_recomputed) {
+                let mut metric_indices = Vec::with_capacity(parsing_cfg.edges.metrics.ids.len());
+                for metric_id in parsing_cfg.edges.metrics.ids.iter() {
+                    metric_indices.push(self.cfg.edges.metrics.try_idx_of(metric_id)?);
+                }
+
+                let metrics = self.metrics();
+                for edge_idx in fwd_edges.iter() {
+                    if !is_recomputed[*edge_idx] {
+                        continue;
+                    }
+                    new_edges.push(NewEdge {
+                        id: fwd_edges.try_id(edge_idx),
+                        way_ids: Vec::new(),
+                        src_id: src_id_of(edge_idx),
+                        dst_id: dst_id_of(edge_idx),
+                        metrics: metric_indices
+                            .iter()
+                            .map(|&metric_idx| metrics.get_value(edge_idx, metric_idx))
+                            .collect(),
+                        old_idx: Some(edge_idx),
+                    });
+                }
+            }
+
+            for proto_edge in changes.edges.into_iter() {
+                if coord_of(proto_edge.src_id).is_none() || coord_of(proto_edge.dst_id).is_none() {
+                    warn!(
+                        "Proto-edge (id:{}->id:{}) is skipped, because a node has no coordinates.",
+                        proto_edge.src_id, proto_edge.dst_id
+                    );
+                    continue;
+                }
+                new_edges.push(NewEdge {
+                    id: None,
+                    way_ids: proto_edge.way_id.into_iter().collect(),
+                    src_id: proto_edge.src_id,
+                    dst_id: proto_edge.dst_id,
+                    metrics: proto_edge.metrics,
+                    old_idx: None,
+                });
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // generate metrics of new edges like the graph's metrics have been generated
+
+        if !new_edges.is_empty() {
+            info!("DO Generate metrics of {} new edges.", new_edges.len());
+            let mut cfg = parsing_cfg.clone();
+            cfg.clipping = None;
+            cfg.edges.metrics.are_normalized = false;
+            if let Some(generating_cfg) = &mut cfg.generating {
+                // Edge-ids are set when merging, and merged values belong to old edge-ids.
+                generating_cfg
+                    .edges
+                    .categories
+                    .retain(|category| match category {
+                        generating::edges::Category::Meta {
+                            info: generating::edges::MetaInfo::EdgeId,
+                            id: _,
+                        } => false,
+                        generating::edges::Category::Merge {
+                            from,
+                            is_file_with_header: _,
+                            key: _,
+                            policies: _,
+                            edges_info: _,
+                            csv: _,
+                        } => {
+                            warn!(
+                                "Values of {} are not merged into new edges.",
+                                from.display()
+                            );
+                            false
+                        }
+                        _ => true,
+                    });
+            }
+
+            // Use edge-ids as key to find the new edges in the generated graph.
+            let mut builder = GraphBuilder::new(cfg);
+            for (key, new_edge) in new_edges.iter().enumerate() {
+                builder.insert(ProtoEdge {
+                    id: Some(key),
+                    way_id: None,
+                    src_id: new_edge.src_id,
+                    dst_id: new_edge.dst_id,
+                    metrics: new_edge.metrics.clone(),
+                })?;
+            }
+            let mut builder = builder.next();
+            for new_edge in new_edges.iter() {
+                for &node_id in &[new_edge.src_id, new_edge.dst_id] {
+                    if let Some(coord) = coord_of(node_id) {
+                        builder.insert(ProtoNode {
+                            id: node_id,
+                            coord,
+                            ch_level: None,
+                        });
+                    }
+                }
+            }
+            let generated_graph = builder.next()?.finalize()?;
+
+            if generated_graph.cfg.edges.metrics.ids != self.cfg.edges.metrics.ids {
+                return Err(err::Msg::from(format!(
+                    "The generated metrics {:?} of new edges don't match the graph's metrics {:?}.",
+                    generated_graph.cfg.edges.metrics.ids, self.cfg.edges.metrics.ids
+                )));
+            }
+
+            let fwd_edges = generated_graph.fwd_edges();
+            for edge_idx in fwd_edges.iter() {
+                new_edges[fwd_edges.id(edge_idx)].metrics =
+                    generated_graph.metrics().get(edge_idx).into_owned();
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // remove duplicates and set edge-ids of new edges
+
+        info!("DO Remove duplicated new edges and set their ids.");
+        let mut additional_way_ids: Vec<(i64, EdgeIdx)> = Vec::new();
+        {
+            let are_metrics_eq = |a: &DimVec<f64>, b: &DimVec<f64>| {
+                a.iter().zip(b.iter()).all(|(a, b)| Approx(a) == Approx(b))
+            };
+
+            // new edges, which are duplicates of kept edges or of other new edges,
+            // only add their ways
+
+            let metrics = self.metrics();
+            let mut seen_edges: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+            let mut is_duplicate = vec![false; new_edges.len()];
+            for new_idx in 0..new_edges.len() {
+                if new_edges[new_idx].old_idx.is_some() {
+                    continue;
+                }
+                let (src_id, dst_id) = (new_edges[new_idx].src_id, new_edges[new_idx].dst_id);
+
+                if let (Ok(src_idx), Ok(dst_idx)) = (nodes.idx_from(src_id), nodes.idx_from(dst_id))
+                {
+                    let kept_idx = fwd_edges.offset_indices(src_idx).find(|&edge_idx| {
+                        !is_removed[*edge_idx]
+                            && !is_recomputed[*edge_idx]
+                            && !fwd_edges.is_shortcut(edge_idx)
+                            && fwd_edges.dst_idx(edge_idx) == dst_idx
+                            && are_metrics_eq(&metrics.get(edge_idx), &new_edges[new_idx].metrics)
+                    });
+                    if let Some(kept_idx) = kept_idx {
+                        for &way_id in new_edges[new_idx].way_ids.iter() {
+                            additional_way_ids.push((way_id, kept_idx));
+                        }
+                        is_duplicate[new_idx] = true;
+                        continue;
+                    }
+                }
+
+                let seen_indices = seen_edges.entry((src_id, dst_id)).or_default();
+                let seen_idx = seen_indices.iter().copied().find(|&seen_idx| {
+                    are_metrics_eq(&new_edges[seen_idx].metrics, &new_edges[new_idx].metrics)
+                });
+                if let Some(seen_idx) = seen_idx {
+                    let way_ids = mem::take(&mut new_edges[new_idx].way_ids);
+                    new_edges[seen_idx].way_ids.extend(way_ids);
+                    is_duplicate[new_idx] = true;
+                } else {
+                    seen_indices.push(new_idx);
+                }
+            }
+            let mut is_duplicate = is_duplicate.into_iter();
+            new_edges.retain(|_| !is_duplicate.next().unwrap_or(false));
+
+            // If the graph has edge-ids, new edges get the id of a removed edge of the same way
+            // and nodes, or the next free id otherwise.
+
+            let edge_id_map = self.edge_id_map();
+            if edge_id_map.len() > 0 {
+                let (max_id, _) = edge_id_map.get(edge_id_map.len() - 1);
+                let mut removed_ids: HashMap<(i64, i64, i64), usize> = HashMap::new();
+                for &(way_id, edge_idx) in self.way_ids_to_idx_map.iter() {
+                    if is_removed[*edge_idx] {
+                        if let Some(id) = fwd_edges.try_id(edge_idx) {
+                            removed_ids
+                                .insert((way_id, src_id_of(edge_idx), dst_id_of(edge_idx)), id);
+                        }
+                    }
+                }
+
+                let mut used_ids = HashSet::new();
+                let mut next_id = max_id + 1;
+                for new_edge in new_edges.iter_mut() {
+                    if new_edge.old_idx.is_some() {
+                        continue;
+                    }
+
+                    let id = match new_edge
+                        .way_ids
+                        .iter()
+                        .filter_map(|&way_id| {
+                            removed_ids.get(&(way_id, new_edge.src_id, new_edge.dst_id))
+                        })
+                        .copied()
+                        .find(|id| !used_ids.contains(id))
+                    {
+                        Some(id) => id,
+                        None => {
+                            next_id += 1;
+                            next_id - 1
+                        }
+                    };
+                    used_ids.insert(id);
+                    new_edge.id = Some(id);
+                }
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // update nodes, whose indices are stable
+
+        info!("DO Update nodes.");
+        let mut moved_nodes = Vec::new();
+        let mut appended_nodes: Vec<(i64, Coordinate)> = Vec::new();
+        // appended or revived nodes
+        let mut added_node_indices: HashMap<i64, NodeIdx> = HashMap::new();
+        let mut removed_node_indices: HashSet<NodeIdx> = HashSet::new();
+        {
+            for proto_node in changes.nodes.iter() {
+                if let Ok(node_idx) = nodes.idx_from(proto_node.id) {
+                    if nodes.coord(node_idx) != proto_node.coord {
+                        moved_nodes.push((node_idx, proto_node.coord));
+                    }
+                }
+            }
+
+            let mut missing_ids: Vec<i64> = new_edges
+                .iter()
+                .flat_map(|new_edge| vec![new_edge.src_id, new_edge.dst_id])
+                .filter(|&node_id| nodes.idx_from(node_id).is_err())
+                .collect();
+            missing_ids.sort_unstable();
+            missing_ids.dedup();
+
+            // Nodes, which have been removed by previous changes, are revived.
+
+            if !missing_ids.is_empty() && !self.node_ids_to_idx_map.is_empty() {
+                for node_idx in nodes.iter() {
+                    let node_id = nodes.id(node_idx);
+                    if missing_ids.binary_search(&node_id).is_ok() {
+                        added_node_indices.insert(node_id, node_idx);
+                        moved_nodes.push((
+                            node_idx,
+                            coord_of(node_id).expect("Nodes of new edges have coordinates."),
+                        ));
+                    }
+                }
+            }
+            for node_id in missing_ids.into_iter() {
+                if added_node_indices.contains_key(&node_id) {
+                    continue;
+                }
+                added_node_indices.insert(node_id, NodeIdx(old_node_count + appended_nodes.len()));
+                appended_nodes.push((
+                    node_id,
+                    coord_of(node_id).expect("Nodes of new edges have coordinates."),
+                ));
+            }
+
+            // Deleted nodes are removed, unless new edges use them.
+
+            let used_node_ids: HashSet<i64> = new_edges
+                .iter()
+                .flat_map(|new_edge| vec![new_edge.src_id, new_edge.dst_id])
+                .collect();
+            removed_node_indices.extend(
+                deleted_node_ids
+                    .iter()
+                    .filter(|node_id| !used_node_ids.contains(node_id))
+                    .filter_map(|&node_id| nodes.idx_from(node_id).ok()),
+            );
+        }
+        let node_idx_of = |node_id: i64| match nodes.idx_from(node_id) {
+            Ok(node_idx) => node_idx,
+            Err(_) => *added_node_indices
+                .get(&node_id)
+                .expect("Every node of an edge should exist."),
+        };
+        let node_ids_to_idx_map = if added_node_indices.is_empty()
+            && removed_node_indices.is_empty()
+        {
+            None
+        } else {
+            let mut kept_entries: Vec<(i64, NodeIdx)> = if self.node_ids_to_idx_map.is_empty() {
+                // node-ids are sorted
+                nodes
+                    .iter()
+                    .map(|node_idx| (nodes.id(node_idx), node_idx))
+                    .collect()
+            } else {
+                self.node_ids_to_idx_map.clone()
+            };
+            kept_entries.retain(|(_node_id, node_idx)| !removed_node_indices.contains(node_idx));
+            let mut added_entries: Vec<(i64, NodeIdx)> = added_node_indices
+                .iter()
+                .map(|(&id, &idx)| (id, idx))
+                .collect();
+            added_entries.sort_unstable();
+            Some(merge_sorted(kept_entries, added_entries))
+        };
+        let new_node_count = old_node_count + appended_nodes.len();
+
+        //----------------------------------------------------------------------------------------//
+        // Kept edges keep their order and new edges are appended.
+
+        info!("DO Append new edges to remaining edges.");
+        let is_kept: Vec<bool> = is_removed.iter().map(|&is_removed| !is_removed).collect();
+        let mut old_to_new = vec![None; old_edge_count];
+        let mut kept_count = 0;
+        for edge_idx in fwd_edges.iter() {
+            if is_kept[*edge_idx] {
+                old_to_new[*edge_idx] = Some(EdgeIdx(kept_count));
+                kept_count += 1;
+            }
+        }
+        let new_idx_of =
+            |old_idx: EdgeIdx| old_to_new[*old_idx].expect("Edge should not have been removed.");
+
+        let mut recomputed_metrics = Vec::new();
+        let mut appended_edges = Vec::new();
+        let mut appended_way_ids = Vec::new();
+        for new_edge in new_edges.into_iter() {
+            match new_edge.old_idx {
+                Some(old_idx) => recomputed_metrics.push((old_idx, new_edge.metrics)),
+                None => {
+                    let edge_idx = EdgeIdx(kept_count + appended_edges.len());
+                    appended_way_ids
+                        .extend(new_edge.way_ids.iter().map(|&way_id| (way_id, edge_idx)));
+                    appended_edges.push(AppendedEdge {
+                        src_idx: node_idx_of(new_edge.src_id),
+                        dst_idx: node_idx_of(new_edge.dst_id),
+                        id: new_edge.id,
+                        metrics: new_edge.metrics,
+                    });
+                }
+            }
+        }
+        let new_edge_count = kept_count + appended_edges.len();
+
+        // edge-ids
+
+        let edge_id_map = self.edge_id_map();
+        let kept_entries: Vec<(usize, EdgeIdx)> = (0..edge_id_map.len())
+            .map(|i| edge_id_map.get(i))
+            .filter_map(|(id, edge_idx)| old_to_new[*edge_idx].map(|edge_idx| (id, edge_idx)))
+            .collect();
+        let mut added_entries: Vec<(usize, EdgeIdx)> = appended_edges
+            .iter()
+            .enumerate()
+            .filter_map(|(i, edge)| edge.id.map(|id| (id, EdgeIdx(kept_count + i))))
+            .collect();
+        added_entries.sort_unstable();
+        let edge_ids_to_idx_map = merge_sorted(kept_entries, added_entries);
+        for i in 1..edge_ids_to_idx_map.len() {
+            if edge_ids_to_idx_map[i - 1].0 == edge_ids_to_idx_map[i].0 {
+                return Err(err::Msg::from(format!(
+                    "The edge-id {} is duplicated.",
+                    edge_ids_to_idx_map[i].0
+                )));
+            }
+        }
+
+        // way-ids (remapping keeps the order of kept entries)
+
+        let kept_entries: Vec<(i64, EdgeIdx)> = self
+            .way_ids_to_idx_map
+            .iter()
+            .filter(|&&(way_id, edge_idx)| is_kept[*edge_idx] && !removed_way_ids.contains(&way_id))
+            .map(|&(way_id, edge_idx)| (way_id, new_idx_of(edge_idx)))
+            .collect();
+        let mut added_entries: Vec<(i64, EdgeIdx)> = additional_way_ids
+            .into_iter()
+            .map(|(way_id, edge_idx)| (way_id, new_idx_of(edge_idx)))
+            .chain(appended_way_ids)
+            .collect();
+        added_entries.sort_unstable();
+        let mut way_ids_to_idx_map = merge_sorted(kept_entries, added_entries);
+        way_ids_to_idx_map.dedup();
+
+        // shortcuts
+
+        let mut sc_edges = Vec::new();
+        for edge_idx in fwd_edges.iter() {
+            if !is_kept[*edge_idx] {
+                continue;
+            }
+            if let Some(old_sc_edges) = fwd_edges.sc_edges(edge_idx) {
+                sc_edges.push([new_idx_of(old_sc_edges[0]), new_idx_of(old_sc_edges[1])]);
+            }
+        }
+
+        //----------------------------------------------------------------------------------------//
+        // offset-arrays, which are sorted again only for nodes with removed or new edges
+
+        info!("DO Update offset-arrays.");
+        let max_edge_id = edge_ids_to_idx_map.last().map(|&(id, _idx)| id);
+        self.check_compactable(new_node_count, new_edge_count, sc_edges.len(), max_edge_id)?;
+        let is_compact = self.is_compact();
+
+        let mut sc_offsets = IdxVec::with_capacity(is_compact, new_edge_count + 1);
+        let mut sc_offset = 0;
+        for edge_idx in fwd_edges.iter() {
+            if !is_kept[*edge_idx] {
+                continue;
+            }
+            sc_offsets.push(sc_offset);
+            if fwd_edges.is_shortcut(edge_idx) {
+                sc_offset += 1;
+            }
+        }
+        while sc_offsets.len() <= new_edge_count {
+            sc_offsets.push(sc_offset);
+        }
+
+        let level_of = |node_idx: NodeIdx| {
+            if *node_idx < old_node_count {
+                nodes.level(node_idx)
+            } else {
+                defaults::network::nodes::LEVEL
+            }
+        };
+        let mut fwd_appended: AppendedPerNode = HashMap::new();
+        let mut bwd_appended: AppendedPerNode = HashMap::new();
+        for (i, edge) in appended_edges.iter().enumerate() {
+            let edge_idx = EdgeIdx(kept_count + i);
+            fwd_appended
+                .entry(edge.src_idx)
+                .or_default()
+                .push((edge.dst_idx, edge.id, edge_idx));
+            bwd_appended
+                .entry(edge.dst_idx)
+                .or_default()
+                .push((edge.src_idx, edge.id, edge_idx));
+        }
+        let fwd_indices = rebuild_offset_indices(
+            &fwd_edges,
+            &old_to_new,
+            &fwd_appended,
+            level_of,
+            new_node_count,
+            is_compact,
+        );
+        let bwd_indices = rebuild_offset_indices(
+            &bwd_edges,
+            &old_to_new,
+            &bwd_appended,
+            level_of,
+            new_node_count,
+            is_compact,
+        );
+
+        let is_contracted = !self.sc_edges.is_empty()
+            || nodes
+                .iter()
+                .any(|node_idx| nodes.level(node_idx) != defaults::network::nodes::LEVEL);
+        let has_changed = kept_count < old_edge_count || !appended_edges.is_empty();
+
+        Ok(Update {
+            moved_nodes,
+            appended_nodes,
+            node_ids_to_idx_map,
+            is_kept,
+            recomputed_metrics,
+            appended_edges,
+            fwd_indices,
+            bwd_indices,
+            edge_ids_to_idx_map,
+            way_ids_to_idx_map,
+            sc_offsets,
+            sc_edges,
+            is_ch_stale: self.is_ch_stale || (is_contracted && has_changed),
+        })
+    }
+
+    /// Nothing can fail anymore, so the graph is updated.
+    fn write_update(&mut self, update: Update) {
+        info!("DO Update nodes, edges, offset-arrays and shortcuts.");
+
+        for (node_idx, coord) in update.moved_nodes.into_iter() {
+            self.node_coords[*node_idx] = coord;
+        }
+        self.append_node_ch_levels(update.appended_nodes.len());
+        for (node_id, coord) in update.appended_nodes.into_iter() {
+            self.node_ids.push(node_id);
+            self.node_coords.push(coord);
+        }
+        if let Some(node_ids_to_idx_map) = update.node_ids_to_idx_map {
+            self.node_ids_to_idx_map = node_ids_to_idx_map;
+        }
+
+        // recomputed edges are kept, so their metrics are set before removing edges
+        let mut metrics = self.metrics_mut();
+        for (edge_idx, edge_metrics) in update.recomputed_metrics.into_iter() {
+            for (metric_idx, &value) in edge_metrics.iter().enumerate() {
+                metrics.set_value(edge_idx, MetricIdx(metric_idx), value);
+            }
+        }
+        self.retain_and_append_edges(&update.is_kept, update.appended_edges);
+        self.replace_edge_indices(
+            update.fwd_indices,
+            update.bwd_indices,
+            update.sc_offsets,
+            update.edge_ids_to_idx_map,
+        );
+        self.way_ids_to_idx_map = update.way_ids_to_idx_map;
+        self.sc_edges = update.sc_edges;
+        self.is_ch_stale = update.is_ch_stale;
+        self.shrink_to_fit();
+    }
+}
+
+/// Rebuilds the offset-array and the to-fwd-idx-array of the given fwd- or bwd-edges.
+///
+/// Ranges of nodes without removed or appended edges are copied with their new edge-indices.
+/// Other ranges are sorted again like a finalized graph, by the other node (and its ch-level) and the edge-id.
+fn rebuild_offset_indices(
+    xwd_edges: &EdgeAccessor,
+    old_to_new: &[Option<EdgeIdx>],
+    appended_edges: &AppendedPerNode,
+    level_of: impl Fn(NodeIdx) -> usize,
+    node_count: usize,
+    is_compact: bool,
+) -> OffsetIndices {
+    let old_node_count = xwd_edges.offsets.len() - 1;
+    let edge_count = old_to_new
+        .iter()
+        .filter(|new_idx| new_idx.is_some())
+        .count()
+        + appended_edges.values().map(Vec::len).sum::<usize>();
+    let mut offsets = IdxVec::with_capacity(is_compact, node_count + 1);
+    let mut xwd_to_fwd_map = IdxVec::with_capacity(is_compact, edge_count);
+
+    offsets.push(0);
+    for node_idx in (0..node_count).map(NodeIdx) {
+        let old_range = if *node_idx < old_node_count {
+            xwd_edges.offsets.get(*node_idx)..xwd_edges.offsets.get(*node_idx + 1)
+        } else {
+            0..0
+        };
+        let old_indices = || old_range.clone().map(|i| xwd_edges.xwd_to_fwd_map.get(i));
+        let appended = appended_edges.get(&node_idx);
+
+        if appended.is_none() && old_indices().all(|edge_idx| old_to_new[*edge_idx].is_some()) {
+            for edge_idx in old_indices() {
+                xwd_to_fwd_map.push(old_to_new[*edge_idx].expect("Edge should be kept."));
+            }
+        } else {
+            let mut range: Vec<(RangeKey, EdgeIdx)> = old_indices()
+                .filter_map(|edge_idx| {
+                    old_to_new[*edge_idx].map(|new_idx| {
+                        let other_idx = xwd_edges.dst_idx(edge_idx);
+                        (
+                            (
+                                Reverse(level_of(other_idx)),
+                                other_idx,
+                                xwd_edges.try_id(edge_idx),
+                            ),
+                            new_idx,
+                        )
+                    })
+                })
+                .chain(
+                    appended
+                        .into_iter()
+                        .flatten()
+                        .map(|&(other_idx, id, new_idx)| {
+                            ((Reverse(level_of(other_idx)), other_idx, id), new_idx)
+                        }),
+                )
+                .collect();
+            if IS_USING_CH_LEVEL_SPEEDUP {
+                range.sort_by_key(|&(key, _new_idx)| key);
+            } else {
+                range.sort_by_key(|&((_level, other_idx, id), _new_idx)| (other_idx, id));
+            }
+            for (_key, new_idx) in range.into_iter() {
+                xwd_to_fwd_map.push(new_idx);
+            }
+        }
+        offsets.push(xwd_to_fwd_map.len());
+    }
+
+    (offsets, xwd_to_fwd_map)
+}
+
+/// Merges two sorted vectors into one sorted vector in linear time.
+fn merge_sorted<T: Ord>(a: Vec<T>, b: Vec<T>) -> Vec<T> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    loop {
+        let is_taking_a = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => x <= y,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => break,
+        };
+        merged.extend(if is_taking_a { a.next() } else { b.next() });
+    }
+    merged
+}
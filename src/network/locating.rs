@@ -0,0 +1,347 @@
+use crate::{
+    defaults,
+    network::{EdgeIdx, Graph, NodeIdx},
+};
+use kissunits::{
+    distance::Kilometers,
+    geo::{self, Coordinate},
+};
+
+/// Uniform grid over the bounding box of the graph's nodes, searched in rings around a coordinate.
+struct Grid {
+    min: Coordinate,
+    cell_lat: f64,
+    cell_lon: f64,
+    num_rows: usize,
+    num_cols: usize,
+    /// Used for a lower bound of a cell's height and width in kilometers, which stops the search.
+    km_per_degree: f64,
+    max_abs_lat: f64,
+}
+
+impl Grid {
+    /// The grid has roughly one item per cell.
+    fn new(graph: &Graph, num_items: usize) -> Grid {
+        let nodes = graph.nodes();
+
+        let mut min = Coordinate {
+            lat: f64::INFINITY,
+            lon: f64::INFINITY,
+        };
+        let mut max = Coordinate {
+            lat: f64::NEG_INFINITY,
+            lon: f64::NEG_INFINITY,
+        };
+        for idx in nodes.iter() {
+            let coord = nodes.coord(idx);
+            min.lat = min.lat.min(coord.lat);
+            min.lon = min.lon.min(coord.lon);
+            max.lat = max.lat.max(coord.lat);
+            max.lon = max.lon.max(coord.lon);
+        }
+        if nodes.count() == 0 {
+            min = Coordinate::zero();
+            max = Coordinate::zero();
+        }
+
+        // roughly one item per cell
+        let num_rows = ((num_items as f64).sqrt().ceil() as usize).max(1);
+        let num_cols = num_rows;
+        // cells are not allowed to be empty, e.g. if all nodes have the same latitude
+        let cell_lat = ((max.lat - min.lat) / num_rows as f64).max(f64::EPSILON);
+        let cell_lon = ((max.lon - min.lon) / num_cols as f64).max(f64::EPSILON);
+
+        Grid {
+            min,
+            cell_lat,
+            cell_lon,
+            num_rows,
+            num_cols,
+            km_per_degree: geo::haversine_distance_km(
+                &Coordinate { lat: 0.0, lon: 0.0 },
+                &Coordinate { lat: 1.0, lon: 0.0 },
+            )
+            .0,
+            max_abs_lat: min.lat.abs().max(max.lat.abs()),
+        }
+    }
+
+    fn num_cells(&self) -> usize {
+        self.num_rows * self.num_cols
+    }
+
+    /// Coordinates outside the grid are clamped to the nearest cell.
+    fn row_col_of(&self, coord: &Coordinate) -> (isize, isize) {
+        let row = ((coord.lat - self.min.lat) / self.cell_lat).floor();
+        let col = ((coord.lon - self.min.lon) / self.cell_lon).floor();
+        (
+            row.max(0.0).min((self.num_rows - 1) as f64) as isize,
+            col.max(0.0).min((self.num_cols - 1) as f64) as isize,
+        )
+    }
+
+    fn cell_of(&self, coord: &Coordinate) -> usize {
+        let (row, col) = self.row_col_of(coord);
+        row as usize * self.num_cols + col as usize
+    }
+
+    /// Visits the cells in rings around the coordinate's cell,
+    /// until the next ring can't contain anything nearer than the best distance,
+    /// which is returned by `visit` (if something has been found).
+    fn search<F>(&self, coord: &Coordinate, max_distance: Kilometers, mut visit: F)
+    where
+        F: FnMut(usize) -> Option<f64>,
+    {
+        let (row, col) = self.row_col_of(coord);
+        // a degree of longitude is shortest at the latitude farthest from the equator
+        let max_abs_lat = self.max_abs_lat.max(coord.lat.abs()).min(90.0);
+        let min_cell_km = (self.cell_lat * self.km_per_degree)
+            .min(self.cell_lon * self.km_per_degree * max_abs_lat.to_radians().cos());
+        let mut best_distance = max_distance.0;
+
+        // search rings of cells around the coordinate's cell
+        let max_ring = self.num_rows.max(self.num_cols) as isize;
+        for ring in 0..=max_ring {
+            // Cells of this ring are at least (ring - 1) cells away from the coordinate,
+            // which could be outside of the grid.
+            let lower_bound = (ring - 1).max(0) as f64 * min_cell_km;
+            if lower_bound > best_distance {
+                break;
+            }
+
+            for (r, c) in ring_cells(row, col, ring) {
+                if r < 0 || c < 0 || r >= self.num_rows as isize || c >= self.num_cols as isize {
+                    continue;
+                }
+                if let Some(distance) = visit(r as usize * self.num_cols + c as usize) {
+                    best_distance = best_distance.min(distance);
+                }
+            }
+        }
+    }
+}
+
+/// Finds the nearest node of a coordinate via a uniform grid over the nodes' coordinates.
+///
+/// The grid has roughly one node per cell, so a query only looks at the cells around the coordinate.
+pub struct NodeLocator {
+    grid: Grid,
+    /// `cells[offsets[i]..offsets[i + 1]]` are the nodes of cell `i`.
+    offsets: Vec<usize>,
+    cells: Vec<(NodeIdx, Coordinate)>,
+}
+
+impl NodeLocator {
+    pub fn new(graph: &Graph) -> NodeLocator {
+        let nodes = graph.nodes();
+        let coords: Vec<(NodeIdx, Coordinate)> =
+            nodes.iter().map(|idx| (idx, nodes.coord(idx))).collect();
+        let grid = Grid::new(graph, coords.len());
+
+        // sort nodes into cells (counting-sort)
+
+        let mut offsets = vec![0; grid.num_cells() + 1];
+        for (_idx, coord) in &coords {
+            offsets[grid.cell_of(coord) + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut next = offsets.clone();
+        let mut cells = vec![(NodeIdx(0), Coordinate::zero()); coords.len()];
+        for (idx, coord) in coords {
+            let cell = grid.cell_of(&coord);
+            cells[next[cell]] = (idx, coord);
+            next[cell] += 1;
+        }
+
+        NodeLocator {
+            grid,
+            offsets,
+            cells,
+        }
+    }
+
+    /// Returns the nearest node, or `None` if the graph has no nodes.
+    pub fn nearest(&self, coord: &Coordinate) -> Option<NodeIdx> {
+        self.nearest_within(coord, Kilometers(f64::INFINITY))
+    }
+
+    /// Returns the nearest node, if its distance is at most the given maximum distance.
+    pub fn nearest_within(&self, coord: &Coordinate, max_distance: Kilometers) -> Option<NodeIdx> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<(NodeIdx, f64)> = None;
+        self.grid.search(coord, max_distance, |cell| {
+            for (idx, node_coord) in &self.cells[self.offsets[cell]..self.offsets[cell + 1]] {
+                let distance = geo::haversine_distance_km(coord, node_coord).0;
+                let is_better = match best {
+                    Some((_best_idx, best_distance)) => distance < best_distance,
+                    None => true,
+                };
+                if is_better && distance <= max_distance.0 {
+                    best = Some((*idx, distance));
+                }
+            }
+            best.map(|(_idx, distance)| distance)
+        });
+
+        best.map(|(idx, _distance)| idx)
+    }
+}
+
+/// Finds the nearest edges of a coordinate via a uniform grid,
+/// where every (non-shortcut) edge is stored in all cells overlapping its bounding box.
+pub struct EdgeLocator {
+    grid: Grid,
+    /// `cells[offsets[i]..offsets[i + 1]]` are the edges of cell `i`.
+    offsets: Vec<usize>,
+    cells: Vec<EdgeIdx>,
+    /// src- and dst-coordinate of every edge
+    segments: Vec<(Coordinate, Coordinate)>,
+}
+
+impl EdgeLocator {
+    pub fn new(graph: &Graph) -> EdgeLocator {
+        let nodes = graph.nodes();
+        let fwd_edges = graph.fwd_edges();
+        let bwd_edges = graph.bwd_edges();
+        let segments: Vec<(Coordinate, Coordinate)> = fwd_edges
+            .iter()
+            .map(|idx| {
+                (
+                    nodes.coord(bwd_edges.dst_idx(idx)),
+                    nodes.coord(fwd_edges.dst_idx(idx)),
+                )
+            })
+            .collect();
+        let edges: Vec<EdgeIdx> = fwd_edges
+            .iter()
+            .filter(|&idx| !fwd_edges.is_shortcut(idx))
+            .collect();
+        let grid = Grid::new(graph, edges.len());
+
+        let num_cols = grid.num_cols;
+        let cells_of = |idx: EdgeIdx| {
+            let (src, dst) = &segments[*idx];
+            let (min_row, min_col) = grid.row_col_of(&Coordinate {
+                lat: src.lat.min(dst.lat),
+                lon: src.lon.min(dst.lon),
+            });
+            let (max_row, max_col) = grid.row_col_of(&Coordinate {
+                lat: src.lat.max(dst.lat),
+                lon: src.lon.max(dst.lon),
+            });
+            (min_row..=max_row).flat_map(move |r| {
+                (min_col..=max_col).map(move |c| r as usize * num_cols + c as usize)
+            })
+        };
+
+        // sort edges into cells (counting-sort)
+
+        let mut offsets = vec![0; grid.num_cells() + 1];
+        for &idx in &edges {
+            for cell in cells_of(idx) {
+                offsets[cell + 1] += 1;
+            }
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut next = offsets.clone();
+        let mut cells = vec![EdgeIdx(0); offsets[offsets.len() - 1]];
+        for &idx in &edges {
+            for cell in cells_of(idx) {
+                cells[next[cell]] = idx;
+                next[cell] += 1;
+            }
+        }
+
+        EdgeLocator {
+            grid,
+            offsets,
+            cells,
+            segments,
+        }
+    }
+
+    /// Returns the nearest edges, which are multiple if they have the same distance,
+    /// e.g. both directions of a street.
+    pub fn nearest(&self, coord: &Coordinate) -> Vec<EdgeIdx> {
+        self.nearest_within(coord, Kilometers(f64::INFINITY))
+    }
+
+    /// Returns the nearest edges, if their distance is at most the given maximum distance.
+    pub fn nearest_within(&self, coord: &Coordinate, max_distance: Kilometers) -> Vec<EdgeIdx> {
+        let mut best: Vec<EdgeIdx> = Vec::new();
+        let mut best_distance = f64::INFINITY;
+        self.grid.search(coord, max_distance, |cell| {
+            for &idx in &self.cells[self.offsets[cell]..self.offsets[cell + 1]] {
+                let (src, dst) = &self.segments[*idx];
+                let distance = distance_km(coord, src, dst);
+                if distance > max_distance.0 {
+                    continue;
+                }
+                if distance < best_distance - defaults::accuracy::F64_ABS {
+                    best.clear();
+                    best_distance = distance;
+                }
+                if distance <= best_distance + defaults::accuracy::F64_ABS && !best.contains(&idx) {
+                    best.push(idx);
+                }
+            }
+            if best.is_empty() {
+                None
+            } else {
+                Some(best_distance)
+            }
+        });
+
+        best.sort_unstable();
+        best
+    }
+}
+
+/// Returns the distance (in kilometers) between the coordinate and the segment from src to dst,
+/// which is projected locally around the coordinate.
+pub(crate) fn distance_km(coord: &Coordinate, src: &Coordinate, dst: &Coordinate) -> f64 {
+    let cos_lat = coord.lat.to_radians().cos().max(f64::EPSILON);
+    let project = |other: &Coordinate| ((other.lon - coord.lon) * cos_lat, other.lat - coord.lat);
+    let (src_x, src_y) = project(src);
+    let (dst_x, dst_y) = project(dst);
+
+    // nearest point of the segment to the origin (the coordinate)
+    let (dx, dy) = (dst_x - src_x, dst_y - src_y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (-(src_x * dx + src_y * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let nearest = Coordinate {
+        lat: coord.lat + src_y + t * dy,
+        lon: coord.lon + (src_x + t * dx) / cos_lat,
+    };
+
+    geo::haversine_distance_km(coord, &nearest).0
+}
+
+/// Returns the cells of the square ring with the given radius around the given cell.
+fn ring_cells(row: isize, col: isize, ring: isize) -> Vec<(isize, isize)> {
+    if ring == 0 {
+        return vec![(row, col)];
+    }
+
+    let mut cells = Vec::with_capacity(8 * ring as usize);
+    for c in (col - ring)..=(col + ring) {
+        cells.push((row - ring, c));
+        cells.push((row + ring, c));
+    }
+    for r in (row - ring + 1)..(row + ring) {
+        cells.push((r, col - ring));
+        cells.push((r, col + ring));
+    }
+    cells
+}
@@ -1,11 +1,22 @@
 mod graph;
 pub use graph::{
     building::{EdgeBuilder, GraphBuilder, NodeBuilder, ProtoEdge, ProtoNode, ProtoShortcut},
+    customizing::CustomizableCh,
+    updating::ProtoChanges,
     EdgeAccessor, EdgeIdx, Graph, HalfEdge, MetricAccessor, MetricIdx, Node, NodeAccessor, NodeIdx,
 };
 
+mod components;
+pub use components::StronglyConnectedComponents;
+
+mod locating;
+pub use locating::{EdgeLocator, NodeLocator};
+
+mod polygons;
+pub use polygons::Polygon;
+
 mod routes;
-pub use routes::RoutePair;
+pub use routes::{CountedRoutePair, Departure, RouteEntry, RoutePair};
 
 /// The street-type, collecting all kind of default-values.
 ///
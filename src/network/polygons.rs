@@ -0,0 +1,92 @@
+use kissunits::geo::Coordinate;
+
+/// A (multi-)polygon as used for clipping, e.g. read from Osmosis' `*.poly`-files.
+///
+/// A coordinate is inside the polygon, if it is inside any outer ring, but not inside any inner ring (hole).
+/// Rings are closed implicitly, so the last coordinate doesn't have to repeat the first one.
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    outer_rings: Vec<Vec<Coordinate>>,
+    inner_rings: Vec<Vec<Coordinate>>,
+}
+
+impl Polygon {
+    pub fn new(outer_rings: Vec<Vec<Coordinate>>, inner_rings: Vec<Vec<Coordinate>>) -> Polygon {
+        Polygon {
+            outer_rings,
+            inner_rings,
+        }
+    }
+
+    pub fn from_bbox(min: Coordinate, max: Coordinate) -> Polygon {
+        Polygon {
+            outer_rings: vec![vec![
+                Coordinate {
+                    lat: min.lat,
+                    lon: min.lon,
+                },
+                Coordinate {
+                    lat: min.lat,
+                    lon: max.lon,
+                },
+                Coordinate {
+                    lat: max.lat,
+                    lon: max.lon,
+                },
+                Coordinate {
+                    lat: max.lat,
+                    lon: min.lon,
+                },
+            ]],
+            inner_rings: Vec::new(),
+        }
+    }
+
+    pub fn contains(&self, coord: &Coordinate) -> bool {
+        self.outer_rings
+            .iter()
+            .any(|ring| Polygon::is_in_ring(ring, coord))
+            && !self
+                .inner_rings
+                .iter()
+                .any(|ring| Polygon::is_in_ring(ring, coord))
+    }
+
+    /// Even-odd-rule via ray-casting along the longitude-axis.
+    /// Boundary-points are counted as inside, which is important for bounding-boxes.
+    fn is_in_ring(ring: &[Coordinate], coord: &Coordinate) -> bool {
+        if ring.len() < 3 {
+            return false;
+        }
+
+        let mut is_inside = false;
+        let mut j = ring.len() - 1;
+        for i in 0..ring.len() {
+            let (a, b) = (&ring[i], &ring[j]);
+
+            if Polygon::is_on_segment(a, b, coord) {
+                return true;
+            }
+
+            if (a.lat > coord.lat) != (b.lat > coord.lat) {
+                let lon_at_lat = a.lon + (coord.lat - a.lat) * (b.lon - a.lon) / (b.lat - a.lat);
+                if coord.lon < lon_at_lat {
+                    is_inside = !is_inside;
+                }
+            }
+
+            j = i;
+        }
+
+        is_inside
+    }
+
+    fn is_on_segment(a: &Coordinate, b: &Coordinate, coord: &Coordinate) -> bool {
+        let cross = (b.lon - a.lon) * (coord.lat - a.lat) - (b.lat - a.lat) * (coord.lon - a.lon);
+        cross == 0.0
+            && a.lat.min(b.lat) <= coord.lat
+            && coord.lat <= a.lat.max(b.lat)
+            && a.lon.min(b.lon) <= coord.lon
+            && coord.lon <= a.lon.max(b.lon)
+    }
+}
@@ -1,11 +1,17 @@
 use crate::network::{Graph, Node, NodeIdx};
+use kissunits::time::Seconds;
+use rand::{distributions::Uniform, Rng};
+use std::fmt::{self, Display};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct RoutePair<T> {
     pub src: T,
     pub dst: T,
 }
 
+/// A route-pair with its number of routes and their optional departure, as given by route-pairs-files.
+pub type CountedRoutePair = (RoutePair<i64>, usize, Option<Departure>);
+
 // impl<T> Into<(T, T)> for RoutePair<T> {
 //     fn into(self) -> (T, T) {
 //         (self.src, self.dst)
@@ -51,3 +57,55 @@ impl RoutePair<NodeIdx> {
         }
     }
 }
+
+/// Departure of a route-pair's routes, given in seconds since the simulation's start.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Departure {
+    /// Every route departs at this time.
+    At(Seconds),
+    /// Every route departs at a uniformly distributed time in `[start, end)`.
+    Within { start: Seconds, end: Seconds },
+}
+
+/// Formats as `t` or `t0-t1` (in seconds), like in route-pairs-files.
+impl Display for Departure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Departure::At(time) => write!(f, "{}", time.0),
+            Departure::Within { start, end } => write!(f, "{}-{}", start.0, end.0),
+        }
+    }
+}
+
+impl Departure {
+    /// Returns the departure of a single route.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Seconds {
+        match self {
+            Departure::At(time) => *time,
+            Departure::Within { start, end } => Seconds(rng.sample(Uniform::new(start.0, end.0))),
+        }
+    }
+}
+
+/// A route-pair of a route-pairs-file with its optional data, which is carried through to output-files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteEntry {
+    pub route_pair: RoutePair<i64>,
+    pub count: usize,
+    pub departure: Option<Departure>,
+    pub label: Option<String>,
+    /// Further columns of csv-files as (column-name, value)
+    pub metadata: Vec<(String, String)>,
+}
+
+impl RouteEntry {
+    pub fn new(route_pair: RoutePair<i64>, count: usize) -> RouteEntry {
+        RouteEntry {
+            route_pair,
+            count,
+            departure: None,
+            label: None,
+            metadata: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use super::dijkstra::{self, Dijkstra};
+use crate::{
+    configs::{
+        self,
+        assigning::{Bpr, Method},
+        parsing::edges::metrics::{Dimension, UnitInfo},
+    },
+    defaults,
+    helpers::err,
+    network::{EdgeIdx, Graph, MetricIdx, NodeIdx, RoutePair},
+};
+use kissunits::speed::KilometersPerHour;
+use log::{info, warn};
+use rayon::prelude::*;
+use smallvec::smallvec;
+
+/// Result of a static traffic-assignment, where all values are given per edge-idx.
+#[derive(Clone, Debug)]
+pub struct Assignment {
+    /// Vehicles per hour
+    pub capacities: Vec<f64>,
+    /// Vehicles per hour
+    pub flows: Vec<f64>,
+    pub free_flow_times: Vec<f64>,
+    /// Travel-times under the flows, in the unit of the free-flow-times
+    pub travel_times: Vec<f64>,
+    pub stats: Vec<IterationStats>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IterationStats {
+    pub iteration: usize,
+    /// `(total travel-time - shortest-paths' travel-time) / total travel-time`, which is 0 in the user-equilibrium.
+    pub relative_gap: f64,
+    /// Sum of every edge's flow times its travel-time
+    pub total_travel_time: f64,
+    /// Step-size towards the all-or-nothing-assignment, which is `None` after converging.
+    pub step_size: Option<f64>,
+}
+
+/// Computes the user-equilibrium of the given route-pairs, whose route-counts are interpreted as vehicles per hour.
+///
+/// Routes are computed with Dijkstra on the free-flow-time-metric, which is replaced by the travel-times while assigning.
+/// When finished, the graph's free-flow-times are restored.
+/// Graphs with shortcuts are not supported, because the shortcuts' metrics would be outdated after every iteration.
+pub fn assign(
+    graph: &mut Graph,
+    route_pairs: &[(RoutePair<NodeIdx>, usize)],
+    assigning_cfg: &configs::assigning::Config,
+) -> err::Result<Assignment> {
+    let (time_idx, capacities) = prepare(graph, assigning_cfg)?;
+    let free_flow_times: Vec<f64> = {
+        let metrics = graph.metrics();
+        (0..capacities.len())
+            .map(|edge_idx| metrics.get_value(EdgeIdx(edge_idx), time_idx))
+            .collect()
+    };
+
+    let mut routing_cfg = configs::routing::Config {
+        route_pairs_file: None,
+        routing_algo: configs::routing::RoutingAlgo::Dijkstra,
+        alphas: smallvec![0.0; graph.metrics().dim()],
+        tolerated_scales: smallvec![
+            defaults::routing::TOLERATED_SCALE_INF;
+            graph.metrics().dim()
+        ],
+    };
+    routing_cfg.alphas[*time_idx] = 1.0;
+
+    info!(
+        "START Assign {} route-pairs via {:?} with {:?}",
+        route_pairs.len(),
+        assigning_cfg.method,
+        assigning_cfg.bpr
+    );
+
+    // initial all-or-nothing-assignment with free-flow-times
+    let mut flows = all_or_nothing(graph, route_pairs, &routing_cfg);
+    let mut travel_times = free_flow_times.clone();
+    let mut stats = Vec::new();
+
+    for iteration in 0..assigning_cfg.max_iter {
+        // update travel-times and route with them
+        update_travel_times(
+            &mut travel_times,
+            &free_flow_times,
+            &flows,
+            &capacities,
+            &assigning_cfg.bpr,
+        );
+        set_times(graph, time_idx, &travel_times);
+        let aon_flows = all_or_nothing(graph, route_pairs, &routing_cfg);
+
+        let total_travel_time = dot(&flows, &travel_times);
+        let shortest_travel_time = dot(&aon_flows, &travel_times);
+        let relative_gap = if total_travel_time > 0.0 {
+            (total_travel_time - shortest_travel_time) / total_travel_time
+        } else {
+            0.0
+        };
+        info!(
+            "Iteration {}: relative gap {:.6}, total travel-time {:.3}",
+            iteration, relative_gap, total_travel_time
+        );
+
+        if relative_gap <= assigning_cfg.relative_gap {
+            stats.push(IterationStats {
+                iteration,
+                relative_gap,
+                total_travel_time,
+                step_size: None,
+            });
+            break;
+        }
+
+        let step_size = match assigning_cfg.method {
+            Method::Msa => 1.0 / (iteration + 2) as f64,
+            Method::FrankWolfe => line_search(
+                &flows,
+                &aon_flows,
+                &free_flow_times,
+                &capacities,
+                &assigning_cfg.bpr,
+            ),
+        };
+        stats.push(IterationStats {
+            iteration,
+            relative_gap,
+            total_travel_time,
+            step_size: Some(step_size),
+        });
+
+        for (flow, aon_flow) in flows.iter_mut().zip(aon_flows) {
+            *flow += step_size * (aon_flow - *flow);
+        }
+    }
+
+    let has_converged = matches!(
+        stats.last(),
+        Some(IterationStats {
+            step_size: None,
+            ..
+        })
+    );
+    if !has_converged {
+        warn!(
+            "The assignment hasn't converged within {} iterations.",
+            assigning_cfg.max_iter
+        );
+    }
+
+    // travel-times of the resulting flows
+    update_travel_times(
+        &mut travel_times,
+        &free_flow_times,
+        &flows,
+        &capacities,
+        &assigning_cfg.bpr,
+    );
+    set_times(graph, time_idx, &free_flow_times);
+    info!("FINISHED");
+
+    Ok(Assignment {
+        capacities,
+        flows,
+        free_flow_times,
+        travel_times,
+        stats,
+    })
+}
+
+/// Checks the graph and returns the metric-idx of the free-flow-times and the capacities.
+fn prepare(
+    graph: &Graph,
+    assigning_cfg: &configs::assigning::Config,
+) -> err::Result<(MetricIdx, Vec<f64>)> {
+    let metrics_cfg = &graph.cfg().edges.metrics;
+    if metrics_cfg.are_normalized {
+        return Err("Traffic can't be assigned with normalized metrics.".into());
+    }
+    let time_idx = metrics_cfg.try_idx_of(&assigning_cfg.metrics.free_flow_time)?;
+    let lane_count_idx = metrics_cfg.try_idx_of(&assigning_cfg.metrics.lane_count)?;
+    let kmph_idx = metrics_cfg.try_idx_of(&assigning_cfg.metrics.kmph)?;
+    if metrics_cfg.units[*lane_count_idx] != UnitInfo::LaneCount {
+        return Err(format!(
+            "The metric {} should be a lane-count.",
+            assigning_cfg.metrics.lane_count
+        )
+        .into());
+    }
+    let speed_unit = metrics_cfg.units[*kmph_idx];
+    if speed_unit.dimension() != Dimension::SPEED {
+        return Err(format!(
+            "The metric {} should be a speed, but is given in {:?}.",
+            assigning_cfg.metrics.kmph, speed_unit
+        )
+        .into());
+    }
+
+    let fwd_edges = graph.fwd_edges();
+    if fwd_edges
+        .iter()
+        .any(|edge_idx| fwd_edges.is_shortcut(edge_idx))
+    {
+        return Err("Traffic can't be assigned on graphs with shortcuts.".into());
+    }
+
+    let nodes = graph.nodes();
+    let bwd_edges = graph.bwd_edges();
+    let metrics = graph.metrics();
+    let capacities = fwd_edges
+        .iter()
+        .map(|edge_idx| {
+            let lane_count = metrics.get_value(edge_idx, lane_count_idx);
+            let kmph = KilometersPerHour(speed_unit.convert(
+                &UnitInfo::KilometersPerHour,
+                metrics.get_value(edge_idx, kmph_idx),
+            ));
+            let capacity = lane_count * defaults::assigning::capacity_per_lane(kmph);
+            if capacity > 0.0 {
+                Ok(capacity)
+            } else {
+                Err(format!(
+                    "The edge (id:{}->id:{}) has no positive capacity (lane-count: {}, speed: {}).",
+                    nodes.id(bwd_edges.dst_idx(edge_idx)),
+                    nodes.id(fwd_edges.dst_idx(edge_idx)),
+                    lane_count,
+                    kmph
+                )
+                .into())
+            }
+        })
+        .collect::<err::Result<Vec<f64>>>()?;
+
+    Ok((time_idx, capacities))
+}
+
+/// Assigns every route-pair's routes to its shortest path.
+fn all_or_nothing(
+    graph: &Graph,
+    route_pairs: &[(RoutePair<NodeIdx>, usize)],
+    routing_cfg: &configs::routing::Config,
+) -> Vec<f64> {
+    let edge_count = graph.fwd_edges().count();
+    route_pairs
+        .par_iter()
+        .fold(
+            || (Dijkstra::new(), vec![0.0; edge_count]),
+            |(mut dijkstra, mut flows), (route_pair, route_count)| {
+                let best_path = dijkstra.compute_best_path(dijkstra::Query {
+                    src_idx: route_pair.src,
+                    dst_idx: route_pair.dst,
+                    graph,
+                    routing_cfg,
+                });
+                if let Some(best_path) = best_path {
+                    for edge_idx in &best_path {
+                        flows[**edge_idx] += *route_count as f64;
+                    }
+                }
+                (dijkstra, flows)
+            },
+        )
+        .map(|(_dijkstra, flows)| flows)
+        .reduce(
+            || vec![0.0; edge_count],
+            |mut a, b| {
+                for (a, b) in a.iter_mut().zip(b) {
+                    *a += b;
+                }
+                a
+            },
+        )
+}
+
+fn bpr_time(free_flow_time: f64, flow: f64, capacity: f64, bpr: &Bpr) -> f64 {
+    free_flow_time * (1.0 + bpr.alpha * (flow / capacity).powf(bpr.beta))
+}
+
+fn update_travel_times(
+    travel_times: &mut [f64],
+    free_flow_times: &[f64],
+    flows: &[f64],
+    capacities: &[f64],
+    bpr: &Bpr,
+) {
+    for (edge_idx, travel_time) in travel_times.iter_mut().enumerate() {
+        *travel_time = bpr_time(
+            free_flow_times[edge_idx],
+            flows[edge_idx],
+            capacities[edge_idx],
+            bpr,
+        );
+    }
+}
+
+fn set_times(graph: &mut Graph, time_idx: MetricIdx, times: &[f64]) {
+    let mut metrics = graph.metrics_mut();
+    for (edge_idx, &time) in times.iter().enumerate() {
+        metrics.set_value(EdgeIdx(edge_idx), time_idx, time);
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Finds the step-size minimizing the Beckmann-objective along the direction from the flows to the all-or-nothing-flows.
+///
+/// The objective's derivative `sum(d * t(x + step * d))` increases with the step-size, so its root is found by bisection.
+fn line_search(
+    flows: &[f64],
+    aon_flows: &[f64],
+    free_flow_times: &[f64],
+    capacities: &[f64],
+    bpr: &Bpr,
+) -> f64 {
+    let derivative = |step_size: f64| -> f64 {
+        (0..flows.len())
+            .map(|edge_idx| {
+                let direction = aon_flows[edge_idx] - flows[edge_idx];
+                direction
+                    * bpr_time(
+                        free_flow_times[edge_idx],
+                        flows[edge_idx] + step_size * direction,
+                        capacities[edge_idx],
+                        bpr,
+                    )
+            })
+            .sum()
+    };
+
+    if derivative(1.0) <= 0.0 {
+        return 1.0;
+    }
+    let (mut lower, mut upper) = (0.0, 1.0);
+    for _ in 0..defaults::assigning::LINE_SEARCH_ITER {
+        let mid = (lower + upper) / 2.0;
+        if derivative(mid) > 0.0 {
+            upper = mid;
+        } else {
+            lower = mid;
+        }
+    }
+    (lower + upper) / 2.0
+}
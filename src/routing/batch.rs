@@ -0,0 +1,107 @@
+use super::{
+    dijkstra::{self, Dijkstra},
+    paths::Path,
+};
+use crate::{
+    configs::{self, routing::RoutingAlgo},
+    helpers::err,
+    network::{Graph, NodeIdx, RouteEntry},
+};
+use log::{info, warn};
+use rayon::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Result of a single query of a batch, keeping the route-entry for its label and metadata.
+#[derive(Clone, Debug)]
+pub struct QueryResult {
+    pub entry: RouteEntry,
+    /// The flattened best path, if one has been found.
+    pub path: Option<Path>,
+    pub query_time: Duration,
+}
+
+/// Computes the best path of every route-entry in parallel, where every thread uses its own Dijkstra.
+///
+/// Results are returned in the order of the route-entries,
+/// where route-entries with unknown node-ids are reported as failed queries (without path).
+/// If a thread-pool is given, it is used instead of rayon's global thread-pool.
+pub fn route(
+    entries: Vec<RouteEntry>,
+    graph: &Graph,
+    routing_cfg: &configs::routing::Config,
+    thread_pool: Option<&rayon::ThreadPool>,
+) -> err::Result<Vec<QueryResult>> {
+    match routing_cfg.routing_algo {
+        RoutingAlgo::Dijkstra | RoutingAlgo::CHDijkstra => (),
+        #[cfg(feature = "gpl")]
+        RoutingAlgo::Explorator { algo: _ } => {
+            return Err(err::Msg::from(
+                "Batch-routing supports only Dijkstra and CHDijkstra.",
+            ))
+        }
+    }
+
+    // check ids before routing
+    let nodes = graph.nodes();
+    let queries: Vec<(RouteEntry, Option<(NodeIdx, NodeIdx)>)> = entries
+        .into_iter()
+        .map(|entry| {
+            let route_pair = entry.route_pair;
+            let idxs = match (nodes.idx_from(route_pair.src), nodes.idx_from(route_pair.dst)) {
+                (Ok(src_idx), Ok(dst_idx)) => Some((src_idx, dst_idx)),
+                _ => {
+                    warn!(
+                        "The route-pair from {} to {} is not part of the graph, hence its query fails.",
+                        route_pair.src, route_pair.dst
+                    );
+                    None
+                }
+            };
+            (entry, idxs)
+        })
+        .collect();
+
+    info!("START Route {} route-pairs", queries.len());
+    let work_off = || {
+        queries
+            .into_par_iter()
+            .map_init(Dijkstra::new, |dijkstra, (entry, idxs)| {
+                let (src_idx, dst_idx) = match idxs {
+                    Some(idxs) => idxs,
+                    None => {
+                        return Ok(QueryResult {
+                            entry,
+                            path: None,
+                            query_time: Duration::from_secs(0),
+                        })
+                    }
+                };
+
+                let now = Instant::now();
+                let path = dijkstra.compute_best_path(dijkstra::Query {
+                    src_idx,
+                    dst_idx,
+                    graph,
+                    routing_cfg,
+                });
+                let query_time = now.elapsed();
+
+                Ok(QueryResult {
+                    entry,
+                    path: match path {
+                        Some(path) => Some(path.try_flatten(graph)?),
+                        None => None,
+                    },
+                    query_time,
+                })
+            })
+            .collect::<err::Result<Vec<QueryResult>>>()
+    };
+    let results = match thread_pool {
+        Some(thread_pool) => thread_pool.install(work_off),
+        None => work_off(),
+    }?;
+    info!("FINISHED");
+
+    Ok(results)
+}
@@ -0,0 +1,145 @@
+use super::paths::Path;
+use crate::{
+    configs::balancing::RouteChoice,
+    helpers,
+    network::{EdgeIdx, Graph},
+};
+use rand::{
+    distributions::{Distribution, Uniform, WeightedIndex},
+    Rng,
+};
+use std::{cmp::Ordering, collections::HashMap};
+
+/// Paths have to be flattened (e.g. via `path.flatten(...)`) and have to connect the same route-pair.
+#[derive(Copy, Clone)]
+pub struct Query<'a> {
+    pub paths: &'a [Path],
+    pub graph: &'a Graph,
+    pub alphas: &'a [f64],
+}
+
+/// Returns the probability of being chosen for every path.
+pub fn probabilities(query: Query, route_choice: &RouteChoice) -> Vec<f64> {
+    if query.paths.is_empty() {
+        return Vec::new();
+    }
+
+    let costs: Vec<f64> = query
+        .paths
+        .iter()
+        .map(|path| helpers::dot_product(query.alphas, path.costs()))
+        .collect();
+
+    let (scale, path_sizes) = match route_choice {
+        RouteChoice::Uniform => return vec![1.0 / query.paths.len() as f64; query.paths.len()],
+        RouteChoice::MultinomialLogit { scale } | RouteChoice::CheapestFirst { scale } => {
+            (*scale, None)
+        }
+        RouteChoice::PathSizeLogit { scale } => (*scale, Some(path_sizes(query, &costs))),
+    };
+
+    // costs are relative to the cheapest path to make the scale independent of the metrics' units
+    let min_costs = costs.iter().cloned().fold(f64::INFINITY, f64::min);
+    let utilities: Vec<f64> = costs
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let relative_costs = if min_costs > 0.0 { c / min_costs } else { *c };
+            let path_size = path_sizes.as_ref().map_or(1.0, |path_sizes| path_sizes[i]);
+            -scale * relative_costs + path_size.ln()
+        })
+        .collect();
+
+    // subtract max-utility to prevent overflows
+    let max_utility = utilities.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = utilities.iter().map(|u| (u - max_utility).exp()).collect();
+    let sum: f64 = weights.iter().sum();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+/// Distributes the given number of routes over the paths and returns the number of routes per path.
+pub fn choose<R: Rng>(
+    query: Query,
+    route_choice: &RouteChoice,
+    route_count: usize,
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut route_counts = vec![0; query.paths.len()];
+    if query.paths.is_empty() {
+        return route_counts;
+    }
+
+    match route_choice {
+        RouteChoice::Uniform => {
+            let die = Uniform::from(0..query.paths.len());
+            for _ in 0..route_count {
+                route_counts[die.sample(rng)] += 1;
+            }
+        }
+        RouteChoice::MultinomialLogit { scale: _ } | RouteChoice::PathSizeLogit { scale: _ } => {
+            let die = WeightedIndex::new(probabilities(query, route_choice))
+                .expect("Probabilities of found paths should be valid weights.");
+            for _ in 0..route_count {
+                route_counts[die.sample(rng)] += 1;
+            }
+        }
+        RouteChoice::CheapestFirst { scale: _ } => {
+            let probabilities = probabilities(query, route_choice);
+            let mut remaining_count = route_count;
+            for (i, p) in probabilities.iter().enumerate() {
+                route_counts[i] = (p * route_count as f64).floor() as usize;
+                remaining_count = remaining_count.saturating_sub(route_counts[i]);
+            }
+
+            // stable sort keeps equally expensive paths in order
+            let mut order: Vec<usize> = (0..query.paths.len()).collect();
+            order.sort_by(|&i, &j| {
+                let costs_i = helpers::dot_product(query.alphas, query.paths[i].costs());
+                let costs_j = helpers::dot_product(query.alphas, query.paths[j].costs());
+                costs_i.partial_cmp(&costs_j).unwrap_or(Ordering::Equal)
+            });
+            for &i in order.iter().cycle().take(remaining_count) {
+                route_counts[i] += 1;
+            }
+        }
+    }
+
+    route_counts
+}
+
+/// The path-size of a path is the sum of its edges' cost-fractions, where every edge's fraction is divided by the number of paths using this edge.
+/// Hence, a path not overlapping with other paths has path-size 1.
+fn path_sizes(query: Query, costs: &[f64]) -> Vec<f64> {
+    let mut edge_usages: HashMap<EdgeIdx, usize> = HashMap::new();
+    for path in query.paths {
+        for &edge_idx in path {
+            *edge_usages.entry(edge_idx).or_insert(0) += 1;
+        }
+    }
+
+    let metrics = query.graph.metrics();
+    query
+        .paths
+        .iter()
+        .zip(costs.iter())
+        .map(|(path, &path_costs)| {
+            let hop_count = path.iter().len();
+            if hop_count == 0 {
+                1.0
+            } else if path_costs > 0.0 {
+                path.iter()
+                    .map(|edge_idx| {
+                        metrics.dot_product(*edge_idx, query.alphas)
+                            / path_costs
+                            / edge_usages[edge_idx] as f64
+                    })
+                    .sum()
+            } else {
+                // without costs, every edge counts the same
+                path.iter()
+                    .map(|edge_idx| 1.0 / hop_count as f64 / edge_usages[edge_idx] as f64)
+                    .sum()
+            }
+        })
+        .collect()
+}
@@ -2,7 +2,6 @@ use super::paths::Path;
 use crate::{
     configs::routing::{Config, RoutingAlgo},
     defaults::routing::IS_USING_CH_LEVEL_SPEEDUP,
-    helpers,
     network::{EdgeIdx, Graph, NodeIdx},
 };
 use std::{cmp::Reverse, collections::BinaryHeap};
@@ -273,8 +272,7 @@ impl Dijkstra {
                     }
                 }
 
-                let new_cost = current.cost
-                    + helpers::dot_product(&query.routing_cfg.alphas, &leaving_edge.metrics());
+                let new_cost = current.cost + leaving_edge.dot_product(&query.routing_cfg.alphas);
                 if new_cost < self.costs[dir][*leaving_edge.dst_idx()] {
                     self.predecessors[dir][*leaving_edge.dst_idx()] = Some(leaving_edge.idx());
                     self.costs[dir][*leaving_edge.dst_idx()] = new_cost;
@@ -1,5 +1,9 @@
+pub mod assigning;
+pub mod batch;
 pub mod dijkstra;
 pub mod paths;
 
+#[cfg(feature = "gpl")]
+pub mod choosing;
 #[cfg(feature = "gpl")]
 pub mod explorating;
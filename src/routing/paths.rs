@@ -76,9 +76,9 @@ impl Path {
             self.costs = Some(
                 self.edges
                     .iter()
-                    .map(|edge_idx| &graph_metrics[edge_idx])
+                    .map(|&edge_idx| graph_metrics.get(edge_idx))
                     .fold(smallvec![0.0; graph_metrics.dim()], |acc, m| {
-                        helpers::add(&acc, m)
+                        helpers::add(&acc, &m)
                     }),
             );
         }
@@ -125,7 +125,7 @@ impl Path {
                     .costs
                     .as_mut()
                     .expect("Flattened path should have calculated costs."),
-                &graph.metrics()[edge_idx],
+                &graph.metrics().get(edge_idx),
             );
         }
 
@@ -0,0 +1,135 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::bidirectional_bait as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs,
+    network::{Graph, NodeIdx, RoutePair},
+    routing::assigning::{self, Assignment},
+};
+
+/// All vehicles drive from left to right.
+fn route_pairs(graph: &Graph, vehicle_count: usize) -> Vec<(RoutePair<NodeIdx>, usize)> {
+    let nodes = graph.nodes();
+    vec![(
+        RoutePair {
+            src: nodes.idx_from(0).expect("Left node should exist."),
+            dst: nodes.idx_from(2).expect("Right node should exist."),
+        },
+        vehicle_count,
+    )]
+}
+
+/// Returns the flow and the travel-time of the path along the given node-ids.
+fn path_values(graph: &Graph, assignment: &Assignment, node_ids: &[i64]) -> (f64, f64) {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let edge_indices: Vec<_> = node_ids
+        .windows(2)
+        .map(|pair| {
+            fwd_edges
+                .between(
+                    nodes.idx_from(pair[0]).expect("Node should exist."),
+                    nodes.idx_from(pair[1]).expect("Node should exist."),
+                )
+                .expect("Edge should exist.")
+                .idx()
+        })
+        .collect();
+
+    // flows are equal along a path, since no other paths exist
+    let flow = assignment.flows[*edge_indices[0]];
+    for edge_idx in &edge_indices {
+        assert!(Approx(flow) == Approx(assignment.flows[**edge_idx]));
+    }
+    let travel_time = edge_indices
+        .iter()
+        .map(|edge_idx| assignment.travel_times[**edge_idx])
+        .sum();
+    (flow, travel_time)
+}
+
+fn assert_user_equilibrium(
+    vehicle_count: usize,
+    assigning_cfg: &configs::assigning::Config,
+    tolerance: f64,
+) {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::ASSIGNING_YAML);
+    let mut graph = parse(parsing_cfg);
+    let route_pairs = route_pairs(&graph, vehicle_count);
+
+    let assignment = match assigning::assign(&mut graph, &route_pairs, assigning_cfg) {
+        Ok(assignment) => assignment,
+        Err(msg) => panic!("Could not assign traffic. ERROR: {}", msg),
+    };
+
+    // converged
+    let last_stats = assignment.stats.last().expect("Stats should be given.");
+    assert!(last_stats.step_size.is_none(), "{:?}", assignment.stats);
+    assert!(last_stats.relative_gap <= assigning_cfg.relative_gap);
+
+    // both paths are used and take the same time
+    let (top_flow, top_time) = path_values(&graph, &assignment, &[0, 4, 3, 2]);
+    let (bottom_flow, bottom_time) = path_values(&graph, &assignment, &[0, 1, 2]);
+    assert!(Approx(top_flow + bottom_flow) == Approx(vehicle_count as f64));
+    assert!(top_flow > 0.0 && bottom_flow > 0.0);
+    assert!(
+        (top_time - bottom_time).abs() <= tolerance * top_time,
+        "Paths should take the same time, but take {} and {}.",
+        top_time,
+        bottom_time
+    );
+
+    // free-flow-times are restored
+    let time_idx = graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID);
+    let metrics = graph.metrics();
+    for edge_idx in &graph.fwd_edges() {
+        assert_eq!(
+            metrics.get_value(edge_idx, time_idx),
+            assignment.free_flow_times[*edge_idx]
+        );
+        assert!(assignment.travel_times[*edge_idx] >= assignment.free_flow_times[*edge_idx]);
+    }
+}
+
+#[test]
+fn assigning_yaml() {
+    let assigning_cfg = configs::assigning::Config::from_yaml(resources::ASSIGNING_YAML);
+    assert_eq!(assigning_cfg.method, configs::assigning::Method::FrankWolfe);
+    assert_eq!(assigning_cfg.max_iter, 100);
+    assert!(configs::assigning::Config::try_from_yaml(resources::FMI_YAML).is_err());
+}
+
+#[test]
+fn single_vehicle_takes_fastest_path() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::ASSIGNING_YAML);
+    let mut graph = parse(parsing_cfg);
+    let assigning_cfg = configs::assigning::Config::from_yaml(resources::ASSIGNING_YAML);
+    let route_pairs = route_pairs(&graph, 1);
+
+    let assignment = assigning::assign(&mut graph, &route_pairs, &assigning_cfg)
+        .expect("Traffic should be assigned.");
+
+    // without congestion, all-or-nothing is the user-equilibrium
+    assert_eq!(assignment.stats.len(), 1);
+    assert!(Approx(assignment.stats[0].relative_gap) == Approx(0.0));
+    let (top_flow, _) = path_values(&graph, &assignment, &[0, 4, 3, 2]);
+    let (bottom_flow, _) = path_values(&graph, &assignment, &[0, 1, 2]);
+    assert!(Approx(top_flow) == Approx(1.0));
+    assert!(Approx(bottom_flow) == Approx(0.0));
+}
+
+#[test]
+fn frank_wolfe() {
+    let assigning_cfg = configs::assigning::Config::from_yaml(resources::ASSIGNING_YAML);
+    assert_user_equilibrium(3_000, &assigning_cfg, 0.001);
+}
+
+#[test]
+fn msa() {
+    let mut assigning_cfg = configs::assigning::Config::from_yaml(resources::ASSIGNING_YAML);
+    assigning_cfg.method = configs::assigning::Method::Msa;
+    // msa converges slowly
+    assigning_cfg.relative_gap = 0.001;
+    assigning_cfg.max_iter = 1_000;
+    assert_user_equilibrium(3_000, &assigning_cfg, 0.05);
+}
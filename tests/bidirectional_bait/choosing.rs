@@ -0,0 +1,164 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::bidirectional_bait as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, balancing::RouteChoice},
+    defaults::capacity::DimVec,
+    network::{Graph, NodeIdx},
+    routing::{choosing, paths::Path},
+};
+use rand::SeedableRng;
+use smallvec::smallvec;
+
+/// Returns the flattened path along the given node-ids.
+fn path_of(graph: &Graph, node_ids: &[i64]) -> Path {
+    let nodes = graph.nodes();
+    let fwd_edges = graph.fwd_edges();
+    let node_indices: Vec<NodeIdx> = node_ids
+        .iter()
+        .map(|&id| nodes.idx_from(id).expect("Node-id should exist."))
+        .collect();
+    let edges = node_indices
+        .windows(2)
+        .map(|pair| {
+            fwd_edges
+                .between(pair[0], pair[1])
+                .expect("Edge should exist.")
+                .idx()
+        })
+        .collect();
+    let src_idx = node_indices[0];
+    let dst_idx = node_indices[node_indices.len() - 1];
+    Path::new(
+        src_idx,
+        nodes.id(src_idx),
+        dst_idx,
+        nodes.id(dst_idx),
+        edges,
+    )
+    .flatten(graph)
+}
+
+/// Returns the graph and its paths from left to right, top (cheaper) before bottom.
+fn setup() -> (Graph, Vec<Path>, DimVec<f64>) {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let graph = parse(parsing_cfg);
+
+    let mut alphas: DimVec<f64> = smallvec![0.0; graph.metrics().dim()];
+    alphas[*graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID)] = 1.0;
+
+    let paths = vec![path_of(&graph, &[0, 4, 3, 2]), path_of(&graph, &[0, 1, 2])];
+    (graph, paths, alphas)
+}
+
+fn assert_probabilities(expected: &[f64], actual: &[f64]) {
+    assert_eq!(expected.len(), actual.len());
+    for (e, a) in expected.iter().zip(actual) {
+        assert!(
+            Approx(e) == Approx(a),
+            "Expected probabilities {:?}, but got {:?}",
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn uniform() {
+    let (graph, paths, alphas) = setup();
+    let query = choosing::Query {
+        paths: &paths,
+        graph: &graph,
+        alphas: &alphas,
+    };
+    assert_probabilities(
+        &[0.5, 0.5],
+        &choosing::probabilities(query, &RouteChoice::Uniform),
+    );
+
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(42);
+    let route_counts = choosing::choose(query, &RouteChoice::Uniform, 100, &mut rng);
+    assert_eq!(route_counts.iter().sum::<usize>(), 100);
+}
+
+#[test]
+fn multinomial_logit() {
+    let (graph, paths, alphas) = setup();
+    let query = choosing::Query {
+        paths: &paths,
+        graph: &graph,
+        alphas: &alphas,
+    };
+
+    // bottom path has 10/9 relative costs
+    // -> with scale 9, top path is e times more likely
+    let e = std::f64::consts::E;
+    assert_probabilities(
+        &[e / (1.0 + e), 1.0 / (1.0 + e)],
+        &choosing::probabilities(query, &RouteChoice::MultinomialLogit { scale: 9.0 }),
+    );
+    assert_probabilities(
+        &[0.5, 0.5],
+        &choosing::probabilities(query, &RouteChoice::MultinomialLogit { scale: 0.0 }),
+    );
+
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(42);
+    let route_counts = choosing::choose(
+        query,
+        &RouteChoice::MultinomialLogit { scale: 1000.0 },
+        100,
+        &mut rng,
+    );
+    assert_eq!(route_counts, vec![100, 0]);
+}
+
+#[test]
+fn path_size_logit() {
+    let (graph, mut paths, alphas) = setup();
+    // top path twice
+    paths.insert(1, paths[0].clone());
+    let query = choosing::Query {
+        paths: &paths,
+        graph: &graph,
+        alphas: &alphas,
+    };
+
+    // overlapping paths share their probability
+    assert_probabilities(
+        &[0.25, 0.25, 0.5],
+        &choosing::probabilities(query, &RouteChoice::PathSizeLogit { scale: 0.0 }),
+    );
+    assert_probabilities(
+        &[1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0],
+        &choosing::probabilities(query, &RouteChoice::MultinomialLogit { scale: 0.0 }),
+    );
+}
+
+#[test]
+fn cheapest_first() {
+    let (graph, paths, alphas) = setup();
+    let query = choosing::Query {
+        paths: &paths,
+        graph: &graph,
+        alphas: &alphas,
+    };
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(42);
+
+    // a single route takes the cheapest path, even if both paths are equally likely
+    let route_choice = RouteChoice::CheapestFirst { scale: 0.0 };
+    assert_eq!(
+        choosing::choose(query, &route_choice, 1, &mut rng),
+        vec![1, 0]
+    );
+    assert_eq!(
+        choosing::choose(query, &route_choice, 4, &mut rng),
+        vec![2, 2]
+    );
+
+    // 7.31 and 2.69 routes
+    let route_choice = RouteChoice::CheapestFirst { scale: 9.0 };
+    assert_eq!(
+        choosing::choose(query, &route_choice, 10, &mut rng),
+        vec![8, 2]
+    );
+}
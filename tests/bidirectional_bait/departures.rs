@@ -0,0 +1,125 @@
+use crate::helpers::{defaults, temp_file};
+use defaults::paths::resources::bidirectional_bait as resources;
+use kissunits::time::Seconds;
+use osmgraphing::{
+    configs::{self, routing::RoutingAlgo},
+    io,
+    network::Departure,
+};
+use rand::SeedableRng;
+use smallvec::smallvec;
+use std::{fs, path::PathBuf};
+
+fn routing_cfg(route_pairs_file: &str) -> configs::routing::Config {
+    configs::routing::Config {
+        route_pairs_file: Some(PathBuf::from(route_pairs_file)),
+        routing_algo: RoutingAlgo::Dijkstra,
+        alphas: smallvec![1.0],
+        tolerated_scales: smallvec![std::f64::INFINITY],
+    }
+}
+
+#[test]
+fn route_pairs_with_departures() {
+    let route_pairs =
+        io::routing::Parser::parse_with_departures(&routing_cfg(resources::DEPARTURES_ROUTE_PAIRS))
+            .expect("Route-pairs with departures should be parsable.");
+
+    let expected = vec![
+        (0, 2, 10, None),
+        (0, 2, 5, Some(Departure::At(Seconds(900.0)))),
+        (
+            2,
+            0,
+            3,
+            Some(Departure::Within {
+                start: Seconds(0.0),
+                end: Seconds(1800.5),
+            }),
+        ),
+        (4, 1, 1, Some(Departure::At(Seconds(3600.0)))),
+    ];
+    assert_eq!(route_pairs.len(), expected.len());
+    for ((route_pair, route_count, departure), (src, dst, count, expected_departure)) in
+        route_pairs.into_iter().zip(expected)
+    {
+        assert_eq!((route_pair.src, route_pair.dst), (src, dst));
+        assert_eq!(route_count, count);
+        assert_eq!(departure, expected_departure);
+    }
+}
+
+#[test]
+fn route_pairs_without_departures() {
+    let route_pairs = io::routing::Parser::parse(&routing_cfg(resources::DEPARTURES_ROUTE_PAIRS))
+        .expect("Departures should be ignorable.");
+    let route_counts: Vec<_> = route_pairs
+        .iter()
+        .map(|(_route_pair, route_count)| *route_count)
+        .collect();
+    assert_eq!(route_counts, vec![10, 5, 3, 1]);
+}
+
+#[test]
+fn empty_departure_period() {
+    assert!(io::routing::Parser::parse_with_departures(&routing_cfg(
+        resources::INVALID_DEPARTURES_ROUTE_PAIRS
+    ))
+    .is_err());
+}
+
+#[test]
+fn departure_notations() {
+    let file = temp_file("notations.route-pairs");
+    let parse = |departure: &str| {
+        fs::write(&file, format!("1\n0 2 1 {}\n", departure))
+            .expect("Temporary route-pairs-file should be writable.");
+        let route_pairs =
+            io::routing::Parser::parse_with_departures(&routing_cfg(&file.to_string_lossy()));
+        route_pairs.map(|route_pairs| route_pairs[0].2).ok()
+    };
+
+    // exponents are no separators
+    assert_eq!(parse("1e-3"), Some(Some(Departure::At(Seconds(0.001)))));
+    assert_eq!(
+        parse("1e-3-1.8e3"),
+        Some(Some(Departure::Within {
+            start: Seconds(0.001),
+            end: Seconds(1800.0)
+        }))
+    );
+    assert_eq!(
+        parse("0-1E+3"),
+        Some(Some(Departure::Within {
+            start: Seconds(0.0),
+            end: Seconds(1000.0)
+        }))
+    );
+    // negative times are rejected
+    for departure in &["-900", "-900-0", "900--1", "900-", "-"] {
+        assert!(
+            parse(departure).is_none(),
+            "{} should be invalid.",
+            departure
+        );
+    }
+
+    fs::remove_file(&file).expect("Temporary route-pairs-file should be removable.");
+}
+
+#[test]
+fn sampled_departures() {
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(42);
+
+    let departure = Departure::At(Seconds(900.0));
+    assert_eq!(departure.sample(&mut rng), Seconds(900.0));
+
+    let departure = Departure::Within {
+        start: Seconds(900.0),
+        end: Seconds(1800.0),
+    };
+    for _ in 0..100 {
+        let time = departure.sample(&mut rng);
+        assert!(Seconds(900.0) <= time && time < Seconds(1800.0));
+    }
+}
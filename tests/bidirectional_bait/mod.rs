@@ -1,2 +1,8 @@
+mod assigning;
+#[cfg(feature = "gpl")]
+mod choosing;
+mod departures;
+#[cfg(feature = "gpl")]
+mod multithreading;
 mod parsing;
 mod routing;
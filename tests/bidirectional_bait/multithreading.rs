@@ -0,0 +1,80 @@
+use crate::helpers::{defaults, parse};
+use defaults::paths::resources::bidirectional_bait as resources;
+use kissunits::time::Seconds;
+use osmgraphing::{
+    balancing::multithreading::{Master, WorkedOff},
+    configs::{self, balancing::RouteChoice},
+    network::{CountedRoutePair, Departure, Graph, NodeIdx, RoutePair},
+};
+use rand::SeedableRng;
+use std::sync::Arc;
+
+/// Works off many route-pairs (more than one work-size), whose departures and route-choices need random numbers.
+fn work_off(arc_graph: &Arc<Graph>, num_threads: usize) -> WorkedOff {
+    // ll -> rr has two pareto-optimal paths wrt kilometers and lane-count (as hop-count)
+    let arc_routing_cfg = Arc::new(configs::routing::Config::from_str(
+        "routing: { algorithm: { Explorator: { algo: 'Dijkstra' } }, \
+         metrics: [{ id: 'kilometers' }, { id: 'lane-count' }] }",
+        arc_graph.cfg(),
+    ));
+    let route_pairs: Vec<CountedRoutePair> = (0..1_000)
+        .map(|i| {
+            (
+                RoutePair {
+                    src: (i % 5) as i64,
+                    dst: ((i / 5 + 1 + i % 4) % 5) as i64,
+                },
+                1 + i % 3,
+                Some(Departure::Within {
+                    start: Seconds(0.0),
+                    end: Seconds(3_600.0),
+                }),
+            )
+        })
+        .filter(|(route_pair, _count, _departure)| route_pair.src != route_pair.dst)
+        .collect();
+
+    let mut master = Master::spawn_some(
+        num_threads,
+        arc_graph,
+        &arc_routing_cfg,
+        &RouteChoice::Uniform,
+    )
+    .expect("Workers should be spawnable.");
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(42);
+    master
+        .work_off(route_pairs, arc_graph, &mut rng, true, Some(Seconds(900.0)))
+        .expect("Route-pairs should be workable.")
+}
+
+#[test]
+fn independent_of_num_threads() {
+    let arc_graph = Arc::new(parse(configs::parsing::Config::from_yaml(
+        resources::ASSIGNING_YAML,
+    )));
+
+    let single_threaded = work_off(&arc_graph, 1);
+    let multi_threaded = work_off(&arc_graph, 4);
+
+    assert_eq!(single_threaded.abs_workloads, multi_threaded.abs_workloads);
+    let single_threaded_paths = single_threaded
+        .chosen_paths
+        .expect("Paths should be collected.");
+    let multi_threaded_paths = multi_threaded
+        .chosen_paths
+        .expect("Paths should be collected.");
+    assert_eq!(single_threaded_paths, multi_threaded_paths);
+
+    // routes of the same route-pair are chosen and depart differently
+    let hop_counts: Vec<usize> = single_threaded_paths
+        .iter()
+        .filter(|(path, _departure)| path.src_idx() == NodeIdx(0) && path.dst_idx() == NodeIdx(2))
+        .map(|(path, _departure)| path.iter().count())
+        .collect();
+    assert!(hop_counts.contains(&2) && hop_counts.contains(&3));
+    let departures: Vec<_> = single_threaded_paths
+        .iter()
+        .map(|(_path, departure)| departure.expect("Routes should have departures."))
+        .collect();
+    assert!(departures.windows(2).any(|pair| pair[0] != pair[1]));
+}
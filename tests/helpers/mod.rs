@@ -6,8 +6,15 @@ use osmgraphing::{
     configs,
     defaults::capacity::DimVec,
     helpers, io,
-    network::{Graph, MetricIdx, RoutePair},
-    routing::dijkstra::{self, Dijkstra},
+    network::{Graph, MetricIdx, NodeIdx, RoutePair},
+    routing::{
+        dijkstra::{self, Dijkstra},
+        paths,
+    },
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
 };
 
 #[allow(dead_code)]
@@ -16,18 +23,40 @@ pub mod defaults {
     pub const DURATION_ID: &str = "hours";
     pub const SPEED_ID: &str = "kmph";
 
+    pub mod node_ids {
+        pub mod simple_stuttgart {
+            pub const OPPENWEILER: i64 = 26033921;
+            pub const BACKNANG: i64 = 26160028;
+            pub const STUTTGART: i64 = 2933335353;
+            pub const WAIBLINGEN: i64 = 252787940;
+            pub const ENDERSBACH: i64 = 298249467;
+            pub const DEAD_END: i64 = 1621605361;
+        }
+    }
+
     pub mod paths {
         pub mod resources {
             pub const DIR: &str = "resources";
 
             pub mod bidirectional_bait {
                 pub const FMI_YAML: &str = "resources/bidirectional_bait/fmi.yaml";
+                pub const ASSIGNING_YAML: &str = "resources/bidirectional_bait/assigning.yaml";
+                pub const DEPARTURES_ROUTE_PAIRS: &str =
+                    "resources/bidirectional_bait/departures.route-pairs";
+                pub const INVALID_DEPARTURES_ROUTE_PAIRS: &str =
+                    "resources/bidirectional_bait/invalid_departures.route-pairs";
             }
 
             pub mod isle_of_man {
                 pub const FMI_YAML: &str = "resources/isle_of_man_2020-03-14/fmi.yaml";
                 pub const CH_FMI_YAML: &str = "resources/isle_of_man_2020-03-14/ch.fmi.yaml";
                 pub const OSM_PBF_YAML: &str = "resources/isle_of_man_2020-03-14/osm.pbf.yaml";
+                pub const CLIPPED_OSM_PBF_YAML: &str =
+                    "resources/isle_of_man_2020-03-14/clipped_osm.pbf.yaml";
+                pub const DOUGLAS_POLY: &str = "resources/isle_of_man_2020-03-14/douglas.poly";
+                pub const PBF_TO_FMI_YAML: &str =
+                    "resources/isle_of_man_2020-03-14/pbf_to_fmi.yaml";
+                pub const CHANGES_OSC: &str = "resources/isle_of_man_2020-03-14/changes.osc";
             }
 
             #[cfg(feature = "custom")]
@@ -51,6 +80,31 @@ pub mod defaults {
                 pub const FMI_YAML: &str = "resources/simple_stuttgart/fmi.yaml";
                 pub const NORMALIZED_FMI_YAML: &str =
                     "resources/simple_stuttgart/normalized_fmi.yaml";
+                pub const CSV_YAML: &str = "resources/simple_stuttgart/csv.yaml";
+                pub const EDGES_INFO_CSV: &str = "resources/simple_stuttgart/edges-info.csv";
+                pub const JOINING_YAML: &str = "resources/simple_stuttgart/joining.yaml";
+                pub const JOINING_CSV: &str = "resources/simple_stuttgart/joining.csv";
+                pub const DIMACS_YAML: &str = "resources/simple_stuttgart/dimacs.yaml";
+                pub const FORMULA_YAML: &str = "resources/simple_stuttgart/formula.yaml";
+                pub const UNITS_YAML: &str = "resources/simple_stuttgart/units.yaml";
+                pub const DIMACS_GR: &str = "resources/simple_stuttgart/graph.gr";
+                pub const DIMACS_CO: &str = "resources/simple_stuttgart/graph.co";
+                pub const GEOJSON_YAML: &str = "resources/simple_stuttgart/geojson.yaml";
+                pub const INVALID_GEOJSON_BBOX_YAML: &str =
+                    "resources/simple_stuttgart/invalid_geojson_bbox.yaml";
+                pub const GRAVITY_YAML: &str = "resources/simple_stuttgart/gravity.yaml";
+                pub const PATHS_YAML: &str = "resources/simple_stuttgart/paths.yaml";
+                pub const QUERIES_YAML: &str = "resources/simple_stuttgart/queries.yaml";
+                pub const SMARTS_YAML: &str = "resources/simple_stuttgart/smarts.yaml";
+                pub const SUMO_YAML: &str = "resources/simple_stuttgart/sumo.yaml";
+                pub const BALANCING_YAML: &str = "resources/simple_stuttgart/balancing.yaml";
+                pub const INVALID_DISTANCE_BINS_YAML: &str =
+                    "resources/simple_stuttgart/invalid_distance_bins.yaml";
+                pub const POPULATION_CSV: &str = "resources/simple_stuttgart/population.csv";
+                pub const OD_CSV: &str = "resources/simple_stuttgart/od.csv";
+                pub const FAR_OD_CSV: &str = "resources/simple_stuttgart/far_od.csv";
+                pub const DEPARTURES_CSV: &str = "resources/simple_stuttgart/departures.csv";
+                pub const POPULATION_ASC: &str = "resources/simple_stuttgart/population.asc";
             }
 
             pub mod small {
@@ -74,6 +128,45 @@ pub fn parse(cfg: configs::parsing::Config) -> Graph {
     }
 }
 
+/// Returns a path in the temporary directory, removing an old file (or dir) of a previous run.
+#[allow(dead_code)]
+pub fn temp_file(name: &str) -> PathBuf {
+    let file = std::env::temp_dir().join(format!("osmgraphing_{}", name));
+    if file.is_dir() {
+        fs::remove_dir_all(&file).expect("Old temporary dir should be removable.");
+    } else if file.exists() {
+        fs::remove_file(&file).expect("Old temporary file should be removable.");
+    }
+    file
+}
+
+/// Returns the content of the written file after removing it.
+#[allow(dead_code)]
+pub fn read_and_remove(file: &Path) -> String {
+    let content = fs::read_to_string(file).expect("Written file should be readable.");
+    fs::remove_file(file).expect("Written file should be removable.");
+    content
+}
+
+/// Returns the shortest path (in kilometers) between the given nodes, which is expected to exist.
+#[allow(dead_code)]
+pub fn best_path(graph: &Graph, src_id: i64, dst_id: i64) -> paths::Path {
+    let nodes = graph.nodes();
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }",
+        graph.cfg(),
+    );
+
+    Dijkstra::new()
+        .compute_best_path(dijkstra::Query {
+            src_idx: nodes.idx_from(src_id).expect("Src should exist."),
+            dst_idx: nodes.idx_from(dst_id).expect("Dst should exist."),
+            graph,
+            routing_cfg: &routing_cfg,
+        })
+        .expect("Path should exist.")
+}
+
 #[allow(dead_code)]
 pub fn test_dijkstra(
     config_file: &str,
@@ -389,3 +482,128 @@ pub fn assert_graph_sloppy(expected_node_count: usize, expected_edge_count: usiz
     //     }
     // }
 }
+
+/// Compares two graphs completely, e.g. when they are built in different ways.
+#[allow(dead_code)]
+pub fn assert_graph_eq(expected: &Graph, actual: &Graph) {
+    let expected_nodes = expected.nodes();
+    let actual_nodes = actual.nodes();
+    assert_eq!(
+        expected_nodes.count(),
+        actual_nodes.count(),
+        "Both graphs should have the same number of nodes."
+    );
+    for idx in &expected_nodes {
+        assert_eq!(expected_nodes.id(idx), actual_nodes.id(idx));
+        assert_eq!(expected_nodes.coord(idx), actual_nodes.coord(idx));
+        assert_eq!(expected_nodes.level(idx), actual_nodes.level(idx));
+    }
+
+    for (expected_xwd_edges, actual_xwd_edges) in vec![
+        (expected.fwd_edges(), actual.fwd_edges()),
+        (expected.bwd_edges(), actual.bwd_edges()),
+    ] {
+        assert_eq!(
+            expected_xwd_edges.count(),
+            actual_xwd_edges.count(),
+            "Both graphs should have the same number of edges."
+        );
+        for idx in &expected_nodes {
+            let expected_leaving_edges: Vec<_> = expected_xwd_edges
+                .starting_from(idx)
+                .map(|edge| (edge.idx(), edge.dst_idx()))
+                .collect();
+            let actual_leaving_edges: Vec<_> = actual_xwd_edges
+                .starting_from(idx)
+                .map(|edge| (edge.idx(), edge.dst_idx()))
+                .collect();
+            assert_eq!(
+                expected_leaving_edges, actual_leaving_edges,
+                "Leaving edges of node-idx {} should be equal.",
+                idx
+            );
+        }
+    }
+
+    let expected_fwd_edges = expected.fwd_edges();
+    let actual_fwd_edges = actual.fwd_edges();
+    let expected_metrics = expected.metrics();
+    let actual_metrics = actual.metrics();
+    for idx in &expected_fwd_edges {
+        assert_eq!(expected_fwd_edges.try_id(idx), actual_fwd_edges.try_id(idx));
+        assert_eq!(
+            expected_fwd_edges.sc_edges(idx),
+            actual_fwd_edges.sc_edges(idx)
+        );
+        assert_eq!(expected_metrics.get(idx), actual_metrics.get(idx));
+    }
+}
+
+/// Compares the costs of Dijkstra and CH-Dijkstra for all given route-pairs.
+#[allow(dead_code)]
+pub fn compare_dijkstras_on_graph(
+    graph: &Graph,
+    alphas: &DimVec<f64>,
+    route_pairs: &[(NodeIdx, NodeIdx)],
+) {
+    let routing_cfg = configs::routing::Config {
+        route_pairs_file: None,
+        routing_algo: configs::routing::RoutingAlgo::Dijkstra,
+        alphas: alphas.clone(),
+        tolerated_scales: alphas
+            .iter()
+            .map(|_| osmgraphing::defaults::routing::TOLERATED_SCALE_INF)
+            .collect(),
+    };
+    let mut ch_routing_cfg = routing_cfg.clone();
+    ch_routing_cfg.routing_algo = configs::routing::RoutingAlgo::CHDijkstra;
+
+    let mut dijkstra = Dijkstra::new();
+    for &(src_idx, dst_idx) in route_pairs {
+        let option_ch_path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg: &ch_routing_cfg,
+        });
+        let option_path = dijkstra.compute_best_path(dijkstra::Query {
+            src_idx,
+            dst_idx,
+            graph,
+            routing_cfg: &routing_cfg,
+        });
+
+        match (option_ch_path, option_path) {
+            (Some(ch_path), Some(path)) => {
+                let ch_cost = helpers::dot_product(alphas, &ch_path.flatten(graph).costs());
+                let cost = helpers::dot_product(alphas, &path.flatten(graph).costs());
+                assert_eq!(
+                    Approx(ch_cost),
+                    Approx(cost),
+                    "CH-Dijkstra's path's cost is different from Dijkstra's path's cost \
+                     from node-idx {} to node-idx {} with alphas {:?}.",
+                    src_idx,
+                    dst_idx,
+                    alphas
+                );
+            }
+            (None, None) => (),
+            (option_ch_path, option_path) => panic!(
+                "CH-Dijkstra's result is {}, while Dijkstra's result is {}. \
+                 Route is from node-idx {} to node-idx {}.",
+                if option_ch_path.is_some() {
+                    "Some"
+                } else {
+                    "None"
+                },
+                if option_path.is_some() {
+                    "Some"
+                } else {
+                    "None"
+                },
+                src_idx,
+                dst_idx
+            ),
+        }
+    }
+}
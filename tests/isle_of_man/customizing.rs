@@ -0,0 +1,37 @@
+use crate::helpers::{compare_dijkstras_on_graph, defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    configs::{self, parsing::storage},
+    defaults::capacity::DimVec,
+    network::{CustomizableCh, NodeIdx},
+};
+use smallvec::smallvec;
+
+#[test]
+fn customized_pbf_graph() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    // customizing keeps the layout
+    parsing_cfg.storage.layout = storage::Layout::Compact;
+    let mut graph = parse(parsing_cfg);
+    let edge_count = graph.fwd_edges().count();
+    let cch = CustomizableCh::preprocess(&graph);
+
+    let mut alphas: DimVec<f64> = smallvec![0.0; graph.metrics().dim()];
+    alphas[*graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID)] = 1.0;
+    if let Err(msg) = graph.customize(&cch, &alphas) {
+        panic!("Could not customize graph. ERROR: {}", msg);
+    }
+    assert!(graph.is_compact());
+    assert!(graph.fwd_edges().count() > edge_count);
+
+    let node_count = graph.nodes().count();
+    let route_pairs: Vec<_> = (0..200)
+        .map(|i| {
+            (
+                NodeIdx((i * 7_919) % node_count),
+                NodeIdx((i * 104_729 + 17) % node_count),
+            )
+        })
+        .collect();
+    compare_dijkstras_on_graph(&graph, &alphas, &route_pairs);
+}
@@ -0,0 +1,93 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{
+        self,
+        parsing::generating::edges::{merge, Category},
+        SimpleId,
+    },
+    network::{EdgeIdx, Graph},
+};
+use std::fs;
+
+fn edge_idx(graph: &Graph, src_id: i64, dst_id: i64) -> EdgeIdx {
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(src_id).expect("Src should exist.");
+    let dst_idx = nodes.idx_from(dst_id).expect("Dst should exist.");
+    graph
+        .fwd_edges()
+        .between(src_idx, dst_idx)
+        .map(|edge| edge.idx())
+        .expect("Edge should exist.")
+}
+
+#[test]
+fn pbf_graph_by_way_ids() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    // way 25985930 (Strawberry Fields) is 283499316 -> 283506183 -> 283512259
+    let nodes = graph.nodes();
+    let src = nodes.coord(nodes.idx_from(283499316).expect("Node should exist."));
+    let dst = nodes.coord(nodes.idx_from(283506183).expect("Node should exist."));
+    let file = temp_file("isle_of_man_way_ids.csv");
+    fs::write(
+        &file,
+        format!(
+            "way-id,latitude,longitude,kmph\n25985930,{},{},7\n",
+            (src.lat + dst.lat) / 2.0,
+            (src.lon + dst.lon) / 2.0
+        ),
+    )
+    .expect("Temporary file should be writable.");
+
+    let mut merging_cfg = parsing_cfg;
+    let id = |id: &str| SimpleId::from(id);
+    merging_cfg
+        .generating
+        .as_mut()
+        .expect("Pbf-yaml should generate edge-data.")
+        .edges
+        .categories
+        .push(Category::Merge {
+            from: file.clone(),
+            is_file_with_header: true,
+            key: merge::Key::Way {
+                way_id: id("way-id"),
+                lat: id("latitude"),
+                lon: id("longitude"),
+            },
+            policies: merge::Policies {
+                unmatched_rows: merge::Policy::Error,
+                unmatched_edges: merge::Policy::Ignore,
+                duplicates: merge::Policy::Error,
+            },
+            edges_info: vec![
+                merge::Category::Id(id("way-id")),
+                merge::Category::Id(id("latitude")),
+                merge::Category::Id(id("longitude")),
+                merge::Category::Id(id(defaults::SPEED_ID)),
+            ],
+            csv: Some(configs::csv::Config::default()),
+        });
+    let merged_graph = parse(merging_cfg);
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+
+    let kmph_idx = merged_graph.cfg().edges.metrics.idx_of(defaults::SPEED_ID);
+    let kmph = |src_id, dst_id| {
+        merged_graph
+            .metrics()
+            .get_value(edge_idx(&merged_graph, src_id, dst_id), kmph_idx)
+    };
+    // both directions of the nearest segment
+    assert!(Approx(kmph(283499316, 283506183)) == Approx(7.0));
+    assert!(Approx(kmph(283506183, 283499316)) == Approx(7.0));
+    // other segments of the way are not merged
+    let old_kmph = graph.metrics().get_value(
+        edge_idx(&graph, 283506183, 283512259),
+        graph.cfg().edges.metrics.idx_of(defaults::SPEED_ID),
+    );
+    assert!(Approx(kmph(283506183, 283512259)) == Approx(old_kmph));
+    assert!(Approx(old_kmph) != Approx(7.0));
+}
@@ -1,2 +1,5 @@
+mod customizing;
+mod merging;
 mod parsing;
 mod routing;
+mod updating;
@@ -1,6 +1,14 @@
-use crate::helpers::{assert_graph_sloppy, defaults, parse};
+use crate::helpers::{assert_graph_eq, assert_graph_sloppy, defaults, parse};
 use defaults::paths::resources::isle_of_man as resources;
-use osmgraphing::configs;
+use osmgraphing::{
+    configs::{
+        self,
+        parsing::{clipping, storage, streaming},
+        writing::routing::NodeWeights,
+    },
+    io,
+};
+use std::{convert::TryFrom, path::PathBuf};
 
 #[test]
 fn pbf_yaml() {
@@ -42,6 +50,140 @@ fn pbf_graph() {
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
 
+#[test]
+fn streamed_pbf_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    // small runs to merge many of them
+    let mut streamed_parsing_cfg = parsing_cfg;
+    streamed_parsing_cfg.streaming = Some(streaming::Config {
+        tmp_dir: std::env::temp_dir(),
+        max_edges_per_run: 10_000,
+    });
+    let streamed_graph = parse(streamed_parsing_cfg);
+
+    let expected_node_count = 30_575;
+    let expected_edge_count = 61_579;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &streamed_graph);
+
+    // streaming should not change the graph
+    assert_graph_eq(&graph, &streamed_graph);
+}
+
+#[test]
+fn compact_pbf_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    let mut compact_parsing_cfg = parsing_cfg;
+    compact_parsing_cfg.storage.layout = storage::Layout::Compact;
+    let compact_graph = parse(compact_parsing_cfg);
+
+    let expected_node_count = 30_575;
+    let expected_edge_count = 61_579;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &compact_graph);
+    assert!(!graph.is_compact());
+    assert!(compact_graph.is_compact());
+
+    // the layout should not change the graph
+    assert_graph_eq(&graph, &compact_graph);
+}
+
+#[test]
+fn compact_f32_pbf_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    let mut compact_parsing_cfg = parsing_cfg;
+    compact_parsing_cfg.storage = storage::Config {
+        layout: storage::Layout::Compact,
+        metric_precision: storage::Precision::F32,
+    };
+    let compact_graph = parse(compact_parsing_cfg);
+    assert!(compact_graph.is_compact());
+
+    // metrics are rounded to f32
+    let metrics = graph.metrics();
+    let compact_metrics = compact_graph.metrics();
+    for edge_idx in &graph.fwd_edges() {
+        for (expected, actual) in metrics
+            .get(edge_idx)
+            .iter()
+            .zip(compact_metrics.get(edge_idx).iter())
+        {
+            assert_eq!(*expected as f32 as f64, *actual);
+        }
+    }
+}
+
+#[test]
+fn wide_f32_storage_cfg() {
+    let proto_cfg = storage::ProtoConfig {
+        layout: Some(storage::Layout::Wide),
+        metric_precision: Some(storage::Precision::F32),
+    };
+    assert!(storage::Config::try_from(proto_cfg).is_err());
+}
+
+#[test]
+fn single_threaded_pbf_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("Thread-pool should be buildable.");
+    let single_threaded_graph = thread_pool.install(|| parse(parsing_cfg));
+
+    // multithreading should not change the graph
+    assert_graph_eq(&graph, &single_threaded_graph);
+}
+
+#[test]
+fn clipped_pbf_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CLIPPED_OSM_PBF_YAML);
+    let graph = parse(parsing_cfg);
+
+    let expected_node_count = 5_623;
+    let expected_edge_count = 11_168;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+}
+
+#[test]
+fn clipped_pbf_graph_keeping_boundary() {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CLIPPED_OSM_PBF_YAML);
+    parsing_cfg
+        .clipping
+        .as_mut()
+        .expect("Clipping-config should exist.")
+        .boundary = clipping::Boundary::Keep;
+    let graph = parse(parsing_cfg);
+
+    let expected_node_count = 5_677;
+    let expected_edge_count = 11_274;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+}
+
+#[test]
+fn poly_clipped_pbf_graph() {
+    // The poly-file describes the same area as the config's bbox.
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CLIPPED_OSM_PBF_YAML);
+    parsing_cfg
+        .clipping
+        .as_mut()
+        .expect("Clipping-config should exist.")
+        .area = clipping::Area::Polygon {
+        file: PathBuf::from(resources::DOUGLAS_POLY),
+    };
+    let graph = parse(parsing_cfg);
+
+    let expected_node_count = 5_623;
+    let expected_edge_count = 11_168;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+}
+
 #[test]
 fn fmi_graph() {
     let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
@@ -61,3 +203,18 @@ fn ch_fmi_graph() {
     let expected_edge_count = 110_619;
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
+
+#[test]
+fn osm_weights() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::OSM_PBF_YAML);
+    let points = io::weights::Parser::parse(&NodeWeights::Osm(parsing_cfg.map_file))
+        .expect("Weights should be parsable.");
+
+    assert!(points
+        .iter()
+        .all(|(_coord, weight)| weight.is_finite() && *weight >= 0.0));
+    let total_weight: f64 = points.iter().map(|(_coord, weight)| weight).sum();
+    // buildings and landuses are weighted by their area, not by their number of nodes
+    assert_eq!(points.len(), 81_523);
+    assert!((total_weight - 41_006.2).abs() < 0.1, "{}", total_weight);
+}
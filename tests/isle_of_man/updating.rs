@@ -0,0 +1,131 @@
+use crate::helpers::{assert_graph_eq, assert_graph_sloppy, defaults, parse};
+use defaults::paths::resources::isle_of_man as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, parsing::storage},
+    io,
+    network::{EdgeIdx, Graph, NodeIdx},
+};
+
+fn edge_idx(graph: &Graph, src_id: i64, dst_id: i64) -> Option<EdgeIdx> {
+    let nodes = graph.nodes();
+    let src_idx = nodes.idx_from(src_id).ok()?;
+    let dst_idx = nodes.idx_from(dst_id).ok()?;
+    let fwd_edges = graph.fwd_edges();
+    let edge_idx = fwd_edges.between(src_idx, dst_idx).map(|edge| edge.idx());
+    edge_idx
+}
+
+#[test]
+fn pbf_graph_with_changes() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::PBF_TO_FMI_YAML);
+    let mut graph = parse(parsing_cfg.clone());
+    let kmph_idx = graph.cfg().edges.metrics.idx_of(defaults::SPEED_ID);
+    let km_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+
+    // modified way
+    let modified_idx = edge_idx(&graph, 283499316, 283506183).expect("Edge should exist.");
+    let modified_id = graph.fwd_edges().id(modified_idx);
+    // edge of moved node
+    let moved_idx = edge_idx(&graph, 283508968, 283513506).expect("Edge should exist.");
+    let moved_id = graph.fwd_edges().id(moved_idx);
+    let moved_km = graph.metrics().get_value(moved_idx, km_idx);
+    // untouched node
+    let untouched_idx = graph
+        .nodes()
+        .idx_from(283508968)
+        .expect("Node should exist.");
+    let node_count = graph.nodes().count();
+    let max_id = graph
+        .fwd_edges()
+        .iter()
+        .map(|edge_idx| graph.fwd_edges().id(edge_idx))
+        .max()
+        .expect("Graph should have edges.");
+
+    let changes = match io::network::osc::Parser::parse(&parsing_cfg, resources::CHANGES_OSC) {
+        Ok(changes) => changes,
+        Err(msg) => panic!("Could not parse {}. ERROR: {}", resources::CHANGES_OSC, msg),
+    };
+    if let Err(msg) = graph.apply_changes(changes, &parsing_cfg) {
+        panic!("Could not apply changes. ERROR: {}", msg);
+    }
+
+    // -4 edges (deleted way), but its nodes are kept
+    // +1 node and +1 edge (created way)
+    let expected_node_count = 30_576;
+    let expected_edge_count = 61_576;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+    assert!(!graph.is_ch_stale());
+
+    // node-indices are stable and new nodes are appended
+    let nodes = graph.nodes();
+    assert_eq!(nodes.idx_from(283508968), Ok(untouched_idx));
+    assert_eq!(nodes.idx_from(-1), Ok(NodeIdx(node_count)));
+    assert!(nodes.iter().all(|node_idx| !nodes.is_removed(node_idx)));
+
+    // offset-ranges contain the edges of their nodes
+    let (fwd_edges, bwd_edges) = (graph.fwd_edges(), graph.bwd_edges());
+    let mut edge_count = 0;
+    for node_idx in nodes.iter() {
+        for edge in fwd_edges.starting_from(node_idx) {
+            assert_eq!(bwd_edges.dst_idx(edge.idx()), node_idx);
+            edge_count += 1;
+        }
+        for edge in bwd_edges.starting_from(node_idx) {
+            assert_eq!(fwd_edges.dst_idx(edge.idx()), node_idx);
+        }
+    }
+    assert_eq!(edge_count, expected_edge_count);
+
+    // deleted way
+    assert!(edge_idx(&graph, 283503782, 283496858).is_none());
+    assert!(edge_idx(&graph, 283496858, 283491014).is_none());
+
+    // modified way keeps its edge-ids, but has a new maxspeed of 20 mph (~30 kmph)
+    let modified_idx = edge_idx(&graph, 283499316, 283506183).expect("Edge should exist.");
+    assert_eq!(graph.fwd_edges().id(modified_idx), modified_id);
+    assert_eq!(
+        Approx(graph.metrics().get_value(modified_idx, kmph_idx)),
+        Approx(30.0)
+    );
+
+    // moved node keeps its edge-ids, but has a new distance
+    let moved_idx = edge_idx(&graph, 283508968, 283513506).expect("Edge should exist.");
+    assert_eq!(graph.fwd_edges().id(moved_idx), moved_id);
+    assert_ne!(
+        Approx(graph.metrics().get_value(moved_idx, km_idx)),
+        Approx(moved_km)
+    );
+
+    // created oneway gets a new edge-id
+    let created_idx = edge_idx(&graph, 283499316, -1).expect("Edge should exist.");
+    assert_eq!(graph.fwd_edges().id(created_idx), max_id + 1);
+    assert!(edge_idx(&graph, -1, 283499316).is_none());
+}
+
+#[test]
+fn compact_pbf_graph_with_changes() {
+    let parse_with_changes = |parsing_cfg: configs::parsing::Config| {
+        let mut graph = parse(parsing_cfg.clone());
+        let changes = match io::network::osc::Parser::parse(&parsing_cfg, resources::CHANGES_OSC) {
+            Ok(changes) => changes,
+            Err(msg) => panic!("Could not parse {}. ERROR: {}", resources::CHANGES_OSC, msg),
+        };
+        if let Err(msg) = graph.apply_changes(changes, &parsing_cfg) {
+            panic!("Could not apply changes. ERROR: {}", msg);
+        }
+        graph
+    };
+
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::PBF_TO_FMI_YAML);
+    let graph = parse_with_changes(parsing_cfg.clone());
+
+    let mut compact_parsing_cfg = parsing_cfg;
+    compact_parsing_cfg.storage.layout = storage::Layout::Compact;
+    let compact_graph = parse_with_changes(compact_parsing_cfg);
+
+    // updating should keep the layout
+    assert!(compact_graph.is_compact());
+    assert_graph_eq(&graph, &compact_graph);
+}
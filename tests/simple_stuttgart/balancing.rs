@@ -0,0 +1,111 @@
+use crate::helpers::{defaults, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::defaults::balancing as balancing_defaults;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Runs the balancer with the given config, resuming the given results-dir if provided.
+fn balance(cfg: &Path, resume_dir: Option<&Path>) -> bool {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_osmgraphing"));
+    cmd.arg("--config").arg(cfg).arg("--balancing");
+    if let Some(resume_dir) = resume_dir {
+        cmd.arg("--resume").arg(resume_dir);
+    }
+    cmd.output()
+        .expect("Balancer should be executable.")
+        .status
+        .success()
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).expect("Dir should be creatable.");
+    for entry in fs::read_dir(from).expect("Dir should be readable.") {
+        let path = entry.expect("Dir-entry should be readable.").path();
+        let new_path = to.join(path.file_name().expect("Dir-entry should have a name."));
+        if path.is_dir() {
+            copy_dir(&path, &new_path);
+        } else {
+            fs::copy(&path, &new_path).expect("File should be copyable.");
+        }
+    }
+}
+
+fn read(file: PathBuf) -> String {
+    fs::read_to_string(&file).unwrap_or_else(|_| panic!("{} should be readable.", file.display()))
+}
+
+#[test]
+fn resuming() {
+    // results are written into a temporary dir
+
+    let results_dir = temp_file("balancing");
+    let cfg = temp_file("balancing.yaml");
+    fs::write(
+        &cfg,
+        read(PathBuf::from(resources::BALANCING_YAML)).replace(
+            "custom/results/simple_stuttgart",
+            &results_dir.to_string_lossy(),
+        ),
+    )
+    .expect("Config should be writable.");
+
+    // uninterrupted run
+
+    assert!(balance(&cfg, None), "Balancing should succeed.");
+    let uninterrupted_dir = fs::read_dir(&results_dir)
+        .expect("Results-dir should be created.")
+        .next()
+        .expect("Results-dir should contain the dated results.")
+        .expect("Dir-entry should be readable.")
+        .path();
+    // nothing to resume after the last iteration
+    assert!(!balance(&cfg, Some(&uninterrupted_dir)));
+    assert!(uninterrupted_dir.join("balanced_graph.fmi").exists());
+
+    // interrupt the third iteration before its checkpoint is written
+
+    let interrupted_dir = results_dir.join("interrupted");
+    copy_dir(&uninterrupted_dir, &interrupted_dir);
+    fs::remove_file(
+        interrupted_dir
+            .join("2")
+            .join(balancing_defaults::checkpoint::DIR)
+            .join(balancing_defaults::checkpoint::files::STATE),
+    )
+    .expect("State of the third iteration should exist.");
+    fs::remove_dir_all(interrupted_dir.join("3")).expect("Last iteration should exist.");
+    assert!(
+        balance(&cfg, Some(&interrupted_dir)),
+        "Resuming should succeed."
+    );
+
+    // resumed workloads equal the uninterrupted ones
+
+    for iter in 0..4 {
+        let abs_workloads = |dir: &Path| {
+            read(
+                dir.join(format!("{}", iter))
+                    .join(balancing_defaults::stats::DIR)
+                    .join(balancing_defaults::stats::files::ABS_WORKLOADS),
+            )
+        };
+        assert_eq!(
+            abs_workloads(&uninterrupted_dir),
+            abs_workloads(&interrupted_dir),
+            "Workloads of iteration {} should be identical.",
+            iter
+        );
+    }
+    for file in &["balanced_graph.fmi", balancing_defaults::files::CONVERGENCE] {
+        assert_eq!(
+            read(uninterrupted_dir.join(file)),
+            read(interrupted_dir.join(file))
+        );
+    }
+
+    fs::remove_dir_all(&results_dir).expect("Results-dir should be removable.");
+    fs::remove_file(&cfg).expect("Config should be removable.");
+}
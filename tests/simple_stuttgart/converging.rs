@@ -0,0 +1,138 @@
+use crate::helpers::{defaults, parse, read_and_remove, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    balancing::converging::{self, IterationStats},
+    configs::{
+        self,
+        balancing::{Plateau, Stopping},
+    },
+};
+use std::fs;
+
+fn stats(iteration: usize, workload_change: Option<f64>, gini: f64) -> IterationStats {
+    IterationStats {
+        iteration,
+        workload_change,
+        max_utilization: 0.5,
+        gini,
+        avg_num_of_found_paths: 1.0,
+    }
+}
+
+#[test]
+fn gini() {
+    assert!(Approx(converging::gini(&[7, 7, 7, 7])) == Approx(0.0));
+    // no workload is balanced
+    assert!(Approx(converging::gini(&[0, 0, 0])) == Approx(0.0));
+    assert!(Approx(converging::gini(&[])) == Approx(0.0));
+
+    // a single spike tends to 1 with (n - 1) / n
+    let mut workloads = vec![0; 1_000];
+    workloads[42] = 100;
+    assert!(Approx(converging::gini(&workloads)) == Approx(0.999));
+    assert!(Approx(converging::gini(&[0, 10])) == Approx(0.5));
+    // independent of the order
+    assert!(Approx(converging::gini(&[3, 1, 2])) == Approx(converging::gini(&[1, 2, 3])));
+}
+
+#[test]
+fn iteration_stats() {
+    let stats = IterationStats::compute(1, &[2, 4, 0], Some(&[2, 4, 0]), 4, 1.0);
+    assert_eq!(stats.workload_change, Some(0.0));
+    assert!(Approx(stats.max_utilization) == Approx(1.0));
+
+    // without previous workloads, no change can be computed
+    let stats = IterationStats::compute(0, &[2, 4, 0], None, 4, 1.0);
+    assert_eq!(stats.workload_change, None);
+
+    // workloads are sorted by their nodes' ids
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let abs_workloads: Vec<usize> = (0..graph.fwd_edges().count()).collect();
+    let workloads = converging::sort_by_node_ids(converging::with_node_ids(&graph, &abs_workloads));
+    assert_eq!(workloads.len(), graph.fwd_edges().count());
+}
+
+#[test]
+fn stopping() {
+    let tolerance = Stopping {
+        tolerance: Some(0.01),
+        plateau: None,
+    };
+    assert!(!converging::is_stopping(&[], &tolerance));
+    assert!(!converging::is_stopping(&[stats(0, None, 0.5)], &tolerance));
+    assert!(!converging::is_stopping(
+        &[stats(0, None, 0.5), stats(1, Some(0.02), 0.4)],
+        &tolerance
+    ));
+    assert!(converging::is_stopping(
+        &[stats(0, None, 0.5), stats(1, Some(0.01), 0.4)],
+        &tolerance
+    ));
+
+    let plateau = Stopping {
+        tolerance: None,
+        plateau: Some(Plateau {
+            num_iter: 2,
+            min_improvement: 0.05,
+        }),
+    };
+    let mut history = vec![stats(0, None, 0.5), stats(1, Some(0.3), 0.3)];
+    // too few iterations for a plateau
+    assert!(!converging::is_stopping(&history, &plateau));
+    history.push(stats(2, Some(0.2), 0.2));
+    assert!(!converging::is_stopping(&history, &plateau));
+    // best gini of the last 2 iterations (0.2) vs before (0.3)
+    history.push(stats(3, Some(0.1), 0.28));
+    assert!(!converging::is_stopping(&history, &plateau));
+    // best gini of the last 2 iterations (0.19) vs before (0.2)
+    history.push(stats(4, Some(0.1), 0.19));
+    assert!(converging::is_stopping(&history, &plateau));
+
+    let never = Stopping {
+        tolerance: None,
+        plateau: None,
+    };
+    assert!(!converging::is_stopping(&history, &never));
+}
+
+#[test]
+fn summary_file() {
+    let results_dir = temp_file("converging");
+    fs::create_dir_all(&results_dir).expect("Temporary results-dir should be creatable.");
+
+    // nothing is written yet
+    assert!(converging::read_and_truncate(3, &results_dir)
+        .expect("Missing summary-files should be ok.")
+        .is_empty());
+
+    for stats in &[
+        stats(0, None, 0.5),
+        stats(1, Some(0.25), 0.4),
+        stats(2, Some(0.125), 0.3),
+    ] {
+        converging::append(stats, &results_dir).expect("Stats should be appendable.");
+    }
+
+    // all iterations are kept
+    let history =
+        converging::read_and_truncate(2, &results_dir).expect("Summary-file should be readable.");
+    let iterations: Vec<_> = history.iter().map(|stats| stats.iteration).collect();
+    assert_eq!(iterations, vec![0, 1, 2]);
+    assert_eq!(history[0].workload_change, None);
+    assert_eq!(history[2].workload_change, Some(0.125));
+    assert!(Approx(history[1].gini) == Approx(0.4));
+
+    // iterations after the given one are removed from the file
+    let history = converging::read_and_truncate(1, &results_dir)
+        .expect("Summary-file should be truncatable.");
+    assert_eq!(history.len(), 2);
+    let history = converging::read_and_truncate(2, &results_dir)
+        .expect("Truncated summary-file should be readable.");
+    assert_eq!(history.len(), 2);
+
+    let summary =
+        read_and_remove(&results_dir.join(osmgraphing::defaults::balancing::files::CONVERGENCE));
+    assert_eq!(summary.lines().count(), 1 + 2);
+    fs::remove_dir(&results_dir).expect("Temporary results-dir should be removable.");
+}
@@ -0,0 +1,136 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, parsing::generating},
+    io,
+    network::Graph,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn kmph(graph: &Graph, edge_id: usize) -> f64 {
+    let metric_idx = graph.cfg().edges.metrics.idx_of(defaults::SPEED_ID);
+    graph
+        .metrics()
+        .get_value(graph.fwd_edges().idx_from(edge_id), metric_idx)
+}
+
+/// Returns the csv-yaml's parsing-config, merging the given file instead of the edges-info.
+fn merging_cfg(file: &Path, csv_cfg: Option<configs::csv::Config>) -> configs::parsing::Config {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::CSV_YAML);
+    for category in parsing_cfg
+        .generating
+        .as_mut()
+        .expect("Csv-yaml should generate edge-data.")
+        .edges
+        .categories
+        .iter_mut()
+    {
+        if let generating::edges::Category::Merge { from, csv, .. } = category {
+            *from = PathBuf::from(file);
+            *csv = csv_cfg;
+        }
+    }
+    parsing_cfg
+}
+
+#[test]
+fn yaml() {
+    let writing_cfg = configs::writing::network::edges::Config::from_yaml(resources::CSV_YAML);
+    assert_eq!(
+        writing_cfg.csv,
+        Some(configs::csv::Config {
+            delimiter: ';',
+            quote: '"'
+        })
+    );
+
+    // whitespace-separated by default
+    let yaml_file = temp_file("whitespace.yaml");
+    fs::write(
+        &yaml_file,
+        "writing: { edges-info: { file: 'edges-info.csv', ids: [] } }",
+    )
+    .expect("Temporary yaml-file should be writable.");
+    let writing_cfg = configs::writing::network::edges::Config::from_yaml(&yaml_file);
+    assert!(writing_cfg.csv.is_none());
+    fs::remove_file(&yaml_file).expect("Temporary yaml-file should be removable.");
+
+    let yaml_file = temp_file("invalid_csv.yaml");
+    fs::write(
+        &yaml_file,
+        "writing: { edges-info: { file: 'edges-info.csv', ids: [], csv: { delimiter: '|', quote: '|' } } }",
+    )
+    .expect("Temporary yaml-file should be writable.");
+    assert!(configs::writing::network::edges::Config::try_from_yaml(&yaml_file).is_err());
+    fs::remove_file(&yaml_file).expect("Temporary yaml-file should be removable.");
+}
+
+#[test]
+fn merging() {
+    // by column-names, with quoted fields and line-breaks
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CSV_YAML));
+    assert!(Approx(kmph(&graph, 4)) == Approx(10.0));
+    assert!(Approx(kmph(&graph, 0)) == Approx(70.0));
+    // not merged
+    assert!(Approx(kmph(&graph, 1)) == Approx(50.0));
+
+    // by column-positions, separated by whitespace
+    let file = temp_file("merging_whitespace.csv");
+    fs::write(&file, "# comment\nkmph edge-id\n20 4\n")
+        .expect("Temporary file should be writable.");
+    let graph = parse(merging_cfg(&file, None));
+    assert!(Approx(kmph(&graph, 4)) == Approx(20.0));
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+
+    // missing column-names
+    let file = temp_file("merging_missing_column.csv");
+    fs::write(&file, "edge-id,speed\n4,20\n").expect("Temporary file should be writable.");
+    let parsing_cfg = merging_cfg(&file, Some(configs::csv::Config::default()));
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+
+    // unclosed quotes
+    let file = temp_file("merging_unclosed_quotes.csv");
+    fs::write(&file, "edge-id,kmph\n4,\"20\n").expect("Temporary file should be writable.");
+    let parsing_cfg = merging_cfg(&file, Some(configs::csv::Config::default()));
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+}
+
+#[test]
+fn writing() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::CSV_YAML));
+
+    let mut writing_cfg = configs::writing::network::edges::Config::from_yaml(resources::CSV_YAML);
+    writing_cfg.file = temp_file("writing_edges-info.csv");
+    io::network::edges::Writer::write(&graph, &writing_cfg)
+        .expect("Edges-info should be writable as csv.");
+    let content = fs::read_to_string(&writing_cfg.file).expect("Written file should be readable.");
+    fs::remove_file(&writing_cfg.file).expect("Written file should be removable.");
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 1 + graph.fwd_edges().count());
+    assert_eq!(lines[0], "edge-id;src-id;_;kmph");
+    assert!(lines.contains(&"4;26160028;_;10"));
+
+    // fields containing the delimiter are quoted
+    let csv_cfg = configs::csv::Config {
+        delimiter: '-',
+        quote: '\'',
+    };
+    writing_cfg.csv = Some(csv_cfg);
+    io::network::edges::Writer::write(&graph, &writing_cfg)
+        .expect("Edges-info should be writable as csv.");
+    let content = fs::read_to_string(&writing_cfg.file).expect("Written file should be readable.");
+    assert!(content.starts_with("'edge-id'-'src-id'-_-kmph\n"));
+
+    // and can be merged again
+    let merged_graph = parse(merging_cfg(&writing_cfg.file, Some(csv_cfg)));
+    fs::remove_file(&writing_cfg.file).expect("Written file should be removable.");
+    for edge_id in 0..graph.fwd_edges().count() {
+        assert!(Approx(kmph(&graph, edge_id)) == Approx(kmph(&merged_graph, edge_id)));
+    }
+}
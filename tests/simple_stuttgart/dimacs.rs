@@ -0,0 +1,217 @@
+use crate::helpers::{defaults, parse, read_and_remove, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::geo::Coordinate;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{self, SimpleId},
+    io,
+    network::{Graph, NodeIdx},
+    routing::dijkstra::{self, Dijkstra},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Returns the node-index of the other graph, whose node has the same coordinate.
+fn same_node(graph: &Graph, node_idx: NodeIdx, other_graph: &Graph) -> NodeIdx {
+    let coord = graph.nodes().coord(node_idx);
+    let other_nodes = other_graph.nodes();
+    other_nodes
+        .iter()
+        .find(|&other_idx| Approx(other_nodes.coord(other_idx)) == Approx(coord))
+        .expect("Both graphs should have the same coordinates.")
+}
+
+/// Asserts, that both graphs have the same nodes and the same kilometers between all nodes.
+fn assert_same_distances(graph: &Graph, other_graph: &Graph) {
+    assert_eq!(graph.nodes().count(), other_graph.nodes().count());
+    assert_eq!(graph.fwd_edges().count(), other_graph.fwd_edges().count());
+
+    let mut dijkstra = Dijkstra::new();
+    let routing_str = "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'kilometers' }] }";
+    let routing_cfg = configs::routing::Config::from_str(routing_str, graph.cfg());
+    let other_routing_cfg = configs::routing::Config::from_str(routing_str, other_graph.cfg());
+    let metric_idx = graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let other_metric_idx = other_graph
+        .cfg()
+        .edges
+        .metrics
+        .idx_of(defaults::DISTANCE_ID);
+
+    for src_idx in graph.nodes().iter() {
+        for dst_idx in graph.nodes().iter() {
+            let costs = dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx,
+                    dst_idx,
+                    graph,
+                    routing_cfg: &routing_cfg,
+                })
+                .map(|mut path| path.calc_costs(graph)[*metric_idx]);
+            let other_costs = dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx: same_node(graph, src_idx, other_graph),
+                    dst_idx: same_node(graph, dst_idx, other_graph),
+                    graph: other_graph,
+                    routing_cfg: &other_routing_cfg,
+                })
+                .map(|mut path| path.calc_costs(other_graph)[*other_metric_idx]);
+            match (costs, other_costs) {
+                (Some(costs), Some(other_costs)) => assert!(
+                    Approx(costs) == Approx(other_costs),
+                    "Costs {} and {} should be equal.",
+                    costs,
+                    other_costs
+                ),
+                (None, None) => (),
+                _ => panic!("Paths should exist in both graphs or in none."),
+            }
+        }
+    }
+}
+
+#[test]
+fn yaml() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::DIMACS_YAML);
+    assert_eq!(
+        parsing_cfg
+            .dimacs
+            .expect("DIMACS-graphs should have a dimacs-config.")
+            .coordinates_file,
+        PathBuf::from(resources::DIMACS_CO)
+    );
+    // coordinates-file defaults to the map-file's path
+    assert_eq!(
+        configs::parsing::dimacs::Config::from(None, Path::new(resources::DIMACS_GR))
+            .coordinates_file,
+        PathBuf::from(resources::DIMACS_CO)
+    );
+    assert!(configs::parsing::Config::from_yaml(resources::FMI_YAML)
+        .dimacs
+        .is_none());
+
+    let writing_cfg = configs::writing::dimacs::Config::from_yaml(resources::DIMACS_YAML);
+    assert_eq!(writing_cfg.metric_id, SimpleId::from("meters"));
+    assert!(configs::writing::dimacs::Config::try_from_yaml(resources::FMI_YAML).is_err());
+}
+
+#[test]
+fn graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::DIMACS_YAML));
+    let fmi_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    let nodes = graph.nodes();
+    let node_idx = nodes.idx_from(1).expect("Node-id 1 should be parsed.");
+    assert_eq!(
+        Approx(nodes.coord(node_idx)),
+        Approx(Coordinate {
+            lat: 48.98401,
+            lon: 9.458919
+        })
+    );
+    assert_same_distances(&fmi_graph, &graph);
+}
+
+#[test]
+fn wrong_counts() {
+    let map_file = temp_file("wrong_counts.gr");
+    let content = fs::read_to_string(resources::DIMACS_GR).expect("DIMACS-file should exist.");
+    fs::write(&map_file, content.replace("p sp 6 13", "p sp 6 14"))
+        .expect("Temporary DIMACS-file should be writable.");
+
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::DIMACS_YAML);
+    parsing_cfg.map_file = map_file.clone();
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+    fs::remove_file(&map_file).expect("Temporary DIMACS-file should be removable.");
+}
+
+#[test]
+fn writing() {
+    let dimacs_graph = parse(configs::parsing::Config::from_yaml(resources::DIMACS_YAML));
+
+    let mut writing_cfg = configs::writing::dimacs::Config::from_yaml(resources::DIMACS_YAML);
+    writing_cfg.graph_file = temp_file("written.gr");
+    writing_cfg.coordinates_file = temp_file("written.co");
+    io::dimacs::Writer::write(&dimacs_graph, &writing_cfg)
+        .expect("Graph should be writable as DIMACS.");
+
+    // parse written files again
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::DIMACS_YAML);
+    parsing_cfg.map_file = writing_cfg.graph_file.clone();
+    parsing_cfg.dimacs = Some(configs::parsing::dimacs::Config {
+        coordinates_file: writing_cfg.coordinates_file.clone(),
+    });
+    let graph = parse(parsing_cfg);
+    assert_same_distances(&dimacs_graph, &graph);
+
+    // DIMACS-ids are kept
+    let arcs = read_and_remove(&writing_cfg.graph_file);
+    let coords = read_and_remove(&writing_cfg.coordinates_file);
+    assert!(arcs.contains("p sp 6 13\n"));
+    assert_eq!(
+        arcs.lines().filter(|line| line.starts_with("a ")).count(),
+        13
+    );
+    assert!(arcs.contains("a 2 3 1069\n"));
+    assert!(coords.contains("p aux sp co 6\n"));
+    assert!(coords.contains("v 3 9418868 48939633\n"));
+
+    // osm-ids are replaced by node-indices
+    let fmi_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    writing_cfg.metric_id = SimpleId::from(defaults::SPEED_ID);
+    io::dimacs::Writer::write(&fmi_graph, &writing_cfg)
+        .expect("Graph should be writable as DIMACS.");
+    let arcs = read_and_remove(&writing_cfg.graph_file);
+    let coords = read_and_remove(&writing_cfg.coordinates_file);
+    // Backnang -> Dead-end with 30 kmph
+    assert!(arcs.contains("a 2 5 30\n"));
+    assert!(coords.contains("v 1 9458919 48984010\n"));
+    assert!(coords.contains("v 5 9418868 48939633\n"));
+}
+
+#[test]
+fn wrong_metric() {
+    let fmi_graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    let mut writing_cfg = configs::writing::dimacs::Config::from_yaml(resources::DIMACS_YAML);
+    writing_cfg.graph_file = temp_file("wrong_metric.gr");
+    writing_cfg.coordinates_file = temp_file("wrong_metric.co");
+    writing_cfg.metric_id = SimpleId::from("unknown");
+    assert!(io::dimacs::Writer::write(&fmi_graph, &writing_cfg).is_err());
+    assert!(!writing_cfg.graph_file.exists() && !writing_cfg.coordinates_file.exists());
+}
+
+#[test]
+fn scaling() {
+    // 400 m are rounded to 0 km
+    let map_file = temp_file("scaling_input.gr");
+    let content = fs::read_to_string(resources::DIMACS_GR).expect("DIMACS-file should exist.");
+    fs::write(&map_file, content.replace("a 2 3 1069", "a 2 3 400"))
+        .expect("Temporary DIMACS-file should be writable.");
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::DIMACS_YAML);
+    parsing_cfg.map_file = map_file.clone();
+    let dimacs_graph = parse(parsing_cfg);
+    fs::remove_file(&map_file).expect("Temporary DIMACS-file should be removable.");
+
+    let mut writing_cfg = configs::writing::dimacs::Config::from_yaml(resources::DIMACS_YAML);
+    assert_eq!(writing_cfg.scale, 1);
+    writing_cfg.graph_file = temp_file("scaling.gr");
+    writing_cfg.coordinates_file = temp_file("scaling.co");
+
+    writing_cfg.metric_id = SimpleId::from("kilometers");
+    assert!(io::dimacs::Writer::write(&dimacs_graph, &writing_cfg).is_err());
+    assert!(!writing_cfg.graph_file.exists() && !writing_cfg.coordinates_file.exists());
+
+    // kilometers are written as meters
+    writing_cfg.scale = 1_000;
+    io::dimacs::Writer::write(&dimacs_graph, &writing_cfg)
+        .expect("Scaled kilometers should be writable as DIMACS.");
+    let arcs = read_and_remove(&writing_cfg.graph_file);
+    read_and_remove(&writing_cfg.coordinates_file);
+    assert!(arcs.contains("a 2 3 400\n"));
+    assert!(arcs.contains("a 1 2 8000\n"));
+
+    writing_cfg.scale = 0;
+    assert!(io::dimacs::Writer::write(&dimacs_graph, &writing_cfg).is_err());
+}
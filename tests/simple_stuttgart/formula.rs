@@ -0,0 +1,179 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{
+        self,
+        parsing::generating::edges::formula::{BinaryOp, Expression},
+        SimpleId,
+    },
+    io,
+    network::Graph,
+};
+use std::fs;
+
+fn value(graph: &Graph, edge_id: usize, metric_id: &str) -> f64 {
+    let metric_idx = graph.cfg().edges.metrics.idx_of(metric_id);
+    graph
+        .metrics()
+        .get_value(graph.fwd_edges().idx_from(edge_id), metric_idx)
+}
+
+/// Returns the formula-yaml's content with the given formula instead of the weighted minutes.
+fn with_formula(unit: &str, id: &str, expression: &str) -> String {
+    let content =
+        fs::read_to_string(resources::FORMULA_YAML).expect("Formula-yaml should be readable.");
+    let new_content = content.replace(
+        "result: { unit: 'Minutes', id: 'weighted-minutes' }\n        expression: 'minutes * (1 + 0.15 * max(0, 3 - lanecount))'",
+        &format!(
+            "result: {{ unit: '{}', id: '{}' }}\n        expression: '{}'",
+            unit, id, expression
+        ),
+    );
+    assert_ne!(new_content, content);
+    new_content
+}
+
+fn parse_content(name: &str, content: &str) -> osmgraphing::helpers::err::Result<Graph> {
+    let yaml_file = temp_file(&format!("formula_{}.yaml", name));
+    fs::write(&yaml_file, content).expect("Temporary yaml-file should be writable.");
+    let parsing_cfg = configs::parsing::Config::try_from_yaml(&yaml_file);
+    fs::remove_file(&yaml_file).expect("Temporary yaml-file should be removable.");
+    io::network::graph::Parser::parse_and_finalize(parsing_cfg?)
+}
+
+#[test]
+fn generating() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FORMULA_YAML));
+
+    // Oppenweiler -> Backnang: 8 km with 50 kmph
+    assert!(Approx(value(&graph, 0, "minutes")) == Approx(9.6));
+    assert!(Approx(value(&graph, 0, "weighted-minutes")) == Approx(9.6 * 1.3));
+    // Backnang -> Waiblingen: 23 km with 120 kmph
+    assert!(Approx(value(&graph, 2, "minutes")) == Approx(11.5));
+
+    // conditionals and constants with units
+    let content = with_formula(
+        "KilometersPerHour",
+        "capped-kmph",
+        "if(kmph > 100[KilometersPerHour] && lanecount < 2, 100[KilometersPerHour], kmph)",
+    );
+    let graph = parse_content("conditional", &content).expect("Formula should be valid.");
+    assert!(Approx(value(&graph, 0, "capped-kmph")) == Approx(50.0));
+    assert!(Approx(value(&graph, 2, "capped-kmph")) == Approx(100.0));
+
+    let content = with_formula("Meters", "detour-meters", "{meters} + 1[Kilometers]");
+    let graph = parse_content("braces", &content).expect("Formula should be valid.");
+    assert!(Approx(value(&graph, 0, "detour-meters")) == Approx(9_000.0));
+}
+
+#[test]
+fn parsing() {
+    let constant = |value| Box::new(Expression::Constant(value));
+    assert_eq!(
+        "1 + 2 * -3".parse::<Expression>().ok(),
+        Some(Expression::Binary {
+            op: BinaryOp::Add,
+            a: constant(1.0),
+            b: Box::new(Expression::Binary {
+                op: BinaryOp::Mul,
+                a: constant(2.0),
+                b: Box::new(Expression::Neg(constant(3.0))),
+            }),
+        })
+    );
+    assert_eq!(
+        "max({free-flow-time}, 1e-3)".parse::<Expression>().ok(),
+        Some(Expression::Max(vec![
+            Expression::Metric(SimpleId::from("free-flow-time")),
+            Expression::Constant(0.001),
+        ]))
+    );
+
+    // displayed expressions can be parsed again
+    for formula in &[
+        "if(kmph > 100[KilometersPerHour] && !(lanecount < 2), min(1, 2), abs(-3))",
+        "{free-flow-time} / max(1e-3, 2) - 4 * 5",
+    ] {
+        let expression = formula
+            .parse::<Expression>()
+            .expect("Formula should be valid.");
+        assert_eq!(
+            expression.to_string().parse::<Expression>().ok(),
+            Some(expression)
+        );
+    }
+
+    for invalid in &[
+        "",
+        "1 +",
+        "max(1",
+        "(1 + 2))",
+        "foo(1)",
+        "1 $ 2",
+        "{free-flow-time",
+        "2[Parsecs]",
+        "if(1 < 2, 3)",
+        "1 2",
+    ] {
+        assert!(
+            invalid.parse::<Expression>().is_err(),
+            "Formula '{}' should be invalid.",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn unit_checking() {
+    for invalid_expression in &[
+        // different dimensions
+        "minutes + kmph",
+        "if(kmph > 100, 100[KilometersPerHour], kmph)",
+        // result is no time
+        "meters",
+        // conditions are no numbers
+        "if(kmph, minutes, minutes)",
+        "(minutes < minutes) * minutes",
+        // unknown metric
+        "seconds",
+    ] {
+        let content = with_formula("Minutes", "weighted-minutes", invalid_expression);
+        assert!(
+            parse_content("invalid", &content).is_err(),
+            "Formula '{}' should be rejected.",
+            invalid_expression
+        );
+    }
+
+    // syntax is checked when reading the config
+    let content = with_formula("Minutes", "weighted-minutes", "minutes *");
+    let yaml_file = temp_file("formula_syntax.yaml");
+    fs::write(&yaml_file, content).expect("Temporary yaml-file should be writable.");
+    assert!(configs::parsing::Config::try_from_yaml(&yaml_file).is_err());
+    fs::remove_file(&yaml_file).expect("Temporary yaml-file should be removable.");
+}
+
+#[test]
+fn non_finite_results() {
+    for expression in &[
+        // division by zero
+        "meters / (kmph - kmph)",
+        // zero divided by zero
+        "(meters - meters) / (kmph - kmph)",
+    ] {
+        let content = with_formula("Minutes", "weighted-minutes", expression);
+        let msg = match parse_content("non_finite", &content) {
+            Ok(_) => panic!(
+                "Formula '{}' shouldn't result in finite values.",
+                expression
+            ),
+            Err(msg) => msg.to_string(),
+        };
+        assert!(
+            msg.contains("weighted-minutes") && msg.contains("edge with id"),
+            "Error '{}' should name the formula and the edge.",
+            msg
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::helpers::{best_path, defaults, parse, read_and_remove, temp_file};
+use defaults::{
+    node_ids::simple_stuttgart::{OPPENWEILER, STUTTGART},
+    paths::resources::simple_stuttgart as resources,
+};
+use osmgraphing::{configs, io};
+use std::path::Path;
+
+/// Reads and removes the written file, checking the FeatureCollection's frame.
+fn read_feature_collection(file: &Path) -> String {
+    let content = read_and_remove(file);
+
+    assert!(
+        content.starts_with("{\"type\": \"FeatureCollection\", \"features\": ["),
+        "GeoJSON should start with a FeatureCollection."
+    );
+    assert!(
+        content.trim_end().ends_with("]}"),
+        "GeoJSON should close the FeatureCollection."
+    );
+    content
+}
+
+fn feature_count(content: &str) -> usize {
+    content.matches("{\"type\": \"Feature\"").count()
+}
+
+#[test]
+fn graph() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    let mut writing_cfg = configs::writing::geojson::Config::from_yaml(resources::GEOJSON_YAML);
+    writing_cfg.file = temp_file("graph.geojson");
+    io::geojson::Writer::write_graph(&graph, &writing_cfg)
+        .expect("Graph should be writable as geojson.");
+    let content = read_feature_collection(&writing_cfg.file);
+
+    // only edges between Backnang, its dead-end, Endersbach and Waiblingen
+    assert_eq!(feature_count(&content), 7);
+    assert!(!content.contains(&format!("\"src-id\": {}", OPPENWEILER)));
+    assert!(!content.contains(&format!("\"dst-id\": {}", STUTTGART)));
+    // Backnang -> Waiblingen, (lon, lat)
+    assert!(content.contains(
+        "\"coordinates\": [[9.433202, 48.941602], [9.309866, 48.82711]]}, \
+         \"properties\": {\"src-id\": 26160028"
+    ));
+    assert!(content.contains("\"src-id\": 26160028, \"dst-id\": 252787940, \"kilometers\": 23, "));
+    assert!(!content.contains("\"meters\""));
+}
+
+#[test]
+fn graph_configs() {
+    assert!(
+        configs::writing::geojson::Config::try_from_yaml(resources::INVALID_GEOJSON_BBOX_YAML)
+            .is_err(),
+        "A bbox with min greater than max should be rejected."
+    );
+}
+
+#[test]
+fn paths() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let path = best_path(&graph, OPPENWEILER, STUTTGART);
+
+    let file = temp_file("paths.geojson");
+    io::geojson::Writer::write_paths(&[path], &graph, &file)
+        .expect("Paths should be writable as geojson.");
+    let content = read_feature_collection(&file);
+
+    // Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+    assert_eq!(feature_count(&content), 1);
+    assert!(content.contains(
+        "[[9.458919, 48.98401], [9.433202, 48.941602], [9.309866, 48.82711], [9.156577, 48.770176]]"
+    ));
+    assert!(content.contains(&format!(
+        "\"src-id\": {}, \"dst-id\": {}, \"hop-distance\": 3, \"costs\": {{",
+        OPPENWEILER, STUTTGART
+    )));
+    assert!(content.contains("\"kilometers\": 48"));
+}
+
+#[test]
+fn workloads() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let workloads: Vec<usize> = (0..graph.fwd_edges().count()).collect();
+
+    let file = temp_file("workloads.geojson");
+    io::geojson::Writer::write_workloads(&workloads, &graph, &file)
+        .expect("Workloads should be writable as geojson.");
+    let content = read_feature_collection(&file);
+
+    // edges without workload are skipped
+    assert_eq!(feature_count(&content), workloads.len() - 1);
+    // quantiles of 1..=12 start at 3, 5, 8 and 10
+    assert_eq!(content.matches("\"bin\": 0,").count(), 2);
+    assert_eq!(content.matches("\"bin\": 4,").count(), 3);
+    assert_eq!(
+        content
+            .matches(&format!(
+                "\"stroke\": \"{}\"",
+                osmgraphing::defaults::writing::geojson::WORKLOAD_COLORS[4]
+            ))
+            .count(),
+        3
+    );
+}
@@ -0,0 +1,282 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use kissunits::distance::Kilometers;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{
+        self,
+        parsing::generating::edges::{merge, Category},
+    },
+    io,
+    network::Graph,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn kmph(graph: &Graph, edge_id: usize) -> f64 {
+    let metric_idx = graph.cfg().edges.metrics.idx_of(defaults::SPEED_ID);
+    graph
+        .metrics()
+        .get_value(graph.fwd_edges().idx_from(edge_id), metric_idx)
+}
+
+fn id(id: &str) -> configs::SimpleId {
+    configs::SimpleId::from(id)
+}
+
+/// Returns the joining-yaml's parsing-config, merging the given csv-file with the given key.
+fn joining_cfg(
+    file: &Path,
+    new_key: merge::Key,
+    new_policies: merge::Policies,
+) -> configs::parsing::Config {
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::JOINING_YAML);
+    for category in parsing_cfg
+        .generating
+        .as_mut()
+        .expect("Joining-yaml should generate edge-data.")
+        .edges
+        .categories
+        .iter_mut()
+    {
+        if let Category::Merge {
+            from,
+            key,
+            policies,
+            edges_info,
+            ..
+        } = category
+        {
+            *from = PathBuf::from(file);
+            *edges_info = new_key
+                .ids()
+                .into_iter()
+                .chain(std::iter::once(&id(defaults::SPEED_ID)))
+                .map(|id| merge::Category::Id(id.clone()))
+                .collect();
+            *key = new_key.clone();
+            *policies = new_policies;
+        }
+    }
+    parsing_cfg
+}
+
+fn nearest_key(max_distance: Option<Kilometers>) -> merge::Key {
+    merge::Key::Nearest {
+        lat: id("latitude"),
+        lon: id("longitude"),
+        max_distance,
+    }
+}
+
+fn node_ids_key() -> merge::Key {
+    merge::Key::NodeIds {
+        src_id: id("src-id"),
+        dst_id: id("dst-id"),
+    }
+}
+
+/// The defaults, which behave like merging by edge-ids always did.
+fn policies() -> merge::Policies {
+    merge::Policies {
+        unmatched_rows: merge::Policy::Error,
+        unmatched_edges: merge::Policy::Ignore,
+        duplicates: merge::Policy::Ignore,
+    }
+}
+
+#[test]
+fn yaml() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::JOINING_YAML);
+    let category = &parsing_cfg
+        .generating
+        .expect("Joining-yaml should generate edge-data.")
+        .edges
+        .categories[1];
+    match category {
+        Category::Merge { key, policies, .. } => {
+            assert_eq!(key, &node_ids_key());
+            assert_eq!(policies.unmatched_rows, merge::Policy::Warn);
+            assert_eq!(policies.unmatched_edges, merge::Policy::Ignore);
+            assert_eq!(policies.duplicates, merge::Policy::Ignore);
+        }
+        _ => panic!("Joining-yaml should merge edge-data."),
+    }
+
+    let content =
+        fs::read_to_string(resources::JOINING_YAML).expect("Joining-yaml should be readable.");
+    let invalid_contents = vec![
+        // edge-id and join
+        content.replace(
+            "        join:\n",
+            "        edge-id: 'edge-id'\n        join:\n",
+        ),
+        // rows can't be set to a default
+        content.replace("unmatched-rows: 'warn'", "unmatched-rows: { default: 1.0 }"),
+        // join-columns have to be part of the edges-info
+        content.replace("        - id: 'dst-id'\n", ""),
+        // unknown policy
+        content.replace("unmatched-rows: 'warn'", "unmatched-rows: 'panic'"),
+    ];
+    for (i, invalid_content) in invalid_contents.into_iter().enumerate() {
+        assert_ne!(invalid_content, content);
+        let yaml_file = temp_file(&format!("joining_invalid_{}.yaml", i));
+        fs::write(&yaml_file, invalid_content).expect("Temporary yaml-file should be writable.");
+        assert!(configs::parsing::Config::try_from_yaml(&yaml_file).is_err());
+        fs::remove_file(&yaml_file).expect("Temporary yaml-file should be removable.");
+    }
+}
+
+#[test]
+fn node_ids() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::JOINING_YAML));
+    // Backnang -> Dead-end
+    assert!(Approx(kmph(&graph, 4)) == Approx(15.0));
+    // Waiblingen -> Stuttgart
+    assert!(Approx(kmph(&graph, 7)) == Approx(90.0));
+    // Stuttgart -> Waiblingen is not merged
+    assert!(Approx(kmph(&graph, 11)) == Approx(100.0));
+
+    // unknown nodes are unmatched rows
+    let mut parsing_cfg = joining_cfg(
+        Path::new(resources::JOINING_CSV),
+        node_ids_key(),
+        policies(),
+    );
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg.clone()).is_err());
+    if let Some(generating_cfg) = parsing_cfg.generating.as_mut() {
+        if let Category::Merge { policies, .. } = &mut generating_cfg.edges.categories[1] {
+            policies.unmatched_rows = merge::Policy::Ignore;
+        }
+    }
+    let graph = parse(parsing_cfg);
+    assert!(Approx(kmph(&graph, 4)) == Approx(15.0));
+}
+
+#[test]
+fn nearest() {
+    let file = temp_file("joining_nearest.csv");
+    fs::write(
+        &file,
+        concat!(
+            "latitude,longitude,kmph\n",
+            // between Oppenweiler and Backnang
+            "48.962806,9.446061,60\n",
+            // between Backnang and its dead-end
+            "48.940618,9.426035,5\n",
+        ),
+    )
+    .expect("Temporary file should be writable.");
+    let graph = parse(joining_cfg(&file, nearest_key(None), policies()));
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+
+    // both directions
+    assert!(Approx(kmph(&graph, 0)) == Approx(60.0));
+    assert!(Approx(kmph(&graph, 1)) == Approx(60.0));
+    // a one-way
+    assert!(Approx(kmph(&graph, 4)) == Approx(5.0));
+    // not merged
+    assert!(Approx(kmph(&graph, 2)) == Approx(120.0));
+
+    // rows too far away are unmatched
+    let file = temp_file("joining_nearest_far_away.csv");
+    fs::write(&file, "latitude,longitude,kmph\n50.0,9.0,60\n")
+        .expect("Temporary file should be writable.");
+    let parsing_cfg = joining_cfg(&file, nearest_key(Some(Kilometers(1.0))), policies());
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+    assert!(result.is_err());
+}
+
+#[test]
+fn policies_of_edges() {
+    let file = temp_file("joining_policies.csv");
+    fs::write(
+        &file,
+        concat!(
+            "src-id,dst-id,kmph\n",
+            "26160028,1621605361,15\n",
+            "26160028,1621605361,25\n",
+        ),
+    )
+    .expect("Temporary file should be writable.");
+
+    // by default, the last row wins
+    let graph = parse(joining_cfg(&file, node_ids_key(), policies()));
+    assert!(Approx(kmph(&graph, 4)) == Approx(25.0));
+    assert!(Approx(kmph(&graph, 0)) == Approx(50.0));
+
+    // duplicates
+    let mut duplicates_policies = policies();
+    duplicates_policies.duplicates = merge::Policy::Error;
+    let parsing_cfg = joining_cfg(&file, node_ids_key(), duplicates_policies);
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+    duplicates_policies.duplicates = merge::Policy::Default(1.0);
+    let graph = parse(joining_cfg(&file, node_ids_key(), duplicates_policies));
+    assert!(Approx(kmph(&graph, 4)) == Approx(1.0));
+
+    // unmatched edges
+    let mut unmatched_policies = policies();
+    unmatched_policies.unmatched_edges = merge::Policy::Error;
+    let parsing_cfg = joining_cfg(&file, node_ids_key(), unmatched_policies);
+    assert!(io::network::graph::Parser::parse_and_finalize(parsing_cfg).is_err());
+    unmatched_policies.unmatched_edges = merge::Policy::Default(7.0);
+    let graph = parse(joining_cfg(&file, node_ids_key(), unmatched_policies));
+    assert!(Approx(kmph(&graph, 4)) == Approx(25.0));
+    for edge_id in (0..graph.fwd_edges().count()).filter(|&edge_id| edge_id != 4) {
+        assert!(Approx(kmph(&graph, edge_id)) == Approx(7.0));
+    }
+
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+}
+
+#[test]
+fn unknown_edge_ids() {
+    let file = temp_file("joining_unknown_edge_ids.csv");
+    fs::write(&file, "edge-id,kmph\n4,33\n1000,25\n").expect("Temporary file should be writable.");
+
+    for policy in &[
+        merge::Policy::Error,
+        merge::Policy::Warn,
+        merge::Policy::Ignore,
+        // rows can't be defaulted, hence they are skipped
+        merge::Policy::Default(7.0),
+    ] {
+        let mut policies = policies();
+        policies.unmatched_rows = *policy;
+        let parsing_cfg = joining_cfg(&file, merge::Key::EdgeId(id("edge-id")), policies);
+        let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+
+        if let merge::Policy::Error = policy {
+            assert!(result.is_err(), "Unknown edge-ids should be an error.");
+        } else {
+            let graph = result.unwrap_or_else(|msg| {
+                panic!("Merging with policy {:?} should work: {}", policy, msg)
+            });
+            // the known edge-id is merged anyways
+            assert!(Approx(kmph(&graph, 4)) == Approx(33.0));
+            assert!(Approx(kmph(&graph, 0)) == Approx(50.0));
+        }
+    }
+
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+}
+
+#[test]
+fn way_ids_of_fmi_graph() {
+    // fmi-graphs don't know their ways
+    let file = temp_file("joining_way_ids.csv");
+    fs::write(&file, "way-id,latitude,longitude,kmph\n1,48.94,9.43,60\n")
+        .expect("Temporary file should be writable.");
+    let key = merge::Key::Way {
+        way_id: id("way-id"),
+        lat: id("latitude"),
+        lon: id("longitude"),
+    };
+    let parsing_cfg = joining_cfg(&file, key, policies());
+    let result = io::network::graph::Parser::parse_and_finalize(parsing_cfg);
+    fs::remove_file(&file).expect("Temporary file should be removable.");
+    assert!(result.is_err());
+}
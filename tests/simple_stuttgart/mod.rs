@@ -1,2 +1,17 @@
+#[cfg(feature = "gpl")]
+mod balancing;
+#[cfg(feature = "gpl")]
+mod converging;
+mod csv;
+mod dimacs;
+mod formula;
+mod geojson;
+mod joining;
 mod parsing;
+mod paths;
+mod queries;
+mod route_pairs;
 mod routing;
+mod smarts;
+mod sumo;
+mod units;
@@ -0,0 +1,76 @@
+use crate::helpers::{best_path, defaults, parse, read_and_remove, temp_file};
+use defaults::{
+    node_ids::simple_stuttgart::{OPPENWEILER, STUTTGART},
+    paths::resources::simple_stuttgart as resources,
+};
+use osmgraphing::{
+    configs,
+    io::{self, SupportingFileExts},
+    network::Graph,
+    routing::paths::Path,
+};
+use std::path::PathBuf;
+
+fn write_and_read(graph: &Graph, paths: &[Path], name: &str) -> String {
+    let writing_cfg = configs::writing::paths::Config {
+        file: temp_file(name),
+        is_denormalizing: false,
+    };
+    io::paths::Writer::write(paths, graph, &writing_cfg).expect("Paths should be writable.");
+    read_and_remove(&writing_cfg.file)
+}
+
+#[test]
+fn configs() {
+    let writing_cfg = configs::writing::paths::Config::from_yaml(resources::PATHS_YAML);
+    assert_eq!(
+        writing_cfg.file,
+        PathBuf::from("resources/simple_stuttgart/paths.gpx")
+    );
+    assert!(writing_cfg.is_denormalizing);
+
+    assert!(io::paths::Writer::find_supported_ext("paths.kml").is_ok());
+    assert!(io::paths::Writer::find_supported_ext("paths.geojson").is_err());
+}
+
+#[test]
+fn gpx() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    // Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+    let path = best_path(&graph, OPPENWEILER, STUTTGART);
+
+    let content = write_and_read(&graph, &[path.clone(), path], "paths.gpx");
+
+    assert!(content.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\""));
+    assert!(content.trim_end().ends_with("</gpx>"));
+    assert_eq!(content.matches("<trk>").count(), 2);
+    assert_eq!(content.matches("<trkpt ").count(), 2 * 4);
+    assert!(content.contains(&format!("<name>{} -&gt; {}</name>", OPPENWEILER, STUTTGART)));
+    assert!(content.contains("<trkpt lat=\"48.98401\" lon=\"9.458919\"/>"));
+    assert!(content.contains(&format!(
+        "<osmgraphing:src-id>{}</osmgraphing:src-id>",
+        OPPENWEILER
+    )));
+    assert!(content.contains("<osmgraphing:cost metric=\"kilometers\">48</osmgraphing:cost>"));
+}
+
+#[test]
+fn kml() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    // Oppenweiler -> Backnang -> Waiblingen -> Stuttgart
+    let path = best_path(&graph, OPPENWEILER, STUTTGART);
+
+    let content = write_and_read(&graph, &[path], "paths.kml");
+
+    assert!(content.contains("<kml xmlns=\"http://www.opengis.net/kml/2.2\">"));
+    assert!(content.trim_end().ends_with("</kml>"));
+    assert_eq!(content.matches("<Placemark>").count(), 1);
+    assert!(content.contains(
+        "<coordinates>9.458919,48.98401 9.433202,48.941602 9.309866,48.82711 9.156577,48.770176</coordinates>"
+    ));
+    assert!(content.contains(&format!(
+        "<Data name=\"dst-id\"><value>{}</value></Data>",
+        STUTTGART
+    )));
+    assert!(content.contains("<Data name=\"kilometers\"><value>48</value></Data>"));
+}
@@ -0,0 +1,176 @@
+use crate::helpers::{defaults, parse, read_and_remove, temp_file};
+use defaults::{
+    node_ids::simple_stuttgart::{BACKNANG, DEAD_END, OPPENWEILER, STUTTGART, WAIBLINGEN},
+    paths::resources::simple_stuttgart as resources,
+};
+use osmgraphing::{
+    configs::{
+        self,
+        writing::queries::{Config as QueriesConfig, Sequence},
+    },
+    io,
+    network::{Graph, RouteEntry, RoutePair},
+    routing::batch::{self, QueryResult},
+};
+
+const UNKNOWN: i64 = 42;
+
+/// Routes the od-pairs (with labels and metadata), an unreachable route-pair and an unknown route-pair.
+fn route(graph: &Graph, num_threads: usize) -> Vec<QueryResult> {
+    let routing_cfg = configs::routing::Config::from_str(
+        &format!(
+            "routing: {{ route-pairs-file: '{}', algorithm: 'Dijkstra', metrics: [{{ id: 'kilometers' }}] }}",
+            resources::OD_CSV
+        ),
+        graph.cfg(),
+    );
+    let mut entries = io::routing::Parser::parse_entries(&routing_cfg, graph)
+        .expect("Route-pairs with coordinates should be parsable.");
+    entries.push(RouteEntry::new(
+        RoutePair {
+            src: DEAD_END,
+            dst: STUTTGART,
+        },
+        1,
+    ));
+    entries.push(RouteEntry::new(
+        RoutePair {
+            src: UNKNOWN,
+            dst: STUTTGART,
+        },
+        1,
+    ));
+
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .expect("Thread-pool should be buildable.");
+    batch::route(entries, graph, &routing_cfg, Some(&thread_pool))
+        .expect("Batch-routing should work.")
+}
+
+fn write_and_read(
+    graph: &Graph,
+    results: &[QueryResult],
+    name: &str,
+    sequence: Option<Sequence>,
+) -> Vec<String> {
+    let writing_cfg = QueriesConfig {
+        file: temp_file(name),
+        is_denormalizing: false,
+        sequence,
+        num_threads: None,
+    };
+    io::queries::Writer::write(results, graph, &writing_cfg)
+        .expect("Query-results should be writable.");
+    read_and_remove(&writing_cfg.file)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn configs() {
+    let writing_cfg = QueriesConfig::from_yaml(resources::QUERIES_YAML);
+    assert_eq!(writing_cfg.sequence, Some(Sequence::NodeIds));
+    assert_eq!(writing_cfg.num_threads, Some(2));
+    assert!(!writing_cfg.is_denormalizing);
+}
+
+#[test]
+fn batch_routing() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    let results = route(&graph, 4);
+    let route_pairs: Vec<(i64, i64, bool)> = results
+        .iter()
+        .map(|result| {
+            (
+                result.entry.route_pair.src,
+                result.entry.route_pair.dst,
+                result.path.is_some(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        route_pairs,
+        vec![
+            (OPPENWEILER, STUTTGART, true),
+            (BACKNANG, WAIBLINGEN, true),
+            (298249467, OPPENWEILER, true),
+            (DEAD_END, STUTTGART, false),
+            // unknown node-ids don't abort the batch
+            (UNKNOWN, STUTTGART, false),
+        ]
+    );
+
+    // independent of the number of threads
+    let single_threaded_results = route(&graph, 1);
+    for (result, single_threaded_result) in results.iter().zip(&single_threaded_results) {
+        assert_eq!(result.entry, single_threaded_result.entry);
+        assert_eq!(result.path, single_threaded_result.path);
+    }
+}
+
+#[test]
+fn csv() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let results = route(&graph, 2);
+
+    let lines = write_and_read(&graph, &results, "queries.csv", Some(Sequence::NodeIds));
+    assert_eq!(lines.len(), 1 + results.len());
+    assert!(lines[0].starts_with("src-id,dst-id,found,hop-distance,query-ms,"));
+    assert!(lines[0].ends_with(",label,operator,node-ids"));
+
+    // the sequence contains the delimiter, hence it is quoted
+    assert!(lines[1].starts_with("26033921,2933335353,true,3,"));
+    assert!(lines[1].ends_with(",commute-1,vvs,\"26033921,26160028,252787940,2933335353\""));
+    let commute: Vec<&str> = lines[1].split(',').collect();
+    assert!(commute.contains(&"48"), "Path should have 48 kilometers.");
+
+    let unreachable: Vec<&str> = lines[4].split(',').collect();
+    assert_eq!(unreachable[..4], ["1621605361", "2933335353", "false", "_"]);
+    assert_eq!(unreachable[unreachable.len() - 3..], ["_", "_", "_"]);
+    assert!(lines[5].starts_with("42,2933335353,false,_,0,"));
+
+    // the graph has no edge-ids
+    let writing_cfg = QueriesConfig {
+        file: temp_file("edge_ids.csv"),
+        is_denormalizing: false,
+        sequence: Some(Sequence::EdgeIds),
+        num_threads: None,
+    };
+    assert!(io::queries::Writer::write(&results, &graph, &writing_cfg).is_err());
+    assert!(!writing_cfg.file.exists());
+}
+
+#[test]
+fn jsonl() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let results = route(&graph, 2);
+
+    let lines = write_and_read(&graph, &results, "queries.jsonl", Some(Sequence::NodeIds));
+    assert_eq!(lines.len(), results.len());
+    for line in &lines {
+        assert!(line.starts_with('{') && line.ends_with('}'));
+    }
+
+    assert!(lines[0].starts_with(&format!(
+        "{{\"src-id\": {}, \"dst-id\": {}, \"found\": true, \"hop-distance\": 3, \"query-ms\": ",
+        OPPENWEILER, STUTTGART
+    )));
+    assert!(lines[0].contains("\"kilometers\": 48"));
+    assert!(lines[0].ends_with(
+        "\"label\": \"commute-1\", \"metadata\": {\"operator\": \"vvs\"}, \
+         \"node-ids\": [26033921, 26160028, 252787940, 2933335353]}"
+    ));
+
+    // missing metadata is skipped
+    assert!(lines[1].ends_with("\"node-ids\": [26160028, 252787940]}"));
+
+    assert!(lines[3].starts_with(&format!(
+        "{{\"src-id\": {}, \"dst-id\": {}, \"found\": false, \"hop-distance\": null, ",
+        DEAD_END, STUTTGART
+    )));
+    assert!(lines[3].ends_with("\"costs\": null, \"node-ids\": null}"));
+}
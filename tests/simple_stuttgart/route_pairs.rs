@@ -0,0 +1,474 @@
+use crate::helpers::{defaults, parse, temp_file};
+use defaults::{
+    node_ids::simple_stuttgart::{
+        BACKNANG, DEAD_END, ENDERSBACH, OPPENWEILER, STUTTGART, WAIBLINGEN,
+    },
+    paths::resources::simple_stuttgart as resources,
+};
+use kissunits::{
+    distance::Kilometers,
+    geo::{self, Coordinate},
+    time::Seconds,
+};
+use osmgraphing::{
+    configs::{
+        self,
+        routing::RoutingAlgo,
+        writing::routing::{Category, DistanceBin, NodeWeights},
+    },
+    io,
+    network::{Departure, Graph, NodeLocator, RouteEntry, RoutePair, StronglyConnectedComponents},
+    routing::dijkstra::{self, Dijkstra},
+};
+use smallvec::smallvec;
+use std::{fs, path::PathBuf};
+
+fn routing_cfg(route_pairs_file: Option<PathBuf>) -> configs::routing::Config {
+    configs::routing::Config {
+        route_pairs_file,
+        routing_algo: RoutingAlgo::Dijkstra,
+        alphas: smallvec![1.0],
+        tolerated_scales: smallvec![std::f64::INFINITY],
+    }
+}
+
+/// Returns the node-weights per node-id.
+fn node_weights(graph: &Graph, weights: &NodeWeights) -> Vec<(i64, f64)> {
+    let nodes = graph.nodes();
+    let node_weights = io::weights::Parser::parse_node_weights(graph, weights)
+        .expect("Node-weights should be parsable.");
+    nodes
+        .iter()
+        .map(|idx| (nodes.id(idx), node_weights[*idx]))
+        .filter(|(_id, weight)| *weight > 0.0)
+        .collect()
+}
+
+/// Writes route-pairs into a temporary file and parses them back.
+fn write_and_parse(graph: &Graph, name: &str, category: Category) -> Vec<(i64, i64, usize)> {
+    let file = temp_file(&format!("{}.route-pairs", name));
+
+    let writing_cfg = configs::writing::routing::Config {
+        file: file.clone(),
+        category,
+    };
+    if let Err(msg) = io::routing::Writer::write(graph, &writing_cfg) {
+        panic!("Could not write route-pairs. ERROR: {}", msg);
+    }
+    let route_pairs = io::routing::Parser::parse(&routing_cfg(Some(file.clone())))
+        .expect("Written route-pairs should be parsable.");
+    fs::remove_file(&file).expect("Route-pairs should be removable.");
+
+    route_pairs
+        .into_iter()
+        .map(|(route_pair, route_count)| (route_pair.src, route_pair.dst, route_count))
+        .collect()
+}
+
+#[test]
+fn nearest_node() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let locator = NodeLocator::new(&graph);
+
+    let nearest_id = |coord: Coordinate| locator.nearest(&coord).map(|idx| nodes.id(idx));
+    assert_eq!(
+        nearest_id(Coordinate {
+            lat: 48.9416,
+            lon: 9.4332,
+        }),
+        Some(BACKNANG)
+    );
+    assert_eq!(
+        nearest_id(Coordinate {
+            lat: 48.75,
+            lon: 9.1,
+        }),
+        Some(STUTTGART)
+    );
+    // outside of the graph's bounding-box
+    assert_eq!(
+        nearest_id(Coordinate {
+            lat: 49.5,
+            lon: 9.5,
+        }),
+        Some(OPPENWEILER)
+    );
+
+    // Munich is farther away than 100 km
+    let munich = Coordinate {
+        lat: 48.137,
+        lon: 11.575,
+    };
+    assert_eq!(locator.nearest_within(&munich, Kilometers(100.0)), None);
+    assert!(locator.nearest_within(&munich, Kilometers(200.0)).is_some());
+}
+
+#[test]
+fn csv_weights() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let weights = NodeWeights::Csv(PathBuf::from(resources::POPULATION_CSV));
+
+    assert_eq!(
+        io::weights::Parser::parse(&weights)
+            .expect("Weights should be parsable.")
+            .len(),
+        5
+    );
+    // Munich is ignored
+    assert_eq!(
+        node_weights(&graph, &weights),
+        vec![(OPPENWEILER, 100.0), (BACKNANG, 200.0), (STUTTGART, 300.0)]
+    );
+}
+
+#[test]
+fn raster_weights() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let weights = NodeWeights::Raster(PathBuf::from(resources::POPULATION_ASC));
+
+    // zeros and no-data are skipped
+    assert_eq!(
+        io::weights::Parser::parse(&weights)
+            .expect("Weights should be parsable.")
+            .len(),
+        2
+    );
+    assert_eq!(node_weights(&graph, &weights), vec![(OPPENWEILER, 12.0)]);
+}
+
+#[test]
+fn configs() {
+    let writing_cfg = configs::writing::routing::Config::from_yaml(resources::GRAVITY_YAML);
+    match writing_cfg.category {
+        Category::Gravity {
+            max_count,
+            weights,
+            distance_scale,
+            ..
+        } => {
+            assert_eq!(max_count, 20);
+            assert_eq!(
+                weights,
+                NodeWeights::Csv(PathBuf::from(resources::POPULATION_CSV))
+            );
+            assert_eq!(distance_scale, Some(Kilometers(10.0)));
+        }
+        category => panic!("Category should be gravity, but is {:?}", category),
+    }
+
+    // bins have to be ascending
+    assert!(configs::writing::routing::Config::try_from_yaml(
+        resources::INVALID_DISTANCE_BINS_YAML
+    )
+    .is_err());
+}
+
+#[test]
+fn gravity() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let route_pairs = write_and_parse(
+        &graph,
+        "gravity",
+        Category::Gravity {
+            seed: 42,
+            max_count: 100,
+            weights: NodeWeights::Csv(PathBuf::from(resources::POPULATION_CSV)),
+            distance_scale: Some(Kilometers(20.0)),
+        },
+    );
+
+    let route_count: usize = route_pairs.iter().map(|(_src, _dst, count)| count).sum();
+    assert_eq!(route_count, 100);
+    for (src_id, dst_id, _count) in &route_pairs {
+        assert_ne!(src_id, dst_id);
+        for id in &[src_id, dst_id] {
+            assert!(
+                [OPPENWEILER, BACKNANG, STUTTGART].contains(id),
+                "Node {} has no weight, but is part of a route.",
+                id
+            );
+        }
+    }
+
+    // same seed, same routes
+    let other_route_pairs = write_and_parse(
+        &graph,
+        "gravity_again",
+        Category::Gravity {
+            seed: 42,
+            max_count: 100,
+            weights: NodeWeights::Csv(PathBuf::from(resources::POPULATION_CSV)),
+            distance_scale: Some(Kilometers(20.0)),
+        },
+    );
+    assert_eq!(route_pairs, other_route_pairs);
+}
+
+#[test]
+fn distance_stratified() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let bins = vec![
+        DistanceBin {
+            max_distance: Kilometers(10.0),
+            share: 0.25,
+        },
+        DistanceBin {
+            max_distance: Kilometers(100.0),
+            share: 0.75,
+        },
+    ];
+    let route_pairs = write_and_parse(
+        &graph,
+        "distance_stratified",
+        Category::DistanceStratified {
+            seed: 42,
+            max_count: 40,
+            weights: None,
+            bins,
+        },
+    );
+
+    let mut counts = [0, 0];
+    for (src_id, dst_id, count) in route_pairs {
+        let src_idx = nodes.idx_from(src_id).expect("Src-node should exist.");
+        let dst_idx = nodes.idx_from(dst_id).expect("Dst-node should exist.");
+        let distance = geo::haversine_distance_km(&nodes.coord(src_idx), &nodes.coord(dst_idx));
+        if distance < Kilometers(10.0) {
+            counts[0] += count;
+        } else {
+            counts[1] += count;
+        }
+    }
+    assert_eq!(counts, [10, 30]);
+}
+
+fn expected_od_entries() -> Vec<RouteEntry> {
+    vec![
+        RouteEntry {
+            route_pair: RoutePair {
+                src: OPPENWEILER,
+                dst: STUTTGART,
+            },
+            count: 20,
+            departure: Some(Departure::Within {
+                start: Seconds(0.0),
+                end: Seconds(3600.0),
+            }),
+            label: Some(String::from("commute-1")),
+            metadata: vec![(String::from("operator"), String::from("vvs"))],
+        },
+        RouteEntry {
+            route_pair: RoutePair {
+                src: BACKNANG,
+                dst: WAIBLINGEN,
+            },
+            count: 5,
+            departure: None,
+            label: Some(String::from("commute-2")),
+            metadata: vec![],
+        },
+        RouteEntry {
+            route_pair: RoutePair {
+                src: ENDERSBACH,
+                dst: OPPENWEILER,
+            },
+            count: 1,
+            departure: Some(Departure::At(Seconds(7200.0))),
+            label: Some(String::from("leisure")),
+            metadata: vec![(String::from("operator"), String::from("vvs"))],
+        },
+    ]
+}
+
+#[test]
+fn csv_with_coordinates() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let od_cfg = routing_cfg(Some(PathBuf::from(resources::OD_CSV)));
+
+    let entries = io::routing::Parser::parse_entries(&od_cfg, &graph)
+        .expect("Route-pairs with coordinates should be parsable.");
+    assert_eq!(entries, expected_od_entries());
+
+    // coordinates can't be snapped without graph
+    assert!(io::routing::Parser::parse(&od_cfg).is_err());
+
+    // Munich is too far away
+    let far_od_cfg = routing_cfg(Some(PathBuf::from(resources::FAR_OD_CSV)));
+    assert!(io::routing::Parser::parse_entries(&far_od_cfg, &graph).is_err());
+}
+
+#[test]
+fn invalid_csv() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let file = temp_file("invalid.csv");
+
+    for content in &[
+        // whitespace-separated
+        &b"src-id dst-id\n26033921 2933335353\n"[..],
+        // missing value
+        &b"src-id,dst-id,label\n26033921,2933335353\n"[..],
+        // unclosed quote
+        &b"src-id,dst-id,label\n26033921,2933335353,\"commute\n"[..],
+        // no utf-8
+        &b"src-id,dst-id,label\n26033921,2933335353,\xff\n"[..],
+    ] {
+        fs::write(&file, content).expect("Temporary csv-file should be writable.");
+        let result = io::routing::Parser::parse_entries(&routing_cfg(Some(file.clone())), &graph);
+        assert!(
+            result.is_err(),
+            "{} should be invalid.",
+            String::from_utf8_lossy(content)
+        );
+    }
+    fs::remove_file(&file).expect("Temporary csv-file should be removable.");
+}
+
+#[test]
+fn csv_round_trip() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut entries = expected_od_entries();
+    // fields with delimiters and quotes are quoted
+    entries[1].label = Some(String::from("commute, \"early\""));
+
+    // csv keeps everything
+    let file = temp_file("round_trip.csv");
+    io::routing::Writer::write_entries(&graph, &entries, &file)
+        .expect("Route-pairs should be writable as csv.");
+    let parsed_entries =
+        io::routing::Parser::parse_entries(&routing_cfg(Some(file.clone())), &graph)
+            .expect("Written csv should be parsable.");
+    fs::remove_file(&file).expect("Route-pairs should be removable.");
+    assert_eq!(parsed_entries, entries);
+
+    // route-pairs-files drop labels and metadata
+    let file = temp_file("round_trip.route-pairs");
+    io::routing::Writer::write_entries(&graph, &entries, &file)
+        .expect("Route-pairs should be writable.");
+    let parsed_entries =
+        io::routing::Parser::parse_entries(&routing_cfg(Some(file.clone())), &graph)
+            .expect("Written route-pairs should be parsable.");
+    fs::remove_file(&file).expect("Route-pairs should be removable.");
+    let expected_entries: Vec<_> = entries
+        .into_iter()
+        .map(|entry| RouteEntry {
+            label: None,
+            metadata: vec![],
+            ..entry
+        })
+        .collect();
+    assert_eq!(parsed_entries, expected_entries);
+}
+
+#[test]
+fn strongly_connected_components() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let nodes = graph.nodes();
+    let idx_of = |id: i64| nodes.idx_from(id).expect("Node should exist.");
+    let components = StronglyConnectedComponents::new(&graph);
+
+    // the dead-end can be reached, but not left
+    assert_eq!(components.count(), 2);
+    assert!(components.is_reachable(idx_of(OPPENWEILER), idx_of(STUTTGART)));
+    assert!(!components.is_reachable(idx_of(DEAD_END), idx_of(BACKNANG)));
+    assert!(components.is_reachable(idx_of(BACKNANG), idx_of(DEAD_END)));
+}
+
+#[test]
+fn disconnected_islands() {
+    // 1 <-> 2 -> 3 and 4 <-> 5 -> 6
+    let map_file = temp_file("islands.gr");
+    fs::write(
+        &map_file,
+        "p sp 6 6\na 1 2 8000\na 2 1 8000\na 2 3 1069\na 4 5 8000\na 5 4 8000\na 5 6 17000\n",
+    )
+    .expect("Temporary DIMACS-file should be writable.");
+    let mut parsing_cfg = configs::parsing::Config::from_yaml(resources::DIMACS_YAML);
+    parsing_cfg.map_file = map_file.clone();
+    let graph = parse(parsing_cfg);
+    fs::remove_file(&map_file).expect("Temporary DIMACS-file should be removable.");
+
+    let nodes = graph.nodes();
+    let idx_of = |id: i64| nodes.idx_from(id).expect("Node should exist.");
+    let components = StronglyConnectedComponents::new(&graph);
+    assert_eq!(components.count(), 4);
+
+    // no island reaches the other one, in both directions
+    for (src_id, dst_id) in &[(1, 4), (4, 1), (2, 6), (6, 2), (3, 5), (5, 3)] {
+        assert!(
+            !components.is_reachable(idx_of(*src_id), idx_of(*dst_id)),
+            "Node {} shouldn't reach node {}.",
+            src_id,
+            dst_id
+        );
+    }
+
+    // reachability is the same as with Dijkstra
+    let mut dijkstra = Dijkstra::new();
+    let routing_cfg = configs::routing::Config::from_str(
+        "routing: { algorithm: 'Dijkstra', metrics: [{ id: 'meters' }] }",
+        graph.cfg(),
+    );
+    for src_idx in nodes.iter() {
+        for dst_idx in nodes.iter() {
+            let is_found = dijkstra
+                .compute_best_path(dijkstra::Query {
+                    src_idx,
+                    dst_idx,
+                    graph: &graph,
+                    routing_cfg: &routing_cfg,
+                })
+                .is_some();
+            assert_eq!(
+                components.is_reachable(src_idx, dst_idx),
+                is_found,
+                "Reachability from node {} to node {}",
+                nodes.id(src_idx),
+                nodes.id(dst_idx)
+            );
+        }
+    }
+}
+
+#[test]
+fn random_or_all() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+
+    // all routes, except from the dead-end and to the same node
+    let route_pairs = write_and_parse(
+        &graph,
+        "all",
+        Category::RandomOrAll {
+            seed: 42,
+            max_count: 100,
+        },
+    );
+    assert_eq!(route_pairs.len(), 5 * 5);
+    for (src_id, dst_id, count) in route_pairs {
+        assert_ne!(src_id, dst_id);
+        assert_ne!(src_id, DEAD_END);
+        assert_eq!(count, 1);
+    }
+
+    // random routes don't depend on the number of threads
+    let write_with_threads = |num_threads: usize| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .expect("Thread-pool should be buildable.")
+            .install(|| {
+                write_and_parse(
+                    &graph,
+                    &format!("random_{}", num_threads),
+                    Category::RandomOrAll {
+                        seed: 42,
+                        max_count: 10,
+                    },
+                )
+            })
+    };
+    let route_pairs = write_with_threads(1);
+    assert!(!route_pairs.is_empty());
+    assert_eq!(route_pairs, write_with_threads(4));
+}
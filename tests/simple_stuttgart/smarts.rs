@@ -0,0 +1,301 @@
+use crate::helpers::{best_path, defaults, parse, read_and_remove, temp_file};
+use defaults::{
+    node_ids::simple_stuttgart::{OPPENWEILER, STUTTGART},
+    paths::resources::simple_stuttgart as resources,
+};
+use kissunits::time::Seconds;
+use osmgraphing::{
+    configs::{
+        self,
+        writing::smarts::{Departures, Share, Vehicles},
+    },
+    io,
+    network::Graph,
+    routing::paths::Path,
+};
+use std::{fs, path::PathBuf};
+
+const NUM_VEHICLES: usize = 100;
+
+/// Writes the vehicles and returns the written (type, start-time, driver-profile) per vehicle.
+fn write(
+    name: &str,
+    chosen_paths: &[(Path, Option<Seconds>)],
+    graph: &Graph,
+    vehicles: Vehicles,
+    seed: u64,
+) -> Vec<(String, f64, String)> {
+    let writing_cfg = configs::writing::smarts::Config {
+        file: temp_file(&format!("smarts_{}_{}.xml", name, seed)),
+        vehicles,
+        seed,
+    };
+    io::smarts::Writer::write(chosen_paths, graph, &writing_cfg)
+        .expect("Chosen paths should be writable for SMARTS.");
+    let content = read_and_remove(&writing_cfg.file);
+
+    let attribute = |line: &str, name: &str| -> String {
+        let start = line
+            .find(&format!("{}=\"", name))
+            .expect("Vehicle should have attribute.")
+            + name.len()
+            + 2;
+        let len = line[start..]
+            .find('"')
+            .expect("Attribute should be closed.");
+        String::from(&line[start..start + len])
+    };
+    content
+        .lines()
+        .filter(|line| line.starts_with("<vehicle "))
+        .map(|line| {
+            (
+                attribute(line, "type"),
+                attribute(line, "start_time")
+                    .parse::<f64>()
+                    .expect("Start-time should be a number."),
+                attribute(line, "driverProfile"),
+            )
+        })
+        .collect()
+}
+
+#[cfg(feature = "gpl")]
+#[test]
+fn configs() {
+    let cfg = configs::evaluating_balance::Config::from_yaml(resources::SMARTS_YAML);
+    let vehicles = &cfg.monitoring.smarts;
+    assert_eq!(
+        vehicles.types,
+        vec![
+            Share {
+                id: String::from("CAR"),
+                share: 0.8
+            },
+            Share {
+                id: String::from("TRUCK"),
+                share: 0.2
+            }
+        ]
+    );
+    assert_eq!(vehicles.driver_profiles.len(), 2);
+    assert_eq!(
+        vehicles.departures,
+        Some(Departures::Empirical {
+            file: PathBuf::from(resources::DEPARTURES_CSV)
+        })
+    );
+
+    // defaults
+    let yaml_str = "evaluating_balance: { results-dir: 'results', monitoring: { edges-info: { file: 'edges-info.csv', ids: [] } } }";
+    let cfg = configs::evaluating_balance::Config::from_str(yaml_str);
+    assert_eq!(cfg.monitoring.smarts, Vehicles::default());
+
+    // invalid configs
+    for smarts in &[
+        "{ vehicle-types: [] }",
+        "{ vehicle-types: [{ id: 'CAR', share: -1 }] }",
+        "{ driver-profiles: [{ id: 'NORMAL', share: 0 }] }",
+        "{ departures: { uniform: { from: 3600, to: 0 } } }",
+        "{ departures: { normal: { mean: 3600, std-dev: -1 } } }",
+        "{ departures: { normal: { mean: -3600, std-dev: 1800 } } }",
+    ] {
+        let yaml_str = format!("evaluating_balance: {{ results-dir: 'results', monitoring: {{ SMARTS: {}, edges-info: {{ file: 'edges-info.csv', ids: [] }} }} }}", smarts);
+        assert!(
+            configs::evaluating_balance::Config::try_from_str(&yaml_str).is_err(),
+            "SMARTS-config {} should be invalid.",
+            smarts
+        );
+    }
+}
+
+#[test]
+fn default_vehicles() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let chosen_paths = vec![
+        (best_path(&graph, OPPENWEILER, STUTTGART), None),
+        (
+            best_path(&graph, OPPENWEILER, STUTTGART),
+            Some(Seconds(60.0)),
+        ),
+    ];
+
+    let vehicles = write(
+        "default_vehicles",
+        &chosen_paths,
+        &graph,
+        Vehicles::default(),
+        42,
+    );
+    assert_eq!(
+        vehicles,
+        vec![
+            (String::from("CAR"), 0.4, String::from("NORMAL")),
+            (String::from("CAR"), 60.0, String::from("NORMAL"))
+        ]
+    );
+}
+
+#[test]
+fn vehicle_mixes() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let chosen_paths: Vec<_> = (0..NUM_VEHICLES)
+        .map(|_| (best_path(&graph, OPPENWEILER, STUTTGART), None))
+        .collect();
+    let vehicles = Vehicles {
+        types: vec![
+            Share {
+                id: String::from("CAR"),
+                share: 0.8,
+            },
+            Share {
+                id: String::from("TRUCK"),
+                share: 0.2,
+            },
+        ],
+        driver_profiles: vec![
+            Share {
+                id: String::from("NORMAL"),
+                share: 3.0,
+            },
+            Share {
+                id: String::from("AGGRESSIVE"),
+                share: 1.0,
+            },
+        ],
+        departures: None,
+    };
+
+    let written = write("vehicle_mixes", &chosen_paths, &graph, vehicles.clone(), 42);
+    assert_eq!(written.len(), NUM_VEHICLES);
+    for (vehicle_type, _start_time, driver_profile) in &written {
+        assert!(vehicle_type == "CAR" || vehicle_type == "TRUCK");
+        assert!(driver_profile == "NORMAL" || driver_profile == "AGGRESSIVE");
+    }
+    let num_cars = written
+        .iter()
+        .filter(|(vehicle_type, _, _)| vehicle_type == "CAR")
+        .count();
+    assert!(NUM_VEHICLES / 2 < num_cars && num_cars < NUM_VEHICLES);
+
+    // same seed leads to same vehicles
+    assert_eq!(
+        write("vehicle_mixes", &chosen_paths, &graph, vehicles.clone(), 42),
+        written
+    );
+    assert_ne!(
+        write("vehicle_mixes", &chosen_paths, &graph, vehicles, 43),
+        written
+    );
+}
+
+#[test]
+fn departures() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let mut chosen_paths: Vec<_> = (0..NUM_VEHICLES)
+        .map(|_| (best_path(&graph, OPPENWEILER, STUTTGART), None))
+        .collect();
+    chosen_paths.push((
+        best_path(&graph, OPPENWEILER, STUTTGART),
+        Some(Seconds(100_000.0)),
+    ));
+
+    let mut vehicles = Vehicles::default();
+
+    // uniform
+    vehicles.departures = Some(Departures::Uniform {
+        from: Seconds(3_600.0),
+        to: Seconds(7_200.0),
+    });
+    let written = write("departures", &chosen_paths, &graph, vehicles.clone(), 42);
+    // given departures are kept
+    assert_eq!(written[NUM_VEHICLES].1, 100_000.0);
+    for (_, start_time, _) in &written[..NUM_VEHICLES] {
+        assert!(3_600.0 <= *start_time && *start_time < 7_200.0);
+    }
+
+    // normal
+    vehicles.departures = Some(Departures::Normal {
+        mean: Seconds(28_800.0),
+        std_dev: Seconds(1_800.0),
+    });
+    let written = write("departures", &chosen_paths, &graph, vehicles.clone(), 42);
+    let mean = written[..NUM_VEHICLES]
+        .iter()
+        .map(|(_, start_time, _)| start_time)
+        .sum::<f64>()
+        / NUM_VEHICLES as f64;
+    assert!((mean - 28_800.0).abs() < 1_800.0);
+    assert!(written[..NUM_VEHICLES]
+        .iter()
+        .any(|(_, start_time, _)| (start_time - 28_800.0).abs() > 1.0));
+
+    // truncated at 0 without piling up samples at 0
+    vehicles.departures = Some(Departures::Normal {
+        mean: Seconds(0.0),
+        std_dev: Seconds(1_800.0),
+    });
+    let written = write("departures", &chosen_paths, &graph, vehicles.clone(), 42);
+    assert!(written[..NUM_VEHICLES]
+        .iter()
+        .all(|(_, start_time, _)| *start_time > 0.0));
+    // mean of the half-normal distribution is std-dev * sqrt(2 / pi), about 1436 seconds
+    let mean = written[..NUM_VEHICLES]
+        .iter()
+        .map(|(_, start_time, _)| start_time)
+        .sum::<f64>()
+        / NUM_VEHICLES as f64;
+    assert!(1_000.0 < mean && mean < 1_900.0);
+
+    // empirical
+    vehicles.departures = Some(Departures::Empirical {
+        file: PathBuf::from(resources::DEPARTURES_CSV),
+    });
+    let written = write("departures", &chosen_paths, &graph, vehicles.clone(), 42);
+    let num_morning = written[..NUM_VEHICLES]
+        .iter()
+        .filter(|(_, start_time, _)| 25_200.0 <= *start_time && *start_time < 28_800.0)
+        .count();
+    let num_evening = written[..NUM_VEHICLES]
+        .iter()
+        .filter(|(_, start_time, _)| 57_600.0 <= *start_time && *start_time < 61_200.0)
+        .count();
+    assert_eq!(num_morning + num_evening, NUM_VEHICLES);
+    assert!(num_morning > num_evening && num_evening > 0);
+
+    // columns are looked up by the header-line
+    let departures_file = temp_file("smarts_departures.csv");
+    fs::write(&departures_file, "\"weight\",from,to\n1,\"25200\",28800\n")
+        .expect("Temporary file should be writable.");
+    vehicles.departures = Some(Departures::Empirical {
+        file: departures_file.clone(),
+    });
+    let written = write("departures", &chosen_paths, &graph, vehicles.clone(), 42);
+    assert!(written[..NUM_VEHICLES]
+        .iter()
+        .all(|(_, start_time, _)| 25_200.0 <= *start_time && *start_time < 28_800.0));
+
+    // whitespace-separated bins are no csv-records
+    fs::write(&departures_file, "from,to,weight\n25200 28800 3\n")
+        .expect("Temporary file should be writable.");
+    let writing_cfg = configs::writing::smarts::Config {
+        file: temp_file("smarts_whitespace_departures.xml"),
+        vehicles: vehicles.clone(),
+        seed: 42,
+    };
+    assert!(io::smarts::Writer::write(&chosen_paths, &graph, &writing_cfg).is_err());
+    assert!(!writing_cfg.file.exists());
+    fs::remove_file(&departures_file).expect("Temporary file should be removable.");
+
+    // missing csv-file leaves no file behind
+    vehicles.departures = Some(Departures::Empirical {
+        file: PathBuf::from("resources/simple_stuttgart/missing_departures.csv"),
+    });
+    let writing_cfg = configs::writing::smarts::Config {
+        file: temp_file("smarts_missing_departures.xml"),
+        vehicles,
+        seed: 42,
+    };
+    assert!(io::smarts::Writer::write(&chosen_paths, &graph, &writing_cfg).is_err());
+    assert!(!writing_cfg.file.exists());
+}
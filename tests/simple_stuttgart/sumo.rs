@@ -0,0 +1,106 @@
+use crate::helpers::{best_path, defaults, parse, read_and_remove, temp_file};
+use defaults::{
+    node_ids::simple_stuttgart::{BACKNANG, OPPENWEILER, STUTTGART, WAIBLINGEN},
+    paths::resources::simple_stuttgart as resources,
+};
+use kissunits::time::Seconds;
+use osmgraphing::{
+    configs::{self, SimpleId},
+    io,
+};
+
+#[test]
+fn network() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::SUMO_YAML));
+
+    let mut writing_cfg = configs::writing::sumo::Config::from_yaml(resources::SUMO_YAML);
+    writing_cfg.nodes_file = temp_file("graph.nod.xml");
+    writing_cfg.edges_file = temp_file("graph.edg.xml");
+    io::sumo::Writer::write_network(&graph, &writing_cfg)
+        .expect("Graph should be writable as SUMO-network.");
+    let nodes = read_and_remove(&writing_cfg.nodes_file);
+    let edges = read_and_remove(&writing_cfg.edges_file);
+
+    assert!(nodes.contains("<nodes>") && nodes.trim_end().ends_with("</nodes>"));
+    assert_eq!(nodes.matches("<node ").count(), graph.nodes().count());
+    assert!(nodes.contains(&format!(
+        "<node id=\"{}\" x=\"9.458919\" y=\"48.98401\"/>",
+        OPPENWEILER
+    )));
+
+    assert!(edges.contains("<edges>") && edges.trim_end().ends_with("</edges>"));
+    assert_eq!(edges.matches("<edge ").count(), graph.fwd_edges().count());
+    // kilometers are converted to meters and kmph to meters per second
+    assert!(edges.contains(&format!(
+        "from=\"{}\" to=\"{}\" numLanes=\"1\" speed=\"33.333333333333336\" length=\"23000\"/>",
+        BACKNANG, WAIBLINGEN
+    )));
+}
+
+#[test]
+fn wrong_metrics() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::SUMO_YAML));
+
+    let mut writing_cfg = configs::writing::sumo::Config::from_yaml(resources::SUMO_YAML);
+    writing_cfg.nodes_file = temp_file("wrong_metrics.nod.xml");
+    writing_cfg.edges_file = temp_file("wrong_metrics.edg.xml");
+    writing_cfg.metrics.kmph = SimpleId::from("kilometers");
+    assert!(io::sumo::Writer::write_network(&graph, &writing_cfg).is_err());
+
+    writing_cfg.metrics.kmph = SimpleId::from("kmph");
+    writing_cfg.metrics.lane_count = Some(SimpleId::from("kmph"));
+    assert!(io::sumo::Writer::write_network(&graph, &writing_cfg).is_err());
+
+    assert!(!writing_cfg.nodes_file.exists() && !writing_cfg.edges_file.exists());
+}
+
+#[test]
+fn routes() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::SUMO_YAML));
+    let chosen_paths = vec![
+        (
+            best_path(&graph, OPPENWEILER, STUTTGART),
+            Some(Seconds(3600.0)),
+        ),
+        (best_path(&graph, BACKNANG, WAIBLINGEN), None),
+    ];
+
+    let file = temp_file("sumo.rou.xml");
+    io::sumo::Writer::write_routes(&chosen_paths, &graph, &file)
+        .expect("Chosen paths should be writable as SUMO-routes.");
+    let routes = read_and_remove(&file);
+
+    assert!(routes.contains("<routes>") && routes.trim_end().ends_with("</routes>"));
+    assert_eq!(routes.matches("<vehicle ").count(), 2);
+    assert!(routes.contains("<vType id=\"car\" vClass=\"passenger\"/>"));
+
+    // sorted by departure
+    let without_departure = routes
+        .find("depart=\"0.00\"")
+        .expect("Route without departure should depart at 0.");
+    let with_departure = routes
+        .find("depart=\"3600.00\"")
+        .expect("Route should depart at its departure.");
+    assert!(without_departure < with_departure);
+
+    // edges are given by their ids
+    let fwd_edges = graph.fwd_edges();
+    let edges: Vec<String> = chosen_paths[0]
+        .0
+        .iter()
+        .map(|&edge_idx| fwd_edges.id(edge_idx).to_string())
+        .collect();
+    assert_eq!(edges.len(), 3);
+    assert!(routes.contains(&format!("<route edges=\"{}\"/>", edges.join(" "))));
+}
+
+#[test]
+fn routes_without_edge_ids() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::FMI_YAML));
+    let chosen_paths = vec![(best_path(&graph, OPPENWEILER, STUTTGART), None)];
+
+    // edge-indices wouldn't match the edges of a network, written from another graph
+    let file = temp_file("without_edge_ids.rou.xml");
+    assert!(io::sumo::Writer::write_routes(&chosen_paths, &graph, &file).is_err());
+    assert!(!file.exists());
+}
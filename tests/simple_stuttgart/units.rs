@@ -0,0 +1,193 @@
+use crate::helpers::{defaults, parse, read_and_remove, temp_file};
+use defaults::paths::resources::simple_stuttgart as resources;
+use osmgraphing::{
+    approximating::Approx,
+    configs::{
+        self,
+        parsing::edges::metrics::{Dimension, UnitInfo},
+        SimpleId,
+    },
+    io,
+    network::Graph,
+};
+use std::{convert::TryFrom, fs};
+
+fn value(graph: &Graph, edge_id: usize, metric_id: &str) -> f64 {
+    let metric_idx = graph.cfg().edges.metrics.idx_of(metric_id);
+    graph
+        .metrics()
+        .get_value(graph.fwd_edges().idx_from(edge_id), metric_idx)
+}
+
+#[test]
+fn converting() {
+    let convert = |from: UnitInfo, to: UnitInfo, raw_value: f64| {
+        from.try_convert(&to, raw_value)
+            .expect("Units of the same dimension should be convertible.")
+    };
+
+    assert!(Approx(convert(UnitInfo::Miles, UnitInfo::Kilometers, 1.0)) == Approx(1.609_344));
+    assert!(Approx(convert(UnitInfo::Meters, UnitInfo::Miles, 1_609.344)) == Approx(1.0));
+    assert!(
+        Approx(convert(
+            UnitInfo::MilesPerHour,
+            UnitInfo::KilometersPerHour,
+            100.0
+        )) == Approx(160.934_4)
+    );
+    assert!(
+        Approx(convert(
+            UnitInfo::KilometersPerHour,
+            UnitInfo::MetersPerSecond,
+            36.0
+        )) == Approx(10.0)
+    );
+    assert!(Approx(convert(UnitInfo::Hours, UnitInfo::Seconds, 0.5)) == Approx(1_800.0));
+    assert!(Approx(convert(UnitInfo::GramsCO2, UnitInfo::GramsCO2, 42.0)) == Approx(42.0));
+    // raw values are kept for f64
+    assert!(Approx(convert(UnitInfo::KilowattHours, UnitInfo::F64, 3.0)) == Approx(3.0));
+    assert!(Approx(convert(UnitInfo::F64, UnitInfo::Miles, 3.0)) == Approx(3.0));
+
+    for (from, to) in &[
+        (UnitInfo::Miles, UnitInfo::MilesPerHour),
+        (UnitInfo::Seconds, UnitInfo::Meters),
+        (UnitInfo::KilowattHours, UnitInfo::GramsCO2),
+        (UnitInfo::PercentGradient, UnitInfo::Meters),
+        // different dimensionless units
+        (UnitInfo::LaneCount, UnitInfo::PercentGradient),
+        (UnitInfo::PercentGradient, UnitInfo::LaneCount),
+    ] {
+        assert!(
+            from.try_convert(to, 1.0).is_err(),
+            "{:?} shouldn't be convertible to {:?}.",
+            from,
+            to
+        );
+    }
+
+    assert_eq!(UnitInfo::MetersPerSecond.dimension(), Dimension::SPEED);
+    assert_eq!(
+        Dimension::LENGTH.div(&Dimension::TIME),
+        UnitInfo::MilesPerHour.dimension()
+    );
+    assert_eq!(UnitInfo::from_name("GramsCO2"), Some(UnitInfo::GramsCO2));
+    assert_eq!(UnitInfo::from_name("Parsecs"), None);
+    // names are parsed and displayed alike
+    assert_eq!(
+        UnitInfo::try_from(UnitInfo::MilesPerHour.to_string()),
+        Ok(UnitInfo::MilesPerHour)
+    );
+    assert!(UnitInfo::try_from(String::from("Parsecs")).is_err());
+}
+
+#[test]
+fn calculating() {
+    let calc = |result: UnitInfo, unit_a: UnitInfo, a: f64, unit_b: UnitInfo, b: f64| {
+        result
+            .try_calc(&unit_a, a, &unit_b, b)
+            .expect("Units should be calculable.")
+    };
+
+    // quotients
+    assert!(
+        Approx(calc(
+            UnitInfo::Minutes,
+            UnitInfo::Kilometers,
+            8.0,
+            UnitInfo::KilometersPerHour,
+            50.0
+        )) == Approx(9.6)
+    );
+    assert!(
+        Approx(calc(
+            UnitInfo::MilesPerHour,
+            UnitInfo::Miles,
+            30.0,
+            UnitInfo::Minutes,
+            30.0
+        )) == Approx(60.0)
+    );
+    // products
+    assert!(
+        Approx(calc(
+            UnitInfo::Meters,
+            UnitInfo::MetersPerSecond,
+            2.0,
+            UnitInfo::Minutes,
+            1.0
+        )) == Approx(120.0)
+    );
+    assert!(
+        Approx(calc(
+            UnitInfo::Meters,
+            UnitInfo::PercentGradient,
+            5.0,
+            UnitInfo::Kilometers,
+            2.0
+        )) == Approx(100.0)
+    );
+
+    // dimensions don't match
+    assert!(UnitInfo::Minutes
+        .try_calc(&UnitInfo::Kilometers, 1.0, &UnitInfo::Hours, 1.0)
+        .is_err());
+    // product and quotient are possible
+    assert!(UnitInfo::Meters
+        .try_calc(&UnitInfo::Meters, 1.0, &UnitInfo::LaneCount, 1.0)
+        .is_err());
+    // f64 has no dimension to check
+    assert!(UnitInfo::F64
+        .try_calc(&UnitInfo::LaneCount, 1.0, &UnitInfo::F64, 1.0)
+        .is_err());
+}
+
+#[test]
+fn generating() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::UNITS_YAML));
+
+    // Oppenweiler -> Backnang: 8 km with 50 kmph
+    assert!(Approx(value(&graph, 0, "miles")) == Approx(8.0 / 1.609_344));
+    assert!(Approx(value(&graph, 0, "mph")) == Approx(50.0 / 1.609_344));
+    assert!(Approx(value(&graph, 0, "minutes")) == Approx(9.6));
+    assert!(Approx(value(&graph, 0, "co2")) == Approx(250.0 * 8.0 / 1.609_344));
+    // the converted metric replaces kmph
+    assert!(graph.cfg().edges.metrics.try_idx_of("kmph").is_err());
+
+    // haversine works with every distance
+    let content =
+        fs::read_to_string(resources::UNITS_YAML).expect("Units-yaml should be readable.");
+    for (unit, is_ok) in &[("Miles", true), ("Meters", true), ("MilesPerHour", false)] {
+        let new_content = content.replace(
+            "    - copy:\n        from: { unit: 'Meters', id: 'meters' }\n        to: { unit: 'Miles', id: 'miles' }",
+            &format!("    - haversine: {{ unit: '{}', id: 'miles' }}", unit),
+        );
+        assert_ne!(new_content, content);
+        let yaml_file = temp_file("units.yaml");
+        fs::write(&yaml_file, new_content).expect("Temporary yaml-file should be writable.");
+        let parsing_cfg = configs::parsing::Config::try_from_yaml(&yaml_file);
+        fs::remove_file(&yaml_file).expect("Temporary yaml-file should be removable.");
+        let result = parsing_cfg
+            .and_then(|parsing_cfg| io::network::graph::Parser::parse_and_finalize(parsing_cfg));
+        assert_eq!(result.is_ok(), *is_ok, "Haversine with unit {}", unit);
+    }
+}
+
+#[test]
+fn writing_sumo() {
+    let graph = parse(configs::parsing::Config::from_yaml(resources::UNITS_YAML));
+
+    let mut writing_cfg = configs::writing::sumo::Config::from_yaml(resources::SUMO_YAML);
+    writing_cfg.nodes_file = temp_file("units.nod.xml");
+    writing_cfg.edges_file = temp_file("units.edg.xml");
+    writing_cfg.metrics.distance = SimpleId::from("miles");
+    writing_cfg.metrics.kmph = SimpleId::from("mph");
+    io::sumo::Writer::write_network(&graph, &writing_cfg)
+        .expect("Miles and miles per hour should be writable.");
+    read_and_remove(&writing_cfg.nodes_file);
+    let edges = read_and_remove(&writing_cfg.edges_file);
+    assert_eq!(edges.matches("<edge ").count(), graph.fwd_edges().count());
+
+    // minutes are no distance
+    writing_cfg.metrics.distance = SimpleId::from("minutes");
+    assert!(io::sumo::Writer::write_network(&graph, &writing_cfg).is_err());
+}
@@ -0,0 +1,112 @@
+use crate::helpers::{compare_dijkstras_on_graph, defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{
+    configs,
+    defaults::capacity::DimVec,
+    network::{CustomizableCh, Graph, NodeIdx},
+};
+use smallvec::smallvec;
+
+fn all_route_pairs(graph: &Graph) -> Vec<(NodeIdx, NodeIdx)> {
+    let nodes = graph.nodes();
+    nodes
+        .iter()
+        .flat_map(|src_idx| nodes.iter().map(move |dst_idx| (src_idx, dst_idx)))
+        .collect()
+}
+
+fn alphas_of(graph: &Graph, metric_ids: &[&str]) -> DimVec<f64> {
+    let mut alphas: DimVec<f64> = smallvec![0.0; graph.metrics().dim()];
+    for metric_id in metric_ids {
+        alphas[*graph.cfg().edges.metrics.idx_of(*metric_id)] = 1.0;
+    }
+    alphas
+}
+
+#[test]
+fn customized_fmi_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let mut graph = parse(parsing_cfg);
+    let cch = CustomizableCh::preprocess(&graph);
+    assert_eq!(cch.node_count(), graph.nodes().count());
+
+    let alphas = alphas_of(&graph, &[defaults::DURATION_ID]);
+    if let Err(msg) = graph.customize(&cch, &alphas) {
+        panic!("Could not customize graph. ERROR: {}", msg);
+    }
+
+    // ch-levels are the ranks of the customizable ch
+    let nodes = graph.nodes();
+    for node_idx in &nodes {
+        assert_eq!(nodes.level(node_idx), cch.rank(node_idx));
+    }
+    let route_pairs = all_route_pairs(&graph);
+    compare_dijkstras_on_graph(&graph, &alphas, &route_pairs);
+}
+
+#[test]
+fn customized_fmi_graph_for_multiple_metrics() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let mut graph = parse(parsing_cfg);
+    let cch = CustomizableCh::preprocess(&graph);
+
+    let alphas = alphas_of(&graph, &[defaults::DISTANCE_ID, defaults::DURATION_ID]);
+    if let Err(msg) = graph.customize(&cch, &alphas) {
+        panic!("Could not customize graph. ERROR: {}", msg);
+    }
+
+    // shortcuts are pareto-optimal, so other alphas of the same metrics are supported as well
+    let km_idx = *graph.cfg().edges.metrics.idx_of(defaults::DISTANCE_ID);
+    let h_idx = *graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID);
+    let route_pairs = all_route_pairs(&graph);
+    for &(km_alpha, h_alpha) in &[(1.0, 0.0), (0.0, 1.0), (1.0, 100.0), (0.01, 1.0)] {
+        let mut other_alphas: DimVec<f64> = smallvec![0.0; graph.metrics().dim()];
+        other_alphas[km_idx] = km_alpha;
+        other_alphas[h_idx] = h_alpha;
+        compare_dijkstras_on_graph(&graph, &other_alphas, &route_pairs);
+    }
+}
+
+#[test]
+fn customized_ch_fmi_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let mut graph = parse(parsing_cfg);
+    let cch = CustomizableCh::preprocess(&graph);
+
+    let alphas = alphas_of(&graph, &[defaults::DURATION_ID]);
+    if let Err(msg) = graph.customize(&cch, &alphas) {
+        panic!("Could not customize graph. ERROR: {}", msg);
+    }
+
+    let route_pairs = all_route_pairs(&graph);
+    compare_dijkstras_on_graph(&graph, &alphas, &route_pairs);
+}
+
+#[test]
+fn recustomized_fmi_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::FMI_YAML);
+    let mut graph = parse(parsing_cfg);
+    let cch = CustomizableCh::preprocess(&graph);
+    let alphas = alphas_of(&graph, &[defaults::DURATION_ID]);
+    if let Err(msg) = graph.customize(&cch, &alphas) {
+        panic!("Could not customize graph. ERROR: {}", msg);
+    }
+
+    // change metrics without contracting again
+    let h_idx = graph.cfg().edges.metrics.idx_of(defaults::DURATION_ID);
+    let edge_count = graph.fwd_edges().count();
+    {
+        let mut metrics = graph.metrics_mut();
+        for edge_idx in (0..edge_count).step_by(3) {
+            let edge_idx = osmgraphing::network::EdgeIdx(edge_idx);
+            let value = metrics.get_value(edge_idx, h_idx);
+            metrics.set_value(edge_idx, h_idx, 10.0 * value);
+        }
+    }
+    if let Err(msg) = graph.customize(&cch, &alphas) {
+        panic!("Could not customize graph. ERROR: {}", msg);
+    }
+
+    let route_pairs = all_route_pairs(&graph);
+    compare_dijkstras_on_graph(&graph, &alphas, &route_pairs);
+}
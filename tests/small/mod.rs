@@ -1,2 +1,4 @@
+mod customizing;
 mod parsing;
 mod routing;
+mod updating;
@@ -1,4 +1,6 @@
-use crate::helpers::{assert_graph, assert_graph_sloppy, defaults, parse, TestEdge, TestNode};
+use crate::helpers::{
+    assert_graph, assert_graph_eq, assert_graph_sloppy, defaults, parse, TestEdge, TestNode,
+};
 use defaults::paths::resources::small as resources;
 use kissunits::{
     distance::Kilometers,
@@ -6,7 +8,10 @@ use kissunits::{
     speed::KilometersPerHour,
     time::{Hours, Seconds},
 };
-use osmgraphing::{configs, network::EdgeIdx};
+use osmgraphing::{
+    configs::{self, parsing::storage},
+    network::EdgeIdx,
+};
 
 #[test]
 fn ch_fmi_yaml() {
@@ -140,3 +145,41 @@ fn ch_fmi_graph() {
     let expected_edge_count = 18;
     assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
 }
+
+#[test]
+fn compact_ch_fmi_graph() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    let mut compact_parsing_cfg = parsing_cfg;
+    compact_parsing_cfg.storage.layout = storage::Layout::Compact;
+    let compact_graph = parse(compact_parsing_cfg);
+    assert!(compact_graph.is_compact());
+
+    // the layout should not change the graph, including shortcuts and ch-levels
+    assert_graph_eq(&graph, &compact_graph);
+}
+
+#[test]
+fn compact_dot_products() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let graph = parse(parsing_cfg.clone());
+
+    let mut compact_parsing_cfg = parsing_cfg;
+    compact_parsing_cfg.storage.layout = storage::Layout::Compact;
+    let compact_graph = parse(compact_parsing_cfg.clone());
+    compact_parsing_cfg.storage.metric_precision = storage::Precision::F32;
+    let f32_graph = parse(compact_parsing_cfg);
+
+    // metrics are read in place, but should equal the copied metrics
+    let alphas: Vec<f64> = (0..graph.metrics().dim()).map(|i| 1.0 + i as f64).collect();
+    for g in &[&graph, &compact_graph, &f32_graph] {
+        let metrics = g.metrics();
+        let fwd_edges = g.fwd_edges();
+        for edge_idx in fwd_edges.iter() {
+            let expected = osmgraphing::helpers::dot_product(&alphas, &metrics.get(edge_idx));
+            assert_eq!(metrics.dot_product(edge_idx, &alphas), expected);
+            assert_eq!(fwd_edges.half_edge(edge_idx).dot_product(&alphas), expected);
+        }
+    }
+}
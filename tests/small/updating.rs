@@ -0,0 +1,43 @@
+use crate::helpers::{assert_graph_sloppy, defaults, parse};
+use defaults::paths::resources::small as resources;
+use osmgraphing::{configs, network::ProtoChanges};
+
+#[test]
+fn ch_fmi_graph_with_deleted_node() {
+    let parsing_cfg = configs::parsing::Config::from_yaml(resources::CH_FMI_YAML);
+    let mut graph = parse(parsing_cfg.clone());
+    assert!(!graph.is_ch_stale());
+    let node_indices: Vec<_> = [0, 1, 2, 3, 4, 5, 6, 7]
+        .iter()
+        .map(|&node_id| graph.nodes().idx_from(node_id).expect("Node should exist."))
+        .collect();
+
+    // delete node d
+    let changes = ProtoChanges {
+        deleted_node_ids: vec![3],
+        ..Default::default()
+    };
+    if let Err(msg) = graph.apply_changes(changes, &parsing_cfg) {
+        panic!("Could not apply changes. ERROR: {}", msg);
+    }
+
+    // node d keeps its index, but is removed
+    // -5 edges of node d
+    // -2 shortcuts containing edges of node d
+    let expected_node_count = 8;
+    let expected_edge_count = 11;
+    assert_graph_sloppy(expected_node_count, expected_edge_count, &graph);
+    let nodes = graph.nodes();
+    assert!(nodes.idx_from(3).is_err());
+    assert!(nodes.is_removed(node_indices[3]));
+    for &node_id in &[0, 1, 2, 4, 5, 6, 7] {
+        let node_idx = node_indices[node_id as usize];
+        assert_eq!(nodes.idx_from(node_id), Ok(node_idx));
+        assert!(!nodes.is_removed(node_idx));
+    }
+    let fwd_edges = graph.fwd_edges();
+    assert!(fwd_edges
+        .iter()
+        .all(|edge_idx| !fwd_edges.is_shortcut(edge_idx)));
+    assert!(graph.is_ch_stale());
+}